@@ -0,0 +1,98 @@
+//! Measure the directional bias of the tetrahedra decompositions on spheres.
+//!
+//! ```text
+//! cargo run --release --example anisotropy [-- <resolution>]
+//! ```
+//!
+//! The cells can be split into tetrahedra in different ways, or the field can be marched on the
+//! body-centered cubic lattice. Each split favors the directions of the diagonals it cuts along,
+//! which shows up as faceting along those diagonals. A sphere has normals in all directions
+//! alike, so the anisotropy of its face normals (see [NormalDistribution]) and the angle between
+//! the face normals and the exact normals show how much a decomposition prefers some directions.
+//! Spheres at several offsets from the lattice are averaged, so the result doesn't depend on how
+//! a single sphere happens to line up.
+
+use marching_cubes::{algorithm::Decomposition, prelude::*, result::NormalDistribution};
+
+type Method = fn(&mut Domain, &dyn ScalarField) -> MarchResult;
+
+const DECOMPOSITIONS: [(&str, Method); 3] = [
+    ("5 tetrahedra", |domain, field| {
+        domain.march_tetrahedras(field, &Bisection)
+    }),
+    ("6 tetrahedra", |domain, field| {
+        domain.march_tetrahedras_with_decomposition(Decomposition::Six, field, &Bisection)
+    }),
+    ("body centered", |domain, field| {
+        domain.march_body_centered(field, &Bisection)
+    }),
+];
+
+const SPHERES: usize = 16;
+
+fn main() {
+    let resolution = std::env::args()
+        .nth(1)
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(32);
+
+    println!(
+        "{:<16} {:>12} {:>12} {:>12}",
+        "decomposition", "anisotropy", "off diagonal", "mean angle"
+    );
+    for (name, decomposition) in DECOMPOSITIONS {
+        let mut anisotropy = 0.0;
+        let mut off_diagonal = 0.0;
+        let mut mean_angle = 0.0;
+        for index in 0..SPHERES {
+            // Offsets spread over a cell, with a golden ratio sequence per axis.
+            let fraction = |factor: Float| (index as Float * factor).fract() - 0.5;
+            let center = Vec3 {
+                x: fraction(0.618_034),
+                y: fraction(0.754_878),
+                z: fraction(0.569_840),
+            } * (6.0 / resolution as Float);
+            let field = move |position: Vec3| 2.5 - (position - center).length();
+            let mut domain = Domain {
+                from: Vec3 {
+                    x: -3.0,
+                    y: -3.0,
+                    z: -3.0,
+                },
+                to: Vec3 {
+                    x: 3.0,
+                    y: 3.0,
+                    z: 3.0,
+                },
+                surface_weight: 0.0,
+                width: resolution,
+                height: resolution,
+                depth: resolution,
+                grid_offset: Vec3::default(),
+                meshes: Vec::new(),
+            };
+            decomposition(&mut domain, &field);
+            let mesh = &domain.meshes[0];
+
+            let distribution = NormalDistribution::new(mesh);
+            let tensor = distribution.tensor;
+            anisotropy += distribution.anisotropy();
+            off_diagonal += tensor[0][1].abs() + tensor[0][2].abs() + tensor[1][2].abs();
+            let angles = mesh.faces.iter().map(|face| {
+                let [a, b, c] = [face.v1, face.v2, face.v3].map(|vert| mesh.verts[vert]);
+                let normal = (b - a).cross(c - a).normalized();
+                let exact = ((a + b + c) * (1.0 / 3.0) - center).normalized();
+                normal.dot(exact).clamp(-1.0, 1.0).acos().to_degrees()
+            });
+            mean_angle += angles.sum::<Float>() / mesh.faces.len().max(1) as Float;
+        }
+        let count = SPHERES as Float;
+        println!(
+            "{:<16} {:>12.6} {:>12.6} {:>11.3}°",
+            name,
+            anisotropy / count,
+            off_diagonal / count,
+            mean_angle / count
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Compare the extraction methods of this crate on the same fields.
+//!
+//! ```text
+//! cargo run --release --example compare [-- <resolution>]
+//! ```
+//!
+//! For every field and method the triangle count, the distance between the vertices and the
+//! exact surface, the error of the enclosed volume and the time are reported. The fields have an
+//! analytic distance to their surface, so the accuracy doesn't depend on the gradient estimate.
+//!
+//! The last rows are reference implementations from other crates, `isosurface` and
+//! `fast-surface-nets`, sampling the same lattice. Their output is converted to a [Mesh].
+
+// The other crates use f32, the casts from `Float` are no-ops with the `f32` feature.
+#![allow(clippy::unnecessary_cast)]
+
+use std::{cell::Cell, time::Instant};
+
+use fast_surface_nets::{
+    SurfaceNetsBuffer,
+    ndshape::{RuntimeShape, Shape},
+    surface_nets,
+};
+use isosurface::{marching_cubes::MarchingCubes, source::Source};
+use marching_cubes::{Face, Newton, algorithm::Decomposition, consts::PI, prelude::*};
+
+fn vec3(x: Float, y: Float, z: Float) -> Vec3 {
+    Vec3 { x, y, z }
+}
+
+/// Field with a known distance to its surface.
+struct Showcase {
+    name: &'static str,
+    field: fn(Vec3) -> Float,
+    surface_weight: Float,
+    /// Exact distance between a position and the surface.
+    distance: fn(Vec3) -> Float,
+    /// Exact enclosed volume.
+    volume: Float,
+}
+
+const SHOWCASES: [Showcase; 3] = [
+    Showcase {
+        name: "sphere",
+        field: |position| 3.0 - position.length(),
+        surface_weight: 0.0,
+        distance: |position| (position.length() - 3.0).abs(),
+        volume: 4.0 / 3.0 * PI * 27.0,
+    },
+    Showcase {
+        name: "metaball",
+        // A single force with the metaball falloff is a sphere with radius `force`.
+        field: |position| 2.5 / position.length(),
+        surface_weight: 1.0,
+        distance: |position| (position.length() - 2.5).abs(),
+        volume: 4.0 / 3.0 * PI * 15.625,
+    },
+    Showcase {
+        name: "torus",
+        field: |position| {
+            let ring = (position.x * position.x + position.z * position.z).sqrt() - 2.5;
+            1.0 - (ring * ring + position.y * position.y).sqrt()
+        },
+        surface_weight: 0.0,
+        distance: |position| {
+            let ring = (position.x * position.x + position.z * position.z).sqrt() - 2.5;
+            ((ring * ring + position.y * position.y).sqrt() - 1.0).abs()
+        },
+        volume: 2.0 * PI * PI * 2.5,
+    },
+];
+
+type Method = fn(&mut Domain, fn(Vec3) -> Float) -> MarchResult;
+
+/// Position of a point given in cells from the lowest corner of the domain.
+fn cell_position(domain: &Domain, cells: [f32; 3]) -> Vec3 {
+    let [x, y, z] = cells.map(|cells| cells as Float);
+    vec3(
+        domain.from.x + x * (domain.to.x - domain.from.x) / domain.width as Float,
+        domain.from.y + y * (domain.to.y - domain.from.y) / domain.height as Float,
+        domain.from.z + z * (domain.to.z - domain.from.z) / domain.depth as Float,
+    )
+}
+
+/// Add the triangles of another crate as a mesh. `flip` swaps the winding of the faces, when the
+/// crate uses the other side of the surface as the outside.
+fn add_mesh(
+    domain: &mut Domain,
+    verts: Vec<Vec3>,
+    indices: &[u32],
+    flip: bool,
+    (min_weight, max_weight): (Float, Float),
+) -> MarchResult {
+    let faces = indices
+        .chunks_exact(3)
+        .map(|face| {
+            let [v1, v2, v3] = [face[0], face[1], face[2]].map(|vert| vert as usize);
+            if flip {
+                Face { v1, v2: v3, v3: v2 }
+            } else {
+                Face { v1, v2, v3 }
+            }
+        })
+        .collect();
+    let mesh = Mesh {
+        verts,
+        faces,
+        ..Mesh::default()
+    };
+    let result = MarchResult::new(&mesh, min_weight, max_weight, domain.surface_weight);
+    domain.meshes.push(mesh);
+    result
+}
+
+/// The field as a signed distance in the unit cube for `isosurface`, negative inside.
+struct UnitCube<'a> {
+    domain: &'a Domain,
+    field: fn(Vec3) -> Float,
+    range: Cell<(Float, Float)>,
+}
+
+impl Source for UnitCube<'_> {
+    fn sample(&self, x: f32, y: f32, z: f32) -> f32 {
+        let cells = [
+            x * self.domain.width as f32,
+            y * self.domain.height as f32,
+            z * self.domain.depth as f32,
+        ];
+        let weight = (self.field)(cell_position(self.domain, cells));
+        let (min, max) = self.range.get();
+        self.range.set((min.min(weight), max.max(weight)));
+        (self.domain.surface_weight - weight) as f32
+    }
+}
+
+fn isosurface_marching_cubes(domain: &mut Domain, field: fn(Vec3) -> Float) -> MarchResult {
+    let source = UnitCube {
+        domain,
+        field,
+        range: Cell::new((Float::INFINITY, Float::NEG_INFINITY)),
+    };
+    let (mut coordinates, mut indices) = (Vec::new(), Vec::new());
+    MarchingCubes::new(domain.width + 1).extract(&source, &mut coordinates, &mut indices);
+    let verts = coordinates
+        .chunks_exact(3)
+        .map(|vert| {
+            let cells = [
+                vert[0] * domain.width as f32,
+                vert[1] * domain.height as f32,
+                vert[2] * domain.depth as f32,
+            ];
+            cell_position(domain, cells)
+        })
+        .collect();
+    let range = source.range.get();
+    // The faces of `isosurface` are wound clockwise.
+    add_mesh(domain, verts, &indices, true, range)
+}
+
+fn fast_surface_nets(domain: &mut Domain, field: fn(Vec3) -> Float) -> MarchResult {
+    let size = [domain.width, domain.height, domain.depth].map(|cells| cells as u32 + 1);
+    let shape = RuntimeShape::<u32, 3>::new(size);
+    let mut range = (Float::INFINITY, Float::NEG_INFINITY);
+    let sdf = (0..shape.usize())
+        .map(|index| {
+            let cells = shape.delinearize(index as u32).map(|cells| cells as f32);
+            let weight = field(cell_position(domain, cells));
+            range = (range.0.min(weight), range.1.max(weight));
+            (domain.surface_weight - weight) as f32
+        })
+        .collect::<Vec<f32>>();
+    let mut buffer = SurfaceNetsBuffer::default();
+    surface_nets(&sdf, &shape, [0; 3], size.map(|size| size - 1), &mut buffer);
+    let verts = buffer
+        .positions
+        .iter()
+        .map(|position| cell_position(domain, *position))
+        .collect();
+    add_mesh(domain, verts, &buffer.indices, false, range)
+}
+
+const METHODS: [(&str, Method); 17] = [
+    ("tetrahedra bisection", |domain, field| {
+        domain.march_tetrahedras(&field, &Bisection)
+    }),
+    ("tetrahedra linear", |domain, field| {
+        domain.march_tetrahedras(&field, &LinearInterp)
+    }),
+    ("tetrahedra weights", |domain, field| {
+        domain.march_tetrahedras(&field, &WeightInterp)
+    }),
+    ("tetrahedra newton", |domain, field| {
+        domain.march_tetrahedras(&field, &Newton::default())
+    }),
+    ("tetrahedra midpoint", |domain, field| {
+        domain.march_tetrahedras(&field, &Midpoint)
+    }),
+    ("six tetrahedra", |domain, field| {
+        domain.march_tetrahedras_with_decomposition(Decomposition::Six, &field, &Bisection)
+    }),
+    ("body centered", |domain, field| {
+        domain.march_body_centered(&field, &Bisection)
+    }),
+    ("marching cubes", |domain, field| {
+        domain.march_cubes(&field, &Bisection)
+    }),
+    ("surface nets", |domain, field| {
+        domain.march_surface_nets(&field, &Bisection)
+    }),
+    ("dual contouring", |domain, field| {
+        domain.march_dual_contouring(&field, &Bisection)
+    }),
+    ("dual tetrahedra", |domain, field| {
+        domain.march_dual_tetrahedras(&field, &Bisection)
+    }),
+    ("sampled", |domain, field| {
+        domain.march_tetrahedras_sampled(&field, &Bisection)
+    }),
+    ("adaptive", |domain, field| {
+        domain.march_adaptive(&field, &Bisection, 3, 0.01).unwrap()
+    }),
+    ("blocky", |domain, field| {
+        domain.extract(ExtractionMethod::Blocky, &field, &Bisection)
+    }),
+    ("tetrahedra 2x", |domain, field| {
+        *domain = domain.with_resolution(domain.width * 2, domain.height * 2, domain.depth * 2);
+        domain.march_tetrahedras(&field, &Bisection)
+    }),
+    ("isosurface mc", isosurface_marching_cubes),
+    ("fast-surface-nets", fast_surface_nets),
+];
+
+fn main() {
+    let resolution = std::env::args()
+        .nth(1)
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(48);
+
+    println!(
+        "{:<10} {:<22} {:>10} {:>12} {:>12} {:>12} {:>10}",
+        "field", "method", "triangles", "mean error", "max error", "volume error", "time"
+    );
+    for showcase in &SHOWCASES {
+        for (method_name, method) in METHODS {
+            let mut domain = Domain {
+                from: vec3(-4.0, -4.0, -4.0),
+                to: vec3(4.0, 4.0, 4.0),
+                surface_weight: showcase.surface_weight,
+                width: resolution,
+                height: resolution,
+                depth: resolution,
+                grid_offset: Vec3::default(),
+                meshes: Vec::new(),
+            };
+            let start = Instant::now();
+            method(&mut domain, showcase.field);
+            let time = start.elapsed();
+
+            let mesh = domain.meshes.last().unwrap();
+            let errors = mesh
+                .verts
+                .iter()
+                .map(|vert| (showcase.distance)(*vert))
+                .collect::<Vec<Float>>();
+            let mean_error = errors.iter().sum::<Float>() / errors.len().max(1) as Float;
+            let max_error = errors.iter().copied().fold(0.0, Float::max);
+            let volume_error = (mesh.signed_volume() - showcase.volume) / showcase.volume;
+            println!(
+                "{:<10} {:<22} {:>10} {:>12.6} {:>12.6} {:>11.3}% {:>10.2?}",
+                showcase.name,
+                method_name,
+                mesh.faces.len(),
+                mean_error,
+                max_error,
+                volume_error * 100.0,
+                time
+            );
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Generate a set of showcase meshes into a directory.
+//!
+//! ```text
+//! cargo run --release --example gallery -- <output directory> [--format bpy|obj|off|stl|stl-ascii|ply|glb]
+//! ```
+//!
+//! Every showcase exercises a different part of the crate, so running the gallery doubles as an
+//! end to end smoke test: it fails when a showcase produces an empty mesh.
+
+use std::{fs, io, path::PathBuf, process::ExitCode};
+
+use marching_cubes::{export::ExportFormat, prelude::*, tangent::UvProjection, voxel::VoxelGrid};
+
+fn vec3(x: Float, y: Float, z: Float) -> Vec3 {
+    Vec3 { x, y, z }
+}
+
+fn cube_domain(half_size: Float, resolution: usize, surface_weight: Float) -> Domain {
+    Domain {
+        from: vec3(-half_size, -half_size, -half_size),
+        to: vec3(half_size, half_size, half_size),
+        surface_weight,
+        width: resolution,
+        height: resolution,
+        depth: resolution,
+        grid_offset: Vec3::default(),
+        meshes: Vec::new(),
+    }
+}
+
+/// Signed distance fields are negative inside, weights are higher inside.
+fn sphere(center: Vec3, radius: Float) -> impl Fn(Vec3) -> Float {
+    move |position| radius - (position - center).length()
+}
+
+fn cuboid(center: Vec3, half_size: Vec3) -> impl Fn(Vec3) -> Float {
+    move |position| {
+        let local = position - center;
+        let outside = vec3(
+            local.x.abs() - half_size.x,
+            local.y.abs() - half_size.y,
+            local.z.abs() - half_size.z,
+        );
+        let clamped = vec3(outside.x.max(0.0), outside.y.max(0.0), outside.z.max(0.0));
+        -(clamped.length() + outside.x.max(outside.y).max(outside.z).min(0.0))
+    }
+}
+
+fn capsule(from: Vec3, to: Vec3, radius: Float) -> impl Fn(Vec3) -> Float {
+    move |position| {
+        let direction = to - from;
+        let factor = ((position - from).dot(direction) / direction.dot(direction)).clamp(0.0, 1.0);
+        radius - (position - from.lerp(to, factor)).length()
+    }
+}
+
+fn metaballs() -> (Domain, MarchResult) {
+    let metaballs = Metaballs::new(vec![
+        Force {
+            position: vec3(-1.5, 0.0, 0.0),
+            force: 1.5,
+        },
+        Force {
+            position: vec3(1.5, 0.5, 0.0),
+            force: 1.2,
+        },
+        Force {
+            position: vec3(0.0, -1.0, 1.5),
+            force: 1.0,
+        },
+    ]);
+    let mut domain = cube_domain(5.0, 48, 1.0);
+    let result = domain.march_tetrahedras(&metaballs, &Bisection);
+    let mesh = domain.meshes.last_mut().unwrap();
+    mesh.compute_gradient_normals(&metaballs, 0.001);
+    (domain, result)
+}
+
+/// Robot built from primitives with union (max), intersection (min) and subtraction.
+fn csg_robot() -> (Domain, MarchResult) {
+    let torso = cuboid(vec3(0.0, 0.0, 0.0), vec3(1.2, 1.5, 0.8));
+    let head = cuboid(vec3(0.0, 2.2, 0.0), vec3(0.7, 0.6, 0.6));
+    let eyes = [
+        sphere(vec3(-0.3, 2.35, 0.65), 0.2),
+        sphere(vec3(0.3, 2.35, 0.65), 0.2),
+    ];
+    let antenna = capsule(vec3(0.0, 2.8, 0.0), vec3(0.0, 3.5, 0.0), 0.08);
+    let antenna_tip = sphere(vec3(0.0, 3.6, 0.0), 0.18);
+    let arms = [
+        capsule(vec3(-1.5, 1.0, 0.0), vec3(-2.2, -0.8, 0.3), 0.35),
+        capsule(vec3(1.5, 1.0, 0.0), vec3(2.2, -0.8, 0.3), 0.35),
+    ];
+    let legs = [
+        capsule(vec3(-0.6, -1.6, 0.0), vec3(-0.7, -3.2, 0.0), 0.4),
+        capsule(vec3(0.6, -1.6, 0.0), vec3(0.7, -3.2, 0.0), 0.4),
+    ];
+    let robot = |position: Vec3| {
+        let head = eyes
+            .iter()
+            .fold(head(position), |head, eye| head.min(-eye(position)));
+        [
+            torso(position),
+            head,
+            antenna(position),
+            antenna_tip(position),
+            arms[0](position),
+            arms[1](position),
+            legs[0](position),
+            legs[1](position),
+        ]
+        .into_iter()
+        .fold(Float::NEG_INFINITY, Float::max)
+    };
+    let mut domain = cube_domain(4.0, 64, 0.0);
+    let result = domain.march_tetrahedras(&robot, &Bisection);
+    // Keep the edges of the boxes crisp when shaded smooth.
+    let mesh = domain.meshes.pop().unwrap();
+    domain.meshes.push(mesh.split_sharp_edges(0.5));
+    (domain, result)
+}
+
+/// Rolling hills extracted as blocks.
+fn terrain_chunk() -> (Domain, MarchResult) {
+    let terrain = |position: Vec3| {
+        let height = (position.x * 0.35).sin() * 1.5
+            + (position.z * 0.25 + position.x * 0.1).cos() * 2.0
+            + (position.x * 0.9 + position.z * 1.1).sin() * 0.4;
+        height - position.y
+    };
+    let mut domain = Domain {
+        from: vec3(-16.0, -6.0, -16.0),
+        to: vec3(16.0, 6.0, 16.0),
+        surface_weight: 0.0,
+        width: 32,
+        height: 12,
+        depth: 32,
+        grid_offset: Vec3::default(),
+        meshes: Vec::new(),
+    };
+    let result = domain.extract(ExtractionMethod::Blocky, &terrain, &Bisection);
+    (domain, result)
+}
+
+/// Gyroid lattice clipped to a cube, with texture coordinates and tangents.
+fn gyroid_cube() -> (Domain, MarchResult) {
+    let bounds = cuboid(Vec3::default(), vec3(3.0, 3.0, 3.0));
+    let gyroid = |position: Vec3| {
+        let scale = 2.0;
+        let (x, y, z) = (position.x * scale, position.y * scale, position.z * scale);
+        let gyroid = x.sin() * y.cos() + y.sin() * z.cos() + z.sin() * x.cos();
+        (0.4 - gyroid.abs()).min(bounds(position))
+    };
+    let mut domain = cube_domain(3.5, 48, 0.0);
+    let result = domain.march_tetrahedras(&gyroid, &LinearInterp);
+    let mesh = domain.meshes.last_mut().unwrap();
+    mesh.compute_tangents(&gyroid, 0.001, UvProjection::Triplanar { scale: 0.5 });
+    (domain, result)
+}
+
+/// Femur like bone voxelized into a grid and smoothed, as if it came from a scan.
+fn scanned_bone() -> (Domain, MarchResult) {
+    let shaft = capsule(vec3(0.0, -6.0, 0.0), vec3(0.0, 6.0, 0.0), 1.0);
+    let knobs = [
+        sphere(vec3(-0.9, 6.5, 0.0), 1.6),
+        sphere(vec3(0.9, 6.5, 0.0), 1.6),
+        sphere(vec3(-0.9, -6.5, 0.0), 1.6),
+        sphere(vec3(0.9, -6.5, 0.0), 1.6),
+    ];
+    let size = [24, 64, 16];
+    let (from, to) = (vec3(-4.0, -9.0, -3.0), vec3(4.0, 9.0, 3.0));
+    let mut grid = VoxelGrid {
+        from,
+        to,
+        width: size[0],
+        height: size[1],
+        depth: size[2],
+        values: Vec::with_capacity(size[0] * size[1] * size[2]),
+    };
+    for z in 0..size[2] {
+        for y in 0..size[1] {
+            for x in 0..size[0] {
+                let voxel_center = |index: usize, count: usize, from: Float, to: Float| {
+                    from + (index as Float + 0.5) / count as Float * (to - from)
+                };
+                let position = vec3(
+                    voxel_center(x, size[0], from.x, to.x),
+                    voxel_center(y, size[1], from.y, to.y),
+                    voxel_center(z, size[2], from.z, to.z),
+                );
+                let inside = knobs.iter().any(|knob| knob(position) > 0.0) || shaft(position) > 0.0;
+                grid.values.push(if inside { 1.0 } else { 0.0 });
+            }
+        }
+    }
+    grid.box_blur();
+    grid.box_blur();
+    let mut domain = Domain {
+        from,
+        to,
+        surface_weight: 0.5,
+        width: 48,
+        height: 128,
+        depth: 32,
+        grid_offset: Vec3::default(),
+        meshes: Vec::new(),
+    };
+    let result = domain.march_tetrahedras(&grid, &Bisection);
+    (domain, result)
+}
+
+type Showcase = fn() -> (Domain, MarchResult);
+
+const SHOWCASES: [(&str, Showcase); 5] = [
+    ("metaballs", metaballs),
+    ("csg_robot", csg_robot),
+    ("terrain_chunk", terrain_chunk),
+    ("gyroid_cube", gyroid_cube),
+    ("scanned_bone", scanned_bone),
+];
+
+fn main() -> io::Result<ExitCode> {
+    let mut output_directory = None;
+    let mut format = ExportFormat::Bpy;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--format" {
+            let name = args.next().unwrap_or_default();
+            let Some(parsed) = ExportFormat::from_name(&name) else {
+                eprintln!("unknown format `{name}`");
+                return Ok(ExitCode::FAILURE);
+            };
+            format = parsed;
+        } else {
+            output_directory = Some(PathBuf::from(arg));
+        }
+    }
+    let Some(output_directory) = output_directory else {
+        eprintln!("usage: gallery <output directory> [--format bpy|obj|off|stl|stl-ascii|ply|glb]");
+        return Ok(ExitCode::FAILURE);
+    };
+    fs::create_dir_all(&output_directory)?;
+
+    let mut failed = false;
+    for (name, showcase) in SHOWCASES {
+        let (domain, result) = showcase();
+        if let Some(diagnosis) = result.empty_diagnosis {
+            eprintln!("{name}: {diagnosis}");
+            failed = true;
+            continue;
+        }
+        let path = output_directory.join(format!("{name}.{}", format.extension()));
+        domain.export_to_file(&path, format)?;
+        let mesh = domain.meshes.last().unwrap();
+        println!(
+            "{name}: {} vertices, {} faces -> {}",
+            mesh.verts.len(),
+            mesh.faces.len(),
+            path.display()
+        );
+        println!("  extraction: {}", result.stats);
+    }
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
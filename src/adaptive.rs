@@ -0,0 +1,516 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    Domain, EdgeKey, Float, GRID_TO_TETRAHEDRA_VERTICES, IVec3, RefineStrategy, ScalarField, Vec3,
+    builder::MeshBuilder, field::gradient, get_vert_offsets, grid::MinMax, limits::SizeError,
+    result::MarchResult, tetrahedra_faces,
+};
+
+/// Position in half cells from the lowest corner of the domain, so the centers of nodes and
+/// their faces are on the lattice too.
+type Half = [i32; 3];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Node {
+    /// Marched as a single cell.
+    Leaf,
+    /// The surface doesn't pass through the node.
+    Empty,
+    Split,
+}
+
+/// Octree over the cells of a domain, nodes are keyed by their lowest corner and size.
+struct Octree<'a, FIELD: ?Sized> {
+    domain: &'a Domain,
+    field: &'a FIELD,
+    max_error: Float,
+    lattice_offset: IVec3,
+    /// Highest corner of the domain.
+    end: Half,
+    nodes: HashMap<(Half, i32), Node>,
+    leaves: Vec<(Half, i32)>,
+    /// Corners of the leaf and empty nodes, other nodes split the sides of faces at them.
+    corners: HashSet<Half>,
+    samples: HashMap<Half, Float>,
+    range: MinMax,
+}
+
+impl<FIELD> Octree<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn position(&self, half: Half) -> Vec3 {
+        let domain = self.domain;
+        Vec3 {
+            x: domain.from.x
+                + (half[0] as Float * 0.5 - domain.grid_offset.x) * (domain.to.x - domain.from.x)
+                    / domain.width as Float,
+            y: domain.from.y
+                + (half[1] as Float * 0.5 - domain.grid_offset.y) * (domain.to.y - domain.from.y)
+                    / domain.height as Float,
+            z: domain.from.z
+                + (half[2] as Float * 0.5 - domain.grid_offset.z) * (domain.to.z - domain.from.z)
+                    / domain.depth as Float,
+        }
+    }
+
+    fn sample(&mut self, half: Half) -> Float {
+        if let Some(weight) = self.samples.get(&half) {
+            return *weight;
+        }
+        let weight = self.field.sample(self.position(half));
+        self.samples.insert(half, weight);
+        weight
+    }
+
+    /// Classify the node and its descendants.
+    fn build(&mut self, from: Half, size: i32) {
+        if (0..3).any(|axis| from[axis] >= self.end[axis]) {
+            return;
+        }
+        let to = [0, 1, 2].map(|axis| from[axis].saturating_add(size).min(self.end[axis]));
+        let clipped = (0..3).any(|axis| to[axis] - from[axis] != size);
+
+        let mut node_range = MinMax::EMPTY;
+        for corner in crate::GRID_TO_VERT_OFFSETS {
+            let weight = self.sample(corner_of(from, to, corner));
+            node_range.include(MinMax {
+                min: weight,
+                max: weight,
+            });
+        }
+        let surface_weight = self.domain.surface_weight;
+        let node = if size == 2 {
+            self.range.include(node_range);
+            if node_range.may_contain_surface(surface_weight) {
+                Node::Leaf
+            } else {
+                Node::Empty
+            }
+        } else {
+            let center = [0, 1, 2].map(|axis| (from[axis] + to[axis]) / 2);
+            let center_weight = self.sample(center);
+            node_range.include(MinMax {
+                min: center_weight,
+                max: center_weight,
+            });
+            self.range.include(node_range);
+            let (node_from, node_to) = (self.position(from), self.position(to));
+            let half_diagonal = (node_to - node_from).length() * 0.5;
+            let step = half_diagonal * 2.0 / size as Float;
+            let slope = gradient(self.field, self.position(center), step).length();
+            if !node_range.may_contain_surface(surface_weight)
+                && (center_weight - surface_weight).abs() > 2.0 * slope * half_diagonal
+            {
+                Node::Empty
+            } else if !clipped && self.is_flat(from, size, slope) {
+                Node::Leaf
+            } else {
+                Node::Split
+            }
+        };
+        self.nodes.insert((from, size), node);
+        match node {
+            Node::Split => {
+                let half = size / 2;
+                for offset in crate::GRID_TO_VERT_OFFSETS {
+                    let child = [
+                        from[0] + offset.x * half,
+                        from[1] + offset.y * half,
+                        from[2] + offset.z * half,
+                    ];
+                    self.build(child, half);
+                }
+            }
+            Node::Leaf | Node::Empty => {
+                let to = [0, 1, 2].map(|axis| from[axis].saturating_add(size));
+                for corner in crate::GRID_TO_VERT_OFFSETS {
+                    self.corners.insert(corner_of(from, to, corner));
+                }
+                if node == Node::Leaf {
+                    self.leaves.push((from, size));
+                }
+            }
+        }
+    }
+
+    /// Whether the field at the center of the node and its faces is within `max_error` of the
+    /// trilinear interpolation of the corners, measured along the gradient.
+    fn is_flat(&mut self, from: Half, size: i32, slope: Float) -> bool {
+        let to = from.map(|coordinate| coordinate + size);
+        let corners =
+            crate::GRID_TO_VERT_OFFSETS.map(|corner| self.sample(corner_of(from, to, corner)));
+        let half = size / 2;
+        // Offsets of the center and face centers, with the corners they interpolate between.
+        let mut points = vec![([half; 3], (0..8).collect::<Vec<usize>>())];
+        for axis in 0..3 {
+            for side in [0, 1] {
+                let mut offset = [half; 3];
+                offset[axis] = side * size;
+                let face_corners = (0..8)
+                    .filter(|corner| {
+                        let corner = crate::GRID_TO_VERT_OFFSETS[*corner];
+                        [corner.x, corner.y, corner.z][axis] == side
+                    })
+                    .collect();
+                points.push((offset, face_corners));
+            }
+        }
+        points.into_iter().all(|(offset, interpolated)| {
+            let weight = self.sample([0, 1, 2].map(|axis| from[axis] + offset[axis]));
+            let linear = interpolated
+                .iter()
+                .map(|corner| corners[*corner])
+                .sum::<Float>()
+                / interpolated.len() as Float;
+            (weight - linear).abs() <= self.max_error * slope
+        })
+    }
+
+    /// March the tetrahedra between the center of each leaf and the triangles of its faces.
+    fn polygonize<REFINE>(&mut self, mesh: &mut MeshBuilder, refine: &REFINE)
+    where
+        REFINE: RefineStrategy,
+    {
+        for (from, size) in std::mem::take(&mut self.leaves) {
+            if size == 2 {
+                // Cells use the tetrahedra of [Domain::march_tetrahedras], their faces are split
+                // along the same diagonals as in [Octree::square_triangles].
+                let lattice_cell =
+                    ivec(from.map(|coordinate| coordinate / 2)) + self.lattice_offset;
+                let (offsets, grid_inverse) = get_vert_offsets(lattice_cell);
+                let corners = offsets.map(|offset| {
+                    [
+                        from[0] + offset.x * 2,
+                        from[1] + offset.y * 2,
+                        from[2] + offset.z * 2,
+                    ]
+                });
+                self.march_tetrahedra(
+                    mesh,
+                    refine,
+                    &corners,
+                    grid_inverse,
+                    &GRID_TO_TETRAHEDRA_VERTICES,
+                );
+                continue;
+            }
+            let center = from.map(|coordinate| coordinate + size / 2);
+            for axis in 0..3 {
+                for side in [0, 1] {
+                    let mut origin = from;
+                    origin[axis] += side * size;
+                    let mut squares = Vec::new();
+                    self.face_squares(axis, side == 1, origin, size, &mut squares);
+                    for (origin, size) in squares {
+                        for [a, b, c] in self.square_triangles(axis, origin, size) {
+                            self.march_tetrahedron(mesh, refine, [center, a, b, c]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Split the face of a node with the given lowest corner and size into the faces of the
+    /// smaller nodes on the other side, so both sides use the same triangles.
+    fn face_squares(
+        &self,
+        axis: usize,
+        positive: bool,
+        origin: Half,
+        size: i32,
+        squares: &mut Vec<(Half, i32)>,
+    ) {
+        let mut across = origin;
+        if !positive {
+            across[axis] -= size;
+        }
+        if self.nodes.get(&(across, size)) != Some(&Node::Split) {
+            squares.push((origin, size));
+            return;
+        }
+        let half = size / 2;
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        for (du, dv) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let mut child = origin;
+            child[u] += du * half;
+            child[v] += dv * half;
+            self.face_squares(axis, positive, child, half, squares);
+        }
+    }
+
+    /// Triangles covering a face square. The corners of smaller nodes along its sides are
+    /// included, squares with such T-junctions are split into a fan around their center.
+    fn square_triangles(&self, axis: usize, origin: Half, size: i32) -> Vec<[Half; 3]> {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let corner = |du: i32, dv: i32| {
+            let mut corner = origin;
+            corner[u] += du * size;
+            corner[v] += dv * size;
+            corner
+        };
+        let corners = [corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1)];
+        let mut ring = Vec::with_capacity(4);
+        for index in 0..4 {
+            ring.push(corners[index]);
+            self.split_side(corners[index], corners[(index + 1) % 4], &mut ring);
+        }
+        if ring.len() == 4 {
+            // Split along the diagonal between the corners with an even sum of lattice
+            // coordinates, like the tetrahedra of [Domain::march_tetrahedras] split cells.
+            let offset = self.lattice_offset;
+            let sum = ring[0]
+                .iter()
+                .map(|coordinate| coordinate / size)
+                .sum::<i32>()
+                + offset.x
+                + offset.y
+                + offset.z;
+            return if sum.rem_euclid(2) == 0 {
+                vec![[ring[0], ring[1], ring[2]], [ring[0], ring[2], ring[3]]]
+            } else {
+                vec![[ring[1], ring[2], ring[3]], [ring[1], ring[3], ring[0]]]
+            };
+        }
+        let mut center = origin;
+        center[u] += size / 2;
+        center[v] += size / 2;
+        (0..ring.len())
+            .map(|index| [center, ring[index], ring[(index + 1) % ring.len()]])
+            .collect()
+    }
+
+    /// Push the node corners strictly between `from` and `to` in order. Nodes are aligned to
+    /// their size, so a side only has corners past its midpoint when the midpoint is one.
+    fn split_side(&self, from: Half, to: Half, ring: &mut Vec<Half>) {
+        let mid = [0, 1, 2].map(|axis| (from[axis] + to[axis]) / 2);
+        if (0..3)
+            .map(|axis| (to[axis] - from[axis]).abs())
+            .sum::<i32>()
+            <= 2
+            || !self.corners.contains(&mid)
+        {
+            return;
+        }
+        self.split_side(from, mid, ring);
+        ring.push(mid);
+        self.split_side(mid, to, ring);
+    }
+
+    /// March a tetrahedron, ordering its corners to have a positive volume.
+    fn march_tetrahedron<REFINE>(
+        &mut self,
+        mesh: &mut MeshBuilder,
+        refine: &REFINE,
+        mut corners: [Half; 4],
+    ) where
+        REFINE: RefineStrategy,
+    {
+        let edge = |corner: Half| [0, 1, 2].map(|axis| (corner[axis] - corners[0][axis]) as i64);
+        let (a, b, c) = (edge(corners[1]), edge(corners[2]), edge(corners[3]));
+        let volume = a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+            + a[2] * (b[0] * c[1] - b[1] * c[0]);
+        if volume < 0 {
+            corners.swap(2, 3);
+        }
+        self.march_tetrahedra(mesh, refine, &corners, false, &[[0, 1, 2, 3]]);
+    }
+
+    /// March the `tetrahedra` between `corners`, see [tetrahedra_faces].
+    fn march_tetrahedra<REFINE>(
+        &mut self,
+        mesh: &mut MeshBuilder,
+        refine: &REFINE,
+        corners: &[Half],
+        grid_inverse: bool,
+        tetrahedra: &[[usize; 4]],
+    ) where
+        REFINE: RefineStrategy,
+    {
+        let weights = corners
+            .iter()
+            .map(|corner| self.sample(*corner))
+            .collect::<Vec<Float>>();
+        let positions = corners
+            .iter()
+            .map(|corner| self.position(*corner))
+            .collect::<Vec<Vec3>>();
+        let is_inside = weights
+            .iter()
+            .map(|weight| self.domain.is_inside(*weight))
+            .collect::<Vec<bool>>();
+        let (field, surface_weight) = (self.field, self.domain.surface_weight);
+        tetrahedra_faces(
+            &is_inside,
+            grid_inverse,
+            tetrahedra,
+            &mut |_, edges, flip| {
+                let verts = edges.map(|[a, b]| {
+                    let key = EdgeKey::new(ivec(corners[a]), ivec(corners[b]));
+                    mesh.vertex(key, || {
+                        refine.refine_with_weights(
+                            positions[a],
+                            weights[a],
+                            positions[b],
+                            weights[b],
+                            field,
+                            surface_weight,
+                        )
+                    })
+                });
+                if flip {
+                    mesh.face(verts[0], verts[2], verts[1]);
+                } else {
+                    mesh.face(verts[0], verts[1], verts[2]);
+                }
+            },
+        );
+    }
+}
+
+/// Corner of the box from `from` to `to`, `offset` is one of [crate::GRID_TO_VERT_OFFSETS].
+fn corner_of(from: Half, to: Half, offset: IVec3) -> Half {
+    [
+        if offset.x == 0 { from[0] } else { to[0] },
+        if offset.y == 0 { from[1] } else { to[1] },
+        if offset.z == 0 { from[2] } else { to[2] },
+    ]
+}
+
+fn ivec(half: Half) -> IVec3 {
+    IVec3 {
+        x: half[0],
+        y: half[1],
+        z: half[2],
+    }
+}
+
+impl Domain {
+    /// March the field using octrees over the cells of the domain, only descending into nodes
+    /// the surface may pass through and marching flat parts of the surface with larger cells.
+    ///
+    /// The domain is covered by root nodes of `2^depth` cells along each axis. A node is
+    /// skipped when its corners and center are on the same side of the surface, unless the
+    /// weight at the center is closer to the surface weight than twice the gradient allows over
+    /// half the diagonal of the node. A node is marched as a single cell when the field at its
+    /// center and the centers of its faces is within `max_error` (in domain units, estimated
+    /// with the gradient) of the trilinear interpolation of its corners, otherwise it is
+    /// subdivided down to the cells of the domain.
+    ///
+    /// Every marched node is split into tetrahedra from its center to the triangles of its
+    /// faces. Faces next to smaller nodes are split into the faces of those nodes, and the
+    /// corners of smaller nodes along their sides are included, so nodes of different sizes
+    /// share their border vertices and the mesh has no cracks.
+    ///
+    /// The skipping is a heuristic: features smaller than a node that barely change the weight
+    /// at the tested positions can be missed, lower `depth` for fields with small details. Use
+    /// [Domain::march_tetrahedras_bounded] when the field can provide bounds. The vertices
+    /// aren't all generated on lattice edges, so the mesh has no vertex keys and can't be
+    /// welded with [crate::Mesh::weld_with].
+    ///
+    /// Fails when `depth` exceeds [crate::limits::MAX_OCTREE_DEPTH] or the domain is too large
+    /// for the octree.
+    pub fn march_adaptive<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+        depth: u32,
+        max_error: Float,
+    ) -> Result<MarchResult, SizeError>
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let root_size = self.octree_root_size(depth)?;
+        let cell_count = self.vertex_grid_size();
+        let mut octree = Octree {
+            domain: self,
+            field,
+            max_error,
+            lattice_offset: self.lattice_offset(),
+            end: [cell_count.x * 2, cell_count.y * 2, cell_count.z * 2],
+            nodes: HashMap::new(),
+            leaves: Vec::new(),
+            corners: HashSet::new(),
+            samples: HashMap::new(),
+            range: MinMax::EMPTY,
+        };
+        for x in (0..octree.end[0]).step_by(root_size as usize) {
+            for y in (0..octree.end[1]).step_by(root_size as usize) {
+                for z in (0..octree.end[2]).step_by(root_size as usize) {
+                    octree.build([x, y, z], root_size);
+                }
+            }
+        }
+        let mut mesh = MeshBuilder::default();
+        octree.polygonize(&mut mesh, refine);
+
+        let range = octree.range;
+        let mut mesh = mesh.finish();
+        mesh.vert_keys.clear();
+        let result = MarchResult::new(&mesh, range.min, range.max, self.surface_weight);
+        self.meshes.push(mesh);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Bisection, Domain, Float, Vec3, limits::SizeError};
+
+    fn domain(resolution: usize) -> Domain {
+        Domain {
+            from: Vec3 {
+                x: -3.0,
+                y: -3.0,
+                z: -3.0,
+            },
+            to: Vec3 {
+                x: 3.0,
+                y: 3.0,
+                z: 3.0,
+            },
+            surface_weight: 0.0,
+            width: resolution,
+            height: resolution,
+            depth: resolution,
+            grid_offset: Vec3::default(),
+            meshes: Vec::new(),
+        }
+    }
+
+    fn sphere(position: Vec3) -> Float {
+        2.5 - position.length()
+    }
+
+    #[test]
+    fn depth_is_checked() {
+        let mut domain = domain(8);
+        assert_eq!(
+            domain.march_adaptive(&sphere, &Bisection, 40, 0.01).err(),
+            Some(SizeError::OctreeDepth { depth: 40 })
+        );
+        assert!(domain.meshes.is_empty());
+    }
+
+    #[test]
+    fn cells_match_tetrahedra() {
+        let mut domain = domain(16);
+        domain.march_tetrahedras(&sphere, &Bisection);
+        domain.march_adaptive(&sphere, &Bisection, 0, 0.0).unwrap();
+        assert_eq!(domain.meshes[1].faces.len(), domain.meshes[0].faces.len());
+    }
+
+    #[test]
+    fn flat_nodes_are_patched() {
+        let mut domain = domain(37);
+        domain.march_tetrahedras(&sphere, &Bisection);
+        domain.march_adaptive(&sphere, &Bisection, 4, 0.05).unwrap();
+        let (uniform, adaptive) = (&domain.meshes[0], &domain.meshes[1]);
+        assert!(adaptive.faces.len() * 2 < uniform.faces.len());
+        // Nodes of different sizes share their border vertices, the sphere stays closed.
+        assert!(adaptive.validate().is_valid());
+        assert!(adaptive.vert_keys.is_empty());
+    }
+}
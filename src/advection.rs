@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use crate::{Float, IVec3, Mesh, ScalarField, Vec3, field::project::project_with_step};
+
+/// How [SurfaceParticles::advect] moves the particles to the surface of the next frame.
+#[derive(Clone, Copy)]
+pub enum Motion<'a> {
+    /// Only follow the surface along the gradient of the field. Fits fields that grow, shrink
+    /// or morph in place; sliding along the surface isn't captured.
+    GradientFlow,
+    /// Move with a velocity per position in units per time step, then follow the surface along
+    /// the gradient. Fits fields with known motion, like metaballs moving around.
+    Velocity(&'a dyn Fn(Vec3) -> Vec3),
+}
+
+/// Points on the surface carrying texture coordinates from frame to frame, so textures stick
+/// to a deforming surface instead of being projected anew every frame.
+///
+/// Create the particles once with [SurfaceParticles::from_mesh]. Then each frame:
+/// [SurfaceParticles::advect] with the field of the new frame, extract the new mesh and
+/// [SurfaceParticles::bake] the coordinates into it. The particles persist between frames, so
+/// the lookup error doesn't accumulate.
+#[derive(Debug, Default, Clone)]
+pub struct SurfaceParticles {
+    pub positions: Vec<Vec3>,
+    pub uvs: Vec<[Float; 2]>,
+}
+
+impl SurfaceParticles {
+    /// One particle per vertex of a mesh with texture coordinates, see
+    /// [Mesh::compute_tangents] to create the coordinates of the first frame.
+    pub fn from_mesh(mesh: &Mesh) -> SurfaceParticles {
+        assert_eq!(
+            mesh.verts.len(),
+            mesh.uvs.len(),
+            "mesh needs texture coordinates"
+        );
+        SurfaceParticles {
+            positions: mesh.verts.clone(),
+            uvs: mesh.uvs.clone(),
+        }
+    }
+
+    /// Move the particles with `motion` and pull them onto the surface of `field` using
+    /// `iterations` Newton steps along the gradient, see [crate::field::project_to_surface].
+    /// `gradient_step` is the step used to estimate the gradient, a fraction of the cell size
+    /// works well. Particles that can't be projected stay where the motion moved them.
+    pub fn advect<FIELD>(
+        &mut self,
+        field: &FIELD,
+        surface_weight: Float,
+        motion: Motion,
+        gradient_step: Float,
+        iterations: usize,
+    ) where
+        FIELD: ScalarField + ?Sized,
+    {
+        for position in &mut self.positions {
+            if let Motion::Velocity(velocity) = motion {
+                *position = *position + velocity(*position);
+            }
+            if let Some(projected) =
+                project_with_step(*position, field, surface_weight, iterations, gradient_step)
+            {
+                *position = projected;
+            }
+        }
+    }
+
+    /// Set the texture coordinates of every vertex of `mesh` to the ones of the nearest
+    /// particle. `cell_size` is the cell size of the domain that generated the mesh and is used
+    /// to look up neighbours. Tangents of the mesh are cleared as they no longer match.
+    ///
+    /// Vertices without a particle within a cell, on surface that appeared this frame, get a new
+    /// particle with the coordinates of the nearest existing one.
+    pub fn bake(&mut self, mesh: &mut Mesh, cell_size: Float) {
+        mesh.tangents.clear();
+        mesh.uvs.clear();
+        if self.positions.is_empty() {
+            return;
+        }
+        let cell_of = |position: Vec3| IVec3 {
+            x: (position.x / cell_size).floor() as i32,
+            y: (position.y / cell_size).floor() as i32,
+            z: (position.z / cell_size).floor() as i32,
+        };
+        let mut cells = HashMap::<IVec3, Vec<usize>>::new();
+        for (index, position) in self.positions.iter().enumerate() {
+            cells.entry(cell_of(*position)).or_default().push(index);
+        }
+
+        let distance_squared = |index: usize, vert: Vec3| {
+            let offset = self.positions[index] - vert;
+            offset.dot(offset)
+        };
+        let mut spawned = Vec::new();
+        for vert in &mesh.verts {
+            let cell = cell_of(*vert);
+            let mut nearest = None::<(usize, Float)>;
+            for x in -1..=1 {
+                for y in -1..=1 {
+                    for z in -1..=1 {
+                        let neighbour = IVec3 {
+                            x: cell.x + x,
+                            y: cell.y + y,
+                            z: cell.z + z,
+                        };
+                        for &index in cells.get(&neighbour).into_iter().flatten() {
+                            let distance = distance_squared(index, *vert);
+                            if nearest.is_none_or(|(_, nearest)| distance < nearest) {
+                                nearest = Some((index, distance));
+                            }
+                        }
+                    }
+                }
+            }
+            let uv = match nearest {
+                Some((index, distance)) if distance <= cell_size * cell_size => self.uvs[index],
+                _ => {
+                    let index = (0..self.positions.len())
+                        .min_by(|a, b| {
+                            distance_squared(*a, *vert).total_cmp(&distance_squared(*b, *vert))
+                        })
+                        .unwrap();
+                    spawned.push((*vert, self.uvs[index]));
+                    self.uvs[index]
+                }
+            };
+            mesh.uvs.push(uv);
+        }
+        for (position, uv) in spawned {
+            self.positions.push(position);
+            self.uvs.push(uv);
+        }
+    }
+}
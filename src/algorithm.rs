@@ -0,0 +1,313 @@
+//! Extension point for extraction algorithms.
+//!
+//! The domain samples the field once per lattice vertex and hands each cell with its corner
+//! weights to an [ExtractionAlgorithm]. The algorithm emits vertices and faces into a
+//! [MeshSink], which welds vertices by [EdgeKey], so new algorithms reuse the sampling, welding,
+//! result reporting and exporters of the crate. [MarchingTetrahedra] is the algorithm behind
+//! [Domain::march_tetrahedras], [SixTetrahedra] splits the cells without mirroring them.
+//!
+//! Engines with their own voxel storage can call [march_cell] on single cells without a
+//! [Domain].
+
+use std::ops::ControlFlow;
+
+use crate::{
+    Domain, EdgeKey, Float, GRID_TO_SIX_TETRAHEDRA_VERTICES, GRID_TO_TETRAHEDRA_VERTICES,
+    GRID_TO_VERT_OFFSETS, IVec3, RefineStrategy, ScalarField, Vec3, builder::MeshBuilder,
+    get_vert_offsets, result::MarchResult, tetrahedra_faces,
+};
+
+/// Cell of the domain with the field sampled at its corners.
+///
+/// Corners are in [crate::GRID_TO_VERT_OFFSETS] order: corner `i` is the lattice vertex
+/// `position + GRID_TO_VERT_OFFSETS[i]`.
+#[derive(Debug, Clone)]
+pub struct Cell {
+    /// Index of the cell within the domain.
+    pub position: IVec3,
+    /// Index of the cell on the global lattice, see [Domain::lattice_offset]. Use it to build
+    /// [EdgeKey]s so vertices are shared with neighboring cells and chunks.
+    pub lattice_position: IVec3,
+    pub corner_positions: [Vec3; 8],
+    pub corner_weights: [Float; 8],
+}
+
+impl Cell {
+    /// Index of the corner at `offset` from the cell, each component is 0 or 1.
+    pub fn corner_index(offset: IVec3) -> usize {
+        (offset.z * 4) as usize + [0, 1, 3, 2][(offset.y * 2 + offset.x) as usize]
+    }
+}
+
+/// Receives the output of an [ExtractionAlgorithm].
+pub struct MeshSink<'a> {
+    pub(crate) builder: &'a mut MeshBuilder,
+}
+
+impl MeshSink<'_> {
+    /// Index of the vertex identified by `key`. `position` is only called the first time the key
+    /// is used, later calls from this or neighboring cells share the vertex.
+    pub fn vertex<POSITION>(&mut self, key: EdgeKey, position: POSITION) -> usize
+    where
+        POSITION: FnOnce() -> Vec3,
+    {
+        self.builder.vertex(key, position)
+    }
+
+    /// Add a face. Vertices are in counter-clockwise order when looking at the outside.
+    pub fn face(&mut self, v1: usize, v2: usize, v3: usize) {
+        self.builder.face(v1, v2, v3);
+    }
+}
+
+/// Turns the sampled cells of a domain into faces, see [Domain::march_with_algorithm].
+pub trait ExtractionAlgorithm {
+    /// Emit the faces of a single cell into `sink`. `field` and `refine` can be used to place
+    /// vertices between the corners.
+    fn polygonize<FIELD, REFINE>(
+        &self,
+        domain: &Domain,
+        cell: &Cell,
+        field: &FIELD,
+        refine: &REFINE,
+        sink: &mut MeshSink,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy;
+}
+
+/// Split every cell into 5 tetrahedra, see [Domain::march_tetrahedras].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MarchingTetrahedra;
+
+impl ExtractionAlgorithm for MarchingTetrahedra {
+    fn polygonize<FIELD, REFINE>(
+        &self,
+        domain: &Domain,
+        cell: &Cell,
+        field: &FIELD,
+        refine: &REFINE,
+        sink: &mut MeshSink,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        // Alternating cells are mirrored so the tetrahedra of neighbors share their faces.
+        let (offsets, _) = get_vert_offsets(cell.lattice_position);
+        let corners = offsets.map(Cell::corner_index);
+        let vert_positions = corners.map(|corner| cell.corner_positions[corner]);
+        let vert_weights = corners.map(|corner| cell.corner_weights[corner]);
+        domain.polygonize_cell(
+            sink.builder,
+            &vert_positions,
+            &vert_weights,
+            cell.position,
+            field,
+            refine,
+        );
+    }
+}
+
+/// How cells are split into tetrahedra, see [Domain::march_tetrahedras_with_decomposition].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Decomposition {
+    /// 5 tetrahedra, see [MarchingTetrahedra]. Alternating cells are mirrored so the diagonals
+    /// on the faces of neighbors match.
+    #[default]
+    Five,
+    /// 6 tetrahedra around the diagonal from the lowest to the highest corner (the Freudenthal
+    /// decomposition), see [SixTetrahedra]. Every cell is split the same way, so the mesh has
+    /// the same shape on every cell, at the cost of more and thinner triangles.
+    Six,
+}
+
+/// Split every cell into 6 tetrahedra with the same orientation, see [Decomposition::Six].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SixTetrahedra;
+
+impl ExtractionAlgorithm for SixTetrahedra {
+    fn polygonize<FIELD, REFINE>(
+        &self,
+        domain: &Domain,
+        cell: &Cell,
+        field: &FIELD,
+        refine: &REFINE,
+        sink: &mut MeshSink,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let vert_is_inside = cell.corner_weights.map(|weight| domain.is_inside(weight));
+        tetrahedra_faces(
+            &vert_is_inside,
+            false,
+            &GRID_TO_SIX_TETRAHEDRA_VERTICES,
+            &mut |_, edges, flip| {
+                let verts = edges.map(|[a, b]| {
+                    let key = EdgeKey::new(
+                        cell.lattice_position + GRID_TO_VERT_OFFSETS[a],
+                        cell.lattice_position + GRID_TO_VERT_OFFSETS[b],
+                    );
+                    sink.vertex(key, || {
+                        refine.refine_with_weights(
+                            cell.corner_positions[a],
+                            cell.corner_weights[a],
+                            cell.corner_positions[b],
+                            cell.corner_weights[b],
+                            field,
+                            domain.surface_weight,
+                        )
+                    })
+                });
+                if flip {
+                    sink.face(verts[0], verts[2], verts[1]);
+                } else {
+                    sink.face(verts[0], verts[1], verts[2]);
+                }
+            },
+        );
+    }
+}
+
+/// Receives the output of [march_cell].
+pub trait CellSink {
+    /// Index of the vertex at `position` on the cell edge between the corners `edge[0]` and
+    /// `edge[1]`, in [crate::GRID_TO_VERT_OFFSETS] order with the lowest corner first. Each edge
+    /// is passed once per cell; map it to the vertex of the neighboring cell that uses the same
+    /// edge to get a welded mesh.
+    fn vertex(&mut self, edge: [usize; 2], position: Vec3) -> usize;
+
+    /// Add a face. Vertices are in counter-clockwise order when looking at the outside.
+    fn face(&mut self, v1: usize, v2: usize, v3: usize);
+}
+
+/// Polygonize a single cell with marching tetrahedra, without a [Domain] or field.
+///
+/// Corners are in [crate::GRID_TO_VERT_OFFSETS] order, corners with a weight above `iso` are
+/// inside. `parity` tells whether the index of the cell along x, y and z is odd: alternating
+/// cells are split into mirrored tetrahedra, so neighbors only share their faces when the parity
+/// follows the position of the cells. Vertices are placed by interpolating the corner weights
+/// linearly.
+///
+/// ```
+/// use marching_cubes::{Vec3, algorithm::{CellSink, march_cell}};
+///
+/// #[derive(Default)]
+/// struct Triangles(Vec<Vec3>, Vec<[usize; 3]>);
+///
+/// impl CellSink for Triangles {
+///     fn vertex(&mut self, _edge: [usize; 2], position: Vec3) -> usize {
+///         self.0.push(position);
+///         self.0.len() - 1
+///     }
+///     fn face(&mut self, v1: usize, v2: usize, v3: usize) {
+///         self.1.push([v1, v2, v3]);
+///     }
+/// }
+///
+/// let corner_positions = marching_cubes::GRID_TO_VERT_OFFSETS.map(|offset| Vec3 {
+///     x: offset.x as _,
+///     y: offset.y as _,
+///     z: offset.z as _,
+/// });
+/// let corner_weights = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+/// let mut triangles = Triangles::default();
+/// march_cell(corner_positions, corner_weights, 0.5, [false; 3], &mut triangles);
+/// assert!(!triangles.1.is_empty());
+/// ```
+pub fn march_cell<SINK>(
+    corner_positions: [Vec3; 8],
+    corner_weights: [Float; 8],
+    iso: Float,
+    parity: [bool; 3],
+    sink: &mut SINK,
+) where
+    SINK: CellSink + ?Sized,
+{
+    let (offsets, grid_inverse) = get_vert_offsets(IVec3 {
+        x: parity[0] as i32,
+        y: parity[1] as i32,
+        z: parity[2] as i32,
+    });
+    let corners = offsets.map(Cell::corner_index);
+    let vert_is_inside = corners.map(|corner| corner_weights[corner] > iso);
+    // Vertex of every edge that was used by an earlier face of this cell.
+    let mut edge_verts = [[usize::MAX; 8]; 8];
+    tetrahedra_faces(
+        &vert_is_inside,
+        grid_inverse,
+        &GRID_TO_TETRAHEDRA_VERTICES,
+        &mut |_, edges, flip| {
+            let verts = edges.map(|[vert_1, vert_2]| {
+                let (a, b) = (
+                    corners[vert_1].min(corners[vert_2]),
+                    corners[vert_1].max(corners[vert_2]),
+                );
+                if edge_verts[a][b] == usize::MAX {
+                    let (weight_a, weight_b) = (corner_weights[a], corner_weights[b]);
+                    let factor = if weight_a != weight_b {
+                        ((iso - weight_a) / (weight_b - weight_a)).clamp(0.0, 1.0)
+                    } else {
+                        0.5
+                    };
+                    let position = corner_positions[a].lerp(corner_positions[b], factor);
+                    edge_verts[a][b] = sink.vertex([a, b], position);
+                }
+                edge_verts[a][b]
+            });
+            if flip {
+                sink.face(verts[0], verts[2], verts[1]);
+            } else {
+                sink.face(verts[0], verts[1], verts[2]);
+            }
+        },
+    );
+}
+
+impl Domain {
+    /// Extract the surface with a custom algorithm and add it as a new mesh.
+    pub fn march_with_algorithm<ALGORITHM, FIELD, REFINE>(
+        &mut self,
+        algorithm: &ALGORITHM,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        ALGORITHM: ExtractionAlgorithm,
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let mut mesh = MeshBuilder::default();
+        let origin = IVec3 { x: 0, y: 0, z: 0 };
+        let (min_weight, max_weight) = self.march_cells_using(
+            &mut mesh,
+            origin,
+            self.vertex_grid_size(),
+            algorithm,
+            field,
+            refine,
+            &mut |_, _| ControlFlow::Continue(()),
+        );
+        let mesh = mesh.finish();
+        let result = MarchResult::new(&mesh, min_weight, max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+
+    /// Same as [Domain::march_tetrahedras], splitting the cells with `decomposition`.
+    pub fn march_tetrahedras_with_decomposition<FIELD, REFINE>(
+        &mut self,
+        decomposition: Decomposition,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        match decomposition {
+            Decomposition::Five => self.march_tetrahedras(field, refine),
+            Decomposition::Six => self.march_with_algorithm(&SixTetrahedra, field, refine),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Mesh archives that are used straight from the bytes of a file.
+//!
+//! [Mesh::write_archive] serializes the vertices and faces with [rkyv] as arrays of little endian
+//! 32 bit values. [ArchivedMesh] validates the bytes once and reads from them without a
+//! deserialization pass, so cached chunks can be memory mapped and their arrays uploaded to the
+//! GPU as is.
+//!
+//! The bytes passed to [ArchivedMesh::new] must be aligned to 4 bytes, as memory maps and
+//! [rkyv::util::AlignedVec] are.
+
+// Archives store f32, the casts from `Float` are no-ops with the `f32` feature.
+#![allow(clippy::unnecessary_cast)]
+
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use rkyv::{
+    Archive, Serialize, rancor,
+    rend::{f32_le, u32_le},
+};
+
+use crate::{Edge, Face, Float, Mesh, Vec3, export::write_file_atomic};
+
+/// Content of an archive. Attributes are empty when the mesh doesn't have one for every vertex.
+#[derive(Archive, Serialize)]
+struct MeshData {
+    bounds: [[f32; 3]; 2],
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    tangents: Vec<[f32; 4]>,
+    indices: Vec<[u32; 3]>,
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn vec3_values(v: &Vec3) -> [f32; 3] {
+    [v.x as f32, v.y as f32, v.z as f32]
+}
+
+fn vec3(values: &[f32_le; 3]) -> Vec3 {
+    Vec3 {
+        x: values[0].to_native() as Float,
+        y: values[1].to_native() as Float,
+        z: values[2].to_native() as Float,
+    }
+}
+
+impl Mesh {
+    /// Write the mesh as an archive, see [crate::archive]. Normals, texture coordinates and
+    /// tangents are included when every vertex has one, vertex keys are not stored.
+    pub fn write_archive<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "mesh too large to archive");
+        u32::try_from(self.verts.len()).map_err(|_| too_large())?;
+        let has = |len: usize| len == self.verts.len();
+        let (min, max) = self.bounds().unwrap_or_default();
+        let data = MeshData {
+            bounds: [vec3_values(&min), vec3_values(&max)],
+            positions: self.verts.iter().map(vec3_values).collect(),
+            normals: match has(self.normals.len()) {
+                true => self.normals.iter().map(vec3_values).collect(),
+                false => Vec::new(),
+            },
+            uvs: match has(self.uvs.len()) {
+                true => self.uvs.iter().map(|uv| uv.map(|v| v as f32)).collect(),
+                false => Vec::new(),
+            },
+            tangents: match has(self.tangents.len()) {
+                true => self.tangents.iter().map(|t| t.map(|v| v as f32)).collect(),
+                false => Vec::new(),
+            },
+            indices: self
+                .faces
+                .iter()
+                .map(|face| [face.v1, face.v2, face.v3].map(|vert| vert as u32))
+                .collect(),
+        };
+        let bytes = rkyv::to_bytes::<rancor::Error>(&data)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error.to_string()))?;
+        writer.write_all(&bytes)
+    }
+
+    /// Write the mesh as an archive to `path`, see [write_file_atomic].
+    pub fn export_to_archive_file(&self, path: &Path) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_archive(writer))
+    }
+}
+
+/// Mesh archive read in place from its bytes, see [crate::archive].
+#[derive(Copy, Clone)]
+pub struct ArchivedMesh<'a> {
+    data: &'a ArchivedMeshData,
+}
+
+impl<'a> ArchivedMesh<'a> {
+    /// Validate `bytes` as an archive. The vertex indices of the faces are only checked by
+    /// [ArchivedMesh::to_mesh].
+    pub fn new(bytes: &'a [u8]) -> io::Result<ArchivedMesh<'a>> {
+        let data = rkyv::access::<ArchivedMeshData, rancor::Error>(bytes)
+            .map_err(|error| invalid_data(&format!("not a mesh archive: {error}")))?;
+        let vertex_count = data.positions.len();
+        let has = |len: usize| len == 0 || len == vertex_count;
+        if !(has(data.normals.len()) && has(data.uvs.len()) && has(data.tangents.len())) {
+            return Err(invalid_data(
+                "mesh archive attributes don't match its vertices",
+            ));
+        }
+        Ok(ArchivedMesh { data })
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.data.positions.len()
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.data.indices.len()
+    }
+
+    /// Bounds of the vertices as stored by [Mesh::write_archive], zero for empty meshes.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        (vec3(&self.data.bounds[0]), vec3(&self.data.bounds[1]))
+    }
+
+    pub fn vertex(&self, index: usize) -> Vec3 {
+        vec3(&self.data.positions[index])
+    }
+
+    pub fn normal(&self, index: usize) -> Option<Vec3> {
+        self.normals().map(|normals| vec3(&normals[index]))
+    }
+
+    pub fn face(&self, index: usize) -> Face {
+        let [v1, v2, v3] = self.data.indices[index].map(|vert| vert.to_native() as usize);
+        Face { v1, v2, v3 }
+    }
+
+    /// Positions as f32 xyz, ready to upload as a vertex buffer.
+    pub fn positions(&self) -> &'a [[f32_le; 3]] {
+        &self.data.positions
+    }
+
+    /// Normals as f32 xyz, `None` when the archive has no normals.
+    pub fn normals(&self) -> Option<&'a [[f32_le; 3]]> {
+        Some(&self.data.normals[..]).filter(|normals| !normals.is_empty())
+    }
+
+    /// Texture coordinates as f32 uv, `None` when the archive has none.
+    pub fn uvs(&self) -> Option<&'a [[f32_le; 2]]> {
+        Some(&self.data.uvs[..]).filter(|uvs| !uvs.is_empty())
+    }
+
+    /// Tangents as f32 xyzw, `None` when the archive has none.
+    pub fn tangents(&self) -> Option<&'a [[f32_le; 4]]> {
+        Some(&self.data.tangents[..]).filter(|tangents| !tangents.is_empty())
+    }
+
+    /// Vertex indices, 3 per face, ready to upload as an index buffer.
+    pub fn indices(&self) -> &'a [[u32_le; 3]] {
+        &self.data.indices
+    }
+
+    /// Copy the archive into a [Mesh], for processing that needs the full mesh.
+    pub fn to_mesh(&self) -> io::Result<Mesh> {
+        let mut mesh = Mesh {
+            verts: self.positions().iter().map(vec3).collect(),
+            normals: self
+                .normals()
+                .map(|normals| normals.iter().map(vec3).collect())
+                .unwrap_or_default(),
+            uvs: self
+                .uvs()
+                .map(|uvs| {
+                    uvs.iter()
+                        .map(|uv| uv.map(|v| v.to_native() as Float))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            tangents: self
+                .tangents()
+                .map(|tangents| {
+                    tangents
+                        .iter()
+                        .map(|tangent| tangent.map(|v| v.to_native() as Float))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ..Mesh::default()
+        };
+        for index in 0..self.face_count() {
+            let face = self.face(index);
+            let Face { v1, v2, v3 } = face;
+            if [v1, v2, v3].iter().any(|vert| *vert >= self.vertex_count()) {
+                return Err(invalid_data("mesh archive face uses a missing vertex"));
+            }
+            mesh.edges.push(Edge { v1, v2 });
+            mesh.edges.push(Edge { v1: v2, v2: v3 });
+            mesh.edges.push(Edge { v1: v3, v2: v1 });
+            mesh.faces.push(face);
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::util::AlignedVec;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut mesh = Mesh::default();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)] {
+            mesh.verts.push(Vec3 { x, y, z: 2.0 });
+            mesh.uvs.push([x, y]);
+        }
+        mesh.faces.push(Face {
+            v1: 0,
+            v2: 1,
+            v3: 2,
+        });
+        let mut bytes = Vec::new();
+        mesh.write_archive(&mut bytes).unwrap();
+        let mut aligned = AlignedVec::<16>::new();
+        aligned.extend_from_slice(&bytes);
+
+        let archive = ArchivedMesh::new(&aligned).unwrap();
+        assert_eq!((archive.vertex_count(), archive.face_count()), (3, 1));
+        assert_eq!(archive.bounds().1.x, 1.0);
+        assert!(archive.normals().is_none() && archive.tangents().is_none());
+        let copy = archive.to_mesh().unwrap();
+        assert_eq!(copy.uvs, mesh.uvs);
+        assert_eq!(copy.faces[0].v3, 2);
+        assert_eq!(copy.verts[2].y, 1.0);
+
+        assert!(ArchivedMesh::new(&aligned[..aligned.len() - 4]).is_err());
+    }
+}
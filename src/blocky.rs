@@ -0,0 +1,139 @@
+use crate::{
+    Domain, EdgeKey, Float, IVec3, ScalarField, builder::MeshBuilder, result::MarchResult,
+};
+
+impl Domain {
+    /// Generate axis aligned quads between solid and empty cells.
+    ///
+    /// A cell is solid when the weight at its center is above the surface weight. Cells outside
+    /// of the domain are empty, so the result is always closed. Faces in the same plane are
+    /// merged greedily into larger rectangles, which leaves T-junctions where rectangles of
+    /// different sizes meet.
+    pub fn march_blocky<FIELD>(&mut self, field: &FIELD) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        let size = [self.width, self.height, self.depth];
+        let mut min_weight = Float::INFINITY;
+        let mut max_weight = Float::NEG_INFINITY;
+        let mut solid = Vec::with_capacity(self.width * self.height * self.depth);
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let corner = self.vertex_position(IVec3 {
+                        x: x as i32,
+                        y: y as i32,
+                        z: z as i32,
+                    });
+                    let opposite = self.vertex_position(IVec3 {
+                        x: x as i32 + 1,
+                        y: y as i32 + 1,
+                        z: z as i32 + 1,
+                    });
+                    let weight = field.sample(corner.lerp(opposite, 0.5));
+                    min_weight = min_weight.min(weight);
+                    max_weight = max_weight.max(weight);
+                    solid.push(weight > self.surface_weight);
+                }
+            }
+        }
+        let is_solid = |cell: [isize; 3]| {
+            (0..3).all(|axis| cell[axis] >= 0 && (cell[axis] as usize) < size[axis])
+                && solid
+                    [(cell[2] as usize * size[1] + cell[1] as usize) * size[0] + cell[0] as usize]
+        };
+
+        let mut mesh = MeshBuilder::default();
+        for axis in 0..3 {
+            let u_axis = (axis + 1) % 3;
+            let v_axis = (axis + 2) % 3;
+            for direction in [-1_isize, 1] {
+                for slice in 0..size[axis] {
+                    // Faces of this slice that separate a solid cell from an empty neighbor.
+                    let mut faces = vec![false; size[u_axis] * size[v_axis]];
+                    for v in 0..size[v_axis] {
+                        for u in 0..size[u_axis] {
+                            let mut cell = [0; 3];
+                            cell[axis] = slice as isize;
+                            cell[u_axis] = u as isize;
+                            cell[v_axis] = v as isize;
+                            let mut neighbor = cell;
+                            neighbor[axis] += direction;
+                            faces[v * size[u_axis] + u] = is_solid(cell) && !is_solid(neighbor);
+                        }
+                    }
+                    let plane = if direction > 0 { slice + 1 } else { slice };
+                    for (u, v, u_len, v_len) in greedy_rectangles(&mut faces, size[u_axis]) {
+                        let corner = |du: usize, dv: usize| {
+                            let mut lattice = [0; 3];
+                            lattice[axis] = plane as i32;
+                            lattice[u_axis] = (u + du) as i32;
+                            lattice[v_axis] = (v + dv) as i32;
+                            IVec3 {
+                                x: lattice[0],
+                                y: lattice[1],
+                                z: lattice[2],
+                            }
+                        };
+                        let mut corners = [
+                            corner(0, 0),
+                            corner(u_len, 0),
+                            corner(u_len, v_len),
+                            corner(0, v_len),
+                        ];
+                        // (u, v, axis) is right handed, so this winding faces the positive axis.
+                        if direction < 0 {
+                            corners.swap(1, 3);
+                        }
+                        let offset = self.lattice_offset();
+                        let verts = corners.map(|corner| {
+                            mesh.vertex(EdgeKey::new(corner + offset, corner + offset), || {
+                                self.vertex_position(corner)
+                            })
+                        });
+                        mesh.quad(verts);
+                    }
+                }
+            }
+        }
+
+        let mesh = mesh.finish();
+        let result = MarchResult::new(&mesh, min_weight, max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+}
+
+/// Cover all set cells of a 2D mask with rectangles `(u, v, u_len, v_len)`, growing each
+/// rectangle along u first and then along v. Covered cells are cleared.
+fn greedy_rectangles(mask: &mut [bool], width: usize) -> Vec<(usize, usize, usize, usize)> {
+    let height = mask.len().checked_div(width).unwrap_or(0);
+    let mut rectangles = Vec::new();
+    for v in 0..height {
+        let mut u = 0;
+        while u < width {
+            if !mask[v * width + u] {
+                u += 1;
+                continue;
+            }
+            let mut u_len = 1;
+            while u + u_len < width && mask[v * width + u + u_len] {
+                u_len += 1;
+            }
+            let mut v_len = 1;
+            while v + v_len < height
+                && (u..u + u_len).all(|row_u| mask[(v + v_len) * width + row_u])
+            {
+                v_len += 1;
+            }
+            for clear_v in v..v + v_len {
+                for clear_u in u..u + u_len {
+                    mask[clear_v * width + clear_u] = false;
+                }
+            }
+            rectangles.push((u, v, u_len, v_len));
+            u += u_len;
+        }
+    }
+    rectangles
+}
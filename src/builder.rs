@@ -0,0 +1,96 @@
+use std::{collections::HashMap, mem::size_of};
+
+use crate::{Edge, EdgeKey, Face, Mesh, Vec3};
+
+/// Builds an indexed [Mesh], sharing a single vertex between all faces that use the same
+/// lattice edge.
+#[derive(Default)]
+pub(crate) struct MeshBuilder {
+    mesh: Mesh,
+    key_to_vert: HashMap<EdgeKey, usize>,
+}
+
+impl MeshBuilder {
+    /// Builder with room for `vert_count` vertices and `face_count` faces, so building doesn't
+    /// reallocate when the counts are known up front.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn with_capacity(vert_count: usize, face_count: usize) -> MeshBuilder {
+        MeshBuilder {
+            mesh: Mesh {
+                verts: Vec::with_capacity(vert_count),
+                vert_keys: Vec::with_capacity(vert_count),
+                faces: Vec::with_capacity(face_count),
+                edges: Vec::with_capacity(face_count * 3),
+                ..Mesh::default()
+            },
+            key_to_vert: HashMap::with_capacity(vert_count),
+        }
+    }
+
+    /// Index of the vertex on the given lattice edge. `position` is only called the first time
+    /// the edge is used.
+    pub(crate) fn vertex<POSITION>(&mut self, key: EdgeKey, position: POSITION) -> usize
+    where
+        POSITION: FnOnce() -> Vec3,
+    {
+        *self.key_to_vert.entry(key).or_insert_with(|| {
+            self.mesh.verts.push(position());
+            self.mesh.vert_keys.push(key);
+            self.mesh.verts.len() - 1
+        })
+    }
+
+    pub(crate) fn face(&mut self, v1: usize, v2: usize, v3: usize) {
+        self.mesh.faces.push(Face { v1, v2, v3 });
+        self.mesh.edges.push(Edge { v1, v2 });
+        self.mesh.edges.push(Edge { v1: v2, v2: v3 });
+        self.mesh.edges.push(Edge { v1: v3, v2: v1 });
+    }
+
+    /// Add a quad as 2 faces. Corners are in counter-clockwise order when looking at the front.
+    pub(crate) fn quad(&mut self, corners: [usize; 4]) {
+        self.face(corners[0], corners[1], corners[2]);
+        self.face(corners[0], corners[2], corners[3]);
+    }
+
+    /// Add the faces of a mesh built by another builder. Vertices on lattice edges this builder
+    /// already uses are shared, so appending in order gives the same mesh as building in a single
+    /// pass.
+    pub(crate) fn append(&mut self, other: Mesh) {
+        let verts = other
+            .vert_keys
+            .iter()
+            .zip(&other.verts)
+            .map(|(key, position)| self.vertex(*key, || *position))
+            .collect::<Vec<usize>>();
+        for face in &other.faces {
+            self.face(verts[face.v1], verts[face.v2], verts[face.v3]);
+        }
+    }
+
+    pub(crate) fn face_count(&self) -> usize {
+        self.mesh.faces.len()
+    }
+
+    /// Bytes allocated by the buffers of the mesh and the vertex lookup so far.
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.mesh.verts.capacity() * size_of::<Vec3>()
+            + self.mesh.vert_keys.capacity() * size_of::<EdgeKey>()
+            + self.mesh.faces.capacity() * size_of::<Face>()
+            + self.mesh.edges.capacity() * size_of::<Edge>()
+            + self.key_to_vert.capacity() * (size_of::<EdgeKey>() + size_of::<usize>())
+    }
+
+    pub(crate) fn finish(self) -> Mesh {
+        self.mesh
+    }
+}
+
+/// Root of `vert` in the union-find forest `parent`, halving the path on the way.
+pub(crate) fn root(parent: &mut [usize], mut vert: usize) -> usize {
+    while parent[vert] != vert {
+        parent[vert] = parent[parent[vert]];
+        vert = parent[vert];
+    }
+    vert
+}
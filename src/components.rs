@@ -0,0 +1,153 @@
+use crate::{Domain, Edge, Face, Float, Mesh, builder::root};
+
+/// Which components [Mesh::remove_small_components] keeps.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ComponentFilter {
+    /// Keep components with at least this many faces.
+    MinFaces(usize),
+    /// Keep components with at least this surface area.
+    MinArea(Float),
+}
+
+impl Mesh {
+    /// Component index of every face and the number of components. Faces are connected when they
+    /// share a vertex, components are numbered in the order of their first face.
+    pub(crate) fn face_components(&self) -> (Vec<usize>, usize) {
+        let mut parent = (0..self.verts.len()).collect::<Vec<usize>>();
+        for face in &self.faces {
+            let a = root(&mut parent, face.v1);
+            for vert in [face.v2, face.v3] {
+                let b = root(&mut parent, vert);
+                parent[b] = a;
+            }
+        }
+
+        let mut root_component = vec![usize::MAX; self.verts.len()];
+        let mut component_count = 0;
+        let components = self
+            .faces
+            .iter()
+            .map(|face| {
+                let component = &mut root_component[root(&mut parent, face.v1)];
+                if *component == usize::MAX {
+                    *component = component_count;
+                    component_count += 1;
+                }
+                *component
+            })
+            .collect();
+        (components, component_count)
+    }
+
+    /// Split the mesh into its connected components, for example to export every blob as a
+    /// separate object.
+    ///
+    /// Faces are connected when they share a vertex index, so the mesh must be welded: meshes of
+    /// [crate::Domain::march_tetrahedras] are, merged chunks need [Mesh::weld_with]. Components
+    /// are in the order of their first face. Vertex keys, normals, texture coordinates,
+    /// tangents and skin weights are copied along with the vertices.
+    pub fn split_components(&self) -> Vec<Mesh> {
+        let (components, component_count) = self.face_components();
+        let mut meshes = (0..component_count)
+            .map(|_| Mesh::default())
+            .collect::<Vec<Mesh>>();
+        // Index of every vertex in the mesh of its component.
+        let mut new_index = vec![usize::MAX; self.verts.len()];
+        for (face, component) in self.faces.iter().zip(components) {
+            let mesh = &mut meshes[component];
+            let [v1, v2, v3] = [face.v1, face.v2, face.v3].map(|vert| {
+                if new_index[vert] == usize::MAX {
+                    new_index[vert] = mesh.verts.len();
+                    self.copy_vert(vert, mesh);
+                }
+                new_index[vert]
+            });
+            push_face(mesh, [v1, v2, v3]);
+        }
+        meshes
+    }
+
+    /// Remove the connected components that don't pass `filter`, such as the tiny shards noisy
+    /// fields produce around the main surface. Returns the number of removed components.
+    ///
+    /// Like [Mesh::split_components] this requires a welded mesh. Unused vertices are removed,
+    /// the remaining vertices keep their attributes.
+    pub fn remove_small_components(&mut self, filter: ComponentFilter) -> usize {
+        let (components, component_count) = self.face_components();
+        let mut face_counts = vec![0; component_count];
+        let mut areas = vec![0.0; component_count];
+        for (face, component) in self.faces.iter().zip(&components) {
+            face_counts[*component] += 1;
+            areas[*component] += self.face_area(face);
+        }
+        let keep = (0..component_count)
+            .map(|component| match filter {
+                ComponentFilter::MinFaces(min_faces) => face_counts[component] >= min_faces,
+                ComponentFilter::MinArea(min_area) => areas[component] >= min_area,
+            })
+            .collect::<Vec<bool>>();
+        let removed = keep.iter().filter(|keep| !**keep).count();
+        if removed == 0 {
+            return 0;
+        }
+
+        let mut mesh = Mesh::default();
+        let mut new_index = vec![usize::MAX; self.verts.len()];
+        for (face, component) in self.faces.iter().zip(components) {
+            if !keep[component] {
+                continue;
+            }
+            let [v1, v2, v3] = [face.v1, face.v2, face.v3].map(|vert| {
+                if new_index[vert] == usize::MAX {
+                    new_index[vert] = mesh.verts.len();
+                    self.copy_vert(vert, &mut mesh);
+                }
+                new_index[vert]
+            });
+            push_face(&mut mesh, [v1, v2, v3]);
+        }
+        *self = mesh;
+        removed
+    }
+
+    /// Append vertex `vert` with its attributes to `mesh`. Attributes that don't have a value for
+    /// every vertex of this mesh are skipped.
+    pub(crate) fn copy_vert(&self, vert: usize, mesh: &mut Mesh) {
+        mesh.verts.push(self.verts[vert]);
+        if self.vert_keys.len() == self.verts.len() {
+            mesh.vert_keys.push(self.vert_keys[vert]);
+        }
+        if self.normals.len() == self.verts.len() {
+            mesh.normals.push(self.normals[vert]);
+        }
+        if self.uvs.len() == self.verts.len() {
+            mesh.uvs.push(self.uvs[vert]);
+        }
+        if self.tangents.len() == self.verts.len() {
+            mesh.tangents.push(self.tangents[vert]);
+        }
+        if self.joints.len() == self.verts.len() && self.joint_weights.len() == self.verts.len() {
+            mesh.joints.push(self.joints[vert]);
+            mesh.joint_weights.push(self.joint_weights[vert]);
+        }
+    }
+}
+
+fn push_face(mesh: &mut Mesh, [v1, v2, v3]: [usize; 3]) {
+    mesh.faces.push(Face { v1, v2, v3 });
+    mesh.edges.push(Edge { v1, v2 });
+    mesh.edges.push(Edge { v1: v2, v2: v3 });
+    mesh.edges.push(Edge { v1: v3, v2: v1 });
+}
+
+impl Domain {
+    /// Replace every mesh by its connected components, see [Mesh::split_components]. Exporters
+    /// write each mesh as a separate object.
+    pub fn split_components(&mut self) {
+        self.meshes = self
+            .meshes
+            .iter()
+            .flat_map(Mesh::split_components)
+            .collect();
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    Bisection, Domain, Float, LinearInterp, Midpoint, Newton, RefineStrategy, ScalarField, Vec3,
+    WeightInterp,
+    components::ComponentFilter,
+    decimate::DecimationTarget,
+    export::{ExportFormat, PlyFormat},
+    extraction::ExtractionMethod,
+    normals::NormalWeighting,
+    result::MarchResult,
+    smooth::Smoothing,
+    tangent::UvProjection,
+};
+
+/// Workflows [MarchConfig::preset] has defaults for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Preset {
+    /// Coarse and fast, for iterating on a field.
+    Preview,
+    /// Fine and accurate closed meshes for slicers.
+    Printing,
+    /// Moderate triangle counts with crisp edges and tangents for game engines.
+    GameLod,
+    /// Accurate vertices without any post processing, for measurements.
+    Scientific,
+}
+
+/// [RefineStrategy] selected at runtime.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Refinement {
+    /// See [Midpoint].
+    Midpoint,
+    /// See [Bisection].
+    #[default]
+    Bisection,
+    /// See [LinearInterp].
+    LinearInterp,
+    /// See [WeightInterp].
+    WeightInterp,
+    /// [Newton] with the default iterations and tolerance.
+    Newton,
+}
+
+impl RefineStrategy for Refinement {
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, field: &FIELD, surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        match self {
+            Refinement::Midpoint => Midpoint.refine(v1, v2, field, surface_weight),
+            Refinement::Bisection => Bisection.refine(v1, v2, field, surface_weight),
+            Refinement::LinearInterp => LinearInterp.refine(v1, v2, field, surface_weight),
+            Refinement::WeightInterp => WeightInterp.refine(v1, v2, field, surface_weight),
+            Refinement::Newton => Newton::default().refine(v1, v2, field, surface_weight),
+        }
+    }
+
+    fn refine_with_weights<FIELD>(
+        &self,
+        v1: Vec3,
+        w1: Float,
+        v2: Vec3,
+        w2: Float,
+        field: &FIELD,
+        surface_weight: Float,
+    ) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        match self {
+            Refinement::WeightInterp => {
+                WeightInterp.refine_with_weights(v1, w1, v2, w2, field, surface_weight)
+            }
+            Refinement::Newton => {
+                Newton::default().refine_with_weights(v1, w1, v2, w2, field, surface_weight)
+            }
+            _ => self.refine(v1, v2, field, surface_weight),
+        }
+    }
+}
+
+/// Where [MarchConfig::normals] come from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NormalSource {
+    /// See [crate::Mesh::compute_gradient_normals].
+    Gradient,
+    /// See [crate::Mesh::compute_vertex_normals].
+    Faces(NormalWeighting),
+}
+
+/// Options of a complete extraction, from sampling to export.
+#[derive(Debug, Copy, Clone)]
+pub struct MarchConfig {
+    /// Number of cells along the longest axis of the bounds, the other axes get cells of about
+    /// the same size. See [MarchConfig::domain].
+    pub resolution: usize,
+    pub method: ExtractionMethod,
+    pub refinement: Refinement,
+    /// Remove small floating components first, see [crate::Mesh::remove_small_components].
+    pub component_filter: Option<ComponentFilter>,
+    /// Smooth the mesh before the other post processing, see [crate::Mesh::smooth].
+    pub smoothing: Option<Smoothing>,
+    /// Reduce the number of faces after smoothing, see [crate::Mesh::decimate].
+    pub decimation: Option<DecimationTarget>,
+    /// Split edges between faces with a larger angle in radians, see
+    /// [crate::Mesh::split_sharp_edges].
+    pub sharp_edge_angle: Option<Float>,
+    /// Compute normals after splitting sharp edges.
+    pub normals: Option<NormalSource>,
+    /// Compute texture coordinates and tangents, see [crate::Mesh::compute_tangents].
+    pub uv_projection: Option<UvProjection>,
+    /// Format to use with [Domain::export_to_file].
+    pub export_format: ExportFormat,
+}
+
+impl MarchConfig {
+    /// Defaults for a workflow. The fields can be changed afterwards.
+    pub fn preset(preset: Preset) -> MarchConfig {
+        match preset {
+            Preset::Preview => MarchConfig {
+                resolution: 32,
+                method: ExtractionMethod::Tetrahedra,
+                refinement: Refinement::Midpoint,
+                component_filter: None,
+                smoothing: None,
+                decimation: None,
+                sharp_edge_angle: None,
+                normals: Some(NormalSource::Gradient),
+                uv_projection: None,
+                export_format: ExportFormat::Bpy,
+            },
+            Preset::Printing => MarchConfig {
+                resolution: 192,
+                method: ExtractionMethod::Tetrahedra,
+                refinement: Refinement::Bisection,
+                component_filter: Some(ComponentFilter::MinFaces(16)),
+                smoothing: Some(Smoothing::Taubin {
+                    iterations: 10,
+                    lambda: 0.5,
+                    mu: -0.53,
+                }),
+                decimation: None,
+                sharp_edge_angle: None,
+                normals: None,
+                uv_projection: None,
+                export_format: ExportFormat::Stl,
+            },
+            Preset::GameLod => MarchConfig {
+                resolution: 64,
+                method: ExtractionMethod::Tetrahedra,
+                refinement: Refinement::LinearInterp,
+                component_filter: None,
+                smoothing: None,
+                decimation: Some(DecimationTarget::FaceRatio(0.25)),
+                sharp_edge_angle: Some(0.8),
+                normals: Some(NormalSource::Faces(NormalWeighting::Angle)),
+                uv_projection: Some(UvProjection::Triplanar { scale: 1.0 }),
+                #[cfg(feature = "gltf")]
+                export_format: ExportFormat::Glb,
+                #[cfg(not(feature = "gltf"))]
+                export_format: ExportFormat::Obj,
+            },
+            Preset::Scientific => MarchConfig {
+                resolution: 128,
+                method: ExtractionMethod::Tetrahedra,
+                refinement: Refinement::Bisection,
+                component_filter: None,
+                smoothing: None,
+                decimation: None,
+                sharp_edge_angle: None,
+                normals: Some(NormalSource::Gradient),
+                uv_projection: None,
+                export_format: ExportFormat::Ply(PlyFormat::BinaryLittleEndian),
+            },
+        }
+    }
+
+    /// Empty domain covering `from`..`to` with [MarchConfig::resolution] cells along the longest
+    /// axis, and at least one cell along every axis.
+    ///
+    /// Panics when `to` isn't above `from` on every axis or the bounds aren't finite.
+    pub fn domain(&self, from: Vec3, to: Vec3, surface_weight: Float) -> Domain {
+        let size = to - from;
+        assert!(
+            [size.x, size.y, size.z]
+                .iter()
+                .all(|length| *length > 0.0 && length.is_finite()),
+            "domain bounds must have a positive, finite size on every axis, got {size:?}"
+        );
+        let longest = size.x.max(size.y).max(size.z);
+        let resolution = self.resolution.max(1);
+        let cells = |length: Float| {
+            ((length / longest * resolution as Float).ceil() as usize).clamp(1, resolution)
+        };
+        Domain {
+            from,
+            to,
+            surface_weight,
+            width: cells(size.x),
+            height: cells(size.y),
+            depth: cells(size.z),
+            grid_offset: Vec3::default(),
+            meshes: Vec::new(),
+        }
+    }
+}
+
+impl Domain {
+    /// Extract the surface with the method and refinement of `config` and apply its post
+    /// processing to the new mesh.
+    pub fn march_with_config<FIELD>(&mut self, field: &FIELD, config: &MarchConfig) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        let result = self.extract(config.method, field, &config.refinement);
+        let mut mesh = self.meshes.pop().unwrap();
+        if let Some(filter) = config.component_filter {
+            mesh.remove_small_components(filter);
+        }
+        if let Some(smoothing) = config.smoothing {
+            mesh.smooth(smoothing);
+        }
+        if let Some(target) = config.decimation {
+            mesh = mesh.decimate(target);
+        }
+        if let Some(max_angle) = config.sharp_edge_angle {
+            mesh = mesh.split_sharp_edges(max_angle);
+        }
+        let cell_size = (self.to.x - self.from.x) / self.width as Float;
+        match config.normals {
+            Some(NormalSource::Gradient) => mesh.compute_gradient_normals(field, cell_size * 0.01),
+            Some(NormalSource::Faces(weighting)) => mesh.compute_vertex_normals(weighting),
+            None => {}
+        }
+        if let Some(projection) = config.uv_projection {
+            mesh.compute_tangents(field, cell_size * 0.01, projection);
+        }
+        self.meshes.push(mesh);
+        result
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{Edge, Face, Float, Mesh, Vec3};
+
+/// Group of connected faces that lie in the same plane.
+struct Region {
+    faces: Vec<usize>,
+    normal: Vec3,
+    /// Boundary loop in the winding order of the faces, `None` when the boundary isn't a single
+    /// simple loop (holes, pinched vertices). Those regions are kept as is.
+    boundary: Option<Vec<usize>>,
+}
+
+impl Mesh {
+    /// Copy of the mesh where connected coplanar faces are merged and retriangulated with as few
+    /// triangles as possible.
+    ///
+    /// Faces are coplanar when their vertices are within `tolerance` of the plane of the first
+    /// face of the region. Vertices on the border between two merged regions are removed when
+    /// they are within `tolerance` of the simplified border, in the same way on both sides so no
+    /// cracks appear. Vertices on open borders, on corners shared by three or more regions and on
+    /// regions with holes are never moved or removed. Useful for blocky meshes and flat regions
+    /// of signed distance fields.
+    ///
+    /// Vertices are welded by their [crate::EdgeKey] first when the mesh has keys, otherwise
+    /// faces are only connected through shared vertex indices. Texture coordinates and tangents
+    /// are not kept.
+    pub fn merge_coplanar(&self, tolerance: Float) -> Mesh {
+        let mut welded = Mesh::default();
+        let mesh = if self.vert_keys.len() == self.verts.len() {
+            welded.weld_with(self);
+            &welded
+        } else {
+            self
+        };
+
+        let mut edge_faces = HashMap::<(usize, usize), Vec<usize>>::new();
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            for (a, b) in face_edges(face) {
+                edge_faces
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push(face_index);
+            }
+        }
+        // Vertices on open or non-manifold edges keep their position in the loops.
+        let mut pinned = HashSet::new();
+        for ((a, b), faces) in &edge_faces {
+            if faces.len() != 2 {
+                pinned.insert(*a);
+                pinned.insert(*b);
+            }
+        }
+
+        let (mut regions, face_region) = grow_regions(mesh, &edge_faces, tolerance);
+        let mut vert_regions = vec![Vec::<usize>::new(); mesh.verts.len()];
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            for vert in face_verts(face) {
+                if !vert_regions[vert].contains(&face_region[face_index]) {
+                    vert_regions[vert].push(face_region[face_index]);
+                }
+            }
+        }
+
+        // Retriangulating a region can fail when simplifying its border made it self
+        // intersecting. Its neighbors must then keep the shared border as well, so start over
+        // until every remaining region succeeds.
+        let triangles = loop {
+            let removable = |vert: usize| {
+                !pinned.contains(&vert)
+                    && vert_regions[vert].len() == 2
+                    && vert_regions[vert]
+                        .iter()
+                        .all(|region| regions[*region].boundary.is_some())
+            };
+            let mut triangles = Vec::with_capacity(regions.len());
+            let mut failed = None;
+            for (region_index, region) in regions.iter().enumerate() {
+                let Some(boundary) = &region.boundary else {
+                    triangles.push(None);
+                    continue;
+                };
+                let simplified = simplify_loop(mesh, boundary, &removable, tolerance);
+                match triangulate(mesh, &simplified, region.normal) {
+                    Some(region_triangles) => triangles.push(Some(region_triangles)),
+                    None => {
+                        failed = Some(region_index);
+                        break;
+                    }
+                }
+            }
+            match failed {
+                Some(region_index) => regions[region_index].boundary = None,
+                None => break triangles,
+            }
+        };
+
+        let mut result = Mesh::default();
+        let mut vert_map = HashMap::<usize, usize>::new();
+        let mut add_face = |result: &mut Mesh, verts: [usize; 3]| {
+            let verts = verts.map(|vert| {
+                *vert_map.entry(vert).or_insert_with(|| {
+                    result.verts.push(mesh.verts[vert]);
+                    if let Some(key) = mesh.vert_keys.get(vert) {
+                        result.vert_keys.push(*key);
+                    }
+                    result.verts.len() - 1
+                })
+            });
+            result.faces.push(Face {
+                v1: verts[0],
+                v2: verts[1],
+                v3: verts[2],
+            });
+            for corner in 0..3 {
+                result.edges.push(Edge {
+                    v1: verts[corner],
+                    v2: verts[(corner + 1) % 3],
+                });
+            }
+        };
+        for (region, region_triangles) in regions.iter().zip(triangles) {
+            match region_triangles {
+                Some(region_triangles) => {
+                    for verts in region_triangles {
+                        add_face(&mut result, verts);
+                    }
+                }
+                None => {
+                    for face_index in &region.faces {
+                        add_face(&mut result, face_verts(&mesh.faces[*face_index]));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+fn face_verts(face: &Face) -> [usize; 3] {
+    [face.v1, face.v2, face.v3]
+}
+
+fn face_edges(face: &Face) -> [(usize, usize); 3] {
+    [(face.v1, face.v2), (face.v2, face.v3), (face.v3, face.v1)]
+}
+
+fn face_normal(mesh: &Mesh, face: &Face) -> Vec3 {
+    let v1 = mesh.verts[face.v1];
+    let v2 = mesh.verts[face.v2];
+    let v3 = mesh.verts[face.v3];
+    (v2 - v1).cross(v3 - v1)
+}
+
+/// Flood fill faces into coplanar regions. Returns the regions and the region of each face.
+fn grow_regions(
+    mesh: &Mesh,
+    edge_faces: &HashMap<(usize, usize), Vec<usize>>,
+    tolerance: Float,
+) -> (Vec<Region>, Vec<usize>) {
+    let mut face_region = vec![usize::MAX; mesh.faces.len()];
+    let mut regions = Vec::new();
+    for seed in 0..mesh.faces.len() {
+        if face_region[seed] != usize::MAX {
+            continue;
+        }
+        let region_index = regions.len();
+        let normal = face_normal(mesh, &mesh.faces[seed]);
+        let degenerate = normal.length().is_nan() || normal.length() <= Float::EPSILON;
+        let normal = normal.normalized();
+        let plane_distance = normal.dot(mesh.verts[mesh.faces[seed].v1]);
+        let in_plane = |face: &Face| {
+            normal.dot(face_normal(mesh, face)) > 0.0
+                && face_verts(face)
+                    .iter()
+                    .all(|vert| (normal.dot(mesh.verts[*vert]) - plane_distance).abs() <= tolerance)
+        };
+
+        let mut faces = vec![seed];
+        face_region[seed] = region_index;
+        let mut queue = VecDeque::from([seed]);
+        while let Some(face_index) = queue.pop_front() {
+            if degenerate {
+                break;
+            }
+            for (a, b) in face_edges(&mesh.faces[face_index]) {
+                let neighbors = &edge_faces[&(a.min(b), a.max(b))];
+                if neighbors.len() != 2 {
+                    continue;
+                }
+                for &neighbor in neighbors {
+                    if face_region[neighbor] == usize::MAX && in_plane(&mesh.faces[neighbor]) {
+                        face_region[neighbor] = region_index;
+                        faces.push(neighbor);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        let boundary = if degenerate {
+            None
+        } else {
+            boundary_loop(mesh, &faces)
+        };
+        regions.push(Region {
+            faces,
+            normal,
+            boundary,
+        });
+    }
+    (regions, face_region)
+}
+
+/// Single boundary loop of a region, or `None` when the boundary has holes or touches itself.
+fn boundary_loop(mesh: &Mesh, faces: &[usize]) -> Option<Vec<usize>> {
+    let region_edges = faces
+        .iter()
+        .flat_map(|face_index| face_edges(&mesh.faces[*face_index]))
+        .collect::<HashSet<(usize, usize)>>();
+    let mut next = HashMap::new();
+    for (a, b) in &region_edges {
+        if region_edges.contains(&(*b, *a)) {
+            continue;
+        }
+        if next.insert(*a, *b).is_some() {
+            return None;
+        }
+    }
+
+    let start = *next.keys().min()?;
+    let mut boundary = vec![start];
+    let mut current = next[&start];
+    while current != start {
+        boundary.push(current);
+        current = *next.get(&current)?;
+        if boundary.len() > next.len() {
+            return None;
+        }
+    }
+    (boundary.len() == next.len()).then_some(boundary)
+}
+
+/// Remove vertices from a boundary loop that are within `tolerance` of the simplified loop.
+///
+/// Runs of removable vertices are simplified with Douglas-Peucker between the fixed vertices
+/// around them. The result only depends on the vertices of a run, not on the direction it is
+/// walked, so both regions sharing a run remove the same vertices.
+fn simplify_loop<REMOVABLE>(
+    mesh: &Mesh,
+    boundary: &[usize],
+    removable: &REMOVABLE,
+    tolerance: Float,
+) -> Vec<usize>
+where
+    REMOVABLE: Fn(usize) -> bool,
+{
+    let mut anchors = (0..boundary.len())
+        .filter(|index| !removable(boundary[*index]))
+        .collect::<Vec<usize>>();
+    if anchors.is_empty() {
+        // A loop shared completely with one other region. Anchor it at the lowest vertex and
+        // the vertex farthest away from it.
+        let first = (0..boundary.len())
+            .min_by_key(|index| boundary[*index])
+            .unwrap();
+        let origin = mesh.verts[boundary[first]];
+        let second = (0..boundary.len())
+            .max_by(|a, b| {
+                let distance_a = (mesh.verts[boundary[*a]] - origin).length();
+                let distance_b = (mesh.verts[boundary[*b]] - origin).length();
+                distance_a
+                    .total_cmp(&distance_b)
+                    .then(boundary[*b].cmp(&boundary[*a]))
+            })
+            .unwrap();
+        anchors = vec![first.min(second), first.max(second)];
+        anchors.dedup();
+    }
+
+    let mut keep = vec![false; boundary.len()];
+    for (anchor_index, &start) in anchors.iter().enumerate() {
+        let end = anchors[(anchor_index + 1) % anchors.len()];
+        let length = (end + boundary.len() - start - 1) % boundary.len() + 1;
+        let run = (0..=length)
+            .map(|step| boundary[(start + step) % boundary.len()])
+            .collect::<Vec<usize>>();
+        let mut run_keep = vec![false; run.len()];
+        douglas_peucker(mesh, &run, &mut run_keep, tolerance);
+        for (step, kept) in run_keep.into_iter().enumerate() {
+            if kept {
+                keep[(start + step) % boundary.len()] = true;
+            }
+        }
+    }
+    boundary
+        .iter()
+        .zip(keep)
+        .filter(|(_, kept)| *kept)
+        .map(|(vert, _)| *vert)
+        .collect()
+}
+
+fn douglas_peucker(mesh: &Mesh, run: &[usize], keep: &mut [bool], tolerance: Float) {
+    let last = run.len() - 1;
+    keep[0] = true;
+    keep[last] = true;
+    if run.len() < 3 {
+        return;
+    }
+    let start = mesh.verts[run[0]];
+    let end = mesh.verts[run[last]];
+    let direction = end - start;
+    let length_squared = direction.dot(direction);
+    let distance = |vert: usize| {
+        let position = mesh.verts[vert];
+        let factor = if length_squared > 0.0 {
+            ((position - start).dot(direction) / length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (position - start.lerp(end, factor)).length()
+    };
+    // Ties are broken by vertex index so the result doesn't depend on the walking direction.
+    let farthest = (1..last)
+        .max_by(|a, b| {
+            distance(run[*a])
+                .total_cmp(&distance(run[*b]))
+                .then(run[*b].cmp(&run[*a]))
+        })
+        .unwrap();
+    if distance(run[farthest]) <= tolerance {
+        return;
+    }
+    douglas_peucker(mesh, &run[..=farthest], &mut keep[..=farthest], tolerance);
+    douglas_peucker(mesh, &run[farthest..], &mut keep[farthest..], tolerance);
+}
+
+/// Ear clip a loop that is wound counter-clockwise around `normal`. Returns `None` when no ear
+/// can be found, which happens when the loop intersects itself.
+fn triangulate(mesh: &Mesh, boundary: &[usize], normal: Vec3) -> Option<Vec<[usize; 3]>> {
+    let helper = if normal.x.abs() < 0.9 {
+        Vec3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    } else {
+        Vec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        }
+    };
+    let u = helper.cross(normal).normalized();
+    let v = normal.cross(u);
+    let points = boundary
+        .iter()
+        .map(|vert| [mesh.verts[*vert].dot(u), mesh.verts[*vert].dot(v)])
+        .collect::<Vec<[Float; 2]>>();
+    let cross = |a: [Float; 2], b: [Float; 2], c: [Float; 2]| {
+        (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+    };
+
+    let mut remaining = (0..boundary.len()).collect::<Vec<usize>>();
+    let mut triangles = Vec::with_capacity(boundary.len().saturating_sub(2));
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|index| {
+            let a = points[remaining[(index + count - 1) % count]];
+            let b = points[remaining[*index]];
+            let c = points[remaining[(index + 1) % count]];
+            if cross(a, b, c) <= Float::EPSILON {
+                return false;
+            }
+            // Points on the border of the ear block it as well, otherwise a vertex that was
+            // kept on a straight border would end up as a T-junction.
+            remaining.iter().all(|other| {
+                let point = points[*other];
+                point == a
+                    || point == b
+                    || point == c
+                    || cross(a, b, point) < 0.0
+                    || cross(b, c, point) < 0.0
+                    || cross(c, a, point) < 0.0
+            })
+        })?;
+        triangles.push([
+            boundary[remaining[(ear + count - 1) % count]],
+            boundary[remaining[ear]],
+            boundary[remaining[(ear + 1) % count]],
+        ]);
+        remaining.remove(ear);
+    }
+    if remaining.len() == 3 {
+        triangles.push([
+            boundary[remaining[0]],
+            boundary[remaining[1]],
+            boundary[remaining[2]],
+        ]);
+    }
+    Some(triangles)
+}
@@ -0,0 +1,101 @@
+//! Classic marching cubes.
+//!
+//! [MarchingCubes] polygonizes each cell as a whole with a 256 case table instead of splitting it
+//! into tetrahedra, which produces fewer and better shaped triangles on the same lattice. It uses
+//! the sampling, welding and refinement of [Domain::march_tetrahedras], so both can be compared
+//! directly:
+//!
+//! ```
+//! use marching_cubes::{field::Sphere, prelude::*};
+//!
+//! let sphere = Sphere {
+//!     center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+//!     radius: 3.0,
+//! };
+//! let config = MarchConfig::preset(Preset::Preview);
+//! let from = Vec3 { x: -4.0, y: -4.0, z: -4.0 };
+//! let to = Vec3 { x: 4.0, y: 4.0, z: 4.0 };
+//! let mut domain = config.domain(from, to, 0.0);
+//! let tetrahedra = domain.march_tetrahedras(&sphere, &Bisection);
+//! let cubes = domain.march_cubes(&sphere, &Bisection);
+//! assert!(cubes.face_count < tetrahedra.face_count);
+//! ```
+//!
+//! Faces of a cell with two diagonal inside corners are ambiguous. They are always resolved by
+//! separating the inside corners, so neighboring cells agree and the mesh is watertight, but
+//! thin connections along such faces open up where the tetrahedra would keep them.
+
+use crate::{
+    CUBE_EDGES_TO_VERT_OFFSETS, Domain, EdgeKey, GRID_TO_VERT_OFFSETS, RefineStrategy, ScalarField,
+    algorithm::{Cell, ExtractionAlgorithm, MeshSink},
+    result::MarchResult,
+    tables::CUBE_CASE_TO_EDGES,
+};
+
+/// Polygonize every cell with the marching cubes table, see [crate::cubes].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MarchingCubes;
+
+impl ExtractionAlgorithm for MarchingCubes {
+    fn polygonize<FIELD, REFINE>(
+        &self,
+        domain: &Domain,
+        cell: &Cell,
+        field: &FIELD,
+        refine: &REFINE,
+        sink: &mut MeshSink,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let mut mask = 0;
+        for (corner, weight) in cell.corner_weights.iter().enumerate() {
+            if domain.is_inside(*weight) {
+                mask |= 1 << corner;
+            }
+        }
+        let mut edge_verts = [usize::MAX; 12];
+        for face in CUBE_CASE_TO_EDGES[mask].chunks_exact(3) {
+            if face[0] == -1 {
+                break;
+            }
+            let verts = [face[0], face[1], face[2]].map(|edge| {
+                let edge = edge as usize;
+                if edge_verts[edge] == usize::MAX {
+                    let [a, b] = CUBE_EDGES_TO_VERT_OFFSETS[edge];
+                    let key = EdgeKey::new(
+                        cell.lattice_position + GRID_TO_VERT_OFFSETS[a],
+                        cell.lattice_position + GRID_TO_VERT_OFFSETS[b],
+                    );
+                    edge_verts[edge] = sink.vertex(key, || {
+                        refine.refine_with_weights(
+                            cell.corner_positions[a],
+                            cell.corner_weights[a],
+                            cell.corner_positions[b],
+                            cell.corner_weights[b],
+                            field,
+                            domain.surface_weight,
+                        )
+                    });
+                }
+                edge_verts[edge]
+            });
+            sink.face(verts[0], verts[1], verts[2]);
+        }
+    }
+}
+
+impl Domain {
+    /// Extract the surface with classic marching cubes and add it as a new mesh, see
+    /// [crate::cubes].
+    ///
+    /// Vertices are placed on the edges of the cells by `refine`, the field is sampled once per
+    /// lattice vertex like [Domain::march_tetrahedras].
+    pub fn march_cubes<FIELD, REFINE>(&mut self, field: &FIELD, refine: &REFINE) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        self.march_with_algorithm(&MarchingCubes, field, refine)
+    }
+}
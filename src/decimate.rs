@@ -0,0 +1,374 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::{Edge, EdgeKey, Face, Float, Mesh, Vec3};
+
+/// When [Mesh::decimate] stops collapsing edges.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DecimationTarget {
+    /// Collapse until the mesh has at most this many faces.
+    FaceCount(usize),
+    /// Collapse until at most this fraction of the faces is left.
+    FaceRatio(Float),
+    /// Collapse while the estimated distance between the decimated and the original surface
+    /// stays below this value.
+    MaxError(Float),
+}
+
+/// Weight of the planes keeping open borders in place, relative to the planes of the faces.
+const BORDER_WEIGHT: Float = 100.0;
+
+/// Faces whose normal turns more than this (as cosine) are considered flipped by a collapse.
+const MIN_NORMAL_COS: Float = 0.2;
+
+/// Weighted sum of squared distances to a set of planes, stored as the upper triangle of a
+/// symmetric 4x4 matrix, together with the total area of the faces.
+#[derive(Debug, Copy, Clone, Default)]
+struct Quadric([Float; 10], Float);
+
+impl Quadric {
+    /// Quadric of the plane through `point` with unit `normal`, scaled by `weight`.
+    fn plane(normal: Vec3, point: Vec3, weight: Float) -> Quadric {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        let d = -normal.dot(point);
+        Quadric(
+            [
+                a * a * weight,
+                a * b * weight,
+                a * c * weight,
+                a * d * weight,
+                b * b * weight,
+                b * c * weight,
+                b * d * weight,
+                c * c * weight,
+                c * d * weight,
+                d * d * weight,
+            ],
+            0.0,
+        )
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, other) in self.0.iter_mut().zip(other.0) {
+            *value += other;
+        }
+        self.1 += other.1;
+    }
+
+    fn error(&self, p: Vec3) -> Float {
+        let q = &self.0;
+        q[0] * p.x * p.x
+            + 2.0 * q[1] * p.x * p.y
+            + 2.0 * q[2] * p.x * p.z
+            + 2.0 * q[3] * p.x
+            + q[4] * p.y * p.y
+            + 2.0 * q[5] * p.y * p.z
+            + 2.0 * q[6] * p.y
+            + q[7] * p.z * p.z
+            + 2.0 * q[8] * p.z
+            + q[9]
+    }
+
+    /// Position with the lowest error, `None` when the planes don't define a single point.
+    fn minimum(&self) -> Option<Vec3> {
+        let q = &self.0;
+        let (a, b, c, d, e, f) = (q[0], q[1], q[2], q[4], q[5], q[7]);
+        let cofactor_x = d * f - e * e;
+        let cofactor_y = c * e - b * f;
+        let cofactor_z = b * e - c * d;
+        let determinant = a * cofactor_x + b * cofactor_y + c * cofactor_z;
+        let scale = a.abs().max(d.abs()).max(f.abs());
+        if determinant.abs() <= scale * scale * scale * 1e-9 {
+            return None;
+        }
+        // Solve A x = -b with Cramer's rule.
+        let (r1, r2, r3) = (-q[3], -q[6], -q[8]);
+        Some(Vec3 {
+            x: (r1 * cofactor_x + r2 * cofactor_y + r3 * cofactor_z) / determinant,
+            y: (r1 * (c * e - b * f) + r2 * (a * f - c * c) + r3 * (b * c - a * e)) / determinant,
+            z: (r1 * (b * e - c * d) + r2 * (b * c - a * e) + r3 * (a * d - b * b)) / determinant,
+        })
+    }
+}
+
+/// Edge collapse in the queue. Candidates become stale when either vertex changed after they
+/// were queued.
+struct Candidate {
+    cost: Float,
+    keep: usize,
+    remove: usize,
+    versions: (u32, u32),
+    position: Vec3,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.cost.total_cmp(&other.cost) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    /// Reversed, so the cheapest collapse is on top of the heap.
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+struct Decimator {
+    verts: Vec<Vec3>,
+    quadrics: Vec<Quadric>,
+    versions: Vec<u32>,
+    removed: Vec<bool>,
+    faces: Vec<[usize; 3]>,
+    face_removed: Vec<bool>,
+    vert_faces: Vec<Vec<usize>>,
+    face_count: usize,
+}
+
+impl Decimator {
+    fn new(mesh: &Mesh) -> Decimator {
+        let faces = mesh
+            .faces
+            .iter()
+            .map(|face| [face.v1, face.v2, face.v3])
+            .collect::<Vec<[usize; 3]>>();
+        let mut quadrics = vec![Quadric::default(); mesh.verts.len()];
+        let mut vert_faces = vec![Vec::new(); mesh.verts.len()];
+        let mut edge_faces = HashMap::<(usize, usize), Vec<usize>>::new();
+        for (face_index, face) in faces.iter().enumerate() {
+            let positions = face.map(|vert| mesh.verts[vert]);
+            let area_normal = (positions[1] - positions[0]).cross(positions[2] - positions[0]);
+            let area = area_normal.length() * 0.5;
+            let mut quadric = Quadric::plane(area_normal.normalized(), positions[0], area);
+            quadric.1 = area;
+            for corner in 0..3 {
+                quadrics[face[corner]].add(&quadric);
+                vert_faces[face[corner]].push(face_index);
+                let (a, b) = (face[corner], face[(corner + 1) % 3]);
+                edge_faces
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push(face_index);
+            }
+        }
+        // Planes perpendicular to the faces along open borders keep the border in place.
+        for ((a, b), edge_face) in &edge_faces {
+            if edge_face.len() != 1 {
+                continue;
+            }
+            let face = faces[edge_face[0]];
+            let positions = face.map(|vert| mesh.verts[vert]);
+            let face_normal = (positions[1] - positions[0])
+                .cross(positions[2] - positions[0])
+                .normalized();
+            let direction = mesh.verts[*b] - mesh.verts[*a];
+            let normal = direction.cross(face_normal).normalized();
+            let weight = BORDER_WEIGHT * direction.dot(direction);
+            let quadric = Quadric::plane(normal, mesh.verts[*a], weight);
+            quadrics[*a].add(&quadric);
+            quadrics[*b].add(&quadric);
+        }
+
+        Decimator {
+            verts: mesh.verts.clone(),
+            quadrics,
+            versions: vec![0; mesh.verts.len()],
+            removed: vec![false; mesh.verts.len()],
+            face_removed: vec![false; faces.len()],
+            face_count: faces.len(),
+            faces,
+            vert_faces,
+        }
+    }
+
+    fn candidate(&self, a: usize, b: usize) -> Candidate {
+        let mut quadric = self.quadrics[a];
+        quadric.add(&self.quadrics[b]);
+        let midpoint = self.verts[a].lerp(self.verts[b], 0.5);
+        let position = [quadric.minimum(), Some(self.verts[a]), Some(self.verts[b])]
+            .into_iter()
+            .flatten()
+            .chain([midpoint])
+            .min_by(|p, q| quadric.error(*p).total_cmp(&quadric.error(*q)))
+            .unwrap();
+        Candidate {
+            // Mean squared distance over the area of the faces merged into the vertex.
+            cost: (quadric.error(position) / quadric.1.max(Float::MIN_POSITIVE)).max(0.0),
+            keep: a,
+            remove: b,
+            versions: (self.versions[a], self.versions[b]),
+            position,
+        }
+    }
+
+    fn neighbours(&self, vert: usize) -> Vec<usize> {
+        let mut neighbours = self.vert_faces[vert]
+            .iter()
+            .filter(|face| !self.face_removed[**face])
+            .flat_map(|face| self.faces[*face])
+            .filter(|other| *other != vert)
+            .collect::<Vec<usize>>();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        neighbours
+    }
+
+    /// Whether collapsing keeps the mesh manifold and doesn't flip any face.
+    fn can_collapse(&self, candidate: &Candidate) -> bool {
+        let (keep, remove) = (candidate.keep, candidate.remove);
+        // Link condition: the vertices connected to both ends are exactly the opposite corners
+        // of the faces sharing the edge.
+        let keep_neighbours = self.neighbours(keep);
+        let shared_neighbours = self
+            .neighbours(remove)
+            .into_iter()
+            .filter(|vert| keep_neighbours.binary_search(vert).is_ok())
+            .count();
+        let shared_faces = self.vert_faces[keep]
+            .iter()
+            .filter(|face| !self.face_removed[**face] && self.faces[**face].contains(&remove))
+            .count();
+        if shared_neighbours != shared_faces {
+            return false;
+        }
+
+        for (moved, other) in [(keep, remove), (remove, keep)] {
+            for face in &self.vert_faces[moved] {
+                let corners = self.faces[*face];
+                if self.face_removed[*face] || corners.contains(&other) {
+                    continue;
+                }
+                let positions = corners.map(|vert| self.verts[vert]);
+                let moved_positions = corners.map(|vert| {
+                    if vert == moved {
+                        candidate.position
+                    } else {
+                        self.verts[vert]
+                    }
+                });
+                let normal = |p: [Vec3; 3]| (p[1] - p[0]).cross(p[2] - p[0]).normalized();
+                if normal(positions).dot(normal(moved_positions)) < MIN_NORMAL_COS {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn collapse(&mut self, candidate: &Candidate) {
+        let (keep, remove) = (candidate.keep, candidate.remove);
+        self.verts[keep] = candidate.position;
+        let quadric = self.quadrics[remove];
+        self.quadrics[keep].add(&quadric);
+        for face in std::mem::take(&mut self.vert_faces[remove]) {
+            if self.face_removed[face] {
+                continue;
+            }
+            if self.faces[face].contains(&keep) {
+                self.face_removed[face] = true;
+                self.face_count -= 1;
+            } else {
+                for vert in &mut self.faces[face] {
+                    if *vert == remove {
+                        *vert = keep;
+                    }
+                }
+                self.vert_faces[keep].push(face);
+            }
+        }
+        let face_removed = &self.face_removed;
+        self.vert_faces[keep].retain(|face| !face_removed[*face]);
+        self.removed[remove] = true;
+        self.versions[keep] += 1;
+    }
+
+    fn finish(self, vert_keys: &[EdgeKey]) -> Mesh {
+        let mut mesh = Mesh::default();
+        let mut new_index = vec![usize::MAX; self.verts.len()];
+        for (face, corners) in self.faces.iter().enumerate() {
+            if self.face_removed[face] {
+                continue;
+            }
+            let [v1, v2, v3] = corners.map(|vert| {
+                if new_index[vert] == usize::MAX {
+                    new_index[vert] = mesh.verts.len();
+                    mesh.verts.push(self.verts[vert]);
+                    if let Some(key) = vert_keys.get(vert) {
+                        mesh.vert_keys.push(*key);
+                    }
+                }
+                new_index[vert]
+            });
+            mesh.faces.push(Face { v1, v2, v3 });
+            mesh.edges.push(Edge { v1, v2 });
+            mesh.edges.push(Edge { v1: v2, v2: v3 });
+            mesh.edges.push(Edge { v1: v3, v2: v1 });
+        }
+        mesh
+    }
+}
+
+impl Mesh {
+    /// Copy of the mesh with edges collapsed until `target` is reached, using quadric error
+    /// metrics: every collapse picks the edge whose merged vertex stays closest to the planes
+    /// of the original faces around it.
+    ///
+    /// Flat regions are simplified first, so the many small near coplanar faces of marching
+    /// tetrahedra are removed with little visible change. Vertices on open borders only move
+    /// along the border, and collapses that would flip a face or make the mesh non-manifold are
+    /// skipped, so the target face count can't always be reached. Remaining vertices keep their
+    /// key, normals, texture coordinates, tangents and skin weights are dropped and have to be
+    /// computed afterwards.
+    pub fn decimate(&self, target: DecimationTarget) -> Mesh {
+        let mut decimator = Decimator::new(self);
+        let (target_faces, max_cost) = match target {
+            DecimationTarget::FaceCount(face_count) => (face_count, Float::INFINITY),
+            DecimationTarget::FaceRatio(ratio) => (
+                (self.faces.len() as Float * ratio.clamp(0.0, 1.0)) as usize,
+                Float::INFINITY,
+            ),
+            DecimationTarget::MaxError(max_error) => (0, max_error * max_error),
+        };
+
+        let mut queue = BinaryHeap::new();
+        for face in &decimator.faces {
+            for corner in 0..3 {
+                let (a, b) = (face[corner], face[(corner + 1) % 3]);
+                if a < b {
+                    queue.push(decimator.candidate(a, b));
+                }
+            }
+        }
+        while decimator.face_count > target_faces {
+            let Some(candidate) = queue.pop() else {
+                break;
+            };
+            if candidate.cost > max_cost {
+                break;
+            }
+            let (keep, remove) = (candidate.keep, candidate.remove);
+            if decimator.removed[keep]
+                || decimator.removed[remove]
+                || candidate.versions != (decimator.versions[keep], decimator.versions[remove])
+                || !decimator.can_collapse(&candidate)
+            {
+                continue;
+            }
+            decimator.collapse(&candidate);
+            for neighbour in decimator.neighbours(keep) {
+                queue.push(decimator.candidate(keep, neighbour));
+            }
+        }
+        decimator.finish(&self.vert_keys)
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{Edge, Face, Float, Mesh, builder::root};
+
+impl Mesh {
+    /// Remove faces without area and merge the vertices they collapse to.
+    ///
+    /// When the surface passes exactly through lattice vertices, several vertices end up at the
+    /// same position and the faces between them have no area. Vertices connected by an edge
+    /// shorter than `epsilon` are merged, faces that lose a corner or have an area below
+    /// `epsilon` squared are removed and unused vertices are dropped. Keys, normals, texture
+    /// coordinates, tangents and skin weights of the remaining vertices are kept. Returns the number of
+    /// removed faces.
+    pub fn remove_degenerates(&mut self, epsilon: Float) -> usize {
+        let mut parent = (0..self.verts.len()).collect::<Vec<usize>>();
+        for face in &self.faces {
+            for (a, b) in [(face.v1, face.v2), (face.v2, face.v3), (face.v3, face.v1)] {
+                let offset = self.verts[b] - self.verts[a];
+                if offset.dot(offset) <= epsilon * epsilon {
+                    let (a, b) = (root(&mut parent, a), root(&mut parent, b));
+                    // The lowest index survives, so merging doesn't depend on the face order.
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let face_count = self.faces.len();
+        let mut faces = Vec::with_capacity(face_count);
+        let mut used = vec![false; self.verts.len()];
+        for face in &self.faces {
+            let [v1, v2, v3] = [face.v1, face.v2, face.v3].map(|vert| root(&mut parent, vert));
+            if v1 == v2 || v2 == v3 || v3 == v1 {
+                continue;
+            }
+            let area = (self.verts[v2] - self.verts[v1])
+                .cross(self.verts[v3] - self.verts[v1])
+                .length()
+                * 0.5;
+            if area <= epsilon * epsilon {
+                continue;
+            }
+            used[v1] = true;
+            used[v2] = true;
+            used[v3] = true;
+            faces.push(Face { v1, v2, v3 });
+        }
+
+        let mut new_index = vec![usize::MAX; self.verts.len()];
+        let mut next_index = 0;
+        for (vert, used) in used.iter().enumerate() {
+            if *used {
+                new_index[vert] = next_index;
+                next_index += 1;
+            }
+        }
+        fn compact<T>(values: &mut Vec<T>, used: &[bool]) {
+            if values.len() == used.len() {
+                let mut used = used.iter();
+                values.retain(|_| *used.next().unwrap());
+            } else {
+                values.clear();
+            }
+        }
+        compact(&mut self.normals, &used);
+        compact(&mut self.uvs, &used);
+        compact(&mut self.tangents, &used);
+        compact(&mut self.joints, &used);
+        compact(&mut self.joint_weights, &used);
+        compact(&mut self.vert_keys, &used);
+        compact(&mut self.verts, &used);
+
+        self.edges.clear();
+        for face in &mut faces {
+            *face = Face {
+                v1: new_index[face.v1],
+                v2: new_index[face.v2],
+                v3: new_index[face.v3],
+            };
+            for (v1, v2) in [(face.v1, face.v2), (face.v2, face.v3), (face.v3, face.v1)] {
+                self.edges.push(Edge { v1, v2 });
+            }
+        }
+        self.faces = faces;
+        face_count - self.faces.len()
+    }
+}
@@ -0,0 +1,416 @@
+//! DICOM series, the format of CT and MRI scanners.
+//!
+//! [VoxelGrid::load_dicom_series] reads a directory with one file per slice into a [VoxelGrid]
+//! with the spacing and position of the scan. CT values are rescaled to Hounsfield units, so
+//! tissues are extracted at their usual iso values, for example about 300 for bone and about
+//! -500 for skin:
+//!
+//! ```no_run
+//! use marching_cubes::{prelude::*, voxel::VoxelGrid};
+//!
+//! let scan = VoxelGrid::load_dicom_series("scans/head".as_ref()).unwrap();
+//! let mut domain = MarchConfig::preset(Preset::Preview).domain(scan.from, scan.to, 300.0);
+//! domain.march_tetrahedras(&scan, &Bisection);
+//! ```
+//!
+//! Supported are single frame grayscale images with uncompressed pixel data, in the implicit or
+//! explicit VR little endian transfer syntax. Like [crate::volume_header] the orientation of the
+//! patient is only used to order the slices, the volume is placed axis aligned at the position
+//! of its first slice.
+
+// Values are stored as f32, the casts from `Float` are no-ops with the `f32` feature.
+#![allow(clippy::unnecessary_cast)]
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Float, Vec3, voxel::VoxelGrid};
+
+const IMPLICIT_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2";
+const EXPLICIT_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2.1";
+
+const TRANSFER_SYNTAX: (u16, u16) = (0x0002, 0x0010);
+const SLICE_THICKNESS: (u16, u16) = (0x0018, 0x0050);
+const SERIES_INSTANCE_UID: (u16, u16) = (0x0020, 0x000e);
+const INSTANCE_NUMBER: (u16, u16) = (0x0020, 0x0013);
+const IMAGE_POSITION: (u16, u16) = (0x0020, 0x0032);
+const IMAGE_ORIENTATION: (u16, u16) = (0x0020, 0x0037);
+const SAMPLES_PER_PIXEL: (u16, u16) = (0x0028, 0x0002);
+const ROWS: (u16, u16) = (0x0028, 0x0010);
+const COLUMNS: (u16, u16) = (0x0028, 0x0011);
+const PIXEL_SPACING: (u16, u16) = (0x0028, 0x0030);
+const BITS_ALLOCATED: (u16, u16) = (0x0028, 0x0100);
+const PIXEL_REPRESENTATION: (u16, u16) = (0x0028, 0x0103);
+const RESCALE_INTERCEPT: (u16, u16) = (0x0028, 0x1052);
+const RESCALE_SLOPE: (u16, u16) = (0x0028, 0x1053);
+const PIXEL_DATA: (u16, u16) = (0x7fe0, 0x0010);
+
+const ITEM: (u16, u16) = (0xfffe, 0xe000);
+const ITEM_END: (u16, u16) = (0xfffe, 0xe00d);
+const SEQUENCE_END: (u16, u16) = (0xfffe, 0xe0dd);
+const UNDEFINED_LENGTH: u32 = u32::MAX;
+
+/// Image of a DICOM file with the attributes needed to assemble a volume.
+#[derive(Debug, Clone)]
+pub struct DicomSlice {
+    pub path: PathBuf,
+    pub series: String,
+    pub instance_number: i64,
+    /// Position of the center of the first pixel in patient coordinates.
+    pub position: Vec3,
+    /// Directions of the rows and the columns in patient coordinates.
+    pub orientation: [Vec3; 2],
+    /// Distance between the centers of neighboring columns and rows.
+    pub pixel_spacing: [Float; 2],
+    pub slice_thickness: Option<Float>,
+    pub columns: usize,
+    pub rows: usize,
+    /// Rescaled values, Hounsfield units for CT, ordered x first, then y.
+    pub values: Vec<f32>,
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Elements of a data set, read one after the other.
+struct Parser<'a> {
+    data: &'a [u8],
+    offset: usize,
+    explicit_vr: bool,
+}
+
+/// Whether the explicit VR uses a 4 byte length after 2 reserved bytes.
+fn long_length(vr: &[u8]) -> bool {
+    matches!(
+        vr,
+        b"OB"
+            | b"OD"
+            | b"OF"
+            | b"OL"
+            | b"OV"
+            | b"OW"
+            | b"SQ"
+            | b"SV"
+            | b"UC"
+            | b"UN"
+            | b"UR"
+            | b"UT"
+            | b"UV"
+    )
+}
+
+impl<'a> Parser<'a> {
+    fn bytes(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + count)
+            .ok_or_else(|| invalid_data("unexpected end of DICOM file"))?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// Tag and value length of the next element.
+    fn header(&mut self) -> io::Result<((u16, u16), u32)> {
+        let tag = (self.u16()?, self.u16()?);
+        // Items and delimiters never have a VR.
+        if tag.0 == 0xfffe || !self.explicit_vr {
+            return Ok((tag, self.u32()?));
+        }
+        let vr = self.bytes(2)?;
+        let length = if long_length(vr) {
+            self.bytes(2)?;
+            self.u32()?
+        } else {
+            self.u16()? as u32
+        };
+        Ok((tag, length))
+    }
+
+    /// Skip a value of undefined length: sequence items up to the sequence delimiter.
+    fn skip_sequence(&mut self) -> io::Result<()> {
+        loop {
+            let (tag, length) = self.header()?;
+            match tag {
+                SEQUENCE_END => return Ok(()),
+                ITEM if length == UNDEFINED_LENGTH => loop {
+                    let (tag, length) = self.header()?;
+                    if tag == ITEM_END {
+                        break;
+                    }
+                    self.skip_value(length)?;
+                },
+                _ => self.skip_value(length)?,
+            }
+        }
+    }
+
+    fn skip_value(&mut self, length: u32) -> io::Result<()> {
+        if length == UNDEFINED_LENGTH {
+            self.skip_sequence()
+        } else {
+            self.bytes(length as usize).map(|_| ())
+        }
+    }
+}
+
+/// Text value without padding.
+fn text(value: &[u8]) -> String {
+    String::from_utf8_lossy(value)
+        .trim_matches(|character: char| character == '\0' || character.is_whitespace())
+        .to_string()
+}
+
+/// Decimal strings separated by backslashes.
+fn decimals(value: &[u8]) -> Option<Vec<Float>> {
+    text(value)
+        .split('\\')
+        .map(|part| part.trim().parse::<Float>().ok())
+        .collect()
+}
+
+fn vector(values: &[Float]) -> Vec3 {
+    Vec3 {
+        x: values[0],
+        y: values[1],
+        z: values[2],
+    }
+}
+
+impl DicomSlice {
+    /// Parse a DICOM Part 10 file, with its 128 byte preamble and `DICM` prefix.
+    pub fn parse(path: &Path, data: &[u8]) -> io::Result<DicomSlice> {
+        if data.get(128..132) != Some(b"DICM") {
+            return Err(invalid_data("not a DICOM file"));
+        }
+        // The file meta information is always explicit VR little endian.
+        let mut parser = Parser {
+            data,
+            offset: 132,
+            explicit_vr: true,
+        };
+        let mut transfer_syntax = None;
+        while parser.data.get(parser.offset..parser.offset + 2) == Some(&[0x02, 0x00]) {
+            let (tag, length) = parser.header()?;
+            let value = parser.bytes(length as usize)?;
+            if tag == TRANSFER_SYNTAX {
+                transfer_syntax = Some(text(value));
+            }
+        }
+        parser.explicit_vr = match transfer_syntax.as_deref() {
+            Some(IMPLICIT_LITTLE_ENDIAN) => false,
+            Some(EXPLICIT_LITTLE_ENDIAN) => true,
+            Some(syntax) => {
+                return Err(invalid_data(format!(
+                    "unsupported DICOM transfer syntax {syntax}"
+                )));
+            }
+            None => return Err(invalid_data("DICOM file without transfer syntax")),
+        };
+
+        let mut elements = HashMap::new();
+        let pixel_data = loop {
+            if parser.offset >= data.len() {
+                return Err(invalid_data("DICOM file without pixel data"));
+            }
+            let (tag, length) = parser.header()?;
+            if tag == PIXEL_DATA {
+                if length == UNDEFINED_LENGTH {
+                    return Err(invalid_data("compressed DICOM pixel data isn't supported"));
+                }
+                break parser.bytes(length as usize)?;
+            }
+            if length == UNDEFINED_LENGTH {
+                parser.skip_sequence()?;
+            } else {
+                elements.insert(tag, parser.bytes(length as usize)?);
+            }
+        };
+
+        let missing = |name: &str| invalid_data(format!("DICOM file without {name}"));
+        let unsigned = |tag| {
+            elements
+                .get(&tag)
+                .filter(|value| value.len() >= 2)
+                .map(|value| u16::from_le_bytes([value[0], value[1]]) as usize)
+        };
+        let decimals = |tag, count: usize| {
+            elements
+                .get(&tag)
+                .and_then(|value| decimals(value))
+                .filter(|values| values.len() >= count)
+        };
+        let rows = unsigned(ROWS).ok_or_else(|| missing("rows"))?;
+        let columns = unsigned(COLUMNS).ok_or_else(|| missing("columns"))?;
+        if unsigned(SAMPLES_PER_PIXEL).unwrap_or(1) != 1 {
+            return Err(invalid_data("only grayscale DICOM images are supported"));
+        }
+        let bits = unsigned(BITS_ALLOCATED).unwrap_or(16);
+        let signed = unsigned(PIXEL_REPRESENTATION) == Some(1);
+        let slope = decimals(RESCALE_SLOPE, 1).map_or(1.0, |values| values[0]);
+        let intercept = decimals(RESCALE_INTERCEPT, 1).map_or(0.0, |values| values[0]);
+        let position =
+            decimals(IMAGE_POSITION, 3).map_or(Vec3::default(), |values| vector(&values));
+        let orientation = decimals(IMAGE_ORIENTATION, 6).map_or(
+            [
+                Vec3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                Vec3 {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+            ],
+            |values| [vector(&values[0..3]), vector(&values[3..6])],
+        );
+        // Row spacing first, then column spacing.
+        let pixel_spacing =
+            decimals(PIXEL_SPACING, 2).map_or([1.0, 1.0], |values| [values[0], values[1]]);
+
+        let pixel_count = rows * columns;
+        let bytes_per_value = bits / 8;
+        if !matches!(bits, 8 | 16 | 32) || pixel_data.len() < pixel_count * bytes_per_value {
+            return Err(invalid_data("unsupported DICOM pixel data"));
+        }
+        let values = pixel_data
+            .chunks_exact(bytes_per_value)
+            .take(pixel_count)
+            .map(|bytes| {
+                let stored = match (bytes.len(), signed) {
+                    (1, false) => bytes[0] as Float,
+                    (1, true) => bytes[0] as i8 as Float,
+                    (2, false) => u16::from_le_bytes([bytes[0], bytes[1]]) as Float,
+                    (2, true) => i16::from_le_bytes([bytes[0], bytes[1]]) as Float,
+                    (_, false) => u32::from_le_bytes(bytes.try_into().unwrap()) as Float,
+                    (_, true) => i32::from_le_bytes(bytes.try_into().unwrap()) as Float,
+                };
+                (stored * slope + intercept) as f32
+            })
+            .collect();
+        Ok(DicomSlice {
+            path: path.to_path_buf(),
+            series: elements
+                .get(&SERIES_INSTANCE_UID)
+                .map(|value| text(value))
+                .unwrap_or_default(),
+            instance_number: elements
+                .get(&INSTANCE_NUMBER)
+                .and_then(|value| text(value).parse().ok())
+                .unwrap_or(0),
+            position,
+            orientation,
+            pixel_spacing: [pixel_spacing[0], pixel_spacing[1]],
+            slice_thickness: decimals(SLICE_THICKNESS, 1).map(|values| values[0]),
+            columns,
+            rows,
+            values,
+        })
+    }
+}
+
+impl VoxelGrid {
+    /// Assemble the DICOM files in `directory` into a volume, see [crate::dicom]. Files that
+    /// aren't DICOM images are skipped. When the directory contains several series, the series
+    /// with the most slices is used.
+    ///
+    /// Slices are ordered along the normal of the image plane, the distance between the first
+    /// and the last slice gives the spacing along z.
+    pub fn load_dicom_series(directory: &Path) -> io::Result<VoxelGrid> {
+        let mut series: HashMap<String, Vec<DicomSlice>> = HashMap::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let data = fs::read(&path)?;
+            if data.get(128..132) != Some(b"DICM") {
+                continue;
+            }
+            let slice = DicomSlice::parse(&path, &data)
+                .map_err(|error| invalid_data(format!("{}: {error}", path.display())))?;
+            series.entry(slice.series.clone()).or_default().push(slice);
+        }
+        let mut slices = series
+            .into_values()
+            .max_by_key(|slices| slices.len())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no DICOM images in {}", directory.display()),
+                )
+            })?;
+        VoxelGrid::from_dicom_slices(&mut slices)
+    }
+
+    /// Assemble slices of one series into a volume, ordering them along the normal of their
+    /// image plane.
+    pub fn from_dicom_slices(slices: &mut [DicomSlice]) -> io::Result<VoxelGrid> {
+        let first = slices
+            .first()
+            .ok_or_else(|| invalid_data("no DICOM slices"))?;
+        let [row_direction, column_direction] = first.orientation;
+        let normal = row_direction.cross(column_direction);
+        slices.sort_by(|a, b| {
+            a.position
+                .dot(normal)
+                .total_cmp(&b.position.dot(normal))
+                .then(a.instance_number.cmp(&b.instance_number))
+        });
+        let first = &slices[0];
+        let last = &slices[slices.len() - 1];
+        let (columns, rows) = (first.columns, first.rows);
+        if let Some(slice) = slices
+            .iter()
+            .find(|slice| (slice.columns, slice.rows) != (columns, rows))
+        {
+            return Err(invalid_data(format!(
+                "{}: image is {}x{}, expected {columns}x{rows} like the other slices",
+                slice.path.display(),
+                slice.columns,
+                slice.rows
+            )));
+        }
+        let depth = slices.len();
+        let slice_spacing = if depth > 1 {
+            (last.position - first.position).dot(normal).abs() / (depth - 1) as Float
+        } else {
+            first.slice_thickness.unwrap_or(1.0)
+        };
+        let spacing = Vec3 {
+            x: first.pixel_spacing[1],
+            y: first.pixel_spacing[0],
+            z: slice_spacing,
+        };
+        let from = first.position - spacing * 0.5;
+        let mut values = Vec::with_capacity(columns * rows * depth);
+        for slice in slices.iter() {
+            values.extend_from_slice(&slice.values);
+        }
+        Ok(VoxelGrid {
+            from,
+            to: Vec3 {
+                x: from.x + spacing.x * columns as Float,
+                y: from.y + spacing.y * rows as Float,
+                z: from.z + spacing.z * depth as Float,
+            },
+            width: columns,
+            height: rows,
+            depth,
+            values,
+        })
+    }
+}
@@ -0,0 +1,285 @@
+use std::{cell::RefCell, collections::HashSet, ops::ControlFlow};
+
+use crate::{
+    Domain, Edge, Face, Float, IVec3, Mesh, RefineStrategy, ScalarField, Vec3,
+    algorithm::{Cell, ExtractionAlgorithm, MeshSink},
+    builder::MeshBuilder,
+    get_vert_offsets,
+    result::MarchResult,
+};
+
+/// Collects the surface patches of every cell that crosses the surface. Vertices are shared
+/// through the sink, so patches of neighboring cells use the same indices.
+#[derive(Default)]
+struct CellPatches {
+    /// Boundary of each patch in counter-clockwise order when looking at the outside.
+    polygons: RefCell<Vec<Vec<usize>>>,
+}
+
+impl ExtractionAlgorithm for CellPatches {
+    fn polygonize<FIELD, REFINE>(
+        &self,
+        domain: &Domain,
+        cell: &Cell,
+        field: &FIELD,
+        refine: &REFINE,
+        sink: &mut MeshSink,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let (offsets, _) = get_vert_offsets(cell.lattice_position);
+        let corners = offsets.map(Cell::corner_index);
+        let vert_positions = corners.map(|corner| cell.corner_positions[corner]);
+        let vert_weights = corners.map(|corner| cell.corner_weights[corner]);
+        // Edges between triangles of the cell cancel out, the rest outlines the patches.
+        let mut edges = Vec::<(usize, usize)>::new();
+        domain.polygonize_tetrahedra(
+            sink.builder,
+            &vert_positions,
+            &vert_weights,
+            cell.position,
+            field,
+            refine,
+            &mut |_, _, face| {
+                for corner in 0..3 {
+                    let (a, b) = (face[corner], face[(corner + 1) % 3]);
+                    if a == b {
+                        continue;
+                    }
+                    match edges.iter().position(|edge| *edge == (b, a)) {
+                        Some(reverse) => {
+                            edges.swap_remove(reverse);
+                        }
+                        None => edges.push((a, b)),
+                    }
+                }
+            },
+        );
+        self.polygons.borrow_mut().extend(outlines(edges));
+    }
+}
+
+/// Chain directed boundary edges into closed polygons, open chains are dropped.
+fn outlines(mut edges: Vec<(usize, usize)>) -> Vec<Vec<usize>> {
+    let mut polygons = Vec::new();
+    while let Some((first, mut current)) = edges.pop() {
+        let mut polygon = vec![first];
+        while current != first {
+            polygon.push(current);
+            match edges.iter().position(|(a, _)| *a == current) {
+                Some(next) => current = edges.swap_remove(next).1,
+                None => break,
+            }
+        }
+        if current == first && polygon.len() >= 3 {
+            polygons.push(polygon);
+        }
+    }
+    polygons
+}
+
+impl Domain {
+    /// Extract the surface with dual marching tetrahedra and add it as a new mesh.
+    ///
+    /// The tetrahedra are the same as in [Domain::march_tetrahedras], but instead of a vertex per
+    /// crossing lattice edge, the triangles of the tetrahedra in a cell are joined across their
+    /// shared faces into patches, and every patch gets a single vertex at the centroid of its
+    /// outline. The vertices of the patches around a crossing lattice edge are connected into a
+    /// polygon, which is split into the fan with the best shaped triangles. Where the surface
+    /// leaves the domain the crossings on the border are kept as vertices, so the mesh still ends
+    /// at the bounds.
+    ///
+    /// Compared to the primal mesh there are fewer slivers and fewer faces, but the centroids lie
+    /// slightly inside convex surfaces.
+    ///
+    /// The vertices aren't generated on lattice edges, so the mesh has no vertex keys and can't
+    /// be welded with [Mesh::weld_with].
+    pub fn march_dual_tetrahedras<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let collector = CellPatches::default();
+        let mut builder = MeshBuilder::default();
+        let origin = IVec3 { x: 0, y: 0, z: 0 };
+        let (min_weight, max_weight) = self.march_cells_using(
+            &mut builder,
+            origin,
+            self.vertex_grid_size(),
+            &collector,
+            field,
+            refine,
+            &mut |_, _| ControlFlow::Continue(()),
+        );
+        let crossings = builder.finish().verts;
+        let mesh = dual_mesh(&crossings, &collector.polygons.into_inner());
+        let result = MarchResult::new(&mesh, min_weight, max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+}
+
+/// Mesh with a vertex per patch and a face per crossing that is shared by the polygons.
+fn dual_mesh(crossings: &[Vec3], polygons: &[Vec<usize>]) -> Mesh {
+    let mut mesh = Mesh {
+        verts: polygons
+            .iter()
+            .map(|polygon| {
+                let sum = polygon
+                    .iter()
+                    .fold(Vec3::default(), |sum, vert| sum + crossings[*vert]);
+                sum * (1.0 / polygon.len() as Float)
+            })
+            .collect(),
+        ..Mesh::default()
+    };
+
+    // Per crossing the polygons using it, with the previous and next corner of the polygon.
+    let mut corners = vec![Vec::<(usize, usize, usize)>::new(); crossings.len()];
+    for (index, polygon) in polygons.iter().enumerate() {
+        for (corner, vert) in polygon.iter().enumerate() {
+            let previous = polygon[(corner + polygon.len() - 1) % polygon.len()];
+            let next = polygon[(corner + 1) % polygon.len()];
+            corners[*vert].push((index, previous, next));
+        }
+    }
+
+    // Vertex of each crossing on the border of the domain.
+    let mut border_verts = vec![None; crossings.len()];
+    for (crossing, ring) in corners.iter().enumerate() {
+        // The polygon across the edge to `next` uses that edge as its incoming edge. On the
+        // border of the domain the ring is open, start at the polygon without a predecessor.
+        let start = ring
+            .iter()
+            .position(|(_, previous, _)| ring.iter().all(|(_, _, next)| next != previous));
+        let mut polygon = Vec::with_capacity(ring.len() + 1);
+        let mut current = start.unwrap_or(0);
+        loop {
+            let (index, _, next) = ring[current];
+            polygon.push(index);
+            match ring.iter().position(|(_, previous, _)| *previous == next) {
+                Some(following) if following != start.unwrap_or(0) => current = following,
+                _ => break,
+            }
+            if polygon.len() > ring.len() {
+                // Not a manifold fan, leave the hole instead of looping.
+                break;
+            }
+        }
+        // Walking over the incoming edges turns clockwise around the crossing.
+        polygon.reverse();
+        if start.is_some() {
+            polygon.insert(0, mesh.verts.len());
+            border_verts[crossing] = Some(mesh.verts.len());
+            mesh.verts.push(crossings[crossing]);
+        }
+        if polygon.len() >= 3 {
+            add_fan(&mut mesh, &polygon);
+        }
+    }
+
+    // Patch edges without a neighbor lie on the border of the domain, close the gap between
+    // the patch vertex and the border.
+    let edges = polygons
+        .iter()
+        .flat_map(|polygon| outline_edges(polygon))
+        .collect::<HashSet<(usize, usize)>>();
+    for (index, polygon) in polygons.iter().enumerate() {
+        for (a, b) in outline_edges(polygon) {
+            if edges.contains(&(b, a)) {
+                continue;
+            }
+            if let (Some(a), Some(b)) = (border_verts[a], border_verts[b]) {
+                add_fan(&mut mesh, &[index, a, b]);
+            }
+        }
+    }
+    mesh
+}
+
+/// Edges of `polygon` in its winding order.
+fn outline_edges(polygon: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..polygon.len()).map(|corner| (polygon[corner], polygon[(corner + 1) % polygon.len()]))
+}
+
+/// Add the fan with the best shaped triangles covering `polygon`.
+fn add_fan(mesh: &mut Mesh, polygon: &[usize]) {
+    let quality = |origin: usize| {
+        fan(polygon, origin)
+            .map(|face| smallest_angle_quality(face.map(|vert| mesh.verts[vert])))
+            .fold(Float::INFINITY, Float::min)
+    };
+    let origin = (0..polygon.len())
+        .max_by(|a, b| quality(*a).total_cmp(&quality(*b)))
+        .unwrap();
+    for [v1, v2, v3] in fan(polygon, origin) {
+        mesh.faces.push(Face { v1, v2, v3 });
+        mesh.edges.push(Edge { v1, v2 });
+        mesh.edges.push(Edge { v1: v2, v2: v3 });
+        mesh.edges.push(Edge { v1: v3, v2: v1 });
+    }
+}
+
+/// Faces of a fan covering `polygon`, starting at the corner `origin`.
+fn fan(polygon: &[usize], origin: usize) -> impl Iterator<Item = [usize; 3]> + '_ {
+    (1..polygon.len() - 1).map(move |index| {
+        [0, index, index + 1].map(|corner| polygon[(origin + corner) % polygon.len()])
+    })
+}
+
+/// Negated cosine of the smallest angle of a triangle, higher is better shaped.
+fn smallest_angle_quality(corners: [Vec3; 3]) -> Float {
+    (0..3)
+        .map(|corner| {
+            let a = (corners[(corner + 1) % 3] - corners[corner]).normalized();
+            let b = (corners[(corner + 2) % 3] - corners[corner]).normalized();
+            -a.dot(b)
+        })
+        .fold(Float::INFINITY, Float::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Bisection, Domain, Float, Vec3};
+
+    #[test]
+    fn fewer_faces_than_primal() {
+        let mut domain = Domain {
+            from: Vec3 {
+                x: -3.0,
+                y: -3.0,
+                z: -3.0,
+            },
+            to: Vec3 {
+                x: 3.0,
+                y: 3.0,
+                z: 3.0,
+            },
+            surface_weight: 0.0,
+            width: 16,
+            height: 16,
+            depth: 16,
+            grid_offset: Vec3::default(),
+            meshes: Vec::new(),
+        };
+        // A sphere that is closed and one that is cut off by the bounds.
+        for radius in [2.5, 3.5] {
+            let field = move |position: Vec3| -> Float { radius - position.length() };
+            domain.march_tetrahedras(&field, &Bisection);
+            domain.march_dual_tetrahedras(&field, &Bisection);
+        }
+        for pair in domain.meshes.chunks(2) {
+            let (primal, dual) = (&pair[0], &pair[1]);
+            assert!(dual.faces.len() <= primal.faces.len());
+            let (primal, dual) = (primal.validate(), dual.validate());
+            assert!(dual.non_manifold_edges.is_empty());
+            assert!(dual.inconsistent_winding.is_empty());
+            assert_eq!(dual.boundary_edges.len(), primal.boundary_edges.len());
+        }
+    }
+}
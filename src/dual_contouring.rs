@@ -0,0 +1,190 @@
+//! Dual contouring with Hermite data.
+//!
+//! The mesh has the topology of [Domain::march_surface_nets], a vertex per cell crossing the
+//! surface connected by a quad per crossing lattice edge. Instead of averaging the crossings,
+//! each vertex minimizes the quadratic error function (QEF) of the planes through the crossings
+//! along the gradient of the field. Where the planes of a cell meet at an angle the vertex moves
+//! onto the edge or corner, so sharp features of CSG and signed distance fields are kept:
+//!
+//! ```
+//! use marching_cubes::{field::Cuboid, prelude::*};
+//!
+//! let cube = Cuboid {
+//!     center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+//!     half_size: Vec3 { x: 2.0, y: 2.0, z: 2.0 },
+//! };
+//! let from = Vec3 { x: -3.3, y: -3.2, z: -3.1 };
+//! let to = Vec3 { x: 3.1, y: 3.2, z: 3.3 };
+//! let mut domain = MarchConfig::preset(Preset::Preview).domain(from, to, 0.0);
+//! domain.march_dual_contouring(&cube, &Bisection);
+//! let mesh = &domain.meshes[0];
+//! // The corners of the cube are reproduced exactly.
+//! assert!(mesh.verts.iter().any(|vert| (vert.x - 2.0).abs() < 1e-3
+//!     && (vert.y - 2.0).abs() < 1e-3
+//!     && (vert.z - 2.0).abs() < 1e-3));
+//! ```
+
+use crate::{
+    Domain, Float, RefineStrategy, ScalarField, Vec3,
+    field::gradient,
+    result::MarchResult,
+    surface_nets::{NetsCell, nets_mesh},
+};
+
+/// Eigenvalues below this fraction of the largest are ignored when solving the QEF. Planes
+/// meeting at less than about 16 degrees are treated as a single smooth surface, which keeps
+/// noise in the gradients from pulling vertices along the surface.
+const SINGULAR_VALUE_THRESHOLD: Float = 0.02;
+
+type Matrix3 = [[Float; 3]; 3];
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix, using Jacobi rotations.
+fn symmetric_eigen(mut a: Matrix3) -> ([Float; 3], Matrix3) {
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    for _ in 0..16 {
+        let off_diagonal = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        let diagonal = a[0][0] * a[0][0] + a[1][1] * a[1][1] + a[2][2] * a[2][2];
+        if off_diagonal <= diagonal * 1e-24 {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            // Rotation in the p, q plane that zeroes a[p][q].
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+            for row in &mut a {
+                let (kp, kq) = (row[p], row[q]);
+                row[p] = c * kp - s * kq;
+                row[q] = s * kp + c * kq;
+            }
+            let (row_p, row_q) = (a[p], a[q]);
+            a[p] = [0, 1, 2].map(|k| c * row_p[k] - s * row_q[k]);
+            a[q] = [0, 1, 2].map(|k| s * row_p[k] + c * row_q[k]);
+            for row in &mut v {
+                let (kp, kq) = (row[p], row[q]);
+                row[p] = c * kp - s * kq;
+                row[q] = s * kp + c * kq;
+            }
+        }
+    }
+    ([a[0][0], a[1][1], a[2][2]], v)
+}
+
+fn mass_point(points: &[Vec3]) -> Vec3 {
+    points
+        .iter()
+        .fold(Vec3::default(), |sum, point| sum + *point)
+        * (1.0 / points.len() as Float)
+}
+
+/// Position minimizing the squared distances to the planes through `points` with unit
+/// `normals`. Directions the planes don't constrain keep the position at the mass point of the
+/// points.
+fn solve_qef(points: &[Vec3], normals: &[Vec3]) -> Vec3 {
+    let mass_point = mass_point(points);
+    let mut ata = [[0.0; 3]; 3];
+    let mut atb = [0.0; 3];
+    for (point, normal) in points.iter().zip(normals) {
+        let n = [normal.x, normal.y, normal.z];
+        // Solve relative to the mass point for precision.
+        let distance = normal.dot(*point - mass_point);
+        for row in 0..3 {
+            for column in 0..3 {
+                ata[row][column] += n[row] * n[column];
+            }
+            atb[row] += n[row] * distance;
+        }
+    }
+    let (values, vectors) = symmetric_eigen(ata);
+    let largest = values.iter().copied().fold(0.0, Float::max);
+    let mut offset = [0.0; 3];
+    for (index, value) in values.iter().enumerate() {
+        if *value <= largest * SINGULAR_VALUE_THRESHOLD || *value <= 0.0 {
+            continue;
+        }
+        let vector = [vectors[0][index], vectors[1][index], vectors[2][index]];
+        let projection = (vector[0] * atb[0] + vector[1] * atb[1] + vector[2] * atb[2]) / value;
+        for axis in 0..3 {
+            offset[axis] += vector[axis] * projection;
+        }
+    }
+    mass_point
+        + Vec3 {
+            x: offset[0],
+            y: offset[1],
+            z: offset[2],
+        }
+}
+
+impl Domain {
+    /// Extract the surface with dual contouring and add it as a new mesh, see
+    /// [crate::dual_contouring].
+    ///
+    /// The crossings on the cell edges are placed by `refine`, their normals are the gradient of
+    /// the field estimated with central differences. Vertices are kept inside their cell, so
+    /// features smaller than a cell are clamped instead of producing spikes. Like
+    /// [Domain::march_surface_nets] the mesh is open half a cell inside the bounds and
+    /// non-manifold where a cell is crossed by more than one sheet of the surface.
+    pub fn march_dual_contouring<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let nets = self.collect_nets(field, refine);
+        let cell_size = |from: Float, to: Float, count: usize| (to - from).abs() / count as Float;
+        let step = 1e-3
+            * cell_size(self.from.x, self.to.x, self.width)
+                .min(cell_size(self.from.y, self.to.y, self.height))
+                .min(cell_size(self.from.z, self.to.z, self.depth));
+        // The weight increases towards the inside, so the normals point against the gradient.
+        let normals = nets
+            .crossing_positions
+            .iter()
+            .map(|position| (gradient(field, *position, step) * -1.0).normalized())
+            .collect::<Vec<Vec3>>();
+        let verts = nets
+            .cells
+            .iter()
+            .map(|cell| contour_vertex(cell, &nets.crossing_positions, &normals))
+            .collect();
+        let mesh = nets_mesh(verts, &nets);
+        let result = MarchResult::new(&mesh, nets.min_weight, nets.max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+}
+
+/// Vertex of a cell minimizing the QEF of its crossings, clamped to the cell.
+fn contour_vertex(cell: &NetsCell, positions: &[Vec3], normals: &[Vec3]) -> Vec3 {
+    // Crossings where the gradient vanishes or isn't finite don't define a plane.
+    let (points, normals): (Vec<Vec3>, Vec<Vec3>) = cell
+        .crossings
+        .iter()
+        .map(|crossing| (positions[*crossing], normals[*crossing]))
+        .filter(|(_, normal)| normal.length() > 0.5)
+        .unzip();
+    let (from, to) = cell.bounds;
+    let position = if points.is_empty() {
+        let points = cell
+            .crossings
+            .iter()
+            .map(|crossing| positions[*crossing])
+            .collect::<Vec<Vec3>>();
+        mass_point(&points)
+    } else {
+        solve_qef(&points, &normals)
+    };
+    Vec3 {
+        x: position.x.clamp(from.x.min(to.x), from.x.max(to.x)),
+        y: position.y.clamp(from.y.min(to.y), from.y.max(to.y)),
+        z: position.z.clamp(from.z.min(to.z), from.z.max(to.z)),
+    }
+}
@@ -0,0 +1,493 @@
+// glTF stores f32, the casts from `Float` are no-ops with the `f32` feature.
+#![allow(clippy::unnecessary_cast)]
+
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Domain, Float, Mesh, Vec3, field::Skeleton};
+
+use super::write_file_atomic;
+
+/// glTF component types and buffer view targets used by the exporter.
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Quote `value` as a JSON string, escaping quotes, backslashes and control characters.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            character if character < ' ' => {
+                quoted.push_str(&format!("\\u{:04x}", character as u32));
+            }
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Node of a mesh added to the [GlbBuilder].
+struct MeshNode {
+    name: String,
+    /// `None` for meshes without faces, glTF doesn't allow empty primitives.
+    mesh: Option<usize>,
+    skin: Option<usize>,
+}
+
+/// Collects the binary buffer and JSON objects of a GLB file.
+#[derive(Default)]
+struct GlbBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+    meshes: Vec<String>,
+    mesh_nodes: Vec<MeshNode>,
+    /// Nodes of the skeleton joints, they follow the mesh nodes.
+    joint_nodes: Vec<String>,
+    root_joints: Vec<usize>,
+    skins: Vec<String>,
+}
+
+impl GlbBuilder {
+    /// Add a buffer view with the given data and an accessor for it, returns the accessor index.
+    fn accessor(
+        &mut self,
+        data: &[u8],
+        target: Option<u32>,
+        component_type: u32,
+        count: usize,
+        accessor_type: &str,
+        bounds: Option<(Vec3, Vec3)>,
+    ) -> usize {
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+        // Every view starts 4 byte aligned.
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        let target = match target {
+            Some(target) => format!(r#","target":{target}"#),
+            None => String::new(),
+        };
+        self.buffer_views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{}{target}}}"#,
+            data.len()
+        ));
+        let bounds = match bounds {
+            Some((min, max)) => format!(
+                r#","min":[{},{},{}],"max":[{},{},{}]"#,
+                min.x as f32, min.y as f32, min.z as f32, max.x as f32, max.y as f32, max.z as f32
+            ),
+            None => String::new(),
+        };
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{component_type},"count":{count},"type":"{accessor_type}"{bounds}}}"#,
+            self.buffer_views.len() - 1
+        ));
+        self.accessors.len() - 1
+    }
+
+    fn float_accessor<const N: usize>(
+        &mut self,
+        values: impl Iterator<Item = [f32; N]>,
+        accessor_type: &str,
+    ) -> usize {
+        let mut data = Vec::new();
+        let mut count = 0;
+        for value in values {
+            for component in value {
+                data.extend_from_slice(&component.to_le_bytes());
+            }
+            count += 1;
+        }
+        self.accessor(&data, Some(ARRAY_BUFFER), FLOAT, count, accessor_type, None)
+    }
+
+    /// Add a mesh node. Skin weights are only written for `skinned` meshes, glTF doesn't allow
+    /// them without a skin. A mesh without faces becomes a node without a mesh.
+    fn mesh(&mut self, mesh: &Mesh, normals: &[Vec3], name: &str, skinned: bool) -> io::Result<()> {
+        if mesh.faces.is_empty() {
+            self.mesh_nodes.push(MeshNode {
+                name: name.to_string(),
+                mesh: None,
+                skin: None,
+            });
+            return Ok(());
+        }
+        let mut indices = Vec::with_capacity(mesh.faces.len() * 12);
+        for face in &mesh.faces {
+            for vert in [face.v1, face.v2, face.v3] {
+                let vert = u32::try_from(vert).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "glTF vertex indices are limited to u32",
+                    )
+                })?;
+                indices.extend_from_slice(&vert.to_le_bytes());
+            }
+        }
+
+        let mut position_data = Vec::with_capacity(mesh.verts.len() * 12);
+        let mut min = Vec3 {
+            x: Float::MAX,
+            y: Float::MAX,
+            z: Float::MAX,
+        };
+        let mut max = min * -1.0;
+        for vert in &mesh.verts {
+            for component in [vert.x, vert.y, vert.z] {
+                position_data.extend_from_slice(&(component as f32).to_le_bytes());
+            }
+            min = Vec3 {
+                x: min.x.min(vert.x),
+                y: min.y.min(vert.y),
+                z: min.z.min(vert.z),
+            };
+            max = Vec3 {
+                x: max.x.max(vert.x),
+                y: max.y.max(vert.y),
+                z: max.z.max(vert.z),
+            };
+        }
+        let positions = self.accessor(
+            &position_data,
+            Some(ARRAY_BUFFER),
+            FLOAT,
+            mesh.verts.len(),
+            "VEC3",
+            Some((min, max)),
+        );
+        let mut attributes = vec![format!(r#""POSITION":{positions}"#)];
+        if normals.len() == mesh.verts.len() {
+            let normals = self.float_accessor(
+                normals
+                    .iter()
+                    .map(|normal| [normal.x as f32, normal.y as f32, normal.z as f32]),
+                "VEC3",
+            );
+            attributes.push(format!(r#""NORMAL":{normals}"#));
+            // Tangents are only valid together with normals.
+            if mesh.tangents.len() == mesh.verts.len() {
+                let tangents = self.float_accessor(
+                    mesh.tangents
+                        .iter()
+                        .map(|tangent| tangent.map(|value| value as f32)),
+                    "VEC4",
+                );
+                attributes.push(format!(r#""TANGENT":{tangents}"#));
+            }
+        }
+        if mesh.uvs.len() == mesh.verts.len() {
+            let uvs = self.float_accessor(
+                mesh.uvs.iter().map(|uv| uv.map(|value| value as f32)),
+                "VEC2",
+            );
+            attributes.push(format!(r#""TEXCOORD_0":{uvs}"#));
+        }
+        if skinned {
+            let data = mesh
+                .joints
+                .iter()
+                .flatten()
+                .flat_map(|joint| joint.to_le_bytes())
+                .collect::<Vec<u8>>();
+            let joints = self.accessor(
+                &data,
+                Some(ARRAY_BUFFER),
+                UNSIGNED_SHORT,
+                mesh.joints.len(),
+                "VEC4",
+                None,
+            );
+            attributes.push(format!(r#""JOINTS_0":{joints}"#));
+            let weights = self.float_accessor(
+                mesh.joint_weights
+                    .iter()
+                    .map(|weights| weights.map(|value| value as f32)),
+                "VEC4",
+            );
+            attributes.push(format!(r#""WEIGHTS_0":{weights}"#));
+        }
+        let indices = self.accessor(
+            &indices,
+            Some(ELEMENT_ARRAY_BUFFER),
+            UNSIGNED_INT,
+            mesh.faces.len() * 3,
+            "SCALAR",
+            None,
+        );
+        self.meshes.push(format!(
+            r#"{{"name":{},"primitives":[{{"attributes":{{{}}},"indices":{indices}}}]}}"#,
+            json_string(name),
+            attributes.join(",")
+        ));
+        self.mesh_nodes.push(MeshNode {
+            name: name.to_string(),
+            mesh: Some(self.meshes.len() - 1),
+            skin: None,
+        });
+        Ok(())
+    }
+
+    /// Add the bones of `skeleton` as joint nodes at the head of each bone and bind them to the
+    /// last mesh. Must be called after all meshes are added, the joint nodes follow them.
+    fn skin(&mut self, skeleton: &Skeleton) -> io::Result<()> {
+        let first_joint = self.mesh_nodes.len();
+        let mut children = vec![Vec::new(); skeleton.bones.len()];
+        for (index, bone) in skeleton.bones.iter().enumerate() {
+            match bone.parent {
+                Some(parent) if parent < index => children[parent].push(first_joint + index),
+                Some(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "bone parents must come before their children",
+                    ));
+                }
+                None => self.root_joints.push(first_joint + index),
+            }
+        }
+        let mut inverse_bind_matrices = Vec::with_capacity(skeleton.bones.len() * 64);
+        for (bone, children) in skeleton.bones.iter().zip(children) {
+            let parent_head = bone
+                .parent
+                .map_or(Vec3::default(), |parent| skeleton.bones[parent].from);
+            let translation = bone.from - parent_head;
+            let children = if children.is_empty() {
+                String::new()
+            } else {
+                let children = children
+                    .iter()
+                    .map(|child| child.to_string())
+                    .collect::<Vec<String>>();
+                format!(r#","children":[{}]"#, children.join(","))
+            };
+            self.joint_nodes.push(format!(
+                r#"{{"translation":[{},{},{}]{children}}}"#,
+                translation.x as f32, translation.y as f32, translation.z as f32
+            ));
+            // The bind pose only translates, column major.
+            let head = bone.from;
+            let matrix = [
+                1.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+                -head.x as f32,
+                -head.y as f32,
+                -head.z as f32,
+                1.0,
+            ];
+            for value in matrix {
+                inverse_bind_matrices.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        let inverse_bind_matrices = self.accessor(
+            &inverse_bind_matrices,
+            None,
+            FLOAT,
+            skeleton.bones.len(),
+            "MAT4",
+            None,
+        );
+        let joints = (first_joint..first_joint + skeleton.bones.len())
+            .map(|joint| joint.to_string())
+            .collect::<Vec<String>>();
+        self.skins.push(format!(
+            r#"{{"inverseBindMatrices":{inverse_bind_matrices},"joints":[{}]}}"#,
+            joints.join(",")
+        ));
+        // A skin needs a mesh to bind to.
+        if let Some(node) = self
+            .mesh_nodes
+            .last_mut()
+            .filter(|node| node.mesh.is_some())
+        {
+            node.skin = Some(self.skins.len() - 1);
+        }
+        Ok(())
+    }
+
+    fn write<W: Write>(self, writer: &mut W) -> io::Result<()> {
+        let mut nodes = self
+            .mesh_nodes
+            .iter()
+            .map(|node| {
+                let mesh = node
+                    .mesh
+                    .map_or(String::new(), |mesh| format!(r#","mesh":{mesh}"#));
+                let skin = node
+                    .skin
+                    .map_or(String::new(), |skin| format!(r#","skin":{skin}"#));
+                format!(r#"{{"name":{}{mesh}{skin}}}"#, json_string(&node.name))
+            })
+            .collect::<Vec<String>>();
+        let node_indices = (0..nodes.len())
+            .chain(self.root_joints)
+            .map(|node| node.to_string())
+            .collect::<Vec<String>>();
+        nodes.extend(self.joint_nodes);
+        // glTF doesn't allow empty arrays or buffers, leave them out instead.
+        let array = |name: &str, items: &[String]| {
+            if items.is_empty() {
+                String::new()
+            } else {
+                format!(r#","{name}":[{}]"#, items.join(","))
+            }
+        };
+        let buffers = if self.buffer.is_empty() {
+            String::new()
+        } else {
+            format!(r#","buffers":[{{"byteLength":{}}}]"#, self.buffer.len())
+        };
+        let scene = if nodes.is_empty() {
+            String::new()
+        } else {
+            format!(
+                r#","scene":0,"scenes":[{{"nodes":[{}]}}]"#,
+                node_indices.join(",")
+            )
+        };
+        let mut json = format!(
+            r#"{{"asset":{{"version":"2.0","generator":"marching-cubes"}}{scene}{}{}{}{}{}{buffers}}}"#,
+            array("nodes", &nodes),
+            array("meshes", &self.meshes),
+            array("skins", &self.skins),
+            array("accessors", &self.accessors),
+            array("bufferViews", &self.buffer_views),
+        )
+        .into_bytes();
+        json.resize(json.len().next_multiple_of(4), b' ');
+
+        // The binary chunk is optional.
+        let binary_length = if self.buffer.is_empty() {
+            0
+        } else {
+            8 + self.buffer.len()
+        };
+        let total_length = 12 + 8 + json.len() + binary_length;
+        let length = |length: usize| {
+            u32::try_from(length).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "GLB files are limited to 4GB")
+            })
+        };
+        writer.write_all(b"glTF")?;
+        writer.write_all(&2_u32.to_le_bytes())?;
+        writer.write_all(&length(total_length)?.to_le_bytes())?;
+        writer.write_all(&length(json.len())?.to_le_bytes())?;
+        writer.write_all(b"JSON")?;
+        writer.write_all(&json)?;
+        if !self.buffer.is_empty() {
+            writer.write_all(&length(self.buffer.len())?.to_le_bytes())?;
+            writer.write_all(b"BIN\0")?;
+            writer.write_all(&self.buffer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Mesh {
+    /// Write the mesh as a binary glTF 2.0 (`.glb`) file.
+    ///
+    /// `normals` are written when they have one entry per vertex, tangents are only written
+    /// together with normals. Texture coordinates are written when present, skin weights only by
+    /// [Mesh::write_skinned_glb].
+    pub fn write_glb<W: Write>(&self, writer: &mut W, normals: &[Vec3]) -> io::Result<()> {
+        let mut glb = GlbBuilder::default();
+        glb.mesh(self, normals, "mesh", false)?;
+        glb.write(writer)
+    }
+
+    /// Write the mesh rigged to `skeleton` as a binary glTF 2.0 file, with [Mesh::normals] when
+    /// present.
+    ///
+    /// Every bone becomes a joint node at the head of the bone, nested like the bones. The
+    /// vertices are bound with [Mesh::joints] and [Mesh::joint_weights], see
+    /// [Mesh::compute_skin_weights].
+    pub fn write_skinned_glb<W: Write>(
+        &self,
+        writer: &mut W,
+        skeleton: &Skeleton,
+    ) -> io::Result<()> {
+        if self.joints.len() != self.verts.len() || self.joint_weights.len() != self.verts.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "skinned export needs skin weights, call Mesh::compute_skin_weights first",
+            ));
+        }
+        let mut glb = GlbBuilder::default();
+        glb.mesh(self, &self.normals, "mesh", true)?;
+        glb.skin(skeleton)?;
+        glb.write(writer)
+    }
+
+    /// Write the mesh rigged to `skeleton` as a `.glb` file to `path`, see
+    /// [Mesh::write_skinned_glb] and [write_file_atomic].
+    pub fn export_to_skinned_glb_file(&self, path: &Path, skeleton: &Skeleton) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_skinned_glb(writer, skeleton))
+    }
+}
+
+impl Domain {
+    /// Write all meshes as separate nodes `{name}.{index}` of a binary glTF 2.0 file, with
+    /// [Mesh::normals] when present.
+    pub fn write_glb<W: Write>(&self, writer: &mut W, name: &str) -> io::Result<()> {
+        let mut glb = GlbBuilder::default();
+        for (index, mesh) in self.meshes.iter().enumerate() {
+            glb.mesh(mesh, &mesh.normals, &format!("{name}.{index}"), false)?;
+        }
+        glb.write(writer)
+    }
+
+    /// Write all meshes as a `.glb` file to `path`, see [write_file_atomic].
+    pub fn export_to_glb_file(&self, path: &Path, name: &str) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_glb(writer, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+    use crate::Mesh;
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string("mesh.0"), r#""mesh.0""#);
+        assert_eq!(json_string(r#"a "b" \ c"#), r#""a \"b\" \\ c""#);
+        assert_eq!(json_string("it's é"), r#""it's é""#);
+        assert_eq!(
+            json_string("tab\tline\n\u{1f}"),
+            r#""tab\u0009line\u000a\u001f""#
+        );
+    }
+
+    #[test]
+    fn empty_mesh_has_no_accessors() {
+        let mut glb = Vec::new();
+        Mesh::default().write_glb(&mut glb, &[]).unwrap();
+        let length = u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize;
+        let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        // Only the JSON chunk, the binary chunk is left out.
+        assert_eq!(length, glb.len());
+        assert_eq!(length, 20 + json_length);
+        let json = std::str::from_utf8(&glb[20..]).unwrap().trim_end();
+        assert_eq!(
+            json,
+            r#"{"asset":{"version":"2.0","generator":"marching-cubes"},"scene":0,"scenes":[{"nodes":[0]}],"nodes":[{"name":"mesh"}]}"#
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{Domain, Mesh};
+
+#[cfg(feature = "gltf")]
+mod gltf;
+mod obj;
+mod off;
+mod ply;
+mod stl;
+mod vti;
+
+pub use ply::PlyFormat;
+
+/// File formats [Domain::export_to_file] can write.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Blender python script, run it from the Blender text editor.
+    Bpy,
+    /// Wavefront OBJ, readable by most 3D tools.
+    Obj,
+    /// ASCII Object File Format, for CGAL and MeshLab.
+    Off,
+    /// Binary STL, for slicers.
+    Stl,
+    /// ASCII STL, for tools that can't read binary STL.
+    StlAscii,
+    /// PLY in the given encoding.
+    Ply(PlyFormat),
+    /// Binary glTF, for game engines and web viewers.
+    #[cfg(feature = "gltf")]
+    Glb,
+}
+
+impl ExportFormat {
+    /// Format that belongs to a file extension, PLY files are written in binary.
+    pub fn from_extension(extension: &str) -> Option<ExportFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "py" | "bpy" => Some(ExportFormat::Bpy),
+            "obj" => Some(ExportFormat::Obj),
+            "off" => Some(ExportFormat::Off),
+            "stl" => Some(ExportFormat::Stl),
+            "ply" => Some(ExportFormat::Ply(PlyFormat::default())),
+            #[cfg(feature = "gltf")]
+            "glb" => Some(ExportFormat::Glb),
+            _ => None,
+        }
+    }
+
+    /// Format by the name used on the command line: an extension, or `stl-ascii` for ASCII STL.
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "stl-ascii" => Some(ExportFormat::StlAscii),
+            extension => Self::from_extension(extension),
+        }
+    }
+
+    /// Format that belongs to the extension of `path`.
+    pub fn from_path(path: &Path) -> Option<ExportFormat> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    /// File extension of the format, without the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Bpy => "py",
+            ExportFormat::Obj => "obj",
+            ExportFormat::Off => "off",
+            ExportFormat::Stl | ExportFormat::StlAscii => "stl",
+            ExportFormat::Ply(_) => "ply",
+            #[cfg(feature = "gltf")]
+            ExportFormat::Glb => "glb",
+        }
+    }
+}
+
+/// Write a file by writing to a temporary file next to it and renaming it when done.
+///
+/// When writing fails or the process dies halfway, the file at `path` is either untouched or
+/// absent, it never contains a partial export.
+pub fn write_file_atomic<F>(path: &Path, write_contents: F) -> io::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write_contents(&mut writer)?;
+        let file = writer.into_inner().map_err(|error| error.into_error())?;
+        file.sync_all()
+    });
+    match result {
+        Ok(()) => fs::rename(&temp_path, path),
+        Err(error) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(error)
+        }
+    }
+}
+
+impl Domain {
+    /// Write all meshes in `format`. `name` is used for the objects in formats that name them.
+    pub fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        format: ExportFormat,
+        name: &str,
+    ) -> io::Result<()> {
+        match format {
+            ExportFormat::Bpy => self.write_bpy(writer, name),
+            ExportFormat::Obj => self.write_obj(writer, name),
+            ExportFormat::Off => self.write_off(writer),
+            ExportFormat::Stl => self.write_stl_binary(writer),
+            ExportFormat::StlAscii => self.write_stl_ascii(writer, name),
+            ExportFormat::Ply(ply_format) => self.write_ply(writer, ply_format),
+            #[cfg(feature = "gltf")]
+            ExportFormat::Glb => self.write_glb(writer, name),
+        }
+    }
+
+    /// Write all meshes in `format` to `path`, see [write_file_atomic]. Objects are named after
+    /// the file name without its extension.
+    pub fn export_to_file(&self, path: &Path, format: ExportFormat) -> io::Result<()> {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("Marching");
+        write_file_atomic(path, |writer| self.write(writer, format, name))
+    }
+
+    pub fn write_bpy<W: Write>(&self, writer: &mut W, name: &str) -> io::Result<()> {
+        writeln!(writer, "import bpy")?;
+        writeln!(writer)?;
+        for mesh in &self.meshes {
+            mesh.write_bpy(writer, name)?;
+        }
+        Ok(())
+    }
+
+    /// Write all meshes as a Blender python script to `path`, see [write_file_atomic].
+    pub fn export_to_bpy_file(&self, path: &Path, name: &str) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_bpy(writer, name))
+    }
+}
+
+impl Mesh {
+    pub fn write_bpy<W: Write>(&self, writer: &mut W, name: &str) -> io::Result<()> {
+        writeln!(writer, "verts = [")?;
+        for vert in &self.verts {
+            writeln!(writer, "  ({:8}, {:8}, {:8}),", vert.x, vert.y, vert.z)?;
+        }
+        writeln!(writer, "]")?;
+        writeln!(writer, "edges = [")?;
+        for edge in &self.edges {
+            writeln!(writer, "  ({:4}, {:4}),", edge.v1, edge.v2)?;
+        }
+        writeln!(writer, "]")?;
+        writeln!(writer, "faces = [")?;
+        for face in &self.faces {
+            writeln!(writer, "  ({:4}, {:4}, {:4}),", face.v1, face.v2, face.v3)?;
+        }
+        writeln!(writer, "]")?;
+        writeln!(writer, "new_mesh = bpy.data.meshes.new('{name}')")?;
+        writeln!(writer, "new_mesh.from_pydata(verts, edges, faces)")?;
+        if !self.normals.is_empty() && self.normals.len() == self.verts.len() {
+            writeln!(writer, "normals = [")?;
+            for normal in &self.normals {
+                writeln!(
+                    writer,
+                    "  ({:8}, {:8}, {:8}),",
+                    normal.x, normal.y, normal.z
+                )?;
+            }
+            writeln!(writer, "]")?;
+            writeln!(
+                writer,
+                "new_mesh.polygons.foreach_set('use_smooth', [True] * len(new_mesh.polygons))"
+            )?;
+            // Custom normals need auto smooth before Blender 4.1.
+            writeln!(writer, "if hasattr(new_mesh, 'use_auto_smooth'):")?;
+            writeln!(writer, "    new_mesh.use_auto_smooth = True")?;
+            writeln!(
+                writer,
+                "new_mesh.normals_split_custom_set_from_vertices(normals)"
+            )?;
+        }
+        if !self.uvs.is_empty() && self.uvs.len() == self.verts.len() {
+            writeln!(writer, "uvs = [")?;
+            for uv in &self.uvs {
+                writeln!(writer, "  ({:8}, {:8}),", uv[0], uv[1])?;
+            }
+            writeln!(writer, "]")?;
+            writeln!(writer, "uv_layer = new_mesh.uv_layers.new()")?;
+            writeln!(writer, "for loop in new_mesh.loops:")?;
+            writeln!(
+                writer,
+                "    uv_layer.data[loop.index].uv = uvs[loop.vertex_index]"
+            )?;
+        }
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "new_object = bpy.data.objects.new('{name}', new_mesh)"
+        )?;
+        writeln!(
+            writer,
+            "bpy.context.scene.collection.objects.link(new_object)"
+        )?;
+        Ok(())
+    }
+}
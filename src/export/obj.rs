@@ -0,0 +1,89 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Domain, Mesh, Vec3};
+
+use super::write_file_atomic;
+
+impl Mesh {
+    /// Write the mesh as a Wavefront OBJ file. Normals and texture coordinates are written when
+    /// present.
+    pub fn write_obj<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_obj_object(writer, None, 0, &self.normals)
+    }
+
+    /// Same as [Mesh::write_obj], with a `vn` record per vertex when `normals` isn't empty.
+    pub fn write_obj_with_normals<W: Write>(
+        &self,
+        writer: &mut W,
+        normals: &[Vec3],
+    ) -> io::Result<()> {
+        self.write_obj_object(writer, None, 0, normals)
+    }
+
+    /// Write the mesh as an object of an OBJ file. Indices are global to the file, so
+    /// `first_vertex` is the number of vertices written by previous objects.
+    fn write_obj_object<W: Write>(
+        &self,
+        writer: &mut W,
+        name: Option<&str>,
+        first_vertex: usize,
+        normals: &[Vec3],
+    ) -> io::Result<()> {
+        let has_uvs = self.uvs.len() == self.verts.len();
+        let has_normals = !normals.is_empty() && normals.len() == self.verts.len();
+        if let Some(name) = name {
+            writeln!(writer, "o {name}")?;
+        }
+        for vert in &self.verts {
+            writeln!(writer, "v {} {} {}", vert.x, vert.y, vert.z)?;
+        }
+        if has_uvs {
+            for uv in &self.uvs {
+                writeln!(writer, "vt {} {}", uv[0], uv[1])?;
+            }
+        }
+        for normal in normals.iter().filter(|_| has_normals) {
+            writeln!(writer, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+        }
+        for face in &self.faces {
+            write!(writer, "f")?;
+            for vert in [face.v1, face.v2, face.v3] {
+                // OBJ indices start at 1.
+                let index = first_vertex + vert + 1;
+                match (has_uvs, has_normals) {
+                    (false, false) => write!(writer, " {index}")?,
+                    (true, false) => write!(writer, " {index}/{index}")?,
+                    (false, true) => write!(writer, " {index}//{index}")?,
+                    (true, true) => write!(writer, " {index}/{index}/{index}")?,
+                }
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Domain {
+    /// Write all meshes as objects `{name}.{index}` of a single Wavefront OBJ file.
+    pub fn write_obj<W: Write>(&self, writer: &mut W, name: &str) -> io::Result<()> {
+        let mut first_vertex = 0;
+        for (index, mesh) in self.meshes.iter().enumerate() {
+            mesh.write_obj_object(
+                writer,
+                Some(&format!("{name}.{index}")),
+                first_vertex,
+                &mesh.normals,
+            )?;
+            first_vertex += mesh.verts.len();
+        }
+        Ok(())
+    }
+
+    /// Write all meshes as a Wavefront OBJ file to `path`, see [write_file_atomic].
+    pub fn export_to_obj_file(&self, path: &Path, name: &str) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_obj(writer, name))
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Domain, Mesh};
+
+use super::write_file_atomic;
+
+impl Mesh {
+    /// Write the mesh as an ASCII Object File Format (OFF) file, as read by CGAL and MeshLab.
+    pub fn write_off<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_off_meshes(writer, std::slice::from_ref(self))
+    }
+}
+
+impl Domain {
+    /// Write all meshes into a single OFF file. OFF has no objects, so the meshes become
+    /// disconnected parts of one mesh.
+    pub fn write_off<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_off_meshes(writer, &self.meshes)
+    }
+
+    /// Write all meshes as an OFF file to `path`, see [write_file_atomic].
+    pub fn export_to_off_file(&self, path: &Path) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_off(writer))
+    }
+}
+
+fn write_off_meshes<W: Write>(writer: &mut W, meshes: &[Mesh]) -> io::Result<()> {
+    let vert_count = meshes.iter().map(|mesh| mesh.verts.len()).sum::<usize>();
+    let face_count = meshes.iter().map(|mesh| mesh.faces.len()).sum::<usize>();
+    writeln!(writer, "OFF")?;
+    // The edge count is required by the header but ignored by readers.
+    writeln!(writer, "{vert_count} {face_count} 0")?;
+    for mesh in meshes {
+        for vert in &mesh.verts {
+            writeln!(writer, "{} {} {}", vert.x, vert.y, vert.z)?;
+        }
+    }
+    let mut first_vertex = 0;
+    for mesh in meshes {
+        for face in &mesh.faces {
+            writeln!(
+                writer,
+                "3 {} {} {}",
+                first_vertex + face.v1,
+                first_vertex + face.v2,
+                first_vertex + face.v3
+            )?;
+        }
+        first_vertex += mesh.verts.len();
+    }
+    Ok(())
+}
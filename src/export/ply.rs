@@ -0,0 +1,192 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Domain, Float, Mesh, Vec3};
+
+use super::write_file_atomic;
+
+/// Encoding of the elements of a PLY file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PlyFormat {
+    Ascii,
+    /// Much smaller and faster to read and write than ASCII for large meshes.
+    #[default]
+    BinaryLittleEndian,
+}
+
+/// Name of [Float] in a PLY header.
+const FLOAT_PROPERTY: &str = if size_of::<Float>() == 8 {
+    "double"
+} else {
+    "float"
+};
+
+struct PlyWriter<'a, W: Write> {
+    writer: &'a mut W,
+    format: PlyFormat,
+}
+
+impl<W: Write> PlyWriter<'_, W> {
+    fn header(
+        &mut self,
+        vertex_count: usize,
+        face_count: usize,
+        has_normals: bool,
+        has_colors: bool,
+    ) -> io::Result<()> {
+        let format = match self.format {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        };
+        writeln!(self.writer, "ply")?;
+        writeln!(self.writer, "format {format} 1.0")?;
+        writeln!(self.writer, "comment generated by marching-cubes")?;
+        writeln!(self.writer, "element vertex {vertex_count}")?;
+        for name in ["x", "y", "z"] {
+            writeln!(self.writer, "property {FLOAT_PROPERTY} {name}")?;
+        }
+        if has_normals {
+            for name in ["nx", "ny", "nz"] {
+                writeln!(self.writer, "property {FLOAT_PROPERTY} {name}")?;
+            }
+        }
+        if has_colors {
+            for name in ["red", "green", "blue"] {
+                writeln!(self.writer, "property uchar {name}")?;
+            }
+        }
+        writeln!(self.writer, "element face {face_count}")?;
+        writeln!(self.writer, "property list uchar uint vertex_indices")?;
+        writeln!(self.writer, "end_header")
+    }
+
+    fn vertex(
+        &mut self,
+        position: Vec3,
+        normal: Option<Vec3>,
+        color: Option<[u8; 3]>,
+    ) -> io::Result<()> {
+        let vectors = [Some(position), normal];
+        match self.format {
+            PlyFormat::Ascii => {
+                let mut values = Vec::new();
+                for vec in vectors.into_iter().flatten() {
+                    values.extend([vec.x, vec.y, vec.z].map(|value| value.to_string()));
+                }
+                if let Some(color) = color {
+                    values.extend(color.map(|value| value.to_string()));
+                }
+                writeln!(self.writer, "{}", values.join(" "))
+            }
+            PlyFormat::BinaryLittleEndian => {
+                for vec in vectors.into_iter().flatten() {
+                    for value in [vec.x, vec.y, vec.z] {
+                        self.writer.write_all(&value.to_le_bytes())?;
+                    }
+                }
+                if let Some(color) = color {
+                    self.writer.write_all(&color)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn face(&mut self, verts: [usize; 3]) -> io::Result<()> {
+        let mut indices = [0_u32; 3];
+        for (index, vert) in indices.iter_mut().zip(verts) {
+            *index = u32::try_from(vert).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "PLY vertex indices are limited to u32",
+                )
+            })?;
+        }
+        let [v1, v2, v3] = indices;
+        match self.format {
+            PlyFormat::Ascii => writeln!(self.writer, "3 {v1} {v2} {v3}"),
+            PlyFormat::BinaryLittleEndian => {
+                self.writer.write_all(&[3])?;
+                for vert in [v1, v2, v3] {
+                    self.writer.write_all(&vert.to_le_bytes())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Mesh {
+    /// Write the mesh as a PLY file. `normals` and `colors` are written as vertex properties
+    /// when they aren't empty, they must have one entry per vertex.
+    pub fn write_ply<W: Write>(
+        &self,
+        writer: &mut W,
+        format: PlyFormat,
+        normals: &[Vec3],
+        colors: &[[u8; 3]],
+    ) -> io::Result<()> {
+        for (name, len) in [("normals", normals.len()), ("colors", colors.len())] {
+            if len != 0 && len != self.verts.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{name} must have one entry per vertex"),
+                ));
+            }
+        }
+        let mut ply = PlyWriter { writer, format };
+        ply.header(
+            self.verts.len(),
+            self.faces.len(),
+            !normals.is_empty(),
+            !colors.is_empty(),
+        )?;
+        for (index, vert) in self.verts.iter().enumerate() {
+            ply.vertex(
+                *vert,
+                normals.get(index).copied(),
+                colors.get(index).copied(),
+            )?;
+        }
+        for face in &self.faces {
+            ply.face([face.v1, face.v2, face.v3])?;
+        }
+        Ok(())
+    }
+}
+
+impl Domain {
+    /// Write all meshes as a single PLY mesh. Normals are written when every mesh has them.
+    pub fn write_ply<W: Write>(&self, writer: &mut W, format: PlyFormat) -> io::Result<()> {
+        let vertex_count = self.meshes.iter().map(|mesh| mesh.verts.len()).sum();
+        let face_count = self.meshes.iter().map(|mesh| mesh.faces.len()).sum();
+        let has_normals = self
+            .meshes
+            .iter()
+            .all(|mesh| mesh.normals.len() == mesh.verts.len())
+            && vertex_count != 0;
+        let mut ply = PlyWriter { writer, format };
+        ply.header(vertex_count, face_count, has_normals, false)?;
+        for mesh in &self.meshes {
+            for (index, vert) in mesh.verts.iter().enumerate() {
+                let normal = has_normals.then(|| mesh.normals[index]);
+                ply.vertex(*vert, normal, None)?;
+            }
+        }
+        let mut first_vertex = 0;
+        for mesh in &self.meshes {
+            for face in &mesh.faces {
+                ply.face([face.v1, face.v2, face.v3].map(|vert| first_vertex + vert))?;
+            }
+            first_vertex += mesh.verts.len();
+        }
+        Ok(())
+    }
+
+    /// Write all meshes as a PLY file to `path`, see [write_file_atomic].
+    pub fn export_to_ply_file(&self, path: &Path, format: PlyFormat) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_ply(writer, format))
+    }
+}
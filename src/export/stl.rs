@@ -0,0 +1,106 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Domain, Mesh};
+
+use super::write_file_atomic;
+
+impl Mesh {
+    /// Write the mesh as a binary STL file.
+    pub fn write_stl_binary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_stl_binary_header(writer, self.faces.len())?;
+        self.write_stl_binary_facets(writer)
+    }
+
+    fn write_stl_binary_facets<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for face in &self.faces {
+            let normal = self.face_normal(face);
+            for vec in [
+                normal,
+                self.verts[face.v1],
+                self.verts[face.v2],
+                self.verts[face.v3],
+            ] {
+                for component in [vec.x, vec.y, vec.z] {
+                    // STL always stores f32, the cast is a no-op with the `f32` feature.
+                    #[allow(clippy::unnecessary_cast)]
+                    writer.write_all(&(component as f32).to_le_bytes())?;
+                }
+            }
+            // Attribute byte count, unused.
+            writer.write_all(&0_u16.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write the mesh as an ASCII STL solid named `name`.
+    pub fn write_stl_ascii<W: Write>(&self, writer: &mut W, name: &str) -> io::Result<()> {
+        writeln!(writer, "solid {name}")?;
+        self.write_stl_ascii_facets(writer)?;
+        writeln!(writer, "endsolid {name}")?;
+        Ok(())
+    }
+
+    fn write_stl_ascii_facets<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for face in &self.faces {
+            let normal = self.face_normal(face);
+            writeln!(
+                writer,
+                "  facet normal {} {} {}",
+                normal.x, normal.y, normal.z
+            )?;
+            writeln!(writer, "    outer loop")?;
+            for vert in [face.v1, face.v2, face.v3] {
+                let vert = self.verts[vert];
+                writeln!(writer, "      vertex {} {} {}", vert.x, vert.y, vert.z)?;
+            }
+            writeln!(writer, "    endloop")?;
+            writeln!(writer, "  endfacet")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_stl_binary_header<W: Write>(writer: &mut W, face_count: usize) -> io::Result<()> {
+    let face_count = u32::try_from(face_count).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "binary STL supports at most u32::MAX faces",
+        )
+    })?;
+    // The header must not start with "solid", readers would take it for an ASCII file.
+    let mut header = [b' '; 80];
+    let title = b"binary STL generated by marching-cubes";
+    header[..title.len()].copy_from_slice(title);
+    writer.write_all(&header)?;
+    writer.write_all(&face_count.to_le_bytes())
+}
+
+impl Domain {
+    /// Write all meshes as a single binary STL file.
+    pub fn write_stl_binary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let face_count = self.meshes.iter().map(|mesh| mesh.faces.len()).sum();
+        write_stl_binary_header(writer, face_count)?;
+        for mesh in &self.meshes {
+            mesh.write_stl_binary_facets(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write all meshes as a single ASCII STL solid named `name`.
+    pub fn write_stl_ascii<W: Write>(&self, writer: &mut W, name: &str) -> io::Result<()> {
+        writeln!(writer, "solid {name}")?;
+        for mesh in &self.meshes {
+            mesh.write_stl_ascii_facets(writer)?;
+        }
+        writeln!(writer, "endsolid {name}")?;
+        Ok(())
+    }
+
+    /// Write all meshes as a binary STL file to `path`, see [write_file_atomic].
+    pub fn export_to_stl_file(&self, path: &Path) -> io::Result<()> {
+        write_file_atomic(path, |writer| self.write_stl_binary(writer))
+    }
+}
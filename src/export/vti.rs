@@ -0,0 +1,130 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Domain, Float, IVec3, ScalarField, Vec3, grid::ScalarGrid};
+
+use super::write_file_atomic;
+
+/// Gradient of the sampled weights using central differences, one sided at the border.
+fn grid_gradient(grid: &ScalarGrid, position: IVec3, spacing: Vec3) -> Vec3 {
+    let axis = |offset: IVec3, size: i32, index: i32, spacing: Float| {
+        let previous = if index > 0 { -1 } else { 0 };
+        let next = if index + 1 < size { 1 } else { 0 };
+        if previous == next {
+            return 0.0;
+        }
+        let at = |step: i32| {
+            grid.weight(IVec3 {
+                x: position.x + offset.x * step,
+                y: position.y + offset.y * step,
+                z: position.z + offset.z * step,
+            })
+        };
+        (at(next) - at(previous)) / ((next - previous) as Float * spacing)
+    };
+    Vec3 {
+        x: axis(
+            IVec3 { x: 1, y: 0, z: 0 },
+            grid.size.x,
+            position.x,
+            spacing.x,
+        ),
+        y: axis(
+            IVec3 { x: 0, y: 1, z: 0 },
+            grid.size.y,
+            position.y,
+            spacing.y,
+        ),
+        z: axis(
+            IVec3 { x: 0, y: 0, z: 1 },
+            grid.size.z,
+            position.z,
+            spacing.z,
+        ),
+    }
+}
+
+impl Domain {
+    /// Write the weights of the field at every lattice vertex used by the march and their
+    /// gradient as a VTK image data (`.vti`) file, for volumetric rendering next to the mesh.
+    ///
+    /// The volume is aligned with the lattice, so the surface of the mesh lies at the surface
+    /// weight of the volume. The gradient is calculated from the samples.
+    pub fn write_vti<W, FIELD>(&self, writer: &mut W, field: &FIELD) -> io::Result<()>
+    where
+        W: Write,
+        FIELD: ScalarField + ?Sized,
+    {
+        let grid = self.sample_grid(field);
+        let origin = self.vertex_position(IVec3 { x: 0, y: 0, z: 0 });
+        let spacing = Vec3 {
+            x: (self.to.x - self.from.x) / self.width as Float,
+            y: (self.to.y - self.from.y) / self.height as Float,
+            z: (self.to.z - self.from.z) / self.depth as Float,
+        };
+        let data_type = format!("Float{}", size_of::<Float>() * 8);
+        let extent = format!(
+            "0 {} 0 {} 0 {}",
+            grid.size.x - 1,
+            grid.size.y - 1,
+            grid.size.z - 1
+        );
+
+        writeln!(writer, r#"<?xml version="1.0"?>"#)?;
+        writeln!(
+            writer,
+            r#"<VTKFile type="ImageData" version="1.0" byte_order="LittleEndian">"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <ImageData WholeExtent="{extent}" Origin="{} {} {}" Spacing="{} {} {}">"#,
+            origin.x, origin.y, origin.z, spacing.x, spacing.y, spacing.z
+        )?;
+        writeln!(writer, r#"    <Piece Extent="{extent}">"#)?;
+        writeln!(
+            writer,
+            r#"      <PointData Scalars="weight" Vectors="gradient">"#
+        )?;
+        writeln!(
+            writer,
+            r#"        <DataArray type="{data_type}" Name="weight" format="ascii">"#
+        )?;
+        for weight in &grid.weights {
+            writeln!(writer, "          {weight}")?;
+        }
+        writeln!(writer, "        </DataArray>")?;
+        writeln!(
+            writer,
+            r#"        <DataArray type="{data_type}" Name="gradient" NumberOfComponents="3" format="ascii">"#
+        )?;
+        for z in 0..grid.size.z {
+            for y in 0..grid.size.y {
+                for x in 0..grid.size.x {
+                    let gradient = grid_gradient(&grid, IVec3 { x, y, z }, spacing);
+                    writeln!(
+                        writer,
+                        "          {} {} {}",
+                        gradient.x, gradient.y, gradient.z
+                    )?;
+                }
+            }
+        }
+        writeln!(writer, "        </DataArray>")?;
+        writeln!(writer, "      </PointData>")?;
+        writeln!(writer, "    </Piece>")?;
+        writeln!(writer, "  </ImageData>")?;
+        writeln!(writer, "</VTKFile>")?;
+        Ok(())
+    }
+
+    /// Write the sampled field as a `.vti` volume to `path`, see [Domain::write_vti] and
+    /// [write_file_atomic].
+    pub fn export_to_vti_file<FIELD>(&self, path: &Path, field: &FIELD) -> io::Result<()>
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        write_file_atomic(path, |writer| self.write_vti(writer, field))
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{Domain, RefineStrategy, ScalarField, algorithm::Decomposition, result::MarchResult};
+
+/// Algorithm used by [Domain::extract] to turn the field into a mesh.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ExtractionMethod {
+    /// Smooth surface using [Domain::march_tetrahedras].
+    #[default]
+    Tetrahedra,
+    /// Smooth surface split the same way in every cell, using
+    /// [Domain::march_tetrahedras_with_decomposition] with [Decomposition::Six].
+    SixTetrahedra,
+    /// Smooth surface with well shaped triangles using [Domain::march_body_centered].
+    BodyCentered,
+    /// Smooth surface with fewer triangles using [Domain::march_cubes].
+    MarchingCubes,
+    /// Smooth surface with a vertex per cell using [Domain::march_surface_nets].
+    SurfaceNets,
+    /// Surface with sharp edges and corners using [Domain::march_dual_contouring].
+    DualContouring,
+    /// Smooth surface with a vertex per surface patch of a cell using
+    /// [Domain::march_dual_tetrahedras].
+    DualTetrahedra,
+    /// Minecraft style axis aligned blocks using [Domain::march_blocky].
+    Blocky,
+}
+
+impl Domain {
+    /// Extract the surface of the field with the given method and add it as a new mesh.
+    ///
+    /// `refine` is only used by methods that place vertices on the surface.
+    pub fn extract<FIELD, REFINE>(
+        &mut self,
+        method: ExtractionMethod,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        match method {
+            ExtractionMethod::Tetrahedra => self.march_tetrahedras(field, refine),
+            ExtractionMethod::SixTetrahedra => {
+                self.march_tetrahedras_with_decomposition(Decomposition::Six, field, refine)
+            }
+            ExtractionMethod::BodyCentered => self.march_body_centered(field, refine),
+            ExtractionMethod::MarchingCubes => self.march_cubes(field, refine),
+            ExtractionMethod::SurfaceNets => self.march_surface_nets(field, refine),
+            ExtractionMethod::DualContouring => self.march_dual_contouring(field, refine),
+            ExtractionMethod::DualTetrahedra => self.march_dual_tetrahedras(field, refine),
+            ExtractionMethod::Blocky => self.march_blocky(field),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{Float, Vec3, grid::MinMax};
+
+use super::{Metaballs, ScalarField};
+
+/// Field that can tell which weights are possible inside a box without sampling it, so the
+/// marcher can skip parts of the domain that can't contain the surface. See
+/// `Domain::march_tetrahedras_bounded`.
+pub trait FieldBounds: ScalarField {
+    /// Range of the weights between `from` and `to`. The range may be wider than the actual
+    /// weights, but never narrower.
+    fn weight_range(&self, from: Vec3, to: Vec3) -> MinMax;
+}
+
+/// Distance from `position` to the nearest and to the farthest point of the box `from`..`to`.
+fn distance_range(position: Vec3, from: Vec3, to: Vec3) -> (Float, Float) {
+    let axis = |position: Float, from: Float, to: Float| {
+        let nearest = if position < from {
+            from - position
+        } else if position > to {
+            position - to
+        } else {
+            0.0
+        };
+        let farthest = (position - from).abs().max((position - to).abs());
+        (nearest, farthest)
+    };
+    let (x_near, x_far) = axis(position.x, from.x, to.x);
+    let (y_near, y_far) = axis(position.y, from.y, to.y);
+    let (z_near, z_far) = axis(position.z, from.z, to.z);
+    (
+        (x_near * x_near + y_near * y_near + z_near * z_near).sqrt(),
+        (x_far * x_far + y_far * y_far + z_far * z_far).sqrt(),
+    )
+}
+
+impl FieldBounds for Metaballs {
+    /// Each force contributes between `force / farthest` and `force / nearest` distance, line
+    /// and ellipsoid forces use conservative distances.
+    fn weight_range(&self, from: Vec3, to: Vec3) -> MinMax {
+        let mut range = MinMax { min: 0.0, max: 0.0 };
+        for force in &self.forces {
+            let (nearest, farthest) = distance_range(force.position, from, to);
+            let (near_weight, far_weight) = (force.force / nearest, force.force / farthest);
+            range.min += near_weight.min(far_weight);
+            range.max += near_weight.max(far_weight);
+        }
+        for line in &self.lines {
+            // The line lies within the bounding box of its points, which bounds how near the
+            // line can get. Every point of the box is at most as far from the line as from the
+            // nearest point of the polyline.
+            let mut line_from = [Float::INFINITY; 3];
+            let mut line_to = [Float::NEG_INFINITY; 3];
+            let mut farthest = Float::INFINITY;
+            for point in &line.points {
+                for (axis, value) in [point.x, point.y, point.z].into_iter().enumerate() {
+                    line_from[axis] = line_from[axis].min(value);
+                    line_to[axis] = line_to[axis].max(value);
+                }
+                farthest = farthest.min(distance_range(*point, from, to).1);
+            }
+            let gap = [from.x, from.y, from.z]
+                .into_iter()
+                .zip([to.x, to.y, to.z])
+                .enumerate()
+                .map(|(axis, (from, to))| (from - line_to[axis]).max(line_from[axis] - to).max(0.0))
+                .map(|gap| gap * gap)
+                .sum::<Float>()
+                .sqrt();
+            let (near_weight, far_weight) = (line.force / gap, line.force / farthest);
+            range.min += near_weight.min(far_weight);
+            range.max += near_weight.max(far_weight);
+        }
+        for ellipsoid in &self.ellipsoids {
+            // Scaled distances are between the distance divided by the largest and by the
+            // smallest radius.
+            let radii = ellipsoid.radii;
+            let largest = radii.x.abs().max(radii.y.abs()).max(radii.z.abs());
+            let smallest = radii.x.abs().min(radii.y.abs()).min(radii.z.abs());
+            let (nearest, farthest) = distance_range(ellipsoid.position, from, to);
+            let near_weight = ellipsoid.force / (nearest / largest);
+            let far_weight = ellipsoid.force / (farthest / smallest);
+            range.min += near_weight.min(far_weight);
+            range.max += near_weight.max(far_weight);
+        }
+        range
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{Float, Vec3};
+
+use super::ScalarField;
+
+// Inside has the higher weight, so a union keeps the maximum of both weights and an intersection
+// the minimum. This holds for any surface weight the two fields share; the weights are only
+// distances for the signed distance primitives, so the smooth variants blend over `radius` units
+// of weight.
+
+/// Volume inside either field.
+pub struct Union<'a, A: ?Sized, B: ?Sized> {
+    pub a: &'a A,
+    pub b: &'a B,
+}
+
+impl<A, B> ScalarField for Union<'_, A, B>
+where
+    A: ScalarField + ?Sized,
+    B: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        self.a.sample(position).max(self.b.sample(position))
+    }
+}
+
+/// Volume inside both fields.
+pub struct Intersection<'a, A: ?Sized, B: ?Sized> {
+    pub a: &'a A,
+    pub b: &'a B,
+}
+
+impl<A, B> ScalarField for Intersection<'_, A, B>
+where
+    A: ScalarField + ?Sized,
+    B: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        self.a.sample(position).min(self.b.sample(position))
+    }
+}
+
+/// Volume inside `a` and outside `b`.
+///
+/// `b` is mirrored around `surface_weight` to turn its inside into its outside, so both fields
+/// must have their surface at that weight.
+pub struct Difference<'a, A: ?Sized, B: ?Sized> {
+    pub a: &'a A,
+    pub b: &'a B,
+    pub surface_weight: Float,
+}
+
+impl<A, B> ScalarField for Difference<'_, A, B>
+where
+    A: ScalarField + ?Sized,
+    B: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        let outside_b = 2.0 * self.surface_weight - self.b.sample(position);
+        self.a.sample(position).min(outside_b)
+    }
+}
+
+/// [Union] that fills the crease where the surfaces meet with a fillet of about `radius`.
+pub struct SmoothUnion<'a, A: ?Sized, B: ?Sized> {
+    pub a: &'a A,
+    pub b: &'a B,
+    pub radius: Float,
+}
+
+impl<A, B> ScalarField for SmoothUnion<'_, A, B>
+where
+    A: ScalarField + ?Sized,
+    B: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        smooth_max(
+            self.a.sample(position),
+            self.b.sample(position),
+            self.radius,
+        )
+    }
+}
+
+/// [Intersection] that rounds the edge where the surfaces meet by about `radius`.
+pub struct SmoothIntersection<'a, A: ?Sized, B: ?Sized> {
+    pub a: &'a A,
+    pub b: &'a B,
+    pub radius: Float,
+}
+
+impl<A, B> ScalarField for SmoothIntersection<'_, A, B>
+where
+    A: ScalarField + ?Sized,
+    B: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        smooth_min(
+            self.a.sample(position),
+            self.b.sample(position),
+            self.radius,
+        )
+    }
+}
+
+/// [Difference] that rounds the edge of the cut by about `radius`.
+pub struct SmoothDifference<'a, A: ?Sized, B: ?Sized> {
+    pub a: &'a A,
+    pub b: &'a B,
+    pub surface_weight: Float,
+    pub radius: Float,
+}
+
+impl<A, B> ScalarField for SmoothDifference<'_, A, B>
+where
+    A: ScalarField + ?Sized,
+    B: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        let outside_b = 2.0 * self.surface_weight - self.b.sample(position);
+        smooth_min(self.a.sample(position), outside_b, self.radius)
+    }
+}
+
+/// Polynomial smooth minimum: equal to `a.min(b)` where the weights differ by more than `radius`
+/// and below both weights in between.
+pub fn smooth_min(a: Float, b: Float, radius: Float) -> Float {
+    if radius <= 0.0 {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / radius).clamp(0.0, 1.0);
+    b + (a - b) * h - radius * h * (1.0 - h)
+}
+
+/// Polynomial smooth maximum, the counterpart of [smooth_min].
+pub fn smooth_max(a: Float, b: Float, radius: Float) -> Float {
+    -smooth_min(-a, -b, radius)
+}
@@ -0,0 +1,566 @@
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use crate::{
+    Float, Vec3, heightmap::Heightmap, sparse::SparseGrid, voxel::VoxelGrid,
+    voxel_file::FileVoxelGrid,
+};
+
+use super::{
+    Capsule, CapsulePath, Cuboid, Cylinder, Difference, Emboss, Intersection, Metaballs, Metric,
+    Normalize, Plane, Printable, Rounded, RoundedCuboid, ScalarField, Skeleton, SmoothDifference,
+    SmoothIntersection, SmoothUnion, Sphere, Torus, Transformed, Union,
+};
+
+/// Tree of the fields and modifiers a field is composed of, see [Describe].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldGraph {
+    /// Name of the type of the field, for example `Sphere` or `SmoothUnion`.
+    pub kind: String,
+    /// Parameters of the field in declaration order, formatted for display.
+    pub parameters: Vec<(String, String)>,
+    /// Fields wrapped by a modifier or combinator, empty for leaf fields.
+    pub children: Vec<FieldGraph>,
+}
+
+/// Field that can describe how it is composed, to debug, diff and visualize procedural scenes.
+///
+/// Every field of this module implements it, modifiers and combinators include the graphs of the
+/// fields they wrap. Closures are opaque, wrap them in [Named] to give them a place in the graph.
+///
+/// ```
+/// use marching_cubes::{field::{Cuboid, Describe, Sphere, Union}, Vec3};
+///
+/// let sphere = Sphere { center: Vec3::default(), radius: 1.0 };
+/// let cuboid = Cuboid { center: Vec3::default(), half_size: Vec3 { x: 2.0, y: 0.5, z: 0.5 } };
+/// let graph = Union { a: &sphere, b: &cuboid }.describe();
+/// assert_eq!(graph.node_count(), 3);
+/// print!("{graph}");
+/// ```
+pub trait Describe {
+    fn describe(&self) -> FieldGraph;
+}
+
+impl FieldGraph {
+    /// Graph of a field of type `kind` without parameters or children.
+    pub fn new(kind: &str) -> FieldGraph {
+        FieldGraph {
+            kind: kind.to_string(),
+            parameters: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Add a parameter, formatted with [fmt::Display].
+    pub fn parameter(mut self, name: &str, value: impl fmt::Display) -> FieldGraph {
+        self.parameters.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add a vector parameter.
+    pub fn vector(self, name: &str, value: Vec3) -> FieldGraph {
+        self.parameter(name, format!("({}, {}, {})", value.x, value.y, value.z))
+    }
+
+    /// Add the graph of a wrapped field.
+    pub fn child(mut self, child: FieldGraph) -> FieldGraph {
+        self.children.push(child);
+        self
+    }
+
+    /// Number of fields in the graph, including this one.
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(FieldGraph::node_count)
+            .sum::<usize>()
+    }
+
+    /// Write the graph in the Graphviz DOT language, render it with `dot -Tsvg`. Nodes list
+    /// their parameters, edges point from modifiers to the fields they wrap.
+    pub fn write_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "digraph field {{")?;
+        writeln!(writer, "    node [shape=box, fontname=monospace];")?;
+        let mut next_id = 0;
+        self.write_dot_node(writer, &mut next_id)?;
+        writeln!(writer, "}}")
+    }
+
+    fn write_dot_node<W: Write>(&self, writer: &mut W, next_id: &mut usize) -> io::Result<usize> {
+        let id = *next_id;
+        *next_id += 1;
+        let mut label = dot_escape(&self.kind);
+        for (name, value) in &self.parameters {
+            label.push_str(&format!("\\l{} = {}", dot_escape(name), dot_escape(value)));
+        }
+        if !self.parameters.is_empty() {
+            label.push_str("\\l");
+        }
+        writeln!(writer, "    n{id} [label=\"{label}\"];")?;
+        for child in &self.children {
+            let child_id = child.write_dot_node(writer, next_id)?;
+            writeln!(writer, "    n{id} -> n{child_id};")?;
+        }
+        Ok(id)
+    }
+
+    /// Write the graph as JSON: an object with `kind`, `parameters` as an object of strings and
+    /// `children` as an array of graphs.
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(
+            writer,
+            "{{\"kind\":{},\"parameters\":{{",
+            json_string(&self.kind)
+        )?;
+        for (index, (name, value)) in self.parameters.iter().enumerate() {
+            if index > 0 {
+                write!(writer, ",")?;
+            }
+            write!(writer, "{}:{}", json_string(name), json_string(value))?;
+        }
+        write!(writer, "}},\"children\":[")?;
+        for (index, child) in self.children.iter().enumerate() {
+            if index > 0 {
+                write!(writer, ",")?;
+            }
+            child.write_json(writer)?;
+        }
+        write!(writer, "]}}")
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.kind, indent = depth * 2)?;
+        for (index, (name, value)) in self.parameters.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{separator}{name}={value}")?;
+        }
+        writeln!(f)?;
+        for child in &self.children {
+            child.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// One field per line, children indented below their modifier, so two versions of a scene can
+/// be compared with a text diff.
+impl fmt::Display for FieldGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            character if (character as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => escaped.push(character),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Give a field without a description, such as a closure, a name in the [FieldGraph].
+pub struct Named<'a, FIELD: ?Sized> {
+    pub field: &'a FIELD,
+    pub name: &'a str,
+}
+
+impl<FIELD> ScalarField for Named<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        self.field.sample(position)
+    }
+
+    fn analytic_gradient(&self, position: Vec3) -> Option<Vec3> {
+        self.field.analytic_gradient(position)
+    }
+}
+
+impl<FIELD: ?Sized> Describe for Named<'_, FIELD> {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Named").parameter("name", self.name)
+    }
+}
+
+impl Describe for Sphere {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Sphere")
+            .vector("center", self.center)
+            .parameter("radius", self.radius)
+    }
+}
+
+impl Describe for Cuboid {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Cuboid")
+            .vector("center", self.center)
+            .vector("half_size", self.half_size)
+    }
+}
+
+impl Describe for RoundedCuboid {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("RoundedCuboid")
+            .vector("center", self.center)
+            .vector("half_size", self.half_size)
+            .parameter("radius", self.radius)
+    }
+}
+
+impl Describe for Torus {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Torus")
+            .vector("center", self.center)
+            .parameter("major_radius", self.major_radius)
+            .parameter("minor_radius", self.minor_radius)
+    }
+}
+
+impl Describe for Capsule {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Capsule")
+            .vector("from", self.from)
+            .vector("to", self.to)
+            .parameter("radius", self.radius)
+    }
+}
+
+impl Describe for Cylinder {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Cylinder")
+            .vector("from", self.from)
+            .vector("to", self.to)
+            .parameter("radius", self.radius)
+    }
+}
+
+impl Describe for Plane {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Plane")
+            .vector("point", self.point)
+            .vector("normal", self.normal)
+    }
+}
+
+impl Describe for Metaballs {
+    fn describe(&self) -> FieldGraph {
+        let graph = self
+            .forces
+            .iter()
+            .fold(FieldGraph::new("Metaballs"), |graph, force| {
+                graph.child(
+                    FieldGraph::new("Force")
+                        .vector("position", force.position)
+                        .parameter("force", force.force),
+                )
+            });
+        let graph = self.lines.iter().fold(graph, |graph, line| {
+            graph.child(
+                FieldGraph::new("LineForce")
+                    .parameter("points", line.points.len())
+                    .parameter("force", line.force),
+            )
+        });
+        self.ellipsoids.iter().fold(graph, |graph, ellipsoid| {
+            graph.child(
+                FieldGraph::new("EllipsoidForce")
+                    .vector("position", ellipsoid.position)
+                    .parameter("force", ellipsoid.force)
+                    .vector("radii", ellipsoid.radii)
+                    .vector("axis", ellipsoid.axis)
+                    .parameter("angle", ellipsoid.angle),
+            )
+        })
+    }
+}
+
+impl Describe for CapsulePath {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("CapsulePath").parameter("points", self.point_count())
+    }
+}
+
+impl Describe for Skeleton {
+    fn describe(&self) -> FieldGraph {
+        self.bones
+            .iter()
+            .fold(FieldGraph::new("Skeleton"), |graph, bone| {
+                let bone_graph = FieldGraph::new("Bone")
+                    .vector("from", bone.from)
+                    .vector("to", bone.to)
+                    .parameter("radius", bone.radius);
+                graph.child(match bone.parent {
+                    Some(parent) => bone_graph.parameter("parent", parent),
+                    None => bone_graph,
+                })
+            })
+    }
+}
+
+impl Describe for SparseGrid {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("SparseGrid")
+            .vector("from", self.from)
+            .vector("to", self.to)
+            .parameter(
+                "resolution",
+                format!("{}x{}x{}", self.width, self.height, self.depth),
+            )
+            .parameter("tile_size", self.tile_size())
+            .parameter("tiles", self.tile_count())
+    }
+}
+
+impl Describe for VoxelGrid {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("VoxelGrid")
+            .vector("from", self.from)
+            .vector("to", self.to)
+            .parameter(
+                "resolution",
+                format!("{}x{}x{}", self.width, self.height, self.depth),
+            )
+    }
+}
+
+impl Describe for Heightmap {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Heightmap")
+            .vector("from", self.from)
+            .vector("to", self.to)
+            .parameter("resolution", format!("{}x{}", self.width, self.height))
+    }
+}
+
+impl Describe for FileVoxelGrid {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("FileVoxelGrid")
+            .vector("from", self.from)
+            .vector("to", self.to)
+            .parameter(
+                "resolution",
+                format!("{}x{}x{}", self.width, self.height, self.depth),
+            )
+            .parameter("format", format!("{:?}", self.format))
+    }
+}
+
+impl<A, B> Describe for Union<'_, A, B>
+where
+    A: Describe + ?Sized,
+    B: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Union")
+            .child(self.a.describe())
+            .child(self.b.describe())
+    }
+}
+
+impl<A, B> Describe for Intersection<'_, A, B>
+where
+    A: Describe + ?Sized,
+    B: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Intersection")
+            .child(self.a.describe())
+            .child(self.b.describe())
+    }
+}
+
+impl<A, B> Describe for Difference<'_, A, B>
+where
+    A: Describe + ?Sized,
+    B: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Difference")
+            .parameter("surface_weight", self.surface_weight)
+            .child(self.a.describe())
+            .child(self.b.describe())
+    }
+}
+
+impl<A, B> Describe for SmoothUnion<'_, A, B>
+where
+    A: Describe + ?Sized,
+    B: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("SmoothUnion")
+            .parameter("radius", self.radius)
+            .child(self.a.describe())
+            .child(self.b.describe())
+    }
+}
+
+impl<A, B> Describe for SmoothIntersection<'_, A, B>
+where
+    A: Describe + ?Sized,
+    B: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("SmoothIntersection")
+            .parameter("radius", self.radius)
+            .child(self.a.describe())
+            .child(self.b.describe())
+    }
+}
+
+impl<A, B> Describe for SmoothDifference<'_, A, B>
+where
+    A: Describe + ?Sized,
+    B: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("SmoothDifference")
+            .parameter("surface_weight", self.surface_weight)
+            .parameter("radius", self.radius)
+            .child(self.a.describe())
+            .child(self.b.describe())
+    }
+}
+
+impl<FIELD> Describe for Transformed<'_, FIELD>
+where
+    FIELD: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Transformed")
+            .vector("translation", self.translation)
+            .vector("axis", self.axis)
+            .parameter("angle", self.angle)
+            .parameter("scale", self.scale)
+            .child(self.field.describe())
+    }
+}
+
+impl<FIELD> Describe for Rounded<'_, FIELD>
+where
+    FIELD: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        let metric = match self.metric {
+            Metric::Distance => "Distance".to_string(),
+            Metric::Density { gradient_step } => {
+                format!("Density {{ gradient_step: {gradient_step} }}")
+            }
+        };
+        FieldGraph::new("Rounded")
+            .parameter("radius", self.radius)
+            .parameter("metric", metric)
+            .child(self.field.describe())
+    }
+}
+
+impl<FIELD> Describe for Normalize<'_, FIELD>
+where
+    FIELD: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Normalize")
+            .parameter("surface_weight", self.surface_weight)
+            .parameter("gradient_step", self.gradient_step)
+            .child(self.field.describe())
+    }
+}
+
+impl<FIELD> Describe for Emboss<'_, FIELD>
+where
+    FIELD: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Emboss")
+            .parameter("polygons", self.polygons.len())
+            .vector("origin", self.origin)
+            .vector("axis", self.axis)
+            .parameter("depth", self.depth)
+            .parameter("blend_radius", self.blend_radius)
+            .child(self.field.describe())
+    }
+}
+
+impl<FIELD> Describe for Printable<'_, FIELD>
+where
+    FIELD: Describe + ?Sized,
+{
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Printable")
+            .parameter("surface_weight", self.surface_weight)
+            .parameter("wall_thickness", self.wall_thickness)
+            .parameter("infill_cell_size", self.infill.cell_size)
+            .parameter("infill_thickness", self.infill.thickness)
+            .child(self.field.describe())
+    }
+}
+
+#[cfg(feature = "expression")]
+impl Describe for super::Expression {
+    fn describe(&self) -> FieldGraph {
+        FieldGraph::new("Expression").parameter("source", self.source())
+    }
+}
+
+#[cfg(feature = "noise")]
+mod noise {
+    use super::{Describe, FieldGraph};
+    use crate::field::{Displace, Fbm, Perlin, Simplex};
+
+    impl Describe for Perlin {
+        fn describe(&self) -> FieldGraph {
+            FieldGraph::new("Perlin").parameter("seed", self.seed())
+        }
+    }
+
+    impl Describe for Simplex {
+        fn describe(&self) -> FieldGraph {
+            FieldGraph::new("Simplex").parameter("seed", self.seed())
+        }
+    }
+
+    impl<NOISE> Describe for Fbm<'_, NOISE>
+    where
+        NOISE: Describe + ?Sized,
+    {
+        fn describe(&self) -> FieldGraph {
+            FieldGraph::new("Fbm")
+                .parameter("octaves", self.octaves)
+                .parameter("frequency", self.frequency)
+                .parameter("lacunarity", self.lacunarity)
+                .parameter("gain", self.gain)
+                .child(self.noise.describe())
+        }
+    }
+
+    impl<FIELD, NOISE> Describe for Displace<'_, FIELD, NOISE>
+    where
+        FIELD: Describe + ?Sized,
+        NOISE: Describe + ?Sized,
+    {
+        fn describe(&self) -> FieldGraph {
+            FieldGraph::new("Displace")
+                .parameter("amplitude", self.amplitude)
+                .child(self.field.describe())
+                .child(self.noise.describe())
+        }
+    }
+}
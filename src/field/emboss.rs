@@ -0,0 +1,101 @@
+use crate::{Float, Vec3};
+
+use super::ScalarField;
+
+/// Field modifier that stamps 2D polygons onto the surface of another field.
+///
+/// The polygons are defined in a plane (`origin`, `axis`) and projected along the axis. Where the
+/// projection hits the base surface the surface is displaced along the axis by `depth`. A
+/// positive depth embosses, a negative depth engraves. Polygons are filled using the even-odd
+/// rule so glyph outlines with counters (the hole in an `o`) can be passed as is.
+///
+/// Only the side of the plane the axis is pointing to is stamped, so the back side of a part
+/// keeps its shape when the plane is placed inside the part.
+pub struct Emboss<'a, FIELD: ?Sized> {
+    pub field: &'a FIELD,
+
+    pub polygons: Vec<Vec<[Float; 2]>>,
+    pub origin: Vec3,
+    pub axis: Vec3,
+    pub depth: Float,
+    pub blend_radius: Float,
+}
+
+impl<FIELD: ?Sized> Emboss<'_, FIELD> {
+    /// Two unit vectors perpendicular to the axis spanning the polygon plane.
+    fn plane_axes(&self) -> (Vec3, Vec3, Vec3) {
+        let normal = self.axis.normalized();
+        let helper = if normal.x.abs() < 0.9 {
+            Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            }
+        } else {
+            Vec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            }
+        };
+        let u = helper.cross(normal).normalized();
+        let v = normal.cross(u);
+        (u, v, normal)
+    }
+
+    /// Signed 2D distance to the polygon outlines, negative inside.
+    fn signed_distance(&self, point: [Float; 2]) -> Float {
+        let mut inside = false;
+        let mut distance = Float::INFINITY;
+        for polygon in &self.polygons {
+            for index in 0..polygon.len() {
+                let a = polygon[index];
+                let b = polygon[(index + 1) % polygon.len()];
+                if (a[1] > point[1]) != (b[1] > point[1])
+                    && point[0] < a[0] + (point[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1])
+                {
+                    inside = !inside;
+                }
+                let edge = [b[0] - a[0], b[1] - a[1]];
+                let to_point = [point[0] - a[0], point[1] - a[1]];
+                let length_squared = edge[0] * edge[0] + edge[1] * edge[1];
+                let factor = if length_squared > 0.0 {
+                    ((to_point[0] * edge[0] + to_point[1] * edge[1]) / length_squared)
+                        .clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let dx = to_point[0] - edge[0] * factor;
+                let dy = to_point[1] - edge[1] * factor;
+                distance = distance.min((dx * dx + dy * dy).sqrt());
+            }
+        }
+        if inside { -distance } else { distance }
+    }
+}
+
+fn smoothstep(value: Float) -> Float {
+    let value = value.clamp(0.0, 1.0);
+    value * value * (3.0 - 2.0 * value)
+}
+
+/// The base field is sampled at a position shifted against the axis, which moves surfaces facing
+/// the axis by `depth` in world units independent of how the base field scales its weights.
+impl<FIELD> ScalarField for Emboss<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        let (u, v, normal) = self.plane_axes();
+        let local = position - self.origin;
+        let height = local.dot(normal);
+        let point = [local.dot(u), local.dot(v)];
+
+        let blend_radius = self.blend_radius.max(Float::EPSILON);
+        let outline_mask = smoothstep(0.5 - self.signed_distance(point) / (2.0 * blend_radius));
+        let side_mask = smoothstep(0.5 + height / (2.0 * blend_radius));
+        let displacement = self.depth * outline_mask * side_mask;
+
+        self.field.sample(position - normal * displacement)
+    }
+}
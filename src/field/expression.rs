@@ -0,0 +1,554 @@
+use std::{error::Error, fmt};
+
+use crate::{Float, Vec3, consts};
+
+use super::ScalarField;
+
+/// Reason why [Expression::parse] rejected an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionError {
+    /// Byte offset in the source where the problem was found.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expression column {}: {}",
+            self.position + 1,
+            self.message
+        )
+    }
+}
+
+impl Error for ExpressionError {}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Sqrt,
+    Abs,
+    Exp,
+    Ln,
+    Floor,
+    Ceil,
+    Round,
+    Sign,
+    Atan2,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+}
+
+impl Function {
+    /// Number of arguments the function takes.
+    fn arity(self) -> usize {
+        match self {
+            Function::Atan2 | Function::Min | Function::Max | Function::Pow => 2,
+            Function::Clamp => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// Functions that can be called from an expression.
+const FUNCTIONS: [(&str, Function); 22] = [
+    ("sin", Function::Sin),
+    ("cos", Function::Cos),
+    ("tan", Function::Tan),
+    ("asin", Function::Asin),
+    ("acos", Function::Acos),
+    ("atan", Function::Atan),
+    ("sinh", Function::Sinh),
+    ("cosh", Function::Cosh),
+    ("tanh", Function::Tanh),
+    ("sqrt", Function::Sqrt),
+    ("abs", Function::Abs),
+    ("exp", Function::Exp),
+    ("ln", Function::Ln),
+    ("floor", Function::Floor),
+    ("ceil", Function::Ceil),
+    ("round", Function::Round),
+    ("sign", Function::Sign),
+    ("atan2", Function::Atan2),
+    ("min", Function::Min),
+    ("max", Function::Max),
+    ("pow", Function::Pow),
+    ("clamp", Function::Clamp),
+];
+
+#[derive(Debug, Clone)]
+enum Node {
+    Number(Float),
+    /// Coordinate of the sample position, 0 for x, 1 for y and 2 for z.
+    Coordinate(usize),
+    Negate(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, position: &[Float; 3]) -> Float {
+        match self {
+            Node::Number(value) => *value,
+            Node::Coordinate(axis) => position[*axis],
+            Node::Negate(node) => -node.evaluate(position),
+            Node::Binary(op, left, right) => {
+                let left = left.evaluate(position);
+                let right = right.evaluate(position);
+                match op {
+                    BinaryOp::Add => left + right,
+                    BinaryOp::Subtract => left - right,
+                    BinaryOp::Multiply => left * right,
+                    BinaryOp::Divide => left / right,
+                    BinaryOp::Remainder => left.rem_euclid(right),
+                    BinaryOp::Power => left.powf(right),
+                }
+            }
+            Node::Call(function, arguments) => {
+                let argument = |index: usize| arguments[index].evaluate(position);
+                match function {
+                    Function::Sin => argument(0).sin(),
+                    Function::Cos => argument(0).cos(),
+                    Function::Tan => argument(0).tan(),
+                    Function::Asin => argument(0).asin(),
+                    Function::Acos => argument(0).acos(),
+                    Function::Atan => argument(0).atan(),
+                    Function::Sinh => argument(0).sinh(),
+                    Function::Cosh => argument(0).cosh(),
+                    Function::Tanh => argument(0).tanh(),
+                    Function::Sqrt => argument(0).sqrt(),
+                    Function::Abs => argument(0).abs(),
+                    Function::Exp => argument(0).exp(),
+                    Function::Ln => argument(0).ln(),
+                    Function::Floor => argument(0).floor(),
+                    Function::Ceil => argument(0).ceil(),
+                    Function::Round => argument(0).round(),
+                    Function::Sign => {
+                        let value = argument(0);
+                        if value == 0.0 { 0.0 } else { value.signum() }
+                    }
+                    Function::Atan2 => argument(0).atan2(argument(1)),
+                    Function::Min => argument(0).min(argument(1)),
+                    Function::Max => argument(0).max(argument(1)),
+                    Function::Pow => argument(0).powf(argument(1)),
+                    Function::Clamp => argument(0).max(argument(1)).min(argument(2)),
+                }
+            }
+        }
+    }
+}
+
+/// Deepest nesting of parentheses, calls, operators and signs [Expression::parse] accepts. The
+/// parser and the evaluation recurse once per level, so this bounds their stack use.
+const MAX_DEPTH: usize = 256;
+
+/// Recursive descent parser over the bytes of the source.
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+    /// Nesting of the node being parsed, see [MAX_DEPTH].
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, position: usize, message: impl Into<String>) -> ExpressionError {
+        ExpressionError {
+            position,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .source
+            .as_bytes()
+            .get(self.position)
+            .is_some_and(|byte| byte.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    /// Go one level deeper, failing when that exceeds [MAX_DEPTH].
+    fn nest(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(self.position, "expression is nested too deeply"));
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ExpressionError> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(self.position, format!("expected `{}`", expected as char)))
+        }
+    }
+
+    /// `sum = product (('+' | '-') product)*`
+    fn sum(&mut self) -> Result<Node, ExpressionError> {
+        // Every operator nests the node parsed so far one level deeper.
+        let depth = self.depth;
+        let mut node = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Some(b'+') => BinaryOp::Add,
+                Some(b'-') => BinaryOp::Subtract,
+                _ => {
+                    self.depth = depth;
+                    return Ok(node);
+                }
+            };
+            self.position += 1;
+            self.nest()?;
+            node = Node::Binary(op, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    /// `product = unary (('*' | '/' | '%') unary)*`
+    fn product(&mut self) -> Result<Node, ExpressionError> {
+        let depth = self.depth;
+        let mut node = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(b'*') => BinaryOp::Multiply,
+                Some(b'/') => BinaryOp::Divide,
+                Some(b'%') => BinaryOp::Remainder,
+                _ => {
+                    self.depth = depth;
+                    return Ok(node);
+                }
+            };
+            self.position += 1;
+            self.nest()?;
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    /// `unary = ('-' | '+') unary | power`
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        // All recursion passes through here: signs, exponents, parentheses and arguments.
+        self.nest()?;
+        let node = match self.peek() {
+            Some(b'-') => {
+                self.position += 1;
+                Node::Negate(Box::new(self.unary()?))
+            }
+            Some(b'+') => {
+                self.position += 1;
+                self.unary()?
+            }
+            _ => self.power()?,
+        };
+        self.depth -= 1;
+        Ok(node)
+    }
+
+    /// `power = primary ('^' unary)?`, right associative and binding tighter than negation, so
+    /// `-x^2` is `-(x^2)`.
+    fn power(&mut self) -> Result<Node, ExpressionError> {
+        let base = self.primary()?;
+        if self.peek() == Some(b'^') {
+            self.position += 1;
+            let exponent = self.unary()?;
+            return Ok(Node::Binary(
+                BinaryOp::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    /// `primary = number | name | name '(' arguments ')' | '(' sum ')'`
+    fn primary(&mut self) -> Result<Node, ExpressionError> {
+        let start = self.position;
+        let bytes = self.source.as_bytes();
+        match self.peek() {
+            Some(b'(') => {
+                self.position += 1;
+                let node = self.sum()?;
+                self.expect(b')')?;
+                Ok(node)
+            }
+            Some(byte) if byte.is_ascii_digit() || byte == b'.' => {
+                let start = self.position;
+                while bytes
+                    .get(self.position)
+                    .is_some_and(|byte| byte.is_ascii_digit() || *byte == b'.')
+                {
+                    self.position += 1;
+                }
+                // Exponent, only when followed by digits so `2e` stays an error.
+                if matches!(bytes.get(self.position), Some(b'e' | b'E')) {
+                    let mut end = self.position + 1;
+                    if matches!(bytes.get(end), Some(b'+' | b'-')) {
+                        end += 1;
+                    }
+                    if bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                        self.position = end;
+                        while bytes.get(self.position).is_some_and(u8::is_ascii_digit) {
+                            self.position += 1;
+                        }
+                    }
+                }
+                let text = &self.source[start..self.position];
+                text.parse()
+                    .map(Node::Number)
+                    .map_err(|_| self.error(start, format!("invalid number `{text}`")))
+            }
+            Some(byte) if byte.is_ascii_alphabetic() || byte == b'_' => {
+                let start = self.position;
+                while bytes
+                    .get(self.position)
+                    .is_some_and(|byte| byte.is_ascii_alphanumeric() || *byte == b'_')
+                {
+                    self.position += 1;
+                }
+                let name = &self.source[start..self.position];
+                if self.peek() == Some(b'(') {
+                    self.position += 1;
+                    return self.call(start, name);
+                }
+                match name {
+                    "x" => Ok(Node::Coordinate(0)),
+                    "y" => Ok(Node::Coordinate(1)),
+                    "z" => Ok(Node::Coordinate(2)),
+                    "pi" => Ok(Node::Number(consts::PI)),
+                    "tau" => Ok(Node::Number(consts::TAU)),
+                    "e" => Ok(Node::Number(consts::E)),
+                    _ => Err(self.error(start, format!("unknown variable `{name}`"))),
+                }
+            }
+            Some(byte) => Err(self.error(self.position, format!("unexpected `{}`", byte as char))),
+            None => Err(self.error(start.max(self.position), "unexpected end of expression")),
+        }
+    }
+
+    /// Arguments of a call to `name`, after the opening parenthesis.
+    fn call(&mut self, start: usize, name: &str) -> Result<Node, ExpressionError> {
+        let function = FUNCTIONS
+            .iter()
+            .find(|(function, _)| *function == name)
+            .map(|(_, function)| *function)
+            .ok_or_else(|| self.error(start, format!("unknown function `{name}`")))?;
+        let mut arguments = Vec::new();
+        if self.peek() == Some(b')') {
+            self.position += 1;
+        } else {
+            loop {
+                arguments.push(self.sum()?);
+                match self.peek() {
+                    Some(b',') => self.position += 1,
+                    Some(b')') => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => return Err(self.error(self.position, "expected `,` or `)`")),
+                }
+            }
+        }
+        let expected = function.arity();
+        if arguments.len() != expected {
+            return Err(self.error(
+                start,
+                format!(
+                    "`{name}` takes {expected} argument{}, found {}",
+                    if expected == 1 { "" } else { "s" },
+                    arguments.len()
+                ),
+            ));
+        }
+        Ok(Node::Call(function, arguments))
+    }
+}
+
+/// Field defined by a math expression of `x`, `y` and `z` that is parsed at runtime, so
+/// surfaces can be meshed without recompiling.
+///
+/// Expressions support numbers, `+ - * / % ^`, parentheses, the constants `pi`, `tau` and `e`
+/// and the functions `sin`, `cos`, `tan`, `asin`, `acos`, `atan`, `atan2`, `sinh`, `cosh`,
+/// `tanh`, `sqrt`, `abs`, `exp`, `ln`, `floor`, `ceil`, `round`, `sign`, `min`, `max`, `pow` and
+/// `clamp`. `%` is the euclidean remainder and `^` is right associative. Parentheses, calls,
+/// signs and chained operators nest at most 256 levels deep, deeper expressions are rejected.
+///
+/// ```
+/// use marching_cubes::{field::Expression, prelude::*};
+///
+/// let gyroid = Expression::parse("sin(x)*cos(y) + sin(y)*cos(z) + sin(z)*cos(x)").unwrap();
+/// let sphere = Expression::parse("4 - sqrt(x^2 + y^2 + z^2)").unwrap();
+/// assert_eq!(sphere.sample(Vec3 { x: 0.0, y: 3.0, z: 0.0 }), 1.0);
+/// assert!(Expression::parse("sin(x").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Expression, ExpressionError> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+            depth: 0,
+        };
+        let root = parser.sum()?;
+        if let Some(byte) = parser.peek() {
+            return Err(parser.error(parser.position, format!("unexpected `{}`", byte as char)));
+        }
+        Ok(Expression {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Source the expression was parsed from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl ScalarField for Expression {
+    fn sample(&self, position: Vec3) -> Float {
+        self.root.evaluate(&[position.x, position.y, position.z])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(source: &str) -> Float {
+        Expression::parse(source).unwrap().sample(Vec3 {
+            x: 3.0,
+            y: 2.0,
+            z: 0.5,
+        })
+    }
+
+    fn error(source: &str) -> ExpressionError {
+        Expression::parse(source).unwrap_err()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3"), 9.0);
+        assert_eq!(evaluate("10 - 4 - 3"), 3.0);
+        assert_eq!(evaluate("8 / 4 / 2"), 1.0);
+        assert_eq!(evaluate("2 * 3 ^ 2"), 18.0);
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(evaluate("7 % 3 * 2"), 2.0);
+        assert_eq!(evaluate("x * y + z"), 6.5);
+        assert_eq!(evaluate("1.5e1 + 2E-1"), 15.2);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(evaluate("-x ^ 2"), -9.0);
+        assert_eq!(evaluate("(-x) ^ 2"), 9.0);
+        assert_eq!(evaluate("2 ^ -1"), 0.5);
+        assert_eq!(evaluate("--x"), 3.0);
+        assert_eq!(evaluate("+x"), 3.0);
+        assert_eq!(evaluate("2 * -y"), -4.0);
+        assert_eq!(evaluate("1 - -1"), 2.0);
+        // `%` is the euclidean remainder, so negative numbers wrap to a positive remainder.
+        assert_eq!(evaluate("-7 % 3"), 2.0);
+    }
+
+    #[test]
+    fn function_arity() {
+        assert_eq!(evaluate("max(x, y)"), 3.0);
+        assert_eq!(evaluate("clamp(x, 0, 1)"), 1.0);
+        assert_eq!(evaluate("pow(y, 3) + abs(-z)"), 8.5);
+        assert_eq!(evaluate("sign(0) + sign(-y)"), -1.0);
+
+        let min = error("1 + min(x)");
+        assert_eq!(min.position, 4);
+        assert_eq!(min.message, "`min` takes 2 arguments, found 1");
+        assert_eq!(
+            error("sin(x, y)").message,
+            "`sin` takes 1 argument, found 2"
+        );
+        assert_eq!(error("sin()").message, "`sin` takes 1 argument, found 0");
+        assert_eq!(error("clamp(x, 0)").position, 0);
+    }
+
+    #[test]
+    fn error_positions() {
+        let cases = [
+            ("1 +", 3, "unexpected end of expression"),
+            ("", 0, "unexpected end of expression"),
+            ("sin(x", 5, "expected `,` or `)`"),
+            ("(x + 1", 6, "expected `)`"),
+            ("x + foo", 4, "unknown variable `foo`"),
+            ("2 * bar(1)", 4, "unknown function `bar`"),
+            ("x )", 2, "unexpected `)`"),
+            ("1 $ 2", 2, "unexpected `$`"),
+            ("2e", 1, "unexpected `e`"),
+            ("1..2", 0, "invalid number `1..2`"),
+        ];
+        for (source, position, message) in cases {
+            let error = error(source);
+            assert_eq!(
+                (error.position, error.message.as_str()),
+                (position, message),
+                "{source}"
+            );
+        }
+        assert_eq!(
+            error("sin(x").to_string(),
+            "expression column 6: expected `,` or `)`"
+        );
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let nested = |depth: usize| format!("{}x{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(evaluate(&nested(MAX_DEPTH - 1)), 3.0);
+        assert_eq!(evaluate(&format!("{}x", "-".repeat(MAX_DEPTH - 2))), 3.0);
+
+        // Deeper expressions are rejected instead of overflowing the stack while parsing,
+        // evaluating or dropping them.
+        for source in [
+            nested(100_000),
+            format!("{}x", "-".repeat(100_000)),
+            format!("{}x", "x^".repeat(100_000)),
+            format!("{}x", "x+".repeat(100_000)),
+            format!("{}x", "x*".repeat(100_000)),
+            format!("{}x{}", "sin(".repeat(100_000), ")".repeat(100_000)),
+        ] {
+            assert_eq!(error(&source).message, "expression is nested too deeply");
+        }
+    }
+}
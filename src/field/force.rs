@@ -0,0 +1,223 @@
+use crate::{Float, Vec3};
+
+use super::{ScalarField, primitives::segment_factor, transform::rotate};
+
+/// Point source of [Metaballs]. The weight falls off with `force / distance`, so with a surface
+/// weight of 1.0 a single force is a sphere with radius `force`.
+#[derive(Debug, Copy, Clone)]
+pub struct Force {
+    pub position: Vec3,
+    pub force: Float,
+}
+
+/// Source of [Metaballs] along a polyline. The weight falls off with `force / distance` to the
+/// nearest point of the line, so with a surface weight of 1.0 a single segment is a capsule
+/// with radius `force` and a longer line is a tube, for limbs, tentacles and branches.
+///
+/// Unlike a row of point forces the tube has the same thickness along the whole line, also
+/// where its segments meet.
+#[derive(Debug, Clone)]
+pub struct LineForce {
+    /// Points of the polyline, a single point behaves like a [Force] and a line without points
+    /// has no effect.
+    pub points: Vec<Vec3>,
+    pub force: Float,
+}
+
+impl LineForce {
+    /// Straight line from `from` to `to`, a capsule when isolated.
+    pub fn segment(from: Vec3, to: Vec3, force: Float) -> LineForce {
+        LineForce {
+            points: vec![from, to],
+            force,
+        }
+    }
+
+    /// Cubic Bézier curve flattened into `segments` straight segments.
+    pub fn bezier(control_points: [Vec3; 4], segments: usize, force: Float) -> LineForce {
+        let segments = segments.max(1);
+        let [p0, p1, p2, p3] = control_points;
+        let points = (0..=segments)
+            .map(|index| {
+                let t = index as Float / segments as Float;
+                let s = 1.0 - t;
+                p0 * (s * s * s)
+                    + p1 * (3.0 * s * s * t)
+                    + p2 * (3.0 * s * t * t)
+                    + p3 * (t * t * t)
+            })
+            .collect();
+        LineForce { points, force }
+    }
+
+    /// Point of the line nearest to `position`, `None` when the line has no points.
+    pub fn nearest(&self, position: Vec3) -> Option<Vec3> {
+        if let [point] = self.points[..] {
+            return Some(point);
+        }
+        self.points
+            .windows(2)
+            .map(|segment| {
+                let [start, end] = [segment[0], segment[1]];
+                start.lerp(end, segment_factor(position, start, end))
+            })
+            .min_by(|a, b| {
+                (position - *a)
+                    .length()
+                    .total_cmp(&(position - *b).length())
+            })
+    }
+
+    /// Distance from `position` to the nearest point of the line. A line without points is
+    /// infinitely far away, so it doesn't contribute to [Metaballs].
+    pub fn distance(&self, position: Vec3) -> Float {
+        self.nearest(position)
+            .map_or(Float::INFINITY, |nearest| (position - nearest).length())
+    }
+}
+
+/// Point source of [Metaballs] squashed or stretched along its own axes. With a surface weight
+/// of 1.0 an isolated force is an ellipsoid with semi-axes of `force` times `radii`, so radii of
+/// 1.0 make it a [Force].
+///
+/// The local axes of the force are rotated by `angle` radians counter-clockwise around `axis`,
+/// as in [super::Transformed]. The weight is `force` divided by the distance measured in radii
+/// along those axes, so the force blends like a point force that was scaled as a whole.
+#[derive(Debug, Copy, Clone)]
+pub struct EllipsoidForce {
+    pub position: Vec3,
+    pub force: Float,
+    /// Scale of the force along its local x, y and z axis.
+    pub radii: Vec3,
+    pub axis: Vec3,
+    pub angle: Float,
+}
+
+impl EllipsoidForce {
+    /// Distance from the center to `position` in units of the radii along each axis.
+    pub fn scaled_distance(&self, position: Vec3) -> Float {
+        self.scaled_length(self.local_offset(position))
+    }
+
+    /// Offset from the center to `position` along the local axes of the force.
+    fn local_offset(&self, position: Vec3) -> Vec3 {
+        let offset = position - self.position;
+        if self.angle == 0.0 {
+            offset
+        } else {
+            rotate(offset, self.axis.normalized(), -self.angle)
+        }
+    }
+
+    fn scaled_length(&self, local: Vec3) -> Float {
+        let x = local.x / self.radii.x;
+        let y = local.y / self.radii.y;
+        let z = local.z / self.radii.z;
+        (x * x + y * y + z * z).sqrt()
+    }
+
+    /// Gradient of the weight `force / scaled_distance`: `-force / distance^3` times the local
+    /// offset divided by the squared radii, rotated back to world space.
+    fn weight_gradient(&self, position: Vec3) -> Vec3 {
+        let local = self.local_offset(position);
+        let distance = self.scaled_length(local);
+        let direction = Vec3 {
+            x: local.x / (self.radii.x * self.radii.x),
+            y: local.y / (self.radii.y * self.radii.y),
+            z: local.z / (self.radii.z * self.radii.z),
+        };
+        let direction = if self.angle == 0.0 {
+            direction
+        } else {
+            rotate(direction, self.axis.normalized(), self.angle)
+        };
+        direction * (-self.force / (distance * distance * distance))
+    }
+}
+
+/// Field summing the contributions of all forces, blending them into metaballs.
+#[derive(Debug, Clone, Default)]
+pub struct Metaballs {
+    pub forces: Vec<Force>,
+    pub lines: Vec<LineForce>,
+    pub ellipsoids: Vec<EllipsoidForce>,
+}
+
+impl Metaballs {
+    /// Metaballs of point forces only, line and ellipsoid forces can be added to their fields
+    /// afterwards.
+    pub fn new(forces: Vec<Force>) -> Metaballs {
+        Metaballs {
+            forces,
+            ..Metaballs::default()
+        }
+    }
+}
+
+impl ScalarField for Metaballs {
+    /// Contributions are added with Neumaier summation, so the weight barely depends on the order
+    /// of the forces and stays reproducible when they are reordered.
+    fn sample(&self, position: Vec3) -> Float {
+        let mut total_weight: Float = 0.0;
+        // Low order bits lost while adding to `total_weight`.
+        let mut compensation = 0.0;
+        let point_weights = self.forces.iter().map(|force| {
+            let dx = position.x - force.position.x;
+            let dy = position.y - force.position.y;
+            let dz = position.z - force.position.z;
+            force.force / (dx * dx + dy * dy + dz * dz).sqrt()
+        });
+        let line_weights = self
+            .lines
+            .iter()
+            .map(|line| line.force / line.distance(position));
+        let ellipsoid_weights = self
+            .ellipsoids
+            .iter()
+            .map(|ellipsoid| ellipsoid.force / ellipsoid.scaled_distance(position));
+        for weight in point_weights.chain(line_weights).chain(ellipsoid_weights) {
+            let sum = total_weight + weight;
+            compensation += if total_weight.abs() >= weight.abs() {
+                (total_weight - sum) + weight
+            } else {
+                (weight - sum) + total_weight
+            };
+            total_weight = sum;
+        }
+        if total_weight.is_finite() {
+            total_weight + compensation
+        } else {
+            // At a force position the weight is infinite and the compensation is NaN.
+            total_weight
+        }
+    }
+
+    /// Sum of the closed form gradients of all forces.
+    fn analytic_gradient(&self, position: Vec3) -> Option<Vec3> {
+        // The gradient of `force / distance` is `-force / distance^3` times the offset from the
+        // nearest point of the source.
+        let gradient = |nearest: Vec3, force: Float| {
+            let offset = position - nearest;
+            let distance = offset.length();
+            offset * (-force / (distance * distance * distance))
+        };
+        let points = self
+            .forces
+            .iter()
+            .map(|force| gradient(force.position, force.force));
+        let lines = self
+            .lines
+            .iter()
+            .filter_map(|line| Some(gradient(line.nearest(position)?, line.force)));
+        let ellipsoids = self
+            .ellipsoids
+            .iter()
+            .map(|ellipsoid| ellipsoid.weight_gradient(position));
+        Some(
+            points
+                .chain(lines)
+                .chain(ellipsoids)
+                .fold(Vec3::default(), |sum, part| sum + part),
+        )
+    }
+}
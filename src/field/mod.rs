@@ -0,0 +1,110 @@
+//! Reusable fields and the [ScalarField] trait they implement.
+//!
+//! Every field in this module is a struct describing the shape that implements [ScalarField], so
+//! it can be passed to `Domain::march_tetrahedras` directly. Modifiers borrow another field and
+//! implement [ScalarField] themselves, so they can be nested. Closures taking a [Vec3] are fields
+//! as well.
+//!
+//! The signed distance primitives ([Sphere], [Cuboid], [RoundedCuboid], [Torus], [Capsule],
+//! [Cylinder] and [Plane]) are positive inside and surface at a weight of 0.0. [Union],
+//! [Intersection], [Difference] and their smooth variants combine two fields into one,
+//! [Transformed] places a field in the world. With the `noise` feature `Perlin`, `Simplex` and
+//! `Fbm` generate terrain and organic detail, `Displace` adds them to another field. With the
+//! `expression` feature `Expression` parses a math expression such as a gyroid at runtime.
+//!
+//! [Describe] turns a composed field into a [FieldGraph] that can be printed, diffed or exported
+//! to Graphviz and JSON.
+
+mod bounds;
+mod csg;
+mod describe;
+mod emboss;
+#[cfg(feature = "expression")]
+mod expression;
+mod force;
+#[cfg(feature = "noise")]
+mod noise;
+mod normalize;
+mod offset;
+mod primitives;
+mod printable;
+mod probe;
+pub(crate) mod project;
+mod skeleton;
+mod sweep;
+mod transform;
+
+use crate::{Float, Vec3};
+
+pub use bounds::FieldBounds;
+pub use csg::{
+    Difference, Intersection, SmoothDifference, SmoothIntersection, SmoothUnion, Union, smooth_max,
+    smooth_min,
+};
+pub use describe::{Describe, FieldGraph, Named};
+pub use emboss::Emboss;
+#[cfg(feature = "expression")]
+pub use expression::{Expression, ExpressionError};
+pub use force::{EllipsoidForce, Force, LineForce, Metaballs};
+#[cfg(feature = "noise")]
+pub use noise::{Displace, Fbm, Perlin, Simplex};
+pub use normalize::Normalize;
+pub use offset::{Metric, Rounded};
+pub use primitives::{Capsule, Cuboid, Cylinder, Plane, RoundedCuboid, Sphere, Torus};
+pub use printable::{Infill, Printable};
+pub use probe::{FieldRange, HISTOGRAM_BINS, probe_range};
+pub use project::project_to_surface;
+pub use skeleton::{Bone, Skeleton};
+pub use sweep::CapsulePath;
+pub use transform::Transformed;
+
+/// Scalar field sampled by the extraction methods. Positions with a weight above the surface
+/// weight of the domain are inside.
+pub trait ScalarField {
+    /// Weight of the field at the given position.
+    fn sample(&self, position: Vec3) -> Float;
+
+    /// Exact gradient of the weight at the given position, for fields where it is cheaper than
+    /// sampling. Used by [crate::Newton], fields without one return `None`.
+    fn analytic_gradient(&self, position: Vec3) -> Option<Vec3> {
+        let _ = position;
+        None
+    }
+}
+
+impl<F> ScalarField for F
+where
+    F: Fn(Vec3) -> Float,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        self(position)
+    }
+}
+
+/// Gradient of a field using central differences with the given step size.
+pub fn gradient<FIELD>(field: &FIELD, position: Vec3, step: Float) -> Vec3
+where
+    FIELD: ScalarField + ?Sized,
+{
+    let dx = Vec3 {
+        x: step,
+        y: 0.0,
+        z: 0.0,
+    };
+    let dy = Vec3 {
+        x: 0.0,
+        y: step,
+        z: 0.0,
+    };
+    let dz = Vec3 {
+        x: 0.0,
+        y: 0.0,
+        z: step,
+    };
+    let scale = 0.5 / step;
+    Vec3 {
+        x: (field.sample(position + dx) - field.sample(position - dx)) * scale,
+        y: (field.sample(position + dy) - field.sample(position - dy)) * scale,
+        z: (field.sample(position + dz) - field.sample(position - dz)) * scale,
+    }
+}
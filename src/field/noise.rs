@@ -0,0 +1,256 @@
+use crate::{Float, Vec3};
+
+use super::ScalarField;
+
+/// Permutation table of 256 entries repeated twice, shuffled by `seed`.
+fn permutation(seed: u64) -> [u8; 512] {
+    let mut table = [0_u8; 512];
+    for (index, value) in table.iter_mut().take(256).enumerate() {
+        *value = index as u8;
+    }
+    // Fisher-Yates shuffle driven by splitmix64.
+    let mut state = seed;
+    for index in (1..256).rev() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        table.swap(index, (z % (index as u64 + 1)) as usize);
+    }
+    for index in 0..256 {
+        table[index + 256] = table[index];
+    }
+    table
+}
+
+/// The 12 gradients of improved Perlin noise, the edge midpoints of a cube.
+const GRADIENTS: [[Float; 3]; 12] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+];
+
+fn gradient_dot(hash: u8, x: Float, y: Float, z: Float) -> Float {
+    let [gx, gy, gz] = GRADIENTS[hash as usize % 12];
+    gx * x + gy * y + gz * z
+}
+
+/// Improved Perlin gradient noise with features of about 1 unit, between about -1.0 and 1.0.
+///
+/// The noise is 0.0 at integer positions. Scale positions with [Fbm] or [super::Transformed] to
+/// change the size of the features.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    seed: u64,
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    /// Noise with a pattern that only depends on `seed`.
+    pub fn new(seed: u64) -> Perlin {
+        Perlin {
+            seed,
+            permutation: permutation(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl ScalarField for Perlin {
+    fn sample(&self, position: Vec3) -> Float {
+        let p = &self.permutation;
+        let floor = [position.x.floor(), position.y.floor(), position.z.floor()];
+        let [xi, yi, zi] = floor.map(|value| (value as i64).rem_euclid(256) as usize);
+        let x = position.x - floor[0];
+        let y = position.y - floor[1];
+        let z = position.z - floor[2];
+        let fade = |t: Float| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let lerp = |a: Float, b: Float, t: Float| a + (b - a) * t;
+
+        let a = p[xi] as usize + yi;
+        let b = p[xi + 1] as usize + yi;
+        let (aa, ab) = (p[a] as usize + zi, p[a + 1] as usize + zi);
+        let (ba, bb) = (p[b] as usize + zi, p[b + 1] as usize + zi);
+        lerp(
+            lerp(
+                lerp(
+                    gradient_dot(p[aa], x, y, z),
+                    gradient_dot(p[ba], x - 1.0, y, z),
+                    u,
+                ),
+                lerp(
+                    gradient_dot(p[ab], x, y - 1.0, z),
+                    gradient_dot(p[bb], x - 1.0, y - 1.0, z),
+                    u,
+                ),
+                v,
+            ),
+            lerp(
+                lerp(
+                    gradient_dot(p[aa + 1], x, y, z - 1.0),
+                    gradient_dot(p[ba + 1], x - 1.0, y, z - 1.0),
+                    u,
+                ),
+                lerp(
+                    gradient_dot(p[ab + 1], x, y - 1.0, z - 1.0),
+                    gradient_dot(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                    u,
+                ),
+                v,
+            ),
+            w,
+        )
+    }
+}
+
+/// Simplex noise with features of about 1 unit, between about -1.0 and 1.0.
+///
+/// Cheaper than [Perlin] and without its grid aligned artifacts.
+#[derive(Debug, Clone)]
+pub struct Simplex {
+    seed: u64,
+    permutation: [u8; 512],
+}
+
+impl Simplex {
+    /// Noise with a pattern that only depends on `seed`.
+    pub fn new(seed: u64) -> Simplex {
+        Simplex {
+            seed,
+            permutation: permutation(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl ScalarField for Simplex {
+    fn sample(&self, position: Vec3) -> Float {
+        const SKEW: Float = 1.0 / 3.0;
+        const UNSKEW: Float = 1.0 / 6.0;
+        let p = &self.permutation;
+
+        // Cell of the skewed lattice and the offsets to its first corner.
+        let skew = (position.x + position.y + position.z) * SKEW;
+        let cell = [
+            (position.x + skew).floor(),
+            (position.y + skew).floor(),
+            (position.z + skew).floor(),
+        ];
+        let unskew = (cell[0] + cell[1] + cell[2]) * UNSKEW;
+        let x0 = [
+            position.x - (cell[0] - unskew),
+            position.y - (cell[1] - unskew),
+            position.z - (cell[2] - unskew),
+        ];
+
+        // Second and third corner of the tetrahedron containing the position.
+        let (second, third) = if x0[0] >= x0[1] {
+            if x0[1] >= x0[2] {
+                ([1, 0, 0], [1, 1, 0])
+            } else if x0[0] >= x0[2] {
+                ([1, 0, 0], [1, 0, 1])
+            } else {
+                ([0, 0, 1], [1, 0, 1])
+            }
+        } else if x0[1] < x0[2] {
+            ([0, 0, 1], [0, 1, 1])
+        } else if x0[0] < x0[2] {
+            ([0, 1, 0], [0, 1, 1])
+        } else {
+            ([0, 1, 0], [1, 1, 0])
+        };
+
+        let [i, j, k] = cell.map(|value| (value as i64).rem_euclid(256) as usize);
+        let mut total = 0.0;
+        for (corner, offset) in [[0, 0, 0], second, third, [1, 1, 1]]
+            .into_iter()
+            .enumerate()
+        {
+            let unskew = corner as Float * UNSKEW;
+            let x = x0[0] - offset[0] as Float + unskew;
+            let y = x0[1] - offset[1] as Float + unskew;
+            let z = x0[2] - offset[2] as Float + unskew;
+            let t = 0.6 - x * x - y * y - z * z;
+            if t > 0.0 {
+                let hash = p[i + offset[0] + p[j + offset[1] + p[k + offset[2]] as usize] as usize];
+                total += t * t * t * t * gradient_dot(hash, x, y, z);
+            }
+        }
+        32.0 * total
+    }
+}
+
+/// Fractal Brownian motion: the sum of `octaves` layers of `noise`, each at `lacunarity` times
+/// the frequency and `gain` times the amplitude of the previous layer.
+///
+/// The first layer has `frequency` and an amplitude of 1.0. With the usual lacunarity of 2.0 and
+/// gain of 0.5 the layers add detail at ever smaller scales, as terrain and rock surfaces have.
+pub struct Fbm<'a, NOISE: ?Sized> {
+    pub noise: &'a NOISE,
+    pub octaves: usize,
+    pub frequency: Float,
+    pub lacunarity: Float,
+    pub gain: Float,
+}
+
+impl<NOISE> ScalarField for Fbm<'_, NOISE>
+where
+    NOISE: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        let mut total = 0.0;
+        let mut frequency = self.frequency;
+        let mut amplitude = 1.0;
+        for octave in 0..self.octaves {
+            // Shift the layers so their zero crossings at integer positions don't line up.
+            let shift = octave as Float * 17.31;
+            let shifted = Vec3 {
+                x: position.x * frequency + shift,
+                y: position.y * frequency + shift,
+                z: position.z * frequency + shift,
+            };
+            total += self.noise.sample(shifted) * amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+        total
+    }
+}
+
+/// Field modifier adding `amplitude` times `noise` to the weights of another field, roughening
+/// its surface.
+///
+/// For signed distance fields the surface moves by up to `amplitude` units. Keep the amplitude
+/// below the size of the features of the noise, otherwise pieces break off the surface.
+pub struct Displace<'a, FIELD: ?Sized, NOISE: ?Sized> {
+    pub field: &'a FIELD,
+    pub noise: &'a NOISE,
+    pub amplitude: Float,
+}
+
+impl<FIELD, NOISE> ScalarField for Displace<'_, FIELD, NOISE>
+where
+    FIELD: ScalarField + ?Sized,
+    NOISE: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        self.field.sample(position) + self.noise.sample(position) * self.amplitude
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{Float, Vec3};
+
+use super::{ScalarField, gradient};
+
+/// Rescale a density-style field into a pseudo signed distance field.
+///
+/// The distance to the surface is estimated as `(weight - surface_weight) / |gradient|`. The
+/// result keeps the surface at `surface_weight` but increases by roughly 1.0 per unit moved into
+/// the surface, so it can be used with [super::Metric::Distance] and other modifiers that expect
+/// distances.
+pub struct Normalize<'a, FIELD: ?Sized> {
+    pub field: &'a FIELD,
+    pub surface_weight: Float,
+    pub gradient_step: Float,
+}
+
+/// Where the gradient vanishes the distance can't be estimated and the weight is pushed to the
+/// far inside or outside.
+impl<FIELD> ScalarField for Normalize<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        let weight = self.field.sample(position) - self.surface_weight;
+        let slope = gradient(self.field, position, self.gradient_step).length();
+        let distance = if slope > Float::EPSILON {
+            weight / slope
+        } else {
+            weight.signum() * Float::MAX
+        };
+        distance + self.surface_weight
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{Float, Vec3};
+
+use super::{ScalarField, gradient};
+
+/// How the weights of a field relate to distances in world space.
+#[derive(Debug, Copy, Clone)]
+pub enum Metric {
+    /// The weight increases by 1.0 for each unit moved into the surface, for example a negated
+    /// signed distance field. Offsets are exact.
+    Distance,
+    /// The weight has no relation to distance, for example forces. Offsets are scaled by the
+    /// gradient of the field, which is only accurate close to the surface.
+    Density { gradient_step: Float },
+}
+
+/// Offset the surface of another field outward by `radius`.
+///
+/// Offsetting a shape that was shrunk by the same radius rounds its convex edges and corners.
+/// Negative radii move the surface inward.
+pub struct Rounded<'a, FIELD: ?Sized> {
+    pub field: &'a FIELD,
+    pub radius: Float,
+    pub metric: Metric,
+}
+
+/// Shifting the iso level of a density field by a constant moves its surface by a different
+/// distance everywhere, so for [Metric::Density] the radius is converted to a weight using the
+/// local gradient length.
+impl<FIELD> ScalarField for Rounded<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        let weight = self.field.sample(position);
+        match self.metric {
+            Metric::Distance => weight + self.radius,
+            Metric::Density { gradient_step } => {
+                let slope = gradient(self.field, position, gradient_step).length();
+                weight + self.radius * slope
+            }
+        }
+    }
+}
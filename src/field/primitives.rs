@@ -0,0 +1,166 @@
+use crate::{Float, Vec3};
+
+use super::ScalarField;
+
+// The primitives sample the negated signed distance to their surface: the weight increases by 1.0
+// for each unit moved into the shape, so march them with a surface weight of 0.0 and use
+// `Metric::Distance` to offset them.
+
+/// Sphere around `center`.
+#[derive(Debug, Copy, Clone)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: Float,
+}
+
+impl ScalarField for Sphere {
+    fn sample(&self, position: Vec3) -> Float {
+        self.radius - (position - self.center).length()
+    }
+
+    fn analytic_gradient(&self, position: Vec3) -> Option<Vec3> {
+        Some((position - self.center).normalized() * -1.0)
+    }
+}
+
+/// Axis aligned box around `center`, extending `half_size` to both sides on each axis.
+#[derive(Debug, Copy, Clone)]
+pub struct Cuboid {
+    pub center: Vec3,
+    pub half_size: Vec3,
+}
+
+impl ScalarField for Cuboid {
+    fn sample(&self, position: Vec3) -> Float {
+        -box_distance(position - self.center, self.half_size)
+    }
+}
+
+/// Axis aligned box with edges and corners rounded by `radius`. The rounding stays within
+/// `half_size`, so the box keeps its outer dimensions.
+#[derive(Debug, Copy, Clone)]
+pub struct RoundedCuboid {
+    pub center: Vec3,
+    pub half_size: Vec3,
+    pub radius: Float,
+}
+
+impl ScalarField for RoundedCuboid {
+    fn sample(&self, position: Vec3) -> Float {
+        let radius = self
+            .radius
+            .min(self.half_size.x)
+            .min(self.half_size.y)
+            .min(self.half_size.z)
+            .max(0.0);
+        let inner = Vec3 {
+            x: self.half_size.x - radius,
+            y: self.half_size.y - radius,
+            z: self.half_size.z - radius,
+        };
+        radius - box_distance(position - self.center, inner)
+    }
+}
+
+/// Torus around `center` lying in the xz plane. `major_radius` is the distance from the center to
+/// the middle of the tube, `minor_radius` the radius of the tube.
+#[derive(Debug, Copy, Clone)]
+pub struct Torus {
+    pub center: Vec3,
+    pub major_radius: Float,
+    pub minor_radius: Float,
+}
+
+impl ScalarField for Torus {
+    fn sample(&self, position: Vec3) -> Float {
+        let local = position - self.center;
+        let ring = (local.x * local.x + local.z * local.z).sqrt() - self.major_radius;
+        self.minor_radius - (ring * ring + local.y * local.y).sqrt()
+    }
+}
+
+/// Segment from `from` to `to` with hemispherical caps.
+#[derive(Debug, Copy, Clone)]
+pub struct Capsule {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub radius: Float,
+}
+
+/// Position of the point of the segment from `start` to `end` nearest to `position`, as a factor
+/// from 0.0 at `start` to 1.0 at `end`. A segment of zero length uses `start`.
+pub(super) fn segment_factor(position: Vec3, start: Vec3, end: Vec3) -> Float {
+    let axis = end - start;
+    let length_squared = axis.dot(axis);
+    if length_squared > 0.0 {
+        ((position - start).dot(axis) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+impl ScalarField for Capsule {
+    fn sample(&self, position: Vec3) -> Float {
+        let factor = segment_factor(position, self.from, self.to);
+        self.radius - (position - self.from.lerp(self.to, factor)).length()
+    }
+}
+
+/// Cylinder from `from` to `to` with flat caps.
+#[derive(Debug, Copy, Clone)]
+pub struct Cylinder {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub radius: Float,
+}
+
+impl ScalarField for Cylinder {
+    fn sample(&self, position: Vec3) -> Float {
+        let axis = self.to - self.from;
+        let half_length = axis.length() * 0.5;
+        if half_length == 0.0 {
+            return -Float::INFINITY;
+        }
+        let direction = axis * (0.5 / half_length);
+        let local = position - self.from.lerp(self.to, 0.5);
+        let along = local.dot(direction);
+        let across = (local - direction * along).length();
+        // Distance to a rectangle in the plane through the axis.
+        let dx = across - self.radius;
+        let dy = along.abs() - half_length;
+        let outside = (dx.max(0.0) * dx.max(0.0) + dy.max(0.0) * dy.max(0.0)).sqrt();
+        -(outside + dx.max(dy).min(0.0))
+    }
+}
+
+/// Half space below the plane through `point`, `normal` points out of the solid side.
+#[derive(Debug, Copy, Clone)]
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+impl ScalarField for Plane {
+    fn sample(&self, position: Vec3) -> Float {
+        -(position - self.point).dot(self.normal.normalized())
+    }
+
+    fn analytic_gradient(&self, _position: Vec3) -> Option<Vec3> {
+        Some(self.normal.normalized() * -1.0)
+    }
+}
+
+/// Signed distance from `local` to a box around the origin, negative inside.
+fn box_distance(local: Vec3, half_size: Vec3) -> Float {
+    let q = Vec3 {
+        x: local.x.abs() - half_size.x,
+        y: local.y.abs() - half_size.y,
+        z: local.z.abs() - half_size.z,
+    };
+    let outside = Vec3 {
+        x: q.x.max(0.0),
+        y: q.y.max(0.0),
+        z: q.z.max(0.0),
+    };
+    outside.length() + q.x.max(q.y).max(q.z).min(0.0)
+}
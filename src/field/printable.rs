@@ -0,0 +1,85 @@
+use crate::{Bisection, Domain, Float, Vec3, consts::TAU, result::MarchResult};
+
+use super::{Normalize, ScalarField};
+
+/// Gyroid lattice used to fill the inside of a printable body.
+#[derive(Debug, Copy, Clone)]
+pub struct Infill {
+    /// Size of a single gyroid cell in world units.
+    pub cell_size: Float,
+    /// Thickness of the gyroid sheet in world units.
+    pub thickness: Float,
+}
+
+/// Body with a solid skin of `wall_thickness` and an [Infill] lattice inside.
+///
+/// The distance to the surface of the base field is estimated using [Normalize], so the wall
+/// thickness is only exact for fields that grow linearly near the surface.
+pub struct Printable<'a, FIELD: ?Sized> {
+    pub field: &'a FIELD,
+    pub surface_weight: Float,
+
+    pub wall_thickness: Float,
+    pub infill: Infill,
+    /// Step of the finite differences estimating the distance to the surface, see [Normalize].
+    /// Must be above 0.0.
+    pub gradient_step: Float,
+}
+
+fn gyroid_distance(position: Vec3, infill: &Infill) -> Float {
+    let frequency = TAU / infill.cell_size;
+    let x = position.x * frequency;
+    let y = position.y * frequency;
+    let z = position.z * frequency;
+    let value = x.sin() * y.cos() + y.sin() * z.cos() + z.sin() * x.cos();
+    infill.thickness * 0.5 - value.abs() / frequency
+}
+
+/// The result uses the same surface weight as the base field.
+impl<FIELD> ScalarField for Printable<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        let normalize = Normalize {
+            field: self.field,
+            surface_weight: self.surface_weight,
+            gradient_step: self.gradient_step,
+        };
+        let inside_distance = normalize.sample(position) - self.surface_weight;
+
+        let skin = self.wall_thickness - inside_distance;
+        let lattice = gyroid_distance(position, &self.infill);
+        let interior = skin.max(lattice);
+        inside_distance.min(interior) + self.surface_weight
+    }
+}
+
+impl Domain {
+    /// Mesh the field as a printable body: a solid skin of `wall_thickness` with a gyroid
+    /// lattice inside. Skin and lattice are combined in a single field so the result is one
+    /// connected mesh instead of overlapping shells.
+    pub fn make_printable<FIELD>(
+        &mut self,
+        field: &FIELD,
+        wall_thickness: Float,
+        infill: Infill,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        // A hundredth of the wall, but not so small that a thin or absent wall makes the
+        // gradient 0 / 0.
+        let cell_size = ((self.to.x - self.from.x) / self.width as Float)
+            .min((self.to.y - self.from.y) / self.height as Float)
+            .min((self.to.z - self.from.z) / self.depth as Float);
+        let printable = Printable {
+            field,
+            surface_weight: self.surface_weight,
+            wall_thickness,
+            infill,
+            gradient_step: (wall_thickness * 0.01).max(cell_size * 0.01),
+        };
+        self.march_tetrahedras(&printable, &Bisection)
+    }
+}
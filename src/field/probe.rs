@@ -0,0 +1,81 @@
+use crate::{Float, Vec3};
+
+use super::ScalarField;
+
+/// Number of bins in [FieldRange::histogram].
+pub const HISTOGRAM_BINS: usize = 16;
+
+/// Statistics of the weights of a field sampled on a regular grid.
+#[derive(Debug, Clone)]
+pub struct FieldRange {
+    pub min: Float,
+    pub max: Float,
+    pub mean: Float,
+    /// Number of samples per bin. Bins are evenly spaced between `min` and `max`.
+    pub histogram: [usize; HISTOGRAM_BINS],
+    /// Number of samples that were NaN or infinite. These are not part of the other statistics.
+    pub non_finite_samples: usize,
+}
+
+impl FieldRange {
+    /// Weight range `(low, high)` covered by a histogram bin.
+    pub fn bin_range(&self, bin: usize) -> (Float, Float) {
+        let bin_size = (self.max - self.min) / HISTOGRAM_BINS as Float;
+        (
+            self.min + bin as Float * bin_size,
+            self.min + (bin + 1) as Float * bin_size,
+        )
+    }
+}
+
+/// Sample a field on a grid of `samples` x `samples` x `samples` points between `from` and `to`.
+///
+/// Useful to pick a surface weight for an unfamiliar field, or to find out why marching didn't
+/// generate any faces.
+pub fn probe_range<FIELD>(field: &FIELD, from: Vec3, to: Vec3, samples: usize) -> FieldRange
+where
+    FIELD: ScalarField + ?Sized,
+{
+    let samples = samples.max(2);
+    let step = (to - from) * (1.0 / (samples - 1) as Float);
+    let mut weights = Vec::with_capacity(samples * samples * samples);
+    let mut non_finite_samples = 0;
+    for x in 0..samples {
+        for y in 0..samples {
+            for z in 0..samples {
+                let position = Vec3 {
+                    x: from.x + x as Float * step.x,
+                    y: from.y + y as Float * step.y,
+                    z: from.z + z as Float * step.z,
+                };
+                let weight = field.sample(position);
+                if weight.is_finite() {
+                    weights.push(weight);
+                } else {
+                    non_finite_samples += 1;
+                }
+            }
+        }
+    }
+
+    let mut range = FieldRange {
+        min: weights.iter().copied().fold(Float::INFINITY, Float::min),
+        max: weights
+            .iter()
+            .copied()
+            .fold(Float::NEG_INFINITY, Float::max),
+        mean: weights.iter().sum::<Float>() / weights.len() as Float,
+        histogram: [0; HISTOGRAM_BINS],
+        non_finite_samples,
+    };
+    let bin_size = (range.max - range.min) / HISTOGRAM_BINS as Float;
+    for weight in weights {
+        let bin = if bin_size > 0.0 {
+            ((weight - range.min) / bin_size) as usize
+        } else {
+            0
+        };
+        range.histogram[bin.min(HISTOGRAM_BINS - 1)] += 1;
+    }
+    range
+}
@@ -0,0 +1,73 @@
+use crate::{Float, ScalarField, Vec3};
+
+use super::gradient;
+
+/// Move `position` onto the surface where the field has `surface_weight`, using at most
+/// `max_steps` Newton steps along the gradient.
+///
+/// Steps that move further away from the surface are halved, so the projection also converges
+/// from positions where the field is far from linear. Returns `None` when the gradient vanishes
+/// or the field isn't finite before the surface is reached. The result is the closest point
+/// that was found, check the weight there when an exact hit matters.
+///
+/// Useful to place objects on implicit terrain, to seed particles on the surface or to snap
+/// points back onto the surface after moving them.
+pub fn project_to_surface<FIELD>(
+    position: Vec3,
+    field: &FIELD,
+    surface_weight: Float,
+    max_steps: usize,
+) -> Option<Vec3>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    // Step that balances truncation and rounding errors of central differences.
+    let scale = 1.0 + position.x.abs().max(position.y.abs()).max(position.z.abs());
+    let step = Float::EPSILON.cbrt() * scale;
+    project_with_step(position, field, surface_weight, max_steps, step)
+}
+
+/// Same as [project_to_surface] with an explicit step for estimating the gradient.
+pub(crate) fn project_with_step<FIELD>(
+    mut position: Vec3,
+    field: &FIELD,
+    surface_weight: Float,
+    max_steps: usize,
+    step: Float,
+) -> Option<Vec3>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    let mut offset = field.sample(position) - surface_weight;
+    for _ in 0..max_steps {
+        if offset == 0.0 {
+            break;
+        }
+        if !offset.is_finite() {
+            return None;
+        }
+        let direction = gradient(field, position, step);
+        let length_squared = direction.dot(direction);
+        if length_squared <= Float::EPSILON || !length_squared.is_finite() {
+            return None;
+        }
+        let mut delta = direction * (-offset / length_squared);
+        let mut next = position + delta;
+        let mut next_offset = field.sample(next) - surface_weight;
+        for _ in 0..8 {
+            if next_offset.abs() < offset.abs() {
+                break;
+            }
+            delta = delta * 0.5;
+            next = position + delta;
+            next_offset = field.sample(next) - surface_weight;
+        }
+        if next_offset.abs() >= offset.abs() {
+            // No step along the gradient gets closer, this is as close as it gets.
+            break;
+        }
+        position = next;
+        offset = next_offset;
+    }
+    offset.is_finite().then_some(position)
+}
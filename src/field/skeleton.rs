@@ -0,0 +1,38 @@
+use crate::{Float, Vec3};
+
+use super::{ScalarField, primitives::segment_factor};
+
+/// Capsule shaped bone of a [Skeleton], from its head at `from` to its tail at `to`.
+#[derive(Debug, Copy, Clone)]
+pub struct Bone {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub radius: Float,
+    /// Index of the parent bone in [Skeleton::bones], parents come before their children.
+    pub parent: Option<usize>,
+}
+
+impl Bone {
+    /// Contribution of the bone to the weight at `position`: `radius / distance` to the segment,
+    /// so an isolated bone has its surface at its radius with a surface weight of 1.0.
+    pub fn influence(&self, position: Vec3) -> Float {
+        let factor = segment_factor(position, self.from, self.to);
+        self.radius / (position - self.from.lerp(self.to, factor)).length()
+    }
+}
+
+/// Creature body built around a hierarchy of bones.
+///
+/// Like [super::Metaballs] the influences of the bones are added, so bones blend into each other
+/// where they meet. The bones double as the rig of the extracted mesh, see
+/// [crate::Mesh::compute_skin_weights].
+#[derive(Debug, Clone, Default)]
+pub struct Skeleton {
+    pub bones: Vec<Bone>,
+}
+
+impl ScalarField for Skeleton {
+    fn sample(&self, position: Vec3) -> Float {
+        self.bones.iter().map(|bone| bone.influence(position)).sum()
+    }
+}
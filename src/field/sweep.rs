@@ -0,0 +1,100 @@
+use crate::{Float, Vec3};
+
+use super::{ScalarField, primitives::segment_factor};
+
+/// Volume swept by a sphere of varying radius that moves along a path.
+///
+/// The path is stored as a polyline. Curves are flattened into segments when constructed. Each
+/// point has its own radius so tubes can taper, for example roots or cables.
+#[derive(Debug, Clone)]
+pub struct CapsulePath {
+    points: Vec<Vec3>,
+    radii: Vec<Float>,
+}
+
+impl CapsulePath {
+    /// Create a swept volume along a polyline.
+    ///
+    /// `radius_profile` receives the normalized arc length (0.0 at the first point, 1.0 at the
+    /// last point) and returns the radius of the tube at that location.
+    pub fn polyline<PROFILE>(points: Vec<Vec3>, radius_profile: PROFILE) -> CapsulePath
+    where
+        PROFILE: Fn(Float) -> Float,
+    {
+        let mut arc_lengths = Vec::with_capacity(points.len());
+        let mut total_length = 0.0;
+        for (index, point) in points.iter().enumerate() {
+            if index > 0 {
+                total_length += (*point - points[index - 1]).length();
+            }
+            arc_lengths.push(total_length);
+        }
+        let radii = arc_lengths
+            .iter()
+            .map(|arc_length| {
+                let factor = if total_length > 0.0 {
+                    arc_length / total_length
+                } else {
+                    0.0
+                };
+                radius_profile(factor)
+            })
+            .collect();
+        CapsulePath { points, radii }
+    }
+
+    /// Create a swept volume along a cubic Bézier curve.
+    ///
+    /// The curve is flattened into `segments` straight segments.
+    pub fn bezier<PROFILE>(
+        control_points: [Vec3; 4],
+        segments: usize,
+        radius_profile: PROFILE,
+    ) -> CapsulePath
+    where
+        PROFILE: Fn(Float) -> Float,
+    {
+        let segments = segments.max(1);
+        let [p0, p1, p2, p3] = control_points;
+        let points = (0..=segments)
+            .map(|index| {
+                let t = index as Float / segments as Float;
+                let s = 1.0 - t;
+                p0 * (s * s * s)
+                    + p1 * (3.0 * s * s * t)
+                    + p2 * (3.0 * s * t * t)
+                    + p3 * (t * t * t)
+            })
+            .collect();
+        CapsulePath::polyline(points, radius_profile)
+    }
+
+    /// Number of points of the polyline, curves count their flattened points.
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+}
+
+/// Uses the same falloff as a force: `radius / distance`, so with a surface weight of 1.0 the
+/// surface is located at the radius of the tube. Segments are combined by taking the maximum,
+/// as consecutive segments share the radius at their joint this doesn't bulge at the joints.
+impl ScalarField for CapsulePath {
+    fn sample(&self, position: Vec3) -> Float {
+        if self.points.len() == 1 {
+            return self.radii[0] / (position - self.points[0]).length();
+        }
+
+        let mut result: Float = 0.0;
+        for index in 1..self.points.len() {
+            let start = self.points[index - 1];
+            let end = self.points[index];
+            let factor = segment_factor(position, start, end);
+            let closest = start.lerp(end, factor);
+            let radius =
+                self.radii[index - 1] + (self.radii[index] - self.radii[index - 1]) * factor;
+            let distance = (position - closest).length();
+            result = result.max(radius / distance);
+        }
+        result
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{Float, Vec3};
+
+use super::ScalarField;
+
+/// Field modifier that places another field in the world: the field is scaled by `scale`,
+/// rotated by `angle` radians counter-clockwise around `axis` and moved by `translation`, in that
+/// order.
+///
+/// Positions are transformed back into the space of the field before sampling it, so primitives
+/// can be placed without changing their definition. Nest modifiers to combine rotations around
+/// different axes.
+///
+/// The weights aren't changed, so density fields keep their surface weight. The distances of a
+/// scaled signed distance field stay in the units of the untransformed field, wrap it in
+/// [super::Normalize] when the modifiers after it expect world distances.
+pub struct Transformed<'a, FIELD: ?Sized> {
+    pub field: &'a FIELD,
+    pub translation: Vec3,
+    pub axis: Vec3,
+    pub angle: Float,
+    pub scale: Float,
+}
+
+impl<'a, FIELD: ?Sized> Transformed<'a, FIELD> {
+    /// Transform that leaves `field` in place, to set the fields that are needed afterwards.
+    pub fn identity(field: &'a FIELD) -> Transformed<'a, FIELD> {
+        Transformed {
+            field,
+            translation: Vec3::default(),
+            axis: Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            angle: 0.0,
+            scale: 1.0,
+        }
+    }
+
+    /// Position in the space of the field that ends up at `position`.
+    pub fn to_local(&self, position: Vec3) -> Vec3 {
+        let rotated = position - self.translation;
+        let local = if self.angle == 0.0 {
+            rotated
+        } else {
+            rotate(rotated, self.axis.normalized(), -self.angle)
+        };
+        local * (1.0 / self.scale)
+    }
+
+    /// Position in the world of `local`, a position in the space of the field.
+    pub fn to_world(&self, local: Vec3) -> Vec3 {
+        let scaled = local * self.scale;
+        let rotated = if self.angle == 0.0 {
+            scaled
+        } else {
+            rotate(scaled, self.axis.normalized(), self.angle)
+        };
+        rotated + self.translation
+    }
+}
+
+impl<FIELD> ScalarField for Transformed<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        self.field.sample(self.to_local(position))
+    }
+}
+
+/// Rotate `v` by `angle` radians around the unit vector `axis` using Rodrigues' formula.
+pub(super) fn rotate(v: Vec3, axis: Vec3, angle: Float) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    v * cos + axis.cross(v) * sin + axis * (axis.dot(v) * (1.0 - cos))
+}
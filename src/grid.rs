@@ -0,0 +1,314 @@
+use crate::{
+    Domain, Float, IVec3, RefineStrategy, ScalarField, Vec3, builder::MeshBuilder,
+    field::FieldBounds, get_vert_offsets, result::MarchResult,
+};
+
+/// Weights of a field sampled once at every vertex of the lattice of a domain.
+#[derive(Debug, Clone)]
+pub struct ScalarGrid {
+    /// Number of samples along each axis.
+    pub size: IVec3,
+    pub weights: Vec<Float>,
+}
+
+/// Number of elements of a 3D array, computed in `usize` as the product can exceed `i32`.
+fn volume(size: IVec3) -> usize {
+    size.x as usize * size.y as usize * size.z as usize
+}
+
+/// Index into a 3D array stored with x varying fastest.
+fn linear_index(position: IVec3, size: IVec3) -> usize {
+    (position.z as usize * size.y as usize + position.y as usize) * size.x as usize
+        + position.x as usize
+}
+
+impl ScalarGrid {
+    fn index(&self, position: IVec3) -> usize {
+        linear_index(position, self.size)
+    }
+
+    pub fn weight(&self, position: IVec3) -> Float {
+        self.weights[self.index(position)]
+    }
+}
+
+/// Lowest and highest weight of a block of cells.
+#[derive(Debug, Copy, Clone)]
+pub struct MinMax {
+    pub min: Float,
+    pub max: Float,
+}
+
+impl MinMax {
+    pub(crate) const EMPTY: MinMax = MinMax {
+        min: Float::INFINITY,
+        max: Float::NEG_INFINITY,
+    };
+
+    pub(crate) fn include(&mut self, other: MinMax) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// The surface can only pass through a block when it has vertices on both sides.
+    pub fn may_contain_surface(&self, surface_weight: Float) -> bool {
+        self.min <= surface_weight && self.max > surface_weight
+    }
+}
+
+/// Min/max pyramid over the cells of a [ScalarGrid].
+///
+/// Level 0 stores the range of each cell, every next level combines 2x2x2 blocks of the previous
+/// level. The ranges are conservative: a block that reports it can't contain the surface doesn't
+/// contain any surface crossing.
+#[derive(Debug, Clone)]
+pub struct MinMaxPyramid {
+    levels: Vec<(IVec3, Vec<MinMax>)>,
+}
+
+impl MinMaxPyramid {
+    pub fn new(grid: &ScalarGrid) -> MinMaxPyramid {
+        let cell_count = IVec3 {
+            x: grid.size.x - 1,
+            y: grid.size.y - 1,
+            z: grid.size.z - 1,
+        };
+        let mut cells = Vec::with_capacity(volume(cell_count));
+        for z in 0..cell_count.z {
+            for y in 0..cell_count.y {
+                for x in 0..cell_count.x {
+                    let mut range = MinMax::EMPTY;
+                    for offset in crate::GRID_TO_VERT_OFFSETS {
+                        let weight = grid.weight(IVec3 { x, y, z } + offset);
+                        range.include(MinMax {
+                            min: weight,
+                            max: weight,
+                        });
+                    }
+                    cells.push(range);
+                }
+            }
+        }
+
+        let mut levels = vec![(cell_count, cells)];
+        loop {
+            let (size, ranges) = levels.last().unwrap();
+            if size.x <= 1 && size.y <= 1 && size.z <= 1 {
+                break;
+            }
+            let parent_size = IVec3 {
+                x: (size.x + 1) / 2,
+                y: (size.y + 1) / 2,
+                z: (size.z + 1) / 2,
+            };
+            let mut parents = vec![MinMax::EMPTY; volume(parent_size)];
+            for z in 0..size.z {
+                for y in 0..size.y {
+                    for x in 0..size.x {
+                        let parent = IVec3 {
+                            x: x / 2,
+                            y: y / 2,
+                            z: z / 2,
+                        };
+                        parents[linear_index(parent, parent_size)]
+                            .include(ranges[linear_index(IVec3 { x, y, z }, *size)]);
+                    }
+                }
+            }
+            levels.push((parent_size, parents));
+        }
+        MinMaxPyramid { levels }
+    }
+
+    /// Number of levels, the last level contains a single block covering all cells.
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Number of blocks along each axis at the given level.
+    pub fn level_size(&self, level: usize) -> IVec3 {
+        self.levels[level].0
+    }
+
+    /// Range of a block. At level `n` a block covers `2^n` cells along each axis.
+    pub fn range(&self, level: usize, block: IVec3) -> MinMax {
+        let (size, ranges) = &self.levels[level];
+        ranges[linear_index(block, *size)]
+    }
+
+    /// Conservative test if a block can contain a part of the surface.
+    pub fn block_may_contain_surface(
+        &self,
+        level: usize,
+        block: IVec3,
+        surface_weight: Float,
+    ) -> bool {
+        self.range(level, block).may_contain_surface(surface_weight)
+    }
+}
+
+impl Domain {
+    /// Sample the weight function at every lattice vertex used by the march.
+    pub fn sample_grid<FIELD>(&self, field: &FIELD) -> ScalarGrid
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        // Cells are marched up to and including the vertex grid size, so one extra vertex is
+        // needed along each axis.
+        let cell_count = self.vertex_grid_size();
+        let size = IVec3 {
+            x: cell_count.x + 1,
+            y: cell_count.y + 1,
+            z: cell_count.z + 1,
+        };
+        let mut weights = Vec::with_capacity(volume(size));
+        for z in 0..size.z {
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    let position = self.vertex_position(IVec3 { x, y, z });
+                    weights.push(field.sample(position));
+                }
+            }
+        }
+        ScalarGrid { size, weights }
+    }
+
+    /// March the field by sampling it into a [ScalarGrid] first and skipping blocks of cells
+    /// that the [MinMaxPyramid] reports can't contain the surface.
+    ///
+    /// Produces the same faces as [Domain::march_tetrahedras], but grouped by block instead of
+    /// in lattice order.
+    pub fn march_tetrahedras_sampled<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        // Grids that can't be allocated fall back to marching plane by plane, which only keeps 2
+        // planes of samples and produces the same faces.
+        if !self.fits_sampled_grid() {
+            return self.march_tetrahedras(field, refine);
+        }
+        let grid = self.sample_grid(field);
+        let pyramid = MinMaxPyramid::new(&grid);
+        let mut mesh = MeshBuilder::default();
+        let top_level = pyramid.level_count() - 1;
+        let top_size = pyramid.level_size(top_level);
+        for z in 0..top_size.z {
+            for y in 0..top_size.y {
+                for x in 0..top_size.x {
+                    self.march_block(
+                        &mut mesh,
+                        &grid,
+                        &pyramid,
+                        top_level,
+                        IVec3 { x, y, z },
+                        field,
+                        refine,
+                    );
+                }
+            }
+        }
+
+        let range = pyramid.range(top_level, IVec3 { x: 0, y: 0, z: 0 });
+        let mesh = mesh.finish();
+        let result = MarchResult::new(&mesh, range.min, range.max, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+
+    /// March the field in chunks of `chunk_size` cells along each axis, skipping chunks where
+    /// [FieldBounds::weight_range] shows the surface can't be.
+    ///
+    /// Produces the same faces as [Domain::march_tetrahedras], but grouped by chunk instead of
+    /// in lattice order. Skipped chunks aren't sampled, so the weight range of the result uses
+    /// their bounds instead.
+    pub fn march_tetrahedras_bounded<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+        chunk_size: usize,
+    ) -> MarchResult
+    where
+        FIELD: FieldBounds + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let chunk_size = chunk_size.clamp(1, i32::MAX as usize) as i32;
+        let cell_count = self.vertex_grid_size();
+        let mut mesh = MeshBuilder::default();
+        let mut range = MinMax::EMPTY;
+        for x in (0..cell_count.x).step_by(chunk_size as usize) {
+            for y in (0..cell_count.y).step_by(chunk_size as usize) {
+                for z in (0..cell_count.z).step_by(chunk_size as usize) {
+                    let from = IVec3 { x, y, z };
+                    let to = IVec3 {
+                        x: (x + chunk_size).min(cell_count.x),
+                        y: (y + chunk_size).min(cell_count.y),
+                        z: (z + chunk_size).min(cell_count.z),
+                    };
+                    let bounds =
+                        field.weight_range(self.vertex_position(from), self.vertex_position(to));
+                    if !bounds.may_contain_surface(self.surface_weight) {
+                        range.include(bounds);
+                        continue;
+                    }
+                    let (min, max) = self.march_cells(&mut mesh, from, to, field, refine);
+                    range.include(MinMax { min, max });
+                }
+            }
+        }
+
+        let mesh = mesh.finish();
+        let result = MarchResult::new(&mesh, range.min, range.max, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn march_block<FIELD, REFINE>(
+        &self,
+        mesh: &mut MeshBuilder,
+        grid: &ScalarGrid,
+        pyramid: &MinMaxPyramid,
+        level: usize,
+        block: IVec3,
+        field: &FIELD,
+        refine: &REFINE,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        if !pyramid.block_may_contain_surface(level, block, self.surface_weight) {
+            return;
+        }
+
+        if level == 0 {
+            let (grid_to_verts_offsets, _) = get_vert_offsets(block + self.lattice_offset());
+            let vert_positions = grid_to_verts_offsets
+                .iter()
+                .map(|offset| self.vertex_position(block + *offset))
+                .collect::<Vec<Vec3>>();
+            let vert_weights = grid_to_verts_offsets
+                .iter()
+                .map(|offset| grid.weight(block + *offset))
+                .collect::<Vec<Float>>();
+            self.polygonize_cell(mesh, &vert_positions, &vert_weights, block, field, refine);
+            return;
+        }
+
+        let child_size = pyramid.level_size(level - 1);
+        for offset in crate::GRID_TO_VERT_OFFSETS {
+            let child = IVec3 {
+                x: block.x * 2,
+                y: block.y * 2,
+                z: block.z * 2,
+            } + offset;
+            if child.x < child_size.x && child.y < child_size.y && child.z < child_size.z {
+                self.march_block(mesh, grid, pyramid, level - 1, child, field, refine);
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Terrain from 2D heightmaps.
+//!
+//! A [Heightmap] is solid below the height of the terrain, with z pointing up. The field is
+//! `height(x, y) - z`: the vertical distance to the terrain, positive below it so it follows the
+//! inside convention of [ScalarField]. Every column crosses the surface once, so the mesh has no
+//! overhangs; combine it with other fields to add caves or arches.
+//!
+//! ```
+//! use marching_cubes::{heightmap::Heightmap, prelude::*};
+//!
+//! // 3x2 grayscale PGM image.
+//! let pgm = b"P5 3 2 255\n\x00\x80\xff\x40\x80\xc0";
+//! let terrain = Heightmap::read_pgm(
+//!     &mut &pgm[..],
+//!     Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+//!     Vec3 { x: 10.0, y: 5.0, z: 2.0 },
+//! )
+//! .unwrap();
+//! let mut domain = MarchConfig::preset(Preset::Preview).domain(
+//!     Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+//!     Vec3 { x: 10.0, y: 5.0, z: 3.0 },
+//!     0.0,
+//! );
+//! domain.march_tetrahedras(&terrain, &Bisection);
+//! assert!(!domain.meshes[0].faces.is_empty());
+//! ```
+
+use std::io;
+
+use crate::{Float, ScalarField, Vec3};
+
+/// Height samples on a regular grid in the xy plane, see [crate::heightmap].
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    /// The first sample is at the x and y of `from`, the last at the x and y of `to`. A value
+    /// of 0.0 is at the height `from.z` and a value of 1.0 at `to.z`.
+    pub from: Vec3,
+    pub to: Vec3,
+    /// Number of samples along x.
+    pub width: usize,
+    /// Number of samples along y.
+    pub height: usize,
+    /// Values ordered x first, then y.
+    pub values: Vec<f32>,
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+impl Heightmap {
+    /// Load a binary grayscale PGM (`P5`) image with 8 or 16 bit samples. Black is at `from.z`,
+    /// white at `to.z`. The first row of the image is at `from.y`.
+    ///
+    /// Most image editors and terrain generators export PGM, convert other formats with for
+    /// example `magick terrain.png terrain.pgm`.
+    pub fn read_pgm<R: io::Read>(reader: &mut R, from: Vec3, to: Vec3) -> io::Result<Heightmap> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if !data.starts_with(b"P5") {
+            return Err(invalid_data("not a binary PGM image"));
+        }
+        // Header: magic, width, height and maximum value separated by whitespace, comments
+        // start with `#` and run to the end of the line.
+        let mut offset = 2;
+        let mut header = [0_usize; 3];
+        for value in &mut header {
+            loop {
+                match data.get(offset) {
+                    Some(b'#') => {
+                        while data.get(offset).is_some_and(|byte| *byte != b'\n') {
+                            offset += 1;
+                        }
+                    }
+                    Some(byte) if byte.is_ascii_whitespace() => offset += 1,
+                    _ => break,
+                }
+            }
+            let start = offset;
+            while data.get(offset).is_some_and(u8::is_ascii_digit) {
+                offset += 1;
+            }
+            *value = std::str::from_utf8(&data[start..offset])
+                .unwrap()
+                .parse()
+                .map_err(|_| invalid_data("invalid PGM header"))?;
+        }
+        // A single whitespace character separates the header from the samples.
+        offset += 1;
+        let [width, height, max_value] = header;
+        if width == 0 || height == 0 || max_value == 0 || max_value > u16::MAX as usize {
+            return Err(invalid_data("invalid PGM header"));
+        }
+        let bytes_per_value = if max_value < 256 { 1 } else { 2 };
+        let samples = data
+            .get(offset..offset + width * height * bytes_per_value)
+            .ok_or_else(|| invalid_data("unexpected end of PGM image"))?;
+        let values = samples
+            .chunks_exact(bytes_per_value)
+            .map(|sample| {
+                // 16 bit samples are big endian.
+                let value = sample
+                    .iter()
+                    .fold(0_u32, |value, byte| value << 8 | *byte as u32);
+                value as f32 / max_value as f32
+            })
+            .collect();
+        Ok(Heightmap {
+            from,
+            to,
+            width,
+            height,
+            values,
+        })
+    }
+
+    /// Load `width * height` little endian f32 values ordered x first, then y, as written by
+    /// terrain tools that export raw elevation. Use a `from.z` of 0.0 and a `to.z` of 1.0 to
+    /// keep the values as heights in world units.
+    pub fn read_raw_f32<R: io::Read>(
+        reader: &mut R,
+        size: [usize; 2],
+        from: Vec3,
+        to: Vec3,
+    ) -> io::Result<Heightmap> {
+        let [width, height] = size;
+        if width == 0 || height == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "heightmap needs at least 1 sample along each axis",
+            ));
+        }
+        let mut bytes = vec![0; width * height * 4];
+        reader.read_exact(&mut bytes)?;
+        Ok(Heightmap {
+            from,
+            to,
+            width,
+            height,
+            values: bytes
+                .chunks_exact(4)
+                .map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+        })
+    }
+
+    pub fn value(&self, x: usize, y: usize) -> f32 {
+        self.values[y * self.width + x]
+    }
+
+    /// Height of the terrain at `x`, `y`, interpolated bilinearly between the samples.
+    /// Positions outside of the grid use the height of the nearest border.
+    pub fn height_at(&self, x: Float, y: Float) -> Float {
+        let axis = |value: Float, from: Float, to: Float, count: usize| {
+            if count < 2 || to == from {
+                return (0, 0, 0.0);
+            }
+            let position = ((value - from) / (to - from) * (count - 1) as Float)
+                .clamp(0.0, (count - 1) as Float);
+            let first = (position.floor() as usize).min(count - 2);
+            (first, first + 1, position - first as Float)
+        };
+        let (x0, x1, fx) = axis(x, self.from.x, self.to.x, self.width);
+        let (y0, y1, fy) = axis(y, self.from.y, self.to.y, self.height);
+        let value = |x: usize, y: usize| Float::from(self.value(x, y));
+        let lower = value(x0, y0) + (value(x1, y0) - value(x0, y0)) * fx;
+        let upper = value(x0, y1) + (value(x1, y1) - value(x0, y1)) * fx;
+        let value = lower + (upper - lower) * fy;
+        self.from.z + value * (self.to.z - self.from.z)
+    }
+}
+
+impl ScalarField for Heightmap {
+    fn sample(&self, position: Vec3) -> Float {
+        self.height_at(position.x, position.y) - position.z
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    Domain, Float, IVec3, Mesh, RefineStrategy, ScalarField, Vec3, builder::MeshBuilder,
+    get_vert_offsets, grid::ScalarGrid, result::MarchResult,
+};
+
+/// Callback of [Hooks::after_sampling].
+pub type SamplingHook<'a> = Box<dyn FnMut(&mut ScalarGrid) + 'a>;
+/// Callback of [Hooks::after_chunk].
+pub type ChunkHook<'a> = Box<dyn FnMut(&mut Mesh, IVec3, IVec3) + 'a>;
+/// Callback of [Hooks::before_export].
+pub type MeshHook<'a> = Box<dyn FnMut(&mut Mesh) + 'a>;
+
+/// Callbacks invoked by [Domain::march_with_hooks] at the stages of the pipeline, with mutable
+/// access to the intermediate data. Stages without a callback are skipped.
+#[derive(Default)]
+pub struct Hooks<'a> {
+    /// Called once with the weights sampled at every lattice vertex. Changing a weight changes
+    /// which side of the surface the vertex is on; vertices on crossing edges are still placed
+    /// using the field.
+    pub after_sampling: Option<SamplingHook<'a>>,
+    /// Called with the faces of each chunk and the range of cells `from`..`to` it covers.
+    /// Faces can be removed or changed, but `vert_keys` must stay in sync with `verts` as the
+    /// chunks are welded by key.
+    pub after_chunk: Option<ChunkHook<'a>>,
+    /// Called with the finished mesh before it is added to the meshes of the domain, the last
+    /// moment before it can be exported.
+    pub before_export: Option<MeshHook<'a>>,
+}
+
+impl Domain {
+    /// Same as [Domain::march_tetrahedras_sampled] without skipping, in chunks of `chunk_size`
+    /// cells along each axis, invoking the callbacks of `hooks` in between.
+    pub fn march_with_hooks<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+        chunk_size: usize,
+        hooks: &mut Hooks,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let mut grid = self.sample_grid(field);
+        if let Some(after_sampling) = &mut hooks.after_sampling {
+            after_sampling(&mut grid);
+        }
+
+        let chunk_size = chunk_size.clamp(1, i32::MAX as usize) as i32;
+        let cell_count = self.vertex_grid_size();
+        let mut mesh = MeshBuilder::default();
+        for x in (0..cell_count.x).step_by(chunk_size as usize) {
+            for y in (0..cell_count.y).step_by(chunk_size as usize) {
+                for z in (0..cell_count.z).step_by(chunk_size as usize) {
+                    let from = IVec3 { x, y, z };
+                    let to = IVec3 {
+                        x: (x + chunk_size).min(cell_count.x),
+                        y: (y + chunk_size).min(cell_count.y),
+                        z: (z + chunk_size).min(cell_count.z),
+                    };
+                    let mut chunk = MeshBuilder::default();
+                    self.march_grid_cells(&mut chunk, &grid, from, to, field, refine);
+                    let mut chunk = chunk.finish();
+                    if let Some(after_chunk) = &mut hooks.after_chunk {
+                        after_chunk(&mut chunk, from, to);
+                    }
+                    mesh.append(chunk);
+                }
+            }
+        }
+
+        let mut mesh = mesh.finish();
+        if let Some(before_export) = &mut hooks.before_export {
+            before_export(&mut mesh);
+        }
+        let min_weight = grid
+            .weights
+            .iter()
+            .copied()
+            .fold(Float::INFINITY, Float::min);
+        let max_weight = grid
+            .weights
+            .iter()
+            .copied()
+            .fold(Float::NEG_INFINITY, Float::max);
+        let result = MarchResult::new(&mesh, min_weight, max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+
+    /// March the cells from `from` up to but not including `to` using the weights of `grid`.
+    fn march_grid_cells<FIELD, REFINE>(
+        &self,
+        mesh: &mut MeshBuilder,
+        grid: &ScalarGrid,
+        from: IVec3,
+        to: IVec3,
+        field: &FIELD,
+        refine: &REFINE,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        for x in from.x..to.x {
+            for y in from.y..to.y {
+                for z in from.z..to.z {
+                    let cell_pos = IVec3 { x, y, z };
+                    let (grid_to_verts_offsets, _) =
+                        get_vert_offsets(cell_pos + self.lattice_offset());
+                    let vert_positions = grid_to_verts_offsets
+                        .iter()
+                        .map(|offset| self.vertex_position(cell_pos + *offset))
+                        .collect::<Vec<Vec3>>();
+                    let vert_weights = grid_to_verts_offsets
+                        .iter()
+                        .map(|offset| grid.weight(cell_pos + *offset))
+                        .collect::<Vec<Float>>();
+                    self.polygonize_cell(
+                        mesh,
+                        &vert_positions,
+                        &vert_weights,
+                        cell_pos,
+                        field,
+                        refine,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,790 @@
+//! Volumes stored as a directory of 2D images, one image per z slice.
+//!
+//! Microscopes, CT scanners and segmentation tools commonly export volumes as numbered PNG or
+//! TIFF files. [VoxelGrid::load_image_stack] reads such a directory into a [VoxelGrid], the
+//! decoders are available on their own as [read_png] and [read_tiff].
+//!
+//! Supported are non-interlaced grayscale, gray with alpha, RGB and RGBA PNG images with 8 or
+//! 16 bits per sample, and grayscale baseline TIFF images with 8, 16 or 32 bit samples stored in
+//! strips, uncompressed or compressed with Deflate or LZW. Color images are converted to the
+//! mean of their color channels, alpha is ignored.
+
+use std::{cmp::Ordering, fs, io, path::Path};
+
+use crate::{Vec3, inflate::inflate_zlib, voxel::VoxelGrid};
+
+/// Grayscale image with values in the units of the file, rows ordered from the top down.
+#[derive(Debug, Clone)]
+pub struct ImageSlice {
+    pub width: usize,
+    pub height: usize,
+    /// Values ordered x first, then y.
+    pub values: Vec<f32>,
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+impl VoxelGrid {
+    /// Load every `.png`, `.tif` and `.tiff` file in `directory` as a slice of constant z. Files
+    /// are ordered by name, with numbers compared by value, so both `slice_0009.png` and
+    /// `slice_9.png` come before `slice_10.png`. The first file is at `from.z`.
+    ///
+    /// The values are kept in the units of the images, so an 8 bit stack is marched with a
+    /// surface weight between 0.0 and 255.0. All images must have the same size.
+    pub fn load_image_stack(directory: &Path, from: Vec3, to: Vec3) -> io::Result<VoxelGrid> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let extension = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(|extension| extension.to_ascii_lowercase());
+            if matches!(extension.as_deref(), Some("png" | "tif" | "tiff")) && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort_by(|a, b| {
+            natural_order(
+                &a.file_name().unwrap().to_string_lossy(),
+                &b.file_name().unwrap().to_string_lossy(),
+            )
+        });
+        if paths.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no PNG or TIFF images in {}", directory.display()),
+            ));
+        }
+
+        let mut grid: Option<VoxelGrid> = None;
+        for path in &paths {
+            let data = fs::read(path)?;
+            let slice = if data.starts_with(PNG_SIGNATURE) {
+                read_png(&mut &data[..])
+            } else {
+                read_tiff(&data)
+            }
+            .map_err(|error| invalid_data(format!("{}: {error}", path.display())))?;
+            let grid = grid.get_or_insert_with(|| VoxelGrid {
+                from,
+                to,
+                width: slice.width,
+                height: slice.height,
+                depth: 0,
+                values: Vec::with_capacity(slice.values.len() * paths.len()),
+            });
+            if (slice.width, slice.height) != (grid.width, grid.height) {
+                return Err(invalid_data(format!(
+                    "{}: image is {}x{}, expected {}x{} like the first slice",
+                    path.display(),
+                    slice.width,
+                    slice.height,
+                    grid.width,
+                    grid.height
+                )));
+            }
+            grid.values.extend_from_slice(&slice.values);
+            grid.depth += 1;
+        }
+        Ok(grid.unwrap())
+    }
+}
+
+/// Compare file names with runs of digits compared by their value.
+fn natural_order(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    while let (Some(&first_a), Some(&first_b)) = (a.first(), b.first()) {
+        let ordering = if first_a.is_ascii_digit() && first_b.is_ascii_digit() {
+            let digits = |text: &[u8]| text.iter().take_while(|byte| byte.is_ascii_digit()).count();
+            let (digits_a, digits_b) = (digits(a), digits(b));
+            let trim = |text: &[u8]| {
+                let zeros = text.iter().take_while(|byte| **byte == b'0').count();
+                text[zeros..].to_vec()
+            };
+            let (number_a, number_b) = (trim(&a[..digits_a]), trim(&b[..digits_b]));
+            let ordering = number_a
+                .len()
+                .cmp(&number_b.len())
+                .then_with(|| number_a.cmp(&number_b));
+            a = &a[digits_a..];
+            b = &b[digits_b..];
+            ordering
+        } else {
+            a = &a[1..];
+            b = &b[1..];
+            first_a.cmp(&first_b)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Decode a PNG image, see [crate::image_stack] for the supported formats.
+pub fn read_png<R: io::Read>(reader: &mut R) -> io::Result<ImageSlice> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if !data.starts_with(PNG_SIGNATURE) {
+        return Err(invalid_data("not a PNG image"));
+    }
+    let mut header = None;
+    let mut compressed = Vec::new();
+    let mut offset = PNG_SIGNATURE.len();
+    while offset + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        let kind = &data[offset + 4..offset + 8];
+        let content = data
+            .get(offset + 8..offset + 8 + length)
+            .ok_or_else(|| invalid_data("unexpected end of PNG image"))?;
+        match kind {
+            b"IHDR" if content.len() >= 13 => header = Some(content),
+            b"IDAT" => compressed.extend_from_slice(content),
+            b"IEND" => break,
+            _ => {}
+        }
+        // Chunk data is followed by a 4 byte CRC.
+        offset += 12 + length;
+    }
+    let header = header.ok_or_else(|| invalid_data("PNG image without header"))?;
+    let width = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+    let (bit_depth, color_type, interlace) = (header[8], header[9], header[12]);
+    let channels = match color_type {
+        0 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => return Err(invalid_data("unsupported PNG color type")),
+    };
+    if !matches!(bit_depth, 8 | 16) {
+        return Err(invalid_data("unsupported PNG bit depth"));
+    }
+    if interlace != 0 {
+        return Err(invalid_data("interlaced PNG images aren't supported"));
+    }
+
+    let pixels = inflate_zlib(&compressed)?;
+    let bytes_per_sample = bit_depth as usize / 8;
+    let bytes_per_pixel = channels * bytes_per_sample;
+    let stride = width
+        .checked_mul(bytes_per_pixel)
+        .ok_or_else(|| invalid_data("PNG image too large"))?;
+    let data_size = (stride + 1)
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data("PNG image too large"))?;
+    if pixels.len() < data_size {
+        return Err(invalid_data("unexpected end of PNG image data"));
+    }
+    let mut previous = vec![0_u8; stride];
+    let mut row = vec![0_u8; stride];
+    let mut values = Vec::with_capacity(width * height);
+    for filtered in pixels.chunks_exact(stride + 1).take(height) {
+        let filter = filtered[0];
+        for index in 0..stride {
+            let left = if index >= bytes_per_pixel {
+                row[index - bytes_per_pixel]
+            } else {
+                0
+            };
+            let up = previous[index];
+            let up_left = if index >= bytes_per_pixel {
+                previous[index - bytes_per_pixel]
+            } else {
+                0
+            };
+            let prediction = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(invalid_data("invalid PNG filter")),
+            };
+            row[index] = filtered[index + 1].wrapping_add(prediction);
+        }
+        for pixel in row.chunks_exact(bytes_per_pixel) {
+            let sample = |channel: usize| {
+                let bytes = &pixel[channel * bytes_per_sample..(channel + 1) * bytes_per_sample];
+                // 16 bit samples are big endian.
+                bytes
+                    .iter()
+                    .fold(0_u32, |value, byte| value << 8 | *byte as u32) as f32
+            };
+            values.push(match channels {
+                1 | 2 => sample(0),
+                _ => (sample(0) + sample(1) + sample(2)) / 3.0,
+            });
+        }
+        std::mem::swap(&mut previous, &mut row);
+    }
+    Ok(ImageSlice {
+        width,
+        height,
+        values,
+    })
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance_left = (estimate - left as i16).abs();
+    let distance_up = (estimate - up as i16).abs();
+    let distance_up_left = (estimate - up_left as i16).abs();
+    if distance_left <= distance_up && distance_left <= distance_up_left {
+        left
+    } else if distance_up <= distance_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Decode the first image of a TIFF file, see [crate::image_stack] for the supported formats.
+pub fn read_tiff(data: &[u8]) -> io::Result<ImageSlice> {
+    let little_endian = match data.get(0..4) {
+        Some(b"II*\0") => true,
+        Some(b"MM\0*") => false,
+        _ => return Err(invalid_data("not a TIFF image")),
+    };
+    let end = || invalid_data("unexpected end of TIFF image");
+    let read_u16 = |offset: usize| -> io::Result<u32> {
+        let bytes: [u8; 2] = data
+            .get(offset..offset + 2)
+            .ok_or_else(end)?
+            .try_into()
+            .unwrap();
+        Ok(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        } as u32)
+    };
+    let read_u32 = |offset: usize| -> io::Result<u32> {
+        let bytes: [u8; 4] = data
+            .get(offset..offset + 4)
+            .ok_or_else(end)?
+            .try_into()
+            .unwrap();
+        Ok(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    // Tags of the first image file directory, values of 4 bytes or less are stored in the entry.
+    let directory = read_u32(4)? as usize;
+    let mut tags = Vec::new();
+    for entry in 0..read_u16(directory)? as usize {
+        let offset = directory + 2 + entry * 12;
+        let (tag, kind, count) = (
+            read_u16(offset)?,
+            read_u16(offset + 2)?,
+            read_u32(offset + 4)?,
+        );
+        // Only SHORT and LONG values are needed.
+        let size = match kind {
+            3 => 2,
+            4 => 4,
+            _ => continue,
+        };
+        let count = count as usize;
+        let start = if count * size <= 4 {
+            offset + 8
+        } else {
+            read_u32(offset + 8)? as usize
+        };
+        let values = (0..count)
+            .map(|index| match size {
+                2 => read_u16(start + index * 2),
+                _ => read_u32(start + index * 4),
+            })
+            .collect::<io::Result<Vec<u32>>>()?;
+        tags.push((tag, values));
+    }
+    let tag = |id: u32| {
+        tags.iter()
+            .find(|(tag, _)| *tag == id)
+            .map(|(_, values)| values)
+    };
+    let first = |id: u32, default: u32| {
+        tag(id)
+            .and_then(|values| values.first().copied())
+            .unwrap_or(default)
+    };
+
+    let width = first(256, 0) as usize;
+    let height = first(257, 0) as usize;
+    let bits = first(258, 1);
+    let compression = first(259, 1);
+    let rows_per_strip = (first(278, u32::MAX) as usize).min(height.max(1));
+    let sample_format = first(339, 1);
+    if width == 0 || height == 0 {
+        return Err(invalid_data("TIFF image without size"));
+    }
+    if first(277, 1) != 1 {
+        return Err(invalid_data("only grayscale TIFF images are supported"));
+    }
+    if first(317, 1) != 1 {
+        return Err(invalid_data("TIFF predictors aren't supported"));
+    }
+    if !matches!((bits, sample_format), (8 | 16 | 32, 1) | (32, 3)) {
+        return Err(invalid_data("unsupported TIFF sample format"));
+    }
+    let (Some(offsets), Some(counts)) = (tag(273), tag(279)) else {
+        return Err(invalid_data(
+            "only TIFF images stored in strips are supported",
+        ));
+    };
+
+    let bytes_per_value = bits as usize / 8;
+    let row_size = width
+        .checked_mul(bytes_per_value)
+        .ok_or_else(|| invalid_data("TIFF image too large"))?;
+    let image_size = row_size
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data("TIFF image too large"))?;
+    let mut pixels = Vec::new();
+    for (strip, (&offset, &count)) in offsets.iter().zip(counts).enumerate() {
+        let strip_data = data
+            .get(offset as usize..offset as usize + count as usize)
+            .ok_or_else(end)?;
+        let mut decoded = match compression {
+            1 => strip_data.to_vec(),
+            5 => decode_lzw(strip_data)?,
+            8 | 32946 => inflate_zlib(strip_data)?,
+            _ => return Err(invalid_data("unsupported TIFF compression")),
+        };
+        let first_row = strip.saturating_mul(rows_per_strip).min(height);
+        let strip_size = rows_per_strip.min(height - first_row) * row_size;
+        if decoded.len() < strip_size {
+            return Err(invalid_data("unexpected end of TIFF strip"));
+        }
+        decoded.truncate(strip_size);
+        pixels.extend_from_slice(&decoded);
+    }
+    if pixels.len() < image_size {
+        return Err(end());
+    }
+    let values = pixels
+        .chunks_exact(bytes_per_value)
+        .map(|bytes| match (bytes.len(), sample_format) {
+            (1, _) => bytes[0] as f32,
+            (2, _) => {
+                let bytes = [bytes[0], bytes[1]];
+                (if little_endian {
+                    u16::from_le_bytes(bytes)
+                } else {
+                    u16::from_be_bytes(bytes)
+                }) as f32
+            }
+            (_, format) => {
+                let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+                let bits = if little_endian {
+                    u32::from_le_bytes(bytes)
+                } else {
+                    u32::from_be_bytes(bytes)
+                };
+                if format == 3 {
+                    f32::from_bits(bits)
+                } else {
+                    bits as f32
+                }
+            }
+        })
+        .collect();
+    Ok(ImageSlice {
+        width,
+        height,
+        values,
+    })
+}
+
+/// Decode TIFF LZW data: codes of 9 to 12 bits, most significant bit first, with the code width
+/// growing one code early.
+fn decode_lzw(data: &[u8]) -> io::Result<Vec<u8>> {
+    const CLEAR: usize = 256;
+    const END: usize = 257;
+    const MAX_CODES: usize = 1 << 12;
+    let mut output = Vec::new();
+    let mut table: Vec<Vec<u8>> = (0..=255).map(|byte| vec![byte]).collect();
+    table.extend([Vec::new(), Vec::new()]);
+    let mut width = 9;
+    let mut previous: Option<usize> = None;
+    let mut bit = 0;
+    while bit + width <= data.len() * 8 {
+        let mut code = 0;
+        for _ in 0..width {
+            code = code << 1 | ((data[bit / 8] >> (7 - bit % 8)) & 1) as usize;
+            bit += 1;
+        }
+        if code == END {
+            break;
+        }
+        if code == CLEAR {
+            table.truncate(258);
+            width = 9;
+            previous = None;
+            continue;
+        }
+        let entry = match previous {
+            _ if code < table.len() => table[code].clone(),
+            Some(previous) if code == table.len() => {
+                let mut entry = table[previous].clone();
+                entry.push(entry[0]);
+                entry
+            }
+            _ => return Err(invalid_data("invalid LZW code")),
+        };
+        output.extend_from_slice(&entry);
+        if let Some(previous) = previous.filter(|_| table.len() < MAX_CODES) {
+            let mut added = table[previous].clone();
+            added.push(entry[0]);
+            table.push(added);
+        }
+        previous = Some(code);
+        if table.len() + 1 >= 1 << width && width < 12 {
+            width += 1;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflate::tests::zlib_stored;
+
+    fn png_chunk(png: &mut Vec<u8>, kind: &[u8], content: &[u8]) {
+        png.extend_from_slice(&(content.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(content);
+        let mut crc = !0_u32;
+        for byte in kind.iter().chain(content) {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    crc >> 1 ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        png.extend_from_slice(&(!crc).to_be_bytes());
+    }
+
+    /// PNG image of `rows` of raw samples, row `y` filtered with `filters[y % filters.len()]`.
+    fn encode_png(
+        width: u32,
+        bit_depth: u8,
+        color_type: u8,
+        rows: &[Vec<u8>],
+        filters: &[u8],
+    ) -> Vec<u8> {
+        let bytes_per_pixel = rows[0].len() / width as usize;
+        let mut filtered = Vec::new();
+        let mut previous = vec![0_u8; rows[0].len()];
+        for (y, row) in rows.iter().enumerate() {
+            let filter = filters[y % filters.len()];
+            filtered.push(filter);
+            for index in 0..row.len() {
+                let left = if index >= bytes_per_pixel {
+                    row[index - bytes_per_pixel]
+                } else {
+                    0
+                };
+                let up = previous[index];
+                let up_left = if index >= bytes_per_pixel {
+                    previous[index - bytes_per_pixel]
+                } else {
+                    0
+                };
+                let prediction = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    _ => paeth(left, up, up_left),
+                };
+                filtered.push(row[index].wrapping_sub(prediction));
+            }
+            previous.clone_from(row);
+        }
+        let mut header = Vec::new();
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&(rows.len() as u32).to_be_bytes());
+        header.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        let mut png = PNG_SIGNATURE.to_vec();
+        png_chunk(&mut png, b"IHDR", &header);
+        // Split the image data over two chunks, readers concatenate them.
+        let compressed = zlib_stored(&filtered, 64);
+        let (first, second) = compressed.split_at(compressed.len() / 2);
+        png_chunk(&mut png, b"IDAT", first);
+        png_chunk(&mut png, b"IDAT", second);
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Rows of a `width` by `height` gradient with some noise, so every filter predicts
+    /// something different.
+    fn gray_values(width: usize, height: usize, max: u32) -> Vec<u32> {
+        (0..width * height)
+            .map(|index| (index as u32 * 37 + (index as u32 * index as u32) % 11) % (max + 1))
+            .collect()
+    }
+
+    #[test]
+    fn png_filters() {
+        let (width, height) = (7, 10);
+        let values = gray_values(width, height, 255);
+        let rows = values
+            .chunks(width)
+            .map(|row| row.iter().map(|value| *value as u8).collect())
+            .collect::<Vec<Vec<u8>>>();
+        for filters in [&[0][..], &[1], &[2], &[3], &[4], &[0, 1, 2, 3, 4]] {
+            let png = encode_png(width as u32, 8, 0, &rows, filters);
+            let slice = read_png(&mut &png[..]).unwrap();
+            assert_eq!((slice.width, slice.height), (width, height));
+            let expected = values
+                .iter()
+                .map(|value| *value as f32)
+                .collect::<Vec<f32>>();
+            assert_eq!(slice.values, expected, "filters {filters:?}");
+        }
+    }
+
+    #[test]
+    fn png_16_bit() {
+        let (width, height) = (5, 6);
+        let values = gray_values(width, height, 65535)
+            .iter()
+            .map(|value| value * 251)
+            .collect::<Vec<u32>>();
+        let rows = values
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .flat_map(|value| (*value as u16).to_be_bytes())
+                    .collect()
+            })
+            .collect::<Vec<Vec<u8>>>();
+        let png = encode_png(width as u32, 16, 0, &rows, &[0, 1, 2, 3, 4]);
+        let slice = read_png(&mut &png[..]).unwrap();
+        let expected = values
+            .iter()
+            .map(|value| *value as u16 as f32)
+            .collect::<Vec<f32>>();
+        assert_eq!(slice.values, expected);
+    }
+
+    #[test]
+    fn png_color() {
+        // RGBA pixels, alpha is ignored and the colors are averaged.
+        let rows = vec![
+            vec![30, 60, 90, 255, 0, 0, 3, 0],
+            vec![255, 255, 255, 7, 1, 2, 3, 4],
+        ];
+        let png = encode_png(2, 8, 6, &rows, &[4]);
+        let slice = read_png(&mut &png[..]).unwrap();
+        assert_eq!(slice.values, vec![60.0, 1.0, 255.0, 2.0]);
+    }
+
+    #[test]
+    fn png_too_large() {
+        let rows = vec![vec![0; 8]];
+        let mut png = encode_png(1, 16, 6, &rows, &[0]);
+        // Patch the header to u32::MAX by u32::MAX pixels, the CRC isn't checked.
+        let header = PNG_SIGNATURE.len() + 8;
+        png[header..header + 8].fill(0xff);
+        let error = read_png(&mut &png[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// TIFF LZW encoder matching [decode_lzw], the table is small enough to never be cleared.
+    fn encode_lzw(data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        let (mut buffer, mut bits) = (0_u64, 0);
+        let mut write = |code: usize, width: usize| {
+            buffer = buffer << width | code as u64;
+            bits += width;
+            while bits >= 8 {
+                bits -= 8;
+                output.push((buffer >> bits) as u8);
+            }
+        };
+        let mut table = std::collections::HashMap::<Vec<u8>, usize>::new();
+        let mut next_code = 258;
+        let mut width = 9;
+        write(256, width);
+        let mut current = Vec::new();
+        for byte in data {
+            let mut extended = current.clone();
+            extended.push(*byte);
+            if extended.len() == 1 || table.contains_key(&extended) {
+                current = extended;
+                continue;
+            }
+            let code = if current.len() == 1 {
+                current[0] as usize
+            } else {
+                table[&current]
+            };
+            write(code, width);
+            table.insert(extended, next_code);
+            next_code += 1;
+            if next_code >= 1 << width {
+                width += 1;
+            }
+            current = vec![*byte];
+        }
+        if !current.is_empty() {
+            let code = if current.len() == 1 {
+                current[0] as usize
+            } else {
+                table[&current]
+            };
+            write(code, width);
+            // The decoder adds an entry for the last code before it reads the end code.
+            if next_code + 1 >= 1 << width {
+                width += 1;
+            }
+        }
+        write(257, width);
+        write(0, 7);
+        output
+    }
+
+    /// Little endian TIFF with `rows_per_strip` rows of `bytes` per strip.
+    fn encode_tiff(
+        width: u32,
+        height: u32,
+        bits: u32,
+        sample_format: u32,
+        lzw: bool,
+        rows_per_strip: u32,
+        bytes: &[u8],
+    ) -> Vec<u8> {
+        let strip_size = width as usize * rows_per_strip as usize * bits as usize / 8;
+        let strips = bytes
+            .chunks(strip_size)
+            .map(|strip| {
+                if lzw {
+                    encode_lzw(strip)
+                } else {
+                    strip.to_vec()
+                }
+            })
+            .collect::<Vec<Vec<u8>>>();
+        const ENTRIES: usize = 8;
+        let offsets_at = 8 + 2 + ENTRIES * 12 + 4;
+        let counts_at = offsets_at + strips.len() * 4;
+        // The offset and byte count of a single strip fit in its entry.
+        let single = strips.len() == 1;
+        let mut data_at = if single {
+            offsets_at
+        } else {
+            counts_at + strips.len() * 4
+        };
+
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8_u32.to_le_bytes());
+        tiff.extend_from_slice(&(ENTRIES as u16).to_le_bytes());
+        let mut entry = |tag: u16, count: usize, value: usize| {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&4_u16.to_le_bytes());
+            tiff.extend_from_slice(&(count as u32).to_le_bytes());
+            tiff.extend_from_slice(&(value as u32).to_le_bytes());
+        };
+        entry(256, 1, width as usize);
+        entry(257, 1, height as usize);
+        entry(258, 1, bits as usize);
+        entry(259, 1, if lzw { 5 } else { 1 });
+        entry(273, strips.len(), if single { data_at } else { offsets_at });
+        entry(278, 1, rows_per_strip as usize);
+        let counts = if single { strips[0].len() } else { counts_at };
+        entry(279, strips.len(), counts);
+        entry(339, 1, sample_format as usize);
+        tiff.extend_from_slice(&0_u32.to_le_bytes());
+        if !single {
+            for strip in &strips {
+                tiff.extend_from_slice(&(data_at as u32).to_le_bytes());
+                data_at += strip.len();
+            }
+            for strip in &strips {
+                tiff.extend_from_slice(&(strip.len() as u32).to_le_bytes());
+            }
+        }
+        for strip in &strips {
+            tiff.extend_from_slice(strip);
+        }
+        tiff
+    }
+
+    #[test]
+    fn tiff_lzw_round_trip() {
+        let data = (0..3000_u32)
+            .map(|index| (index * index / 7 % 13 + index / 300) as u8)
+            .collect::<Vec<u8>>();
+        assert_eq!(decode_lzw(&encode_lzw(&data)).unwrap(), data);
+        assert_eq!(decode_lzw(&encode_lzw(&[])).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn tiff_8_bit() {
+        let (width, height) = (6, 5);
+        let values = gray_values(width, height, 255);
+        let bytes = values.iter().map(|value| *value as u8).collect::<Vec<u8>>();
+        for (lzw, rows_per_strip) in [(false, 5), (false, 2), (true, 5), (true, 2)] {
+            let tiff = encode_tiff(6, 5, 8, 1, lzw, rows_per_strip, &bytes);
+            let slice = read_tiff(&tiff).unwrap();
+            assert_eq!((slice.width, slice.height), (width, height));
+            let expected = values
+                .iter()
+                .map(|value| *value as f32)
+                .collect::<Vec<f32>>();
+            assert_eq!(
+                slice.values, expected,
+                "lzw {lzw}, {rows_per_strip} rows per strip"
+            );
+        }
+    }
+
+    #[test]
+    fn tiff_16_bit_and_float() {
+        let values = gray_values(4, 4, 65535);
+        let bytes = values
+            .iter()
+            .flat_map(|value| (*value as u16).to_le_bytes())
+            .collect::<Vec<u8>>();
+        let slice = read_tiff(&encode_tiff(4, 4, 16, 1, true, 3, &bytes)).unwrap();
+        let expected = values
+            .iter()
+            .map(|value| *value as f32)
+            .collect::<Vec<f32>>();
+        assert_eq!(slice.values, expected);
+
+        let floats = [0.5_f32, -1.25, 1e-3, 7.0];
+        let bytes = floats
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let slice = read_tiff(&encode_tiff(2, 2, 32, 3, true, 2, &bytes)).unwrap();
+        assert_eq!(slice.values, floats);
+    }
+
+    #[test]
+    fn tiff_too_large() {
+        // A short strip of an image claiming to be huge is rejected without allocating it.
+        let tiff = encode_tiff(1 << 20, 1 << 20, 8, 1, false, 1 << 20, &[0; 16]);
+        let error = read_tiff(&tiff).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let tiff = encode_tiff(u32::MAX, u32::MAX, 32, 1, false, 1, &[0; 16]);
+        let error = read_tiff(&tiff).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,333 @@
+//! Decompression of deflate streams, used to read compressed images and volumes.
+
+use std::io;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Bits of a deflate stream, least significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: usize) -> io::Result<usize> {
+        let mut value = 0;
+        for index in 0..count {
+            let byte = *self
+                .data
+                .get(self.bit / 8)
+                .ok_or_else(|| invalid_data("unexpected end of deflate data"))?;
+            value |= (((byte >> (self.bit % 8)) & 1) as usize) << index;
+            self.bit += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// Canonical Huffman code, decoded one bit at a time.
+struct Huffman {
+    /// Number of codes of each length.
+    counts: [usize; 16],
+    /// Symbols ordered by code.
+    symbols: Vec<usize>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize]] = symbol;
+                offsets[*length as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> io::Result<usize> {
+        // First code and first symbol index of the current length.
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for count in &self.counts[1..] {
+            code |= reader.bits(1)?;
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid_data("invalid deflate code"))
+    }
+}
+
+const LENGTH_BASES: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [usize; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [usize; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which the lengths of the code length code are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompress a zlib stream (RFC 1950), as used by PNG and Deflate compressed TIFF.
+pub(crate) fn inflate_zlib(data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() < 2 || data[0] & 0x0f != 8 || data[1] & 0x20 != 0 {
+        return Err(invalid_data("unsupported zlib stream"));
+    }
+    inflate(&data[2..])
+}
+
+/// Decompress the first member of a gzip file (RFC 1952).
+pub(crate) fn inflate_gzip(data: &[u8]) -> io::Result<Vec<u8>> {
+    const EXTRA: u8 = 4;
+    const NAME: u8 = 8;
+    const COMMENT: u8 = 16;
+    const HEADER_CRC: u8 = 2;
+    if data.len() < 10 || data[0..3] != [0x1f, 0x8b, 8] {
+        return Err(invalid_data("not a gzip stream"));
+    }
+    let flags = data[3];
+    let mut offset = 10;
+    if flags & EXTRA != 0 {
+        let length = data
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+            .ok_or_else(|| invalid_data("unexpected end of gzip header"))?;
+        offset += 2 + length;
+    }
+    // File name and comment are zero terminated.
+    for flag in [NAME, COMMENT] {
+        if flags & flag != 0 {
+            while data.get(offset).is_some_and(|byte| *byte != 0) {
+                offset += 1;
+            }
+            offset += 1;
+        }
+    }
+    if flags & HEADER_CRC != 0 {
+        offset += 2;
+    }
+    inflate(
+        data.get(offset..)
+            .ok_or_else(|| invalid_data("unexpected end of gzip header"))?,
+    )
+}
+
+/// Decompress raw deflate data (RFC 1951).
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut reader = BitReader { data, bit: 0 };
+    let mut output = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                // Stored block: byte aligned length, its complement and the bytes.
+                let start = reader.bit.div_ceil(8);
+                let header = data
+                    .get(start..start + 4)
+                    .ok_or_else(|| invalid_data("unexpected end of deflate data"))?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let bytes = data
+                    .get(start + 4..start + 4 + length)
+                    .ok_or_else(|| invalid_data("unexpected end of deflate data"))?;
+                output.extend_from_slice(bytes);
+                reader.bit = (start + 4 + length) * 8;
+            }
+            1 => {
+                let mut lengths = [8_u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                inflate_block(&mut reader, &literals, &distances, &mut output)?;
+            }
+            2 => {
+                let literal_count = reader.bits(5)? + 257;
+                let distance_count = reader.bits(5)? + 1;
+                let code_length_count = reader.bits(4)? + 4;
+                let mut code_lengths = [0_u8; 19];
+                for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+                    code_lengths[*index] = reader.bits(3)? as u8;
+                }
+                let code_length_code = Huffman::new(&code_lengths);
+                let mut lengths = Vec::with_capacity(literal_count + distance_count);
+                while lengths.len() < literal_count + distance_count {
+                    let (value, repeat) = match code_length_code.decode(&mut reader)? {
+                        symbol @ 0..=15 => (symbol as u8, 1),
+                        16 => {
+                            let previous = *lengths
+                                .last()
+                                .ok_or_else(|| invalid_data("invalid deflate code lengths"))?;
+                            (previous, 3 + reader.bits(2)?)
+                        }
+                        17 => (0, 3 + reader.bits(3)?),
+                        _ => (0, 11 + reader.bits(7)?),
+                    };
+                    lengths.extend(std::iter::repeat_n(value, repeat));
+                }
+                if lengths.len() > literal_count + distance_count {
+                    return Err(invalid_data("invalid deflate code lengths"));
+                }
+                let literals = Huffman::new(&lengths[..literal_count]);
+                let distances = Huffman::new(&lengths[literal_count..]);
+                inflate_block(&mut reader, &literals, &distances, &mut output)?;
+            }
+            _ => return Err(invalid_data("invalid deflate block")),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Decode the symbols of a compressed block up to its end of block symbol.
+fn inflate_block(
+    reader: &mut BitReader,
+    literals: &Huffman,
+    distances: &Huffman,
+    output: &mut Vec<u8>,
+) -> io::Result<()> {
+    loop {
+        let symbol = literals.decode(reader)?;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASES.len() {
+                    return Err(invalid_data("invalid deflate length"));
+                }
+                let length = LENGTH_BASES[index] + reader.bits(LENGTH_EXTRA_BITS[index])?;
+                let index = distances.decode(reader)?;
+                if index >= DISTANCE_BASES.len() {
+                    return Err(invalid_data("invalid deflate distance"));
+                }
+                let distance = DISTANCE_BASES[index] + reader.bits(DISTANCE_EXTRA_BITS[index])?;
+                if distance > output.len() {
+                    return Err(invalid_data("invalid deflate distance"));
+                }
+                // Copies may overlap their own output, so copy byte by byte.
+                let start = output.len() - distance;
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Zlib stream of stored blocks of at most `block_size` bytes.
+    pub(crate) fn zlib_stored(data: &[u8], block_size: usize) -> Vec<u8> {
+        let mut stream = vec![0x78, 0x01];
+        let blocks = data.chunks(block_size.max(1)).collect::<Vec<_>>();
+        for (index, block) in blocks.iter().enumerate() {
+            stream.push((index + 1 == blocks.len()) as u8);
+            let length = block.len() as u16;
+            stream.extend_from_slice(&length.to_le_bytes());
+            stream.extend_from_slice(&(!length).to_le_bytes());
+            stream.extend_from_slice(block);
+        }
+        if blocks.is_empty() {
+            stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+        }
+        let (mut a, mut b) = (1_u32, 0_u32);
+        for byte in data {
+            a = (a + *byte as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        stream.extend_from_slice(&(b << 16 | a).to_be_bytes());
+        stream
+    }
+
+    #[test]
+    fn stored_blocks() {
+        let data = (0..1000)
+            .map(|index| (index * 7 % 251) as u8)
+            .collect::<Vec<u8>>();
+        for block_size in [1, 100, 1000] {
+            assert_eq!(inflate_zlib(&zlib_stored(&data, block_size)).unwrap(), data);
+        }
+        assert_eq!(
+            inflate_zlib(&zlib_stored(&[], 1)).unwrap(),
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn fixed_block() {
+        // zlib.compress(b"marching cubes", 9)
+        let stream = [
+            0x78, 0xda, 0xcb, 0x4d, 0x2c, 0x4a, 0xce, 0xc8, 0xcc, 0x4b, 0x57, 0x48, 0x2e, 0x4d,
+            0x4a, 0x2d, 0x06, 0x00, 0x29, 0x72, 0x05, 0x7c,
+        ];
+        assert_eq!(inflate_zlib(&stream).unwrap(), b"marching cubes");
+    }
+
+    #[test]
+    fn dynamic_block() {
+        let text = (0..200)
+            .map(|index| format!("{} ", index * index % 97))
+            .collect::<String>();
+        // zlib.compress(text, 9), a single dynamic block with back references.
+        let stream = [
+            0x78, 0xda, 0xed, 0x90, 0x09, 0x8d, 0x45, 0x21, 0x0c, 0x45, 0xad, 0x1c, 0x09, 0xaf,
+            0x2d, 0x14, 0xf0, 0x6f, 0x6c, 0x0e, 0x18, 0xf8, 0x06, 0x26, 0x21, 0x84, 0xe5, 0xae,
+            0xfd, 0x08, 0x06, 0x87, 0x68, 0x72, 0x52, 0xcd, 0x38, 0xf4, 0x60, 0x07, 0x45, 0x0e,
+            0xc6, 0x62, 0x25, 0x49, 0x05, 0x9d, 0x1c, 0x11, 0xc5, 0xfa, 0x88, 0x64, 0x16, 0x47,
+            0xb4, 0x5c, 0x05, 0x3c, 0x0f, 0xe6, 0xc7, 0xa6, 0x27, 0xb9, 0xd8, 0x9b, 0x39, 0xc8,
+            0x64, 0xab, 0xa6, 0xd4, 0x24, 0x82, 0xdd, 0xb4, 0x8c, 0x4d, 0x25, 0x21, 0x92, 0x53,
+            0xec, 0xc9, 0x3a, 0x2c, 0xf7, 0x7a, 0xeb, 0x5d, 0x7d, 0xf4, 0xab, 0x2f, 0x48, 0xa8,
+            0x04, 0x69, 0x92, 0xe3, 0x09, 0x29, 0xa7, 0xa8, 0xd2, 0x1a, 0x68, 0xa3, 0x99, 0x96,
+            0xfb, 0x9a, 0x1b, 0xe1, 0x06, 0x89, 0x17, 0xaa, 0x6f, 0x40, 0x63, 0x1a, 0xd6, 0xc8,
+            0x06, 0xef, 0x57, 0x22, 0x6f, 0x1d, 0x4b, 0x59, 0xad, 0x6e, 0x49, 0xab, 0x5a, 0xb8,
+            0x5e, 0x79, 0x47, 0x70, 0x9c, 0x45, 0xf0, 0xfd, 0xcf, 0xe4, 0xc7, 0x4c, 0xfe, 0x00,
+            0x40, 0x31, 0x64, 0x4d,
+        ];
+        assert_eq!((stream[2] >> 1) & 3, 2);
+        assert_eq!(inflate_zlib(&stream).unwrap(), text.as_bytes());
+    }
+
+    #[test]
+    fn truncated_stream() {
+        let stream = zlib_stored(b"marching cubes", 100);
+        for length in 0..stream.len() - 4 {
+            let error = inflate_zlib(&stream[..length]).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn gzip_header() {
+        let mut stream = vec![0x1f, 0x8b, 8, 8, 0, 0, 0, 0, 0, 3];
+        stream.extend_from_slice(b"cubes.raw\0");
+        stream.extend_from_slice(&zlib_stored(b"marching cubes", 100)[2..]);
+        assert_eq!(inflate_gzip(&stream).unwrap(), b"marching cubes");
+    }
+}
@@ -0,0 +1,736 @@
+//! Extract iso surfaces from scalar fields using marching tetrahedra.
+//!
+//! A [Domain] describes the region and resolution to sample. Calling
+//! [Domain::march_tetrahedras] with a [ScalarField] and a [RefineStrategy] adds a [Mesh] of the
+//! surface where the weight crosses `Domain::surface_weight`. Positions with a weight above the
+//! surface weight are inside.
+//!
+//! ```no_run
+//! use marching_cubes::prelude::*;
+//!
+//! let mut domain = Domain {
+//!     from: Vec3 { x: -4.0, y: -4.0, z: -4.0 },
+//!     to: Vec3 { x: 4.0, y: 4.0, z: 4.0 },
+//!     surface_weight: 1.0,
+//!     width: 32,
+//!     height: 32,
+//!     depth: 32,
+//!     grid_offset: Vec3::default(),
+//!     meshes: Vec::default(),
+//! };
+//! let metaballs = Metaballs::new(vec![Force {
+//!     position: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+//!     force: 2.0,
+//! }]);
+//! domain.march_tetrahedras(&metaballs, &Bisection);
+//! domain.write_bpy(&mut std::io::stdout(), "Sphere").unwrap();
+//! ```
+//!
+//! # Modules
+//!
+//! - Core: [Domain], [Mesh], the math types and [RefineStrategy] live at the root.
+//! - Fields: [field] contains [ScalarField] implementations and modifiers, [voxel], [sparse]
+//!   and [grid] hold sampled volumes, [image_stack] and [volume_header] load them from PNG and
+//!   TIFF slices and from NRRD and MHD files, with the `dicom` feature `dicom` assembles CT and MRI series.
+//!   [heightmap] turns 2D elevation data into terrain.
+//! - Extraction: [extraction] selects between [Domain::march_tetrahedras], [bcc], [cubes],
+//!   [surface_nets], [dual_contouring], [dual] and [blocky], [algorithm] is the extension point
+//!   for new algorithms. [narrow_band] ignores the field away from the surface, [partition]
+//!   splits huge extractions into work items for other processes. [tetrahedral] marches
+//!   unstructured tetrahedral meshes of simulations.
+//! - Mesh processing: [weld], [components], [smooth], [decimate], [degenerate], [sharp_edges],
+//!   [normals], [tangent], [skinning] and [slice](mod@slice). [validate] checks that a mesh is
+//!   watertight, [measure] computes its area and volume, [lod] maps between levels of detail.
+//! - Export: [export] writes meshes to files, [scene] reads the setup of the binary.
+//! - Configuration: [config] bundles the options above into presets for common workflows.
+//!
+//! [prelude] re-exports the types most programs need. APIs that may still change between minor
+//! versions are only available with the `unstable` feature.
+
+use std::ops::{Add, ControlFlow, Mul, Sub};
+
+pub mod adaptive;
+#[cfg(feature = "unstable")]
+pub mod advection;
+pub mod algorithm;
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod bcc;
+pub mod blocky;
+mod builder;
+pub mod components;
+pub mod config;
+#[cfg(feature = "unstable")]
+pub mod coplanar;
+pub mod cubes;
+pub mod decimate;
+pub mod degenerate;
+#[cfg(feature = "dicom")]
+pub mod dicom;
+pub mod dual;
+pub mod dual_contouring;
+pub mod export;
+pub mod extraction;
+pub mod field;
+pub mod grid;
+pub mod heightmap;
+pub mod hooks;
+pub mod image_stack;
+mod inflate;
+pub mod limits;
+pub mod lod;
+pub mod measure;
+pub mod narrow_band;
+pub mod normals;
+pub mod occupancy;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod partition;
+pub mod prelude;
+pub mod progress;
+mod refine;
+#[cfg(feature = "unstable")]
+pub mod region;
+pub mod result;
+pub mod scene;
+pub mod sharp_edges;
+pub mod skinning;
+pub mod slice;
+pub mod smooth;
+pub mod sparse;
+pub mod surface_nets;
+mod tables;
+pub mod tangent;
+#[cfg(feature = "unstable")]
+pub mod temporal;
+pub mod tetrahedral;
+pub mod validate;
+pub mod volume_header;
+pub mod voxel;
+pub mod voxel_file;
+pub mod weld;
+
+use algorithm::{Cell, ExtractionAlgorithm, MarchingTetrahedra, MeshSink};
+use builder::MeshBuilder;
+pub use field::ScalarField;
+pub use refine::{
+    Bisection, BisectionWithTolerance, LinearInterp, Midpoint, Newton, RefineStrategy, WeightInterp,
+};
+use result::MarchResult;
+
+/// Floating point type used for positions and weights throughout the crate. Enable the `f32`
+/// feature to run the whole pipeline in single precision.
+#[cfg(not(feature = "f32"))]
+pub type Float = f64;
+/// Floating point type used for positions and weights throughout the crate. Enable the `f32`
+/// feature to run the whole pipeline in single precision.
+#[cfg(feature = "f32")]
+pub type Float = f32;
+
+/// Mathematical constants of [Float].
+pub mod consts {
+    #[cfg(feature = "f32")]
+    pub use std::f32::consts::*;
+    #[cfg(not(feature = "f32"))]
+    pub use std::f64::consts::*;
+}
+
+/// Tetrahedra has 4 verts and 4 faces. The first vert is considered the top, the others part of the bottom.
+///
+/// Map each tetrahedra vertex masks to the edges that will be based for the faces.
+/// Although there are 16 possible vert maps, the last 8 are the inverse of the first 8 so we only need to store 8 of them.
+/// When using the inverse the edge2 and edge3 should be inversed as well to ensure correct "normals".
+///
+/// The table is generated from the crossing edges of each mask, see [tables].
+pub const TETRADEDRA_VERTMASK_TO_EDGES: [[isize; 6]; 8] = tables::FIVE_TETRAHEDRA_VERTMASK_TO_EDGES;
+
+/// Ordering of verts inside a grid block
+pub const GRID_TO_VERT_OFFSETS: [IVec3; 8] = [
+    IVec3 { x: 0, y: 0, z: 0 },
+    IVec3 { x: 1, y: 0, z: 0 },
+    IVec3 { x: 1, y: 1, z: 0 },
+    IVec3 { x: 0, y: 1, z: 0 },
+    IVec3 { x: 0, y: 0, z: 1 },
+    IVec3 { x: 1, y: 0, z: 1 },
+    IVec3 { x: 1, y: 1, z: 1 },
+    IVec3 { x: 0, y: 1, z: 1 },
+];
+/// Split a grid into 5 tetrahedras.
+pub const GRID_TO_TETRAHEDRA_VERTICES: [[usize; 4]; 5] = [
+    [0, 2, 7, 5],
+    [1, 0, 5, 2],
+    [3, 2, 7, 0],
+    [4, 0, 7, 5],
+    [6, 2, 5, 7],
+];
+/// Split a grid into 6 tetrahedras around the diagonal from the lowest to the highest corner.
+/// Unlike [GRID_TO_TETRAHEDRA_VERTICES] every cell is split the same way, see
+/// [algorithm::Decomposition::Six].
+pub const GRID_TO_SIX_TETRAHEDRA_VERTICES: [[usize; 4]; 6] = [
+    [0, 1, 2, 6],
+    [0, 5, 1, 6],
+    [0, 2, 3, 6],
+    [0, 3, 7, 6],
+    [0, 4, 5, 6],
+    [0, 7, 4, 6],
+];
+pub const TETRAHEDRA_EDGES_TO_VERT_OFFSETS: [[usize; 2]; 6] =
+    [[0, 1], [0, 2], [0, 3], [1, 2], [2, 3], [3, 1]];
+/// Corners of the 12 edges of a cell, in [GRID_TO_VERT_OFFSETS] order.
+pub const CUBE_EDGES_TO_VERT_OFFSETS: [[usize; 2]; 12] = [
+    [0, 1],
+    [1, 2],
+    [2, 3],
+    [3, 0],
+    [4, 5],
+    [5, 6],
+    [6, 7],
+    [7, 4],
+    [0, 4],
+    [1, 5],
+    [2, 6],
+    [3, 7],
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IVec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Add<IVec3> for IVec3 {
+    type Output = IVec3;
+
+    fn add(self, rhs: IVec3) -> Self::Output {
+        IVec3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Vec3 {
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
+}
+
+impl Vec3 {
+    pub fn dot(self, rhs: Vec3) -> Float {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(self, rhs: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
+
+    pub fn length(self) -> Float {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalized(self) -> Vec3 {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+
+    pub fn lerp(self, rhs: Vec3, factor: Float) -> Vec3 {
+        self + (rhs - self) * factor
+    }
+}
+
+impl Add<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, rhs: Vec3) -> Self::Output {
+        Vec3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl Sub<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, rhs: Vec3) -> Self::Output {
+        Vec3 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl Mul<Float> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Float) -> Self::Output {
+        Vec3 {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+
+/// Identifies the lattice edge a vertex was generated on, using global lattice coordinates
+/// (see `Domain::lattice_offset`). Vertices with the same key are at the same location, also
+/// when they were generated by different domains.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeKey {
+    pub a: IVec3,
+    pub b: IVec3,
+}
+
+impl EdgeKey {
+    pub fn new(a: IVec3, b: IVec3) -> EdgeKey {
+        if a <= b {
+            EdgeKey { a, b }
+        } else {
+            EdgeKey { a: b, b: a }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Face {
+    pub v1: usize,
+    pub v2: usize,
+    pub v3: usize,
+}
+#[derive(Debug)]
+pub struct Edge {
+    pub v1: usize,
+    pub v2: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Mesh {
+    pub verts: Vec<Vec3>,
+    pub faces: Vec<Face>,
+    pub edges: Vec<Edge>,
+
+    /// Per vertex the lattice edge it was generated on, see [EdgeKey].
+    pub vert_keys: Vec<EdgeKey>,
+
+    /// Per vertex unit normals pointing outwards, empty until
+    /// [Mesh::compute_gradient_normals] or [Mesh::compute_vertex_normals] is called. Exporters
+    /// write them when present.
+    pub normals: Vec<Vec3>,
+    /// Per vertex texture coordinates, empty until [Mesh::compute_tangents] is called.
+    pub uvs: Vec<[Float; 2]>,
+    /// Per vertex tangents in glTF `TANGENT` layout: xyz is the tangent, w is the sign of the
+    /// bitangent (`bitangent = cross(normal, tangent) * w`).
+    pub tangents: Vec<[Float; 4]>,
+    /// Per vertex indices of the 4 bones with the most influence, empty until
+    /// [Mesh::compute_skin_weights] is called. Unused slots are 0 with a weight of 0.0.
+    pub joints: Vec<[u16; 4]>,
+    /// Per vertex weights of [Mesh::joints], they sum up to 1.0.
+    pub joint_weights: Vec<[Float; 4]>,
+}
+
+impl Mesh {
+    /// Axis aligned bounding box of the vertices as minimum and maximum corner, `None` when the
+    /// mesh has no vertices.
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let first = *self.verts.first()?;
+        Some(self.verts.iter().fold((first, first), |(min, max), vert| {
+            (
+                Vec3 {
+                    x: min.x.min(vert.x),
+                    y: min.y.min(vert.y),
+                    z: min.z.min(vert.z),
+                },
+                Vec3 {
+                    x: max.x.max(vert.x),
+                    y: max.y.max(vert.y),
+                    z: max.z.max(vert.z),
+                },
+            )
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct Domain {
+    pub from: Vec3,
+    pub to: Vec3,
+
+    pub surface_weight: Float,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    /// Shift of the lattice in cells. Lattice vertices are placed at
+    /// `from + (index - grid_offset) * cell_size`, so offsets between 0.0 and 1.0 move the
+    /// lattice without uncovering any part of the bounds. Useful for jittering the extraction
+    /// over time or aligning with an external grid.
+    pub grid_offset: Vec3,
+
+    pub meshes: Vec<Mesh>,
+}
+
+impl Domain {
+    /// Create an empty domain with the same bounds, surface weight and grid offset but a different
+    /// resolution.
+    pub fn with_resolution(&self, width: usize, height: usize, depth: usize) -> Domain {
+        Domain {
+            from: self.from,
+            to: self.to,
+            surface_weight: self.surface_weight,
+            width,
+            height,
+            depth,
+            grid_offset: self.grid_offset,
+            meshes: Vec::default(),
+        }
+    }
+
+    /// Position of `from` on the global lattice.
+    ///
+    /// The global lattice has its origin at (0, 0, 0) and uses the cell size of this domain.
+    /// Domains with the same cell size whose `from` lies on a lattice point share the same
+    /// lattice, their meshes can be welded using [EdgeKey]s.
+    ///
+    /// Panics when the domain is too large for the lattice, see [Domain::check_size].
+    pub fn lattice_offset(&self) -> IVec3 {
+        self.lattice_offset_checked()
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Number of cells marched along each axis, one more than the resolution.
+    ///
+    /// Panics when the domain is too large for the lattice, see [Domain::check_size].
+    pub fn vertex_grid_size(&self) -> IVec3 {
+        self.vertex_grid_size_checked(self.lattice_offset())
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Whether a lattice vertex with the given weight is inside the surface.
+    ///
+    /// This is the only predicate used to classify lattice vertices, and each marcher samples a
+    /// lattice vertex at the position returned by [Domain::vertex_position]. The comparison is
+    /// exact, so cells sharing a vertex always agree on its side and the topology doesn't depend
+    /// on rounding. A weight equal to the surface weight is outside, as is NaN.
+    pub fn is_inside(&self, weight: Float) -> bool {
+        weight > self.surface_weight
+    }
+
+    pub fn vertex_position(&self, vertex_grid_position: IVec3) -> Vec3 {
+        Vec3 {
+            x: self.from.x
+                + (vertex_grid_position.x as Float - self.grid_offset.x)
+                    * (self.to.x - self.from.x)
+                    / self.width as Float,
+            y: self.from.y
+                + (vertex_grid_position.y as Float - self.grid_offset.y)
+                    * (self.to.y - self.from.y)
+                    / self.height as Float,
+            z: self.from.z
+                + (vertex_grid_position.z as Float - self.grid_offset.z)
+                    * (self.to.z - self.from.z)
+                    / self.depth as Float,
+        }
+    }
+}
+
+pub fn get_vert_offsets(cell_pos: IVec3) -> ([IVec3; 8], bool) {
+    let flip_x = cell_pos.x.abs() & 1 != 0;
+    let flip_y = cell_pos.y.abs() & 1 != 0;
+    let flip_z = cell_pos.z.abs() & 1 != 0;
+    let grid_inverse = [flip_x, flip_y, flip_z].iter().filter(|v| **v).count() & 1 != 0;
+
+    let mut result = GRID_TO_VERT_OFFSETS;
+
+    for offset in &mut result {
+        if flip_x {
+            offset.x = 1 - offset.x;
+        }
+        if flip_y {
+            offset.y = 1 - offset.y;
+        }
+        if flip_z {
+            offset.z = 1 - offset.z;
+        }
+    }
+    (result, grid_inverse)
+}
+
+impl Domain {
+    /// Extract the surface of the field and add it as a new mesh to `meshes`.
+    ///
+    /// Each cell of the domain is split into 5 tetrahedra. Vertices are placed on the edges of
+    /// the tetrahedra that cross the surface, their position is determined by `refine`. The
+    /// field is sampled once per lattice vertex, only `refine` samples in between.
+    pub fn march_tetrahedras<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let mut mesh = MeshBuilder::default();
+        let origin = IVec3 { x: 0, y: 0, z: 0 };
+        let (min_weight, max_weight) =
+            self.march_cells(&mut mesh, origin, self.vertex_grid_size(), field, refine);
+        let mesh = mesh.finish();
+        let result = MarchResult::new(&mesh, min_weight, max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+
+    /// March the cells from `from` up to but not including `to` in lattice order. Returns the
+    /// minimum and maximum sampled weight.
+    pub(crate) fn march_cells<FIELD, REFINE>(
+        &self,
+        mesh: &mut MeshBuilder,
+        from: IVec3,
+        to: IVec3,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> (Float, Float)
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        self.march_cells_with(mesh, from, to, field, refine, &mut |_| {})
+    }
+
+    /// Same as [Domain::march_cells], calling `after_plane` with the mesh so far after each plane
+    /// of cells with the same x.
+    pub(crate) fn march_cells_with<FIELD, REFINE>(
+        &self,
+        mesh: &mut MeshBuilder,
+        from: IVec3,
+        to: IVec3,
+        field: &FIELD,
+        refine: &REFINE,
+        after_plane: &mut dyn FnMut(&MeshBuilder),
+    ) -> (Float, Float)
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        self.march_cells_using(
+            mesh,
+            from,
+            to,
+            &MarchingTetrahedra,
+            field,
+            refine,
+            &mut |mesh, plane_done| {
+                if plane_done {
+                    after_plane(mesh);
+                }
+                ControlFlow::Continue(())
+            },
+        )
+    }
+
+    /// Same as [Domain::march_cells], polygonizing the cells with `algorithm`. `after_row` is
+    /// called with the mesh so far after each row of cells with the same x and y, and whether
+    /// that row completed a plane. Marching stops early when it returns [ControlFlow::Break].
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn march_cells_using<ALGORITHM, FIELD, REFINE>(
+        &self,
+        mesh: &mut MeshBuilder,
+        from: IVec3,
+        to: IVec3,
+        algorithm: &ALGORITHM,
+        field: &FIELD,
+        refine: &REFINE,
+        after_row: &mut dyn FnMut(&MeshBuilder, bool) -> ControlFlow<()>,
+    ) -> (Float, Float)
+    where
+        ALGORITHM: ExtractionAlgorithm,
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let mut min_weight = Float::INFINITY;
+        let mut max_weight = Float::NEG_INFINITY;
+        // Weights of a plane of lattice vertices with the same x. Only the planes on both sides
+        // of the current cells are kept, so every vertex is sampled once.
+        let mut sample_plane = |x: i32| {
+            let mut weights = Vec::new();
+            for y in from.y..=to.y {
+                for z in from.z..=to.z {
+                    let weight = field.sample(self.vertex_position(IVec3 { x, y, z }));
+                    min_weight = min_weight.min(weight);
+                    max_weight = max_weight.max(weight);
+                    weights.push(weight);
+                }
+            }
+            weights
+        };
+        let mut next_plane = sample_plane(from.x);
+        for x in from.x..to.x {
+            let plane = std::mem::replace(&mut next_plane, sample_plane(x + 1));
+            for y in from.y..to.y {
+                for z in from.z..to.z {
+                    let cell_pos = IVec3 { x, y, z };
+                    let mut cell = Cell {
+                        position: cell_pos,
+                        lattice_position: cell_pos + self.lattice_offset(),
+                        corner_positions: [Vec3::default(); 8],
+                        corner_weights: [0.0; 8],
+                    };
+                    for (corner, offset) in GRID_TO_VERT_OFFSETS.iter().enumerate() {
+                        let weights = if offset.x == 0 { &plane } else { &next_plane };
+                        cell.corner_weights[corner] =
+                            weights[((y + offset.y - from.y) * (to.z - from.z + 1) + z + offset.z
+                                - from.z) as usize];
+                        cell.corner_positions[corner] = self.vertex_position(cell_pos + *offset);
+                    }
+                    algorithm.polygonize(
+                        self,
+                        &cell,
+                        field,
+                        refine,
+                        &mut MeshSink { builder: mesh },
+                    );
+                }
+                if after_row(mesh, y + 1 == to.y).is_break() {
+                    return (min_weight, max_weight);
+                }
+            }
+        }
+        (min_weight, max_weight)
+    }
+
+    /// Generate the faces of a single cell. Vertices on lattice edges that were already used by
+    /// a neighboring tetrahedron are reused.
+    ///
+    /// `vert_positions` and `vert_weights` are ordered by the vertex offsets returned by
+    /// `get_vert_offsets` for the lattice position of this cell.
+    fn polygonize_cell<FIELD, REFINE>(
+        &self,
+        mesh: &mut MeshBuilder,
+        vert_positions: &[Vec3],
+        vert_weights: &[Float],
+        cell_pos: IVec3,
+        field: &FIELD,
+        refine: &REFINE,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        self.polygonize_tetrahedra(
+            mesh,
+            vert_positions,
+            vert_weights,
+            cell_pos,
+            field,
+            refine,
+            &mut |mesh, _, [v1, v2, v3]| mesh.face(v1, v2, v3),
+        );
+    }
+
+    /// Same as [Domain::polygonize_cell], passing each face to `face` together with the index
+    /// of the tetrahedron in [GRID_TO_TETRAHEDRA_VERTICES] instead of adding it.
+    #[allow(clippy::too_many_arguments)]
+    fn polygonize_tetrahedra<FIELD, REFINE, FACE>(
+        &self,
+        mesh: &mut MeshBuilder,
+        vert_positions: &[Vec3],
+        vert_weights: &[Float],
+        cell_pos: IVec3,
+        field: &FIELD,
+        refine: &REFINE,
+        face: &mut FACE,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+        FACE: FnMut(&mut MeshBuilder, usize, [usize; 3]),
+    {
+        let lattice_cell = cell_pos + self.lattice_offset();
+        let (grid_to_verts_offsets, grid_inverse) = get_vert_offsets(lattice_cell);
+        let mut vert_is_inside = [false; 8];
+        for (is_inside, weight) in vert_is_inside.iter_mut().zip(vert_weights) {
+            *is_inside = self.is_inside(*weight);
+        }
+        tetrahedra_faces(
+            &vert_is_inside,
+            grid_inverse,
+            &GRID_TO_TETRAHEDRA_VERTICES,
+            &mut |tetrahedron, edges, flip| {
+                let verts = edges.map(|[vert_1, vert_2]| {
+                    let key = EdgeKey::new(
+                        lattice_cell + grid_to_verts_offsets[vert_1],
+                        lattice_cell + grid_to_verts_offsets[vert_2],
+                    );
+                    mesh.vertex(key, || {
+                        refine.refine_with_weights(
+                            vert_positions[vert_1],
+                            vert_weights[vert_1],
+                            vert_positions[vert_2],
+                            vert_weights[vert_2],
+                            field,
+                            self.surface_weight,
+                        )
+                    })
+                });
+                if flip {
+                    face(mesh, tetrahedron, [verts[0], verts[2], verts[1]]);
+                } else {
+                    face(mesh, tetrahedron, verts);
+                }
+            },
+        );
+    }
+}
+
+/// Call `face` for every face of the surface in the `tetrahedra` of a cell, with the index of the
+/// tetrahedron, the vertices of the edges its corners lie on and whether the corners have to be
+/// swapped to face outwards.
+///
+/// `vert_is_inside` and the vertices passed to `face` are ordered by the vertex offsets returned
+/// by [get_vert_offsets], `grid_inverse` is its second result. Decompositions that don't mirror
+/// cells use [GRID_TO_VERT_OFFSETS] and pass `false`.
+fn tetrahedra_faces<FACE>(
+    vert_is_inside: &[bool],
+    grid_inverse: bool,
+    tetrahedra: &[[usize; 4]],
+    face: &mut FACE,
+) where
+    FACE: FnMut(usize, [[usize; 2]; 3], bool),
+{
+    for (tetrahedron, tetrahedron_indices) in tetrahedra.iter().enumerate() {
+        // determine vert mask + inverse
+        let mut mask = 0;
+        for index in 0..tetrahedron_indices.len() {
+            let index_mask = 1 << index;
+            if vert_is_inside[tetrahedron_indices[index]] {
+                mask |= index_mask;
+            }
+        }
+        let compressed_mask = if mask > 7 { 15 - mask } else { mask } as usize;
+        let inversed_mask = (mask > 7) != grid_inverse;
+        for face_index in 0..2 {
+            let e1 = TETRADEDRA_VERTMASK_TO_EDGES[compressed_mask][face_index * 3];
+            let e2 = TETRADEDRA_VERTMASK_TO_EDGES[compressed_mask][face_index * 3 + 1];
+            let e3 = TETRADEDRA_VERTMASK_TO_EDGES[compressed_mask][face_index * 3 + 2];
+            if e1 == -1 {
+                // No faces left to add for this tetrahedra.
+                break;
+            }
+            let edges = [e1, e2, e3].map(|edge_index| {
+                TETRAHEDRA_EDGES_TO_VERT_OFFSETS[edge_index as usize]
+                    .map(|vert_offs| tetrahedron_indices[vert_offs])
+            });
+            face(tetrahedron, edges, inversed_mask);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    error::Error,
+    fmt,
+    mem::size_of,
+    ops::ControlFlow,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Domain, Float, IVec3, RefineStrategy, ScalarField, algorithm::MarchingTetrahedra,
+    builder::MeshBuilder, result::MarchResult,
+};
+
+/// Reason why a domain is too large to march.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SizeError {
+    /// The lattice coordinates along an axis don't fit in `i32`. `cells` is the resolution along
+    /// the axis and `offset` the position of the domain on the global lattice.
+    LatticeOverflow {
+        axis: char,
+        cells: usize,
+        offset: i64,
+    },
+    /// The number of lattice vertices doesn't fit in `usize`.
+    SampleCountOverflow,
+    /// The root nodes of [Domain::march_adaptive] are deeper than [MAX_OCTREE_DEPTH].
+    OctreeDepth { depth: u32 },
+}
+
+/// Deepest octree [Domain::march_adaptive] accepts, its nodes are addressed in half cells.
+pub const MAX_OCTREE_DEPTH: u32 = 29;
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeError::LatticeOverflow {
+                axis,
+                cells,
+                offset,
+            } => write!(
+                f,
+                "domain is too large: {cells} cells along {axis} at lattice offset {offset} exceed the i32 lattice coordinates, use a lower resolution or move the bounds towards the origin"
+            ),
+            SizeError::SampleCountOverflow => write!(
+                f,
+                "domain is too large: the number of lattice vertices doesn't fit in usize"
+            ),
+            SizeError::OctreeDepth { depth } => write!(
+                f,
+                "octree depth {depth} exceeds the maximum of {MAX_OCTREE_DEPTH}, use a lower depth"
+            ),
+        }
+    }
+}
+
+impl Error for SizeError {}
+
+impl MarchLimits {
+    fn exceeded(&self, mesh: &MeshBuilder, start: Instant) -> Option<LimitExceeded> {
+        if let Some(limit) = self.max_faces.filter(|limit| mesh.face_count() > *limit) {
+            return Some(LimitExceeded::Faces { limit });
+        }
+        if let Some(limit) = self
+            .max_memory_bytes
+            .filter(|limit| mesh.memory_bytes() > *limit)
+        {
+            return Some(LimitExceeded::MemoryBytes { limit });
+        }
+        self.max_duration
+            .filter(|limit| start.elapsed() > *limit)
+            .map(|limit| LimitExceeded::Duration { limit })
+    }
+}
+
+/// Budgets for [Domain::march_tetrahedras_with_limits]. `None` doesn't limit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct MarchLimits {
+    pub max_faces: Option<usize>,
+    /// Bytes allocated for the mesh, including the lookup that shares vertices between faces.
+    pub max_memory_bytes: Option<usize>,
+    pub max_duration: Option<Duration>,
+}
+
+/// Limit of [MarchLimits] that stopped a march, reported in [MarchResult::limit_exceeded].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Faces { limit: usize },
+    MemoryBytes { limit: usize },
+    Duration { limit: Duration },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Faces { limit } => write!(
+                f,
+                "march stopped after exceeding {limit} faces, the mesh is incomplete; lower the resolution or smooth the field"
+            ),
+            LimitExceeded::MemoryBytes { limit } => write!(
+                f,
+                "march stopped after exceeding {limit} bytes of mesh memory, the mesh is incomplete; lower the resolution or smooth the field"
+            ),
+            LimitExceeded::Duration { limit } => write!(
+                f,
+                "march stopped after exceeding {limit:?}, the mesh is incomplete; lower the resolution or use a cheaper field"
+            ),
+        }
+    }
+}
+
+/// Bytes a [crate::grid::ScalarGrid] may use before [Domain::march_tetrahedras_sampled] marches
+/// plane by plane instead.
+const MAX_SAMPLED_GRID_BYTES: usize = isize::MAX as usize;
+
+impl Domain {
+    /// Same as [Domain::march_tetrahedras], stopping when the mesh exceeds one of `limits`
+    /// instead of exhausting the memory of the host, for example when a noisy field is sampled
+    /// at a much higher resolution than intended.
+    ///
+    /// The limits are checked after each row of cells with the same x and y, so the mesh can
+    /// exceed them by the faces of a row. A stopped march still adds the mesh of the cells
+    /// marched so far and reports the limit in [MarchResult::limit_exceeded].
+    pub fn march_tetrahedras_with_limits<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+        limits: &MarchLimits,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let start = Instant::now();
+        let mut limit_exceeded = None;
+        let mut mesh = MeshBuilder::default();
+        let origin = IVec3 { x: 0, y: 0, z: 0 };
+        let (min_weight, max_weight) = self.march_cells_using(
+            &mut mesh,
+            origin,
+            self.vertex_grid_size(),
+            &MarchingTetrahedra,
+            field,
+            refine,
+            &mut |mesh, _| {
+                limit_exceeded = limits.exceeded(mesh, start);
+                match limit_exceeded {
+                    Some(_) => ControlFlow::Break(()),
+                    None => ControlFlow::Continue(()),
+                }
+            },
+        );
+        let mesh = mesh.finish();
+        let mut result = MarchResult::new(&mesh, min_weight, max_weight, self.surface_weight);
+        result.limit_exceeded = limit_exceeded;
+        self.meshes.push(mesh);
+        result
+    }
+
+    /// Check that the lattice of the domain fits the integer types used while marching.
+    ///
+    /// Marching a domain that fails this check panics with the error, instead of wrapping
+    /// around silently.
+    pub fn check_size(&self) -> Result<(), SizeError> {
+        let offset = self.lattice_offset_checked()?;
+        self.vertex_grid_size_checked(offset)?;
+        self.sample_count().ok_or(SizeError::SampleCountOverflow)?;
+        Ok(())
+    }
+
+    /// Number of lattice vertices [Domain::sample_grid] samples, `None` when it doesn't fit in
+    /// `usize`.
+    pub fn sample_count(&self) -> Option<usize> {
+        self.width
+            .checked_add(2)?
+            .checked_mul(self.height.checked_add(2)?)?
+            .checked_mul(self.depth.checked_add(2)?)
+    }
+
+    /// Whether a [crate::grid::ScalarGrid] of the whole domain fits in memory on this platform.
+    pub(crate) fn fits_sampled_grid(&self) -> bool {
+        self.sample_count()
+            .and_then(|count| count.checked_mul(size_of::<Float>()))
+            .is_some_and(|bytes| bytes <= MAX_SAMPLED_GRID_BYTES)
+    }
+
+    pub(crate) fn lattice_offset_checked(&self) -> Result<IVec3, SizeError> {
+        let axis_offset = |axis: char, from: Float, to: Float, cells: usize| {
+            let offset = (from * cells as Float / (to - from)).round();
+            if offset.is_finite() && offset >= i32::MIN as Float && offset <= i32::MAX as Float {
+                Ok(offset as i32)
+            } else {
+                Err(SizeError::LatticeOverflow {
+                    axis,
+                    cells,
+                    offset: offset as i64,
+                })
+            }
+        };
+        Ok(IVec3 {
+            x: axis_offset('x', self.from.x, self.to.x, self.width)?,
+            y: axis_offset('y', self.from.y, self.to.y, self.height)?,
+            z: axis_offset('z', self.from.z, self.to.z, self.depth)?,
+        })
+    }
+
+    /// Size of the root nodes of [Domain::march_adaptive] in half cells, checking `depth` and
+    /// that every position of the octree fits in `i32`.
+    pub(crate) fn octree_root_size(&self, depth: u32) -> Result<i32, SizeError> {
+        if depth > MAX_OCTREE_DEPTH {
+            return Err(SizeError::OctreeDepth { depth });
+        }
+        let offset = self.lattice_offset_checked()?;
+        let size = self.vertex_grid_size_checked(offset)?;
+        for (axis, cells, offset) in [
+            ('x', size.x, offset.x),
+            ('y', size.y, offset.y),
+            ('z', size.z, offset.z),
+        ] {
+            if cells.checked_mul(2).is_none() {
+                return Err(SizeError::LatticeOverflow {
+                    axis,
+                    cells: cells as usize,
+                    offset: offset as i64,
+                });
+            }
+        }
+        Ok(2 << depth)
+    }
+
+    /// Vertex grid size, checking that every lattice coordinate of the domain, including the
+    /// extra vertex past the last cell, fits in `i32`.
+    pub(crate) fn vertex_grid_size_checked(&self, offset: IVec3) -> Result<IVec3, SizeError> {
+        let axis_size = |axis: char, cells: usize, offset: i32| {
+            i32::try_from(cells)
+                .ok()
+                .and_then(|cells| cells.checked_add(1))
+                .filter(|size| {
+                    size.checked_add(1)
+                        .and_then(|end| offset.checked_add(end))
+                        .is_some()
+                })
+                .ok_or(SizeError::LatticeOverflow {
+                    axis,
+                    cells,
+                    offset: offset as i64,
+                })
+        };
+        Ok(IVec3 {
+            x: axis_size('x', self.width, offset.x)?,
+            y: axis_size('y', self.height, offset.y)?,
+            z: axis_size('z', self.depth, offset.z)?,
+        })
+    }
+}
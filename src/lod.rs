@@ -0,0 +1,240 @@
+//! Correspondence between levels of detail for geomorphing.
+//!
+//! Terrain renderers blend a fine mesh into the coarse mesh that replaces it, so switching between
+//! levels of detail doesn't pop. [Mesh::lod_map] computes for every vertex of the fine mesh the
+//! closest point on the coarse mesh, in the order of the fine vertices, so the result can be
+//! stored as an extra vertex attribute:
+//!
+//! ```
+//! use marching_cubes::prelude::*;
+//!
+//! let metaballs = Metaballs::new(vec![Force { position: Vec3::default(), force: 2.0 }]);
+//! let march = |resolution: usize| {
+//!     let mut domain = MarchConfig::preset(Preset::Preview).domain(
+//!         Vec3 { x: -4.0, y: -4.0, z: -4.0 },
+//!         Vec3 { x: 4.0, y: 4.0, z: 4.0 },
+//!         1.0,
+//!     );
+//!     domain.width = resolution;
+//!     domain.height = resolution;
+//!     domain.depth = resolution;
+//!     domain.march_tetrahedras(&metaballs, &Bisection);
+//!     domain.meshes.remove(0)
+//! };
+//! let fine = march(32);
+//! let coarse = march(8);
+//! let morph_targets = fine.lod_morph_targets(&coarse);
+//! assert_eq!(morph_targets.len(), fine.verts.len());
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{Float, Mesh, Vec3};
+
+/// Closest point on a face of the coarse mesh, see [Mesh::lod_map].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LodReference {
+    /// Index of the face in the coarse mesh.
+    pub face: usize,
+    /// Weights of the vertices `v1`, `v2` and `v3` of the face, they sum up to 1.0.
+    pub barycentric: [Float; 3],
+}
+
+impl LodReference {
+    /// Position of the reference on `coarse`, the mesh the reference was computed for.
+    pub fn position(&self, coarse: &Mesh) -> Vec3 {
+        let face = &coarse.faces[self.face];
+        let [a, b, c] = self.barycentric;
+        coarse.verts[face.v1] * a + coarse.verts[face.v2] * b + coarse.verts[face.v3] * c
+    }
+
+    /// Vertex of the face with the largest weight, for renderers that morph towards coarse
+    /// vertices instead of points on the coarse surface.
+    pub fn nearest_vertex(&self, coarse: &Mesh) -> usize {
+        let face = &coarse.faces[self.face];
+        let [a, b, c] = self.barycentric;
+        if a >= b && a >= c {
+            face.v1
+        } else if b >= c {
+            face.v2
+        } else {
+            face.v3
+        }
+    }
+}
+
+impl Mesh {
+    /// Map every vertex of this mesh to the closest point on `coarse`, a lower level of detail
+    /// of the same field. The result has an element per vertex of this mesh, `None` when
+    /// `coarse` has no faces.
+    ///
+    /// The coarse faces are binned in a uniform grid, so the cost grows with the number of
+    /// vertices instead of the product of vertices and faces. Vertices far away from the coarse
+    /// surface, for example of shapes that are too thin for the coarse level, search more cells.
+    pub fn lod_map(&self, coarse: &Mesh) -> Vec<Option<LodReference>> {
+        let Some((min, max)) = coarse.bounds() else {
+            return vec![None; self.verts.len()];
+        };
+        if coarse.faces.is_empty() {
+            return vec![None; self.verts.len()];
+        }
+        // Cells about the size of a coarse face keep the number of faces per cell small.
+        let mean_edge = coarse
+            .faces
+            .iter()
+            .map(|face| (coarse.verts[face.v2] - coarse.verts[face.v1]).length())
+            .sum::<Float>()
+            / coarse.faces.len() as Float;
+        let cell_size = if mean_edge > 0.0 { mean_edge } else { 1.0 };
+        let cell_of = |position: Vec3| {
+            [
+                ((position.x - min.x) / cell_size).floor() as i64,
+                ((position.y - min.y) / cell_size).floor() as i64,
+                ((position.z - min.z) / cell_size).floor() as i64,
+            ]
+        };
+        let mut cells = HashMap::<[i64; 3], Vec<usize>>::new();
+        for (index, face) in coarse.faces.iter().enumerate() {
+            let [v1, v2, v3] = [face.v1, face.v2, face.v3].map(|vert| cell_of(coarse.verts[vert]));
+            for x in v1[0].min(v2[0]).min(v3[0])..=v1[0].max(v2[0]).max(v3[0]) {
+                for y in v1[1].min(v2[1]).min(v3[1])..=v1[1].max(v2[1]).max(v3[1]) {
+                    for z in v1[2].min(v2[2]).min(v3[2])..=v1[2].max(v2[2]).max(v3[2]) {
+                        cells.entry([x, y, z]).or_default().push(index);
+                    }
+                }
+            }
+        }
+        let last_cell = cell_of(max);
+
+        self.verts
+            .iter()
+            .map(|vert| {
+                let center = cell_of(*vert);
+                // Rings beyond this one don't contain any cell of the coarse mesh.
+                let max_ring = (0..3)
+                    .map(|axis| {
+                        center[axis]
+                            .abs()
+                            .max((center[axis] - last_cell[axis]).abs())
+                    })
+                    .max()
+                    .unwrap();
+                let mut best: Option<(Float, LodReference)> = None;
+                for ring in 0..=max_ring {
+                    // Cells of this ring are at least `ring - 1` cells away from the vertex.
+                    if let Some((distance_squared, _)) = best {
+                        let ring_distance = (ring - 1) as Float * cell_size;
+                        if ring_distance * ring_distance > distance_squared {
+                            break;
+                        }
+                    }
+                    for_each_ring_cell(center, ring, |cell| {
+                        for face_index in cells.get(&cell).into_iter().flatten() {
+                            let face = &coarse.faces[*face_index];
+                            let (point, barycentric) = closest_point_on_triangle(
+                                *vert,
+                                coarse.verts[face.v1],
+                                coarse.verts[face.v2],
+                                coarse.verts[face.v3],
+                            );
+                            let offset = point - *vert;
+                            let distance_squared = offset.dot(offset);
+                            if best
+                                .is_none_or(|(best_distance, _)| distance_squared < best_distance)
+                            {
+                                best = Some((
+                                    distance_squared,
+                                    LodReference {
+                                        face: *face_index,
+                                        barycentric,
+                                    },
+                                ));
+                            }
+                        }
+                    });
+                }
+                best.map(|(_, reference)| reference)
+            })
+            .collect()
+    }
+
+    /// Positions on `coarse` to morph the vertices of this mesh to, see [Mesh::lod_map].
+    /// Vertices without a reference keep their position.
+    pub fn lod_morph_targets(&self, coarse: &Mesh) -> Vec<Vec3> {
+        self.lod_map(coarse)
+            .iter()
+            .zip(&self.verts)
+            .map(|(reference, vert)| {
+                reference.map_or(*vert, |reference| reference.position(coarse))
+            })
+            .collect()
+    }
+}
+
+/// Call `visit` for every cell on the surface of the cube of cells `ring` cells around `center`.
+fn for_each_ring_cell<VISIT>(center: [i64; 3], ring: i64, mut visit: VISIT)
+where
+    VISIT: FnMut([i64; 3]),
+{
+    for x in -ring..=ring {
+        for y in -ring..=ring {
+            let on_side = x.abs() == ring || y.abs() == ring;
+            let z_step = if on_side {
+                1
+            } else {
+                (2 * ring).max(1) as usize
+            };
+            for z in (-ring..=ring).step_by(z_step) {
+                visit([center[0] + x, center[1] + y, center[2] + z]);
+            }
+        }
+    }
+}
+
+/// Closest point to `p` on the triangle `a`, `b`, `c` and its barycentric coordinates, from
+/// Ericson's Real-Time Collision Detection.
+fn closest_point_on_triangle(p: Vec3, a: Vec3, b: Vec3, c: Vec3) -> (Vec3, [Float; 3]) {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a, [1.0, 0.0, 0.0]);
+    }
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b, [0.0, 1.0, 0.0]);
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return (a + ab * v, [1.0 - v, v, 0.0]);
+    }
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c, [0.0, 0.0, 1.0]);
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return (a + ac * w, [1.0 - w, 0.0, w]);
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b + (c - b) * w, [0.0, 1.0 - w, w]);
+    }
+    let denominator = va + vb + vc;
+    if denominator == 0.0 {
+        // Degenerate triangle, all vertices are equally close.
+        return (a, [1.0, 0.0, 0.0]);
+    }
+    let v = vb / denominator;
+    let w = vc / denominator;
+    (a + ab * v + ac * w, [1.0 - v - w, v, w])
+}
@@ -1,394 +1,353 @@
-use std::{f64::consts::TAU, mem::swap, ops::Add};
+use std::{
+    io::{self, BufWriter, IsTerminal, Write},
+    num::NonZeroUsize,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
 
-fn main() {
-    const SIZE: usize = 32;
+use clap::Parser;
+#[cfg(feature = "expression")]
+use marching_cubes::field::Expression;
+use marching_cubes::{
+    consts::TAU, export::ExportFormat, prelude::*, progress::Progress, scene::Scene,
+};
 
-    let mut domain = Domain {
-        from: Vec3 {
-            x: -16.0,
-            y: -16.0,
-            z: -16.0,
-        },
-        to: Vec3 {
-            x: 16.0,
-            y: 16.0,
-            z: 16.0,
-        },
-        surface_weight: 1.0,
-        width: SIZE,
-        height: SIZE,
-        depth: SIZE,
-        meshes: Vec::default(),
-    };
-    for frame in 1..=100 {
-        let anim_rad = (frame as f64 / 100.0) * TAU;
-        let x = 2.0 + anim_rad.cos() * 2.0;
-        let y = 2.0 + anim_rad.sin() * 4.0;
-        let z = 0.0 + anim_rad.cos() * 1.0;
-        let f = 2.0 + anim_rad.sin() * 1.0;
-        let forces = vec![
-            Force {
-                position: Vec3 { x, y, z },
-                force: f,
-            },
-            Force {
-                position: Vec3 {
-                    x: -4.0,
-                    y: 6.0,
-                    z: 0.0,
-                },
-                force: 2.5,
-            },
-            Force {
-                position: Vec3 {
-                    x: 4.0,
-                    y: -6.0,
-                    z: -4.0,
-                },
-                force: 2.5,
-            },
-        ];
-        domain.march_tetrahedras(&weight_function, &refine_function_linear, &forces);
-    }
+const FRAMES: usize = 100;
 
-    domain.export_to_bpy("Marching");
+/// Progress bar on stderr for all frames, only drawn when stderr is a terminal.
+struct ProgressBar {
+    enabled: bool,
+    frames: usize,
+    start: Instant,
+    last_draw: Option<Instant>,
+    /// Faces and samples of the frames that are done.
+    faces: usize,
+    samples: usize,
 }
 
-struct Force {
-    position: Vec3,
-    force: f64,
-}
+impl ProgressBar {
+    const WIDTH: usize = 30;
 
-fn weight_function(position: Vec3, data: &Vec<Force>) -> f64 {
-    let mut total_weight = 0.0;
-    for force in data {
-        let dx = position.x - force.position.x;
-        let dy = position.y - force.position.y;
-        let dz = position.z - force.position.z;
-        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
-        let weight = force.force / distance;
-        total_weight += weight;
-    }
-    total_weight
-}
-
-fn refine_function_center<WEIGHT, DATA>(
-    v1: Vec3,
-    v2: Vec3,
-    _weight_function: &WEIGHT,
-    _weight_user_data: &DATA,
-    _surface_weight: f64,
-) -> Vec3
-where
-    WEIGHT: Fn(Vec3, &DATA) -> f64,
-{
-    Vec3 {
-        x: (v1.x + v2.x) * 0.5,
-        y: (v1.y + v2.y) * 0.5,
-        z: (v1.z + v2.z) * 0.5,
+    fn new(frames: usize) -> ProgressBar {
+        ProgressBar {
+            enabled: io::stderr().is_terminal(),
+            frames,
+            start: Instant::now(),
+            last_draw: None,
+            faces: 0,
+            samples: 0,
+        }
     }
-}
 
-fn refine_function_linear<WEIGHT, DATA>(
-    v1: Vec3,
-    v2: Vec3,
-    weight_function: &WEIGHT,
-    weight_user_data: &DATA,
-    surface_weight: f64,
-) -> Vec3
-where
-    WEIGHT: Fn(Vec3, &DATA) -> f64,
-{
-    let mut pos_left = v1;
-    let mut pos_right = v2;
-    let w_left = weight_function(pos_left, weight_user_data);
-    let w_right = weight_function(pos_right, weight_user_data);
-    if w_left > w_right {
-        swap(&mut pos_left, &mut pos_right);
-    }
+    fn update(&mut self, frame: usize, progress: &Progress) {
+        let done = progress.planes_done == progress.plane_count;
+        if done {
+            self.faces += progress.faces;
+            self.samples += progress.samples;
+        }
+        let now = Instant::now();
+        let throttled = self
+            .last_draw
+            .is_some_and(|last_draw| now - last_draw < Duration::from_millis(50));
+        if !self.enabled || (throttled && !(done && frame == self.frames)) {
+            return;
+        }
+        self.last_draw = Some(now);
 
-    let mut pos_center = pos_left;
-    for _ in 0..8 {
-        pos_center = refine_function_center(
-            pos_left,
-            pos_right,
-            weight_function,
-            weight_user_data,
-            surface_weight,
-        );
-        let w_center = weight_function(pos_center, weight_user_data);
-        if w_center < surface_weight {
-            pos_left = pos_center;
+        let frame_fraction = progress.planes_done as f64 / progress.plane_count.max(1) as f64;
+        let fraction = ((frame - 1) as f64 + frame_fraction) / self.frames as f64;
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let (faces, samples) = if done {
+            (self.faces, self.samples)
         } else {
-            pos_right = pos_center;
+            (self.faces + progress.faces, self.samples + progress.samples)
+        };
+        let rate = samples as f64 / elapsed.max(1e-9);
+        let eta = if fraction > 0.0 {
+            elapsed * (1.0 - fraction) / fraction
+        } else {
+            0.0
+        };
+        let filled = (fraction * Self::WIDTH as f64).round() as usize;
+        let bar = "█".repeat(filled);
+        let rest = "░".repeat(Self::WIDTH - filled);
+        let percentage = fraction * 100.0;
+        let frames = self.frames;
+        let mega_rate = rate / 1e6;
+        // Bar in green, the rest in gray, counters in cyan.
+        eprint!(
+            "\r\x1b[32m{bar}\x1b[90m{rest}\x1b[0m {percentage:3.0}% frame \x1b[36m{frame}/{frames}\x1b[0m | \x1b[36m{faces}\x1b[0m triangles | \x1b[36m{mega_rate:.2}M\x1b[0m samples/s | ETA \x1b[36m{eta:.1}s\x1b[0m\x1b[K"
+        );
+        if done && frame == self.frames {
+            eprintln!();
         }
+        let _ = io::stderr().flush();
     }
-
-    pos_center
 }
 
-/// Tetrahedra has 4 verts and 4 faces. The first vert is considered the top, the others part of the bottom.
-///
-
-/// Map each tetrahedra vertex masks to the edges that will be based for the faces.
-/// Although there are 16 possible vert maps, the last 8 are the inverse of the first 8 so we only need to store 8 of them.
-/// When using the inverse the edge2 and edge3 should be inversed as well to ensure correct "normals".
-const TETRADEDRA_VERTMASK_TO_EDGES: [[isize; 6]; 8] = [
-    [-1, -1, -1, -1, -1, -1], // 0000/1111
-    [0, 1, 2, -1, -1, -1],    // 0001/1110
-    [0, 5, 3, -1, -1, -1],    // 0010/1101
-    [1, 2, 3, 3, 2, 5],       // 0011/1100
-    [1, 3, 4, -1, -1, -1],    // 0100/1011
-    [4, 2, 3, 3, 2, 0],       // 0101/1010
-    [1, 0, 4, 4, 0, 5],       // 0110/1001
-    [2, 5, 4, -1, -1, -1],    // 0111/1000
-];
-
-/// Ordering of verts inside a grid block
-const GRID_TO_VERT_OFFSETS: [IVec3; 8] = [
-    IVec3 { x: 0, y: 0, z: 0 },
-    IVec3 { x: 1, y: 0, z: 0 },
-    IVec3 { x: 1, y: 1, z: 0 },
-    IVec3 { x: 0, y: 1, z: 0 },
-    IVec3 { x: 0, y: 0, z: 1 },
-    IVec3 { x: 1, y: 0, z: 1 },
-    IVec3 { x: 1, y: 1, z: 1 },
-    IVec3 { x: 0, y: 1, z: 1 },
-];
-/// Split a grid into 5 tetrahedras.
-const GRID_TO_TETRAHEDRA_VERTICES: [[usize; 4]; 5] = [
-    [0, 2, 7, 5],
-    [1, 0, 5, 2],
-    [3, 2, 7, 0],
-    [4, 0, 7, 5],
-    [6, 2, 5, 7],
-];
-const TETRAHEDRA_EDGES_TO_VERT_OFFSETS: [[usize; 2]; 6] =
-    [[0, 1], [0, 2], [0, 3], [1, 2], [2, 3], [3, 1]];
-
-#[derive(Copy, Clone, Debug)]
-struct IVec3 {
-    x: i32,
-    y: i32,
-    z: i32,
-}
-
-impl Add<IVec3> for IVec3 {
-    type Output = IVec3;
-
-    fn add(self, rhs: IVec3) -> Self::Output {
-        IVec3 {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-            z: self.z + rhs.z,
-        }
+/// Parse `--bounds`: a size for a domain from -size to size on every axis, or the two corners
+/// as `x,y,z,x,y,z`.
+fn parse_bounds(value: &str) -> Result<(Vec3, Vec3), String> {
+    let values = value
+        .split(',')
+        .map(|value| value.trim().parse::<Float>())
+        .collect::<Result<Vec<Float>, _>>()
+        .map_err(|error| error.to_string())?;
+    let (from, to) = match values[..] {
+        [size] => (
+            Vec3 {
+                x: -size,
+                y: -size,
+                z: -size,
+            },
+            Vec3 {
+                x: size,
+                y: size,
+                z: size,
+            },
+        ),
+        [x0, y0, z0, x1, y1, z1] => (
+            Vec3 {
+                x: x0,
+                y: y0,
+                z: z0,
+            },
+            Vec3 {
+                x: x1,
+                y: y1,
+                z: z1,
+            },
+        ),
+        _ => return Err("expected a size or 6 coordinates".to_string()),
+    };
+    if !(from.x < to.x && from.y < to.y && from.z < to.z) {
+        return Err("the first corner must be below the second on every axis".to_string());
     }
+    Ok((from, to))
 }
 
-#[derive(Copy, Clone, Debug)]
-struct Vec3 {
-    x: f64,
-    y: f64,
-    z: f64,
+fn parse_format(value: &str) -> Result<ExportFormat, String> {
+    ExportFormat::from_name(value).ok_or_else(|| {
+        "expected bpy, obj, off, stl, stl-ascii, ply or glb (glb needs the gltf feature)"
+            .to_string()
+    })
 }
 
-#[derive(Debug)]
-struct Face {
-    v1: usize,
-    v2: usize,
-    v3: usize,
-}
-#[derive(Debug)]
-struct Edge {
-    v1: usize,
-    v2: usize,
+#[cfg(feature = "expression")]
+fn parse_expression(value: &str) -> Result<Expression, String> {
+    Expression::parse(value).map_err(|error| error.to_string())
 }
 
-#[derive(Debug, Default)]
-struct Mesh {
-    verts: Vec<Vec3>,
-    faces: Vec<Face>,
-    edges: Vec<Edge>,
+/// March metaballs, a scene or an expression with marching tetrahedra and write the meshes.
+///
+/// Without a scene the metaballs of the built-in animation are marched for every frame.
+#[derive(Parser)]
+#[command(name = "marching-cubes", version)]
+struct Options {
+    /// March the domain and forces of a scene file once, see the scene module
+    #[arg(long, value_name = "path")]
+    scene: Option<PathBuf>,
+    /// March a math expression of x, y and z once, for example a gyroid
+    /// `sin(x)*cos(y) + sin(y)*cos(z) + sin(z)*cos(x)`
+    #[cfg(feature = "expression")]
+    #[arg(
+        long = "expr",
+        value_name = "expression",
+        value_parser = parse_expression,
+        conflicts_with = "scene"
+    )]
+    expression: Option<Expression>,
+    /// Cells along each axis [default: 32]
+    #[arg(long, value_name = "n")]
+    resolution: Option<NonZeroUsize>,
+    /// Domain from -size to size on every axis, or from the first to the second corner given as
+    /// x,y,z,x,y,z [default: 16]
+    #[arg(long, value_name = "size", value_parser = parse_bounds, allow_hyphen_values = true)]
+    bounds: Option<(Vec3, Vec3)>,
+    /// Surface weight of the metaballs [default: 1] or expression [default: 0]
+    #[arg(long, value_name = "weight", allow_hyphen_values = true)]
+    iso: Option<Float>,
+    /// Write to a file instead of stdout
+    #[arg(long, value_name = "path")]
+    output: Option<PathBuf>,
+    /// bpy, obj, off, stl, stl-ascii, ply or glb [default: from the output extension, else bpy]
+    #[arg(long, value_name = "format", value_parser = parse_format)]
+    format: Option<ExportFormat>,
+    /// Also march every frame at resolution n, as a separate object
+    #[arg(long = "preview", value_name = "n")]
+    preview_size: Option<NonZeroUsize>,
 }
 
-#[derive(Debug)]
-struct Domain {
-    from: Vec3,
-    to: Vec3,
-
-    surface_weight: f64,
-    width: usize,
-    height: usize,
-    depth: usize,
-
-    meshes: Vec<Mesh>,
-}
+impl Options {
+    /// Format of the `--format` option, else the one of the `--output` extension.
+    fn format(&self) -> ExportFormat {
+        self.format
+            .or_else(|| self.output.as_deref().and_then(ExportFormat::from_path))
+            .unwrap_or(ExportFormat::Bpy)
+    }
 
-impl Domain {
-    fn vertex_grid_size(&self) -> IVec3 {
-        IVec3 {
-            x: self.width as i32 + 1,
-            y: self.height as i32 + 1,
-            z: self.depth as i32 + 1,
-        }
+    /// Field of the `--expr` option.
+    #[cfg(feature = "expression")]
+    fn expression(&self) -> Option<&dyn ScalarField> {
+        self.expression
+            .as_ref()
+            .map(|expression| expression as &dyn ScalarField)
     }
 
-    fn vertex_position(&self, vertex_grid_position: IVec3) -> Vec3 {
-        Vec3 {
-            x: self.from.x
-                + vertex_grid_position.x as f64 * (self.to.x - self.from.x) / self.width as f64,
-            y: self.from.y
-                + vertex_grid_position.y as f64 * (self.to.y - self.from.y) / self.height as f64,
-            z: self.from.z
-                + vertex_grid_position.z as f64 * (self.to.z - self.from.z) / self.depth as f64,
-        }
+    #[cfg(not(feature = "expression"))]
+    fn expression(&self) -> Option<&dyn ScalarField> {
+        None
     }
 }
 
-fn get_vert_offsets(cell_pos: IVec3) -> ([IVec3; 8], bool) {
-    let flip_x = cell_pos.x.abs() & 1 != 0;
-    let flip_y = cell_pos.y.abs() & 1 != 0;
-    let flip_z = cell_pos.z.abs() & 1 != 0;
-    let grid_inverse = [flip_x, flip_y, flip_z].iter().filter(|v| **v).count() & 1 != 0;
-
-    let mut result = GRID_TO_VERT_OFFSETS;
-
-    for index in 0..8 {
-        if flip_x {
-            result[index].x = 1 - result[index].x;
-        }
-        if flip_y {
-            result[index].y = 1 - result[index].y;
-        }
-        if flip_z {
-            result[index].z = 1 - result[index].z;
-        }
-    }
-    (result, grid_inverse)
+/// Metaballs of the built-in animation at `frame` out of [FRAMES].
+fn animated_metaballs(frame: usize) -> Metaballs {
+    let anim_rad = (frame as Float / FRAMES as Float) * TAU;
+    let x = 2.0 + anim_rad.cos() * 2.0;
+    let y = 2.0 + anim_rad.sin() * 4.0;
+    let z = 0.0 + anim_rad.cos() * 1.0;
+    let f = 2.0 + anim_rad.sin() * 1.0;
+    let forces = vec![
+        Force {
+            position: Vec3 { x, y, z },
+            force: f,
+        },
+        Force {
+            position: Vec3 {
+                x: -4.0,
+                y: 6.0,
+                z: 0.0,
+            },
+            force: 2.5,
+        },
+        Force {
+            position: Vec3 {
+                x: 4.0,
+                y: -6.0,
+                z: -4.0,
+            },
+            force: 2.5,
+        },
+    ];
+    Metaballs::new(forces)
 }
 
-impl Domain {
-    fn march_tetrahedras<WEIGHT, REFINE, DATA>(
-        &mut self,
-        weight_function: &WEIGHT,
-        refine_function: &REFINE,
-        weight_user_data: &DATA,
-    ) where
-        WEIGHT: Fn(Vec3, &DATA) -> f64,
-        DATA: Sized,
-        REFINE: Fn(Vec3, Vec3, &WEIGHT, &DATA, f64) -> Vec3,
-    {
-        let mut mesh = Mesh::default();
-        let max_cell_position = self.vertex_grid_size();
-        for x in 0..max_cell_position.x {
-            for y in 0..max_cell_position.y {
-                for z in 0..max_cell_position.z {
-                    let cell_pos = IVec3 { x, y, z };
-                    let (grid_to_verts_offsets, grid_inverse) = get_vert_offsets(cell_pos);
-                    let vert_positions = grid_to_verts_offsets
-                        .iter()
-                        .map(|offset| cell_pos + *offset)
-                        .map(|grid_position| self.vertex_position(grid_position))
-                        .collect::<Vec<Vec3>>();
+fn main() -> io::Result<ExitCode> {
+    let options = Options::parse();
+    let format = options.format();
+    if options.preview_size.is_some() && options.output.is_none() && format != ExportFormat::Bpy {
+        eprintln!("--preview with a format other than bpy requires --output");
+        return Ok(ExitCode::FAILURE);
+    }
 
-                    let vert_is_inside = vert_positions
-                        .iter()
-                        .map(|vert_position| weight_function(*vert_position, weight_user_data))
-                        .map(|weight| weight > self.surface_weight)
-                        .collect::<Vec<bool>>();
-                    for tetrahedron_indices in GRID_TO_TETRAHEDRA_VERTICES {
-                        // determine vert mask + inverse
-                        let mut mask = 0;
-                        for index in 0..tetrahedron_indices.len() {
-                            let index_mask = 1 << index;
-                            if vert_is_inside[tetrahedron_indices[index]] {
-                                mask |= index_mask;
-                            }
-                        }
-                        let compressed_mask = if mask > 7 { 15 - mask } else { mask } as usize;
-                        let inversed_mask = (mask > 7) != grid_inverse;
-                        for face_index in 0..2 {
-                            let e1 = TETRADEDRA_VERTMASK_TO_EDGES[compressed_mask][face_index * 3];
-                            let e2 =
-                                TETRADEDRA_VERTMASK_TO_EDGES[compressed_mask][face_index * 3 + 1];
-                            let e3 =
-                                TETRADEDRA_VERTMASK_TO_EDGES[compressed_mask][face_index * 3 + 2];
-                            if e1 == -1 {
-                                // No faces left to add for this tetrahedra.
-                                break;
-                            }
-                            let face_vert_start_index = mesh.verts.len();
-                            mesh.faces.push(Face {
-                                v1: face_vert_start_index,
-                                v2: face_vert_start_index + if inversed_mask { 2 } else { 1 },
-                                v3: face_vert_start_index + if inversed_mask { 1 } else { 2 },
-                            });
-                            mesh.edges.push(Edge {
-                                v1: face_vert_start_index,
-                                v2: face_vert_start_index + 1,
-                            });
-                            mesh.edges.push(Edge {
-                                v1: face_vert_start_index + 1,
-                                v2: face_vert_start_index + 2,
-                            });
-                            mesh.edges.push(Edge {
-                                v1: face_vert_start_index + 2,
-                                v2: face_vert_start_index,
-                            });
-                            for edge_index in [e1, e2, e3] {
-                                let edge_vert_offs =
-                                    TETRAHEDRA_EDGES_TO_VERT_OFFSETS[edge_index as usize];
-                                let vert_offs_1 = edge_vert_offs[0];
-                                let vert_offs_2 = edge_vert_offs[1];
-                                let vert_pos_1 = vert_positions[tetrahedron_indices[vert_offs_1]];
-                                let vert_pos_2 = vert_positions[tetrahedron_indices[vert_offs_2]];
-                                let edge_pos = refine_function(
-                                    vert_pos_1,
-                                    vert_pos_2,
-                                    weight_function,
-                                    weight_user_data,
-                                    self.surface_weight,
-                                );
-                                mesh.verts.push(edge_pos);
-                            }
-                        }
-                    }
-                }
+    let scene = match &options.scene {
+        Some(path) => match Scene::load(path) {
+            Ok(scene) => Some(scene),
+            Err(error) => {
+                eprintln!("{}: {error}", path.display());
+                return Ok(ExitCode::FAILURE);
             }
-        }
-        self.meshes.push(mesh);
+        },
+        None => None,
+    };
+    let mut domain = match &scene {
+        Some(scene) => scene.domain.with_resolution(
+            scene.domain.width,
+            scene.domain.height,
+            scene.domain.depth,
+        ),
+        None => Domain {
+            from: Vec3 {
+                x: -16.0,
+                y: -16.0,
+                z: -16.0,
+            },
+            to: Vec3 {
+                x: 16.0,
+                y: 16.0,
+                z: 16.0,
+            },
+            surface_weight: if options.expression().is_some() {
+                0.0
+            } else {
+                1.0
+            },
+            width: 32,
+            height: 32,
+            depth: 32,
+            grid_offset: Vec3::default(),
+            meshes: Vec::default(),
+        },
+    };
+    if let Some(resolution) = options.resolution.map(NonZeroUsize::get) {
+        domain = domain.with_resolution(resolution, resolution, resolution);
+    }
+    if let Some((from, to)) = options.bounds {
+        (domain.from, domain.to) = (from, to);
+    }
+    if let Some(iso) = options.iso {
+        domain.surface_weight = iso;
     }
+    if let Err(error) = domain.check_size() {
+        eprintln!("{error}");
+        return Ok(ExitCode::FAILURE);
+    }
+    // Scenes and expressions are static, only the built-in animation has multiple frames.
+    let static_field = match &scene {
+        Some(scene) => Some(&scene.metaballs as &dyn ScalarField),
+        None => options.expression(),
+    };
+    let frames = if static_field.is_some() { 1 } else { FRAMES };
 
-    fn export_to_bpy(&self, name: &str) {
-        println!("import bpy");
-        println!("");
-        for mesh in &self.meshes {
-            mesh.export_to_bpy(name);
+    // The preview marches every frame at a lower resolution as well, so the preview and final
+    // result are created in a single run.
+    let mut preview_domain = options
+        .preview_size
+        .map(NonZeroUsize::get)
+        .map(|size| domain.with_resolution(size, size, size));
+    let mut progress_bar = ProgressBar::new(frames);
+    for frame in 1..=frames {
+        let animated;
+        let field = match static_field {
+            Some(field) => field,
+            None => {
+                animated = animated_metaballs(frame);
+                &animated
+            }
+        };
+        let result = domain.march_tetrahedras_with_progress(field, &Bisection, |progress| {
+            progress_bar.update(frame, progress)
+        });
+        if let Some(diagnosis) = result.empty_diagnosis {
+            eprintln!("frame {frame}: {diagnosis}");
+        }
+        if let Some(preview_domain) = &mut preview_domain {
+            preview_domain.march_tetrahedras(field, &Bisection);
         }
     }
-}
 
-impl Mesh {
-    fn export_to_bpy(&self, name: &str) {
-        println!("verts = [");
-        for vert in &self.verts {
-            println!("  ({:8}, {:8}, {:8}),", vert.x, vert.y, vert.z);
-        }
-        println!("]");
-        println!("edges = [");
-        for edge in &self.edges {
-            println!("  ({:4}, {:4}),", edge.v1, edge.v2);
+    match &options.output {
+        Some(path) => {
+            domain.export_to_file(path, format)?;
+            if let Some(preview_domain) = &preview_domain {
+                let mut preview_name = path.file_stem().unwrap_or_default().to_os_string();
+                preview_name.push("_preview.");
+                preview_name.push(format.extension());
+                preview_domain.export_to_file(&path.with_file_name(preview_name), format)?;
+            }
         }
-        println!("]");
-        println!("faces = [");
-        for face in &self.faces {
-            println!("  ({:4}, {:4}, {:4}),", face.v1, face.v2, face.v3);
+        None => {
+            let mut output = BufWriter::new(io::stdout().lock());
+            domain.write(&mut output, format, "Marching")?;
+            // Bpy scripts can hold multiple objects, other formats need a file per domain.
+            if let Some(preview_domain) = &preview_domain {
+                for mesh in &preview_domain.meshes {
+                    mesh.write_bpy(&mut output, "MarchingPreview")?;
+                }
+            }
+            output.flush()?;
         }
-        println!("]");
-        println!("new_mesh = bpy.data.meshes.new('{name}')");
-        println!("new_mesh.from_pydata(verts, edges, faces)");
-        println!("");
-        println!("new_object = bpy.data.objects.new('{name}', new_mesh)");
-        println!("bpy.context.scene.collection.objects.link(new_object)");
     }
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,35 @@
+use crate::{Face, Float, Mesh};
+
+impl Mesh {
+    /// Area of a single face.
+    pub fn face_area(&self, face: &Face) -> Float {
+        let v1 = self.verts[face.v1];
+        let v2 = self.verts[face.v2];
+        let v3 = self.verts[face.v3];
+        (v2 - v1).cross(v3 - v1).length() * 0.5
+    }
+
+    /// Total area of the faces.
+    pub fn surface_area(&self) -> Float {
+        self.faces.iter().map(|face| self.face_area(face)).sum()
+    }
+
+    /// Volume enclosed by the mesh, from the divergence theorem: the sum of the signed volumes
+    /// of the tetrahedra between the origin and every face.
+    ///
+    /// Positive when the faces point outwards, as the meshes of this crate do. Only meaningful
+    /// for watertight meshes, see [Mesh::validate]; meshes cut open by the bounds of the domain
+    /// give a value that depends on the position of the origin.
+    pub fn signed_volume(&self) -> Float {
+        self.faces
+            .iter()
+            .map(|face| {
+                let v1 = self.verts[face.v1];
+                let v2 = self.verts[face.v2];
+                let v3 = self.verts[face.v3];
+                v1.dot(v2.cross(v3))
+            })
+            .sum::<Float>()
+            / 6.0
+    }
+}
@@ -0,0 +1,87 @@
+//! Extraction limited to a narrow band around the surface, for level set simulations.
+//!
+//! Level set solvers only keep their signed distance field valid within a few cells of the
+//! interface; further away the values are stale or arbitrary, and marching them produces
+//! spurious surfaces. [NarrowBand] only polygonizes cells whose corners are all within the band,
+//! so those values never reach the mesh:
+//!
+//! ```
+//! use marching_cubes::{field::Sphere, prelude::*};
+//!
+//! let mut domain = MarchConfig::preset(Preset::Preview).domain(
+//!     Vec3 { x: -4.0, y: -4.0, z: -4.0 },
+//!     Vec3 { x: 4.0, y: 4.0, z: 4.0 },
+//!     0.0,
+//! );
+//! let sphere = Sphere { center: Vec3::default(), radius: 2.0 };
+//! // Stale values with the wrong sign outside the band.
+//! let level_set = |position: Vec3| {
+//!     let weight = sphere.sample(position);
+//!     if weight.abs() < 1.0 { weight } else { -2.0 - (position.x * 7.0).sin() }
+//! };
+//! domain.march_narrow_band(&level_set, &Bisection, 1.0);
+//! assert_eq!(domain.meshes[0].split_components().len(), 1);
+//!
+//! // Marching every cell adds a spurious surface where the stale values start.
+//! domain.march_tetrahedras(&level_set, &Bisection);
+//! assert_eq!(domain.meshes[1].split_components().len(), 2);
+//! ```
+
+use crate::{
+    Domain, Float, RefineStrategy, ScalarField,
+    algorithm::{Cell, ExtractionAlgorithm, MarchingTetrahedra, MeshSink},
+    result::MarchResult,
+};
+
+/// [MarchingTetrahedra] that skips cells with a corner outside the band, see
+/// [crate::narrow_band].
+///
+/// A corner is within the band when its weight is finite and differs less than `band_width` from
+/// the surface weight. For a signed distance field the band has to be wider than the diagonal of
+/// a cell, otherwise cells crossing the surface can have a corner outside the band and the mesh
+/// gets holes.
+#[derive(Debug, Copy, Clone)]
+pub struct NarrowBand {
+    pub band_width: Float,
+}
+
+impl ExtractionAlgorithm for NarrowBand {
+    fn polygonize<FIELD, REFINE>(
+        &self,
+        domain: &Domain,
+        cell: &Cell,
+        field: &FIELD,
+        refine: &REFINE,
+        sink: &mut MeshSink,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let in_band = cell
+            .corner_weights
+            .iter()
+            .all(|weight| (weight - domain.surface_weight).abs() < self.band_width);
+        if in_band {
+            MarchingTetrahedra.polygonize(domain, cell, field, refine, sink);
+        }
+    }
+}
+
+impl Domain {
+    /// March only the cells within `band_width` of the surface, see [NarrowBand].
+    ///
+    /// Every lattice vertex is still sampled once to find the band, the minimum and maximum
+    /// weight of the result include the samples outside the band.
+    pub fn march_narrow_band<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+        band_width: Float,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        self.march_with_algorithm(&NarrowBand { band_width }, field, refine)
+    }
+}
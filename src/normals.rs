@@ -0,0 +1,84 @@
+use crate::{Face, Float, Mesh, ScalarField, Vec3, field::gradient};
+
+/// How [Mesh::compute_vertex_normals] weights the normals of the faces around a vertex.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum NormalWeighting {
+    /// Weight by the area of the face. Cheap, large faces dominate.
+    #[default]
+    Area,
+    /// Weight by the angle of the face at the vertex. Independent of how the faces around the
+    /// vertex are triangulated.
+    Angle,
+}
+
+impl Mesh {
+    /// Unit normal of a face, following the counter-clockwise winding of its vertices.
+    pub fn face_normal(&self, face: &Face) -> Vec3 {
+        let v1 = self.verts[face.v1];
+        let v2 = self.verts[face.v2];
+        let v3 = self.verts[face.v3];
+        (v2 - v1).cross(v3 - v1).normalized()
+    }
+
+    /// Unit normal of every face, in the order of [Mesh::faces].
+    pub fn compute_face_normals(&self) -> Vec<Vec3> {
+        self.faces
+            .iter()
+            .map(|face| self.face_normal(face))
+            .collect()
+    }
+
+    /// Set [Mesh::normals] by averaging the normals of the faces around each vertex.
+    ///
+    /// Works for any mesh, also when no field is at hand. Vertices that aren't used by any face
+    /// get a zero normal.
+    pub fn compute_vertex_normals(&mut self, weighting: NormalWeighting) {
+        let mut normals = vec![Vec3::default(); self.verts.len()];
+        for face in &self.faces {
+            let verts = [face.v1, face.v2, face.v3];
+            let positions = verts.map(|vert| self.verts[vert]);
+            // The length of the cross product is twice the area of the face.
+            let area_normal = (positions[1] - positions[0]).cross(positions[2] - positions[0]);
+            for corner in 0..3 {
+                let weighted = match weighting {
+                    NormalWeighting::Area => area_normal,
+                    NormalWeighting::Angle => {
+                        let position = positions[corner];
+                        let to_next = (positions[(corner + 1) % 3] - position).normalized();
+                        let to_previous = (positions[(corner + 2) % 3] - position).normalized();
+                        let angle = to_next.dot(to_previous).clamp(-1.0, 1.0).acos();
+                        area_normal.normalized() * angle
+                    }
+                };
+                normals[verts[corner]] = normals[verts[corner]] + weighted;
+            }
+        }
+        self.normals = normals.into_iter().map(Vec3::normalized).collect();
+    }
+
+    /// Set [Mesh::normals] from the gradient of the field at each vertex, estimated with central
+    /// differences of `step`.
+    ///
+    /// Gradient normals follow the implicit surface instead of the faces, so they shade smooth
+    /// even on coarse meshes. The weight increases towards the inside, so the normals point
+    /// against the gradient.
+    pub fn compute_gradient_normals<FIELD>(&mut self, field: &FIELD, step: Float)
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        self.compute_normals_with(|position| gradient(field, position, step));
+    }
+
+    /// Same as [Mesh::compute_gradient_normals] with a gradient provided by the caller, for fields
+    /// with an analytic gradient.
+    pub fn compute_normals_with<G>(&mut self, gradient: G)
+    where
+        G: Fn(Vec3) -> Vec3,
+    {
+        self.normals = self
+            .verts
+            .iter()
+            .map(|vert| (gradient(*vert) * -1.0).normalized())
+            .collect();
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::{self, Write};
+
+use crate::{Domain, GRID_TO_VERT_OFFSETS, IVec3, ScalarField};
+
+/// One bit per cell of a domain.
+#[derive(Debug, Clone)]
+pub struct BitGrid {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    /// Bits ordered x first, then y, then z. Packed 64 cells per word, starting at the lowest bit.
+    pub words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize, depth: usize) -> BitGrid {
+        BitGrid {
+            width,
+            height,
+            depth,
+            words: vec![0; (width * height * depth).div_ceil(64)],
+        }
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.height + y) * self.width + x
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> bool {
+        let index = self.index(x, y, z);
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: bool) {
+        let index = self.index(x, y, z);
+        if value {
+            self.words[index / 64] |= 1 << (index % 64);
+        } else {
+            self.words[index / 64] &= !(1 << (index % 64));
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Write the grid as a compact bitset: width, height and depth as little endian `u32`,
+    /// followed by the words as little endian `u64`.
+    pub fn write_bitset<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for size in [self.width, self.height, self.depth] {
+            writer.write_all(&(size as u32).to_le_bytes())?;
+        }
+        for word in &self.words {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write the grid as a MagicaVoxel `.vox` file using palette index 1 for each set cell.
+    ///
+    /// The format limits models to 256 voxels along each axis.
+    pub fn write_vox<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.width > 256 || self.height > 256 || self.depth > 256 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "vox files are limited to 256 voxels along each axis",
+            ));
+        }
+
+        let mut voxels = Vec::with_capacity(self.count_ones() * 4);
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    if self.get(x, y, z) {
+                        voxels.extend_from_slice(&[x as u8, y as u8, z as u8, 1]);
+                    }
+                }
+            }
+        }
+
+        let size_content = 12;
+        let xyzi_content = 4 + voxels.len();
+        let children = (12 + size_content) + (12 + xyzi_content);
+
+        writer.write_all(b"VOX ")?;
+        writer.write_all(&150_u32.to_le_bytes())?;
+        write_chunk_header(writer, b"MAIN", 0, children)?;
+        write_chunk_header(writer, b"SIZE", size_content, 0)?;
+        for size in [self.width, self.height, self.depth] {
+            writer.write_all(&(size as u32).to_le_bytes())?;
+        }
+        write_chunk_header(writer, b"XYZI", xyzi_content, 0)?;
+        writer.write_all(&((voxels.len() / 4) as u32).to_le_bytes())?;
+        writer.write_all(&voxels)
+    }
+}
+
+fn write_chunk_header<W: Write>(
+    writer: &mut W,
+    id: &[u8; 4],
+    content_size: usize,
+    children_size: usize,
+) -> io::Result<()> {
+    writer.write_all(id)?;
+    writer.write_all(&(content_size as u32).to_le_bytes())?;
+    writer.write_all(&(children_size as u32).to_le_bytes())
+}
+
+impl Domain {
+    /// Classify the cells of the domain without generating triangles.
+    ///
+    /// A cell is set when at least one of its corners is inside, which includes the cells the
+    /// surface passes through and the cells completely inside of it.
+    pub fn occupancy_grid<FIELD>(&self, field: &FIELD) -> BitGrid
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        let grid = self.sample_grid(field);
+        let mut result = BitGrid::new(self.width, self.height, self.depth);
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let cell_pos = IVec3 {
+                        x: x as i32,
+                        y: y as i32,
+                        z: z as i32,
+                    };
+                    let occupied = GRID_TO_VERT_OFFSETS
+                        .iter()
+                        .any(|offset| self.is_inside(grid.weight(cell_pos + *offset)));
+                    result.set(x, y, z, occupied);
+                }
+            }
+        }
+        result
+    }
+}
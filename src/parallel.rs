@@ -0,0 +1,88 @@
+use rayon::prelude::*;
+
+use crate::{
+    Domain, Float, IVec3, Mesh, RefineStrategy, ScalarField, Vec3, builder::MeshBuilder,
+    result::MarchResult,
+};
+
+/// Mesh of a slab with its sampled weight range and bounds.
+type Slab = (Mesh, Float, Float, Option<(Vec3, Vec3)>);
+
+impl Domain {
+    /// Same as [Domain::march_tetrahedras], but the domain is split into slabs along the x axis
+    /// that are marched on the current rayon thread pool.
+    ///
+    /// The slabs are merged in order, so the mesh is identical to the one of
+    /// [Domain::march_tetrahedras]. `threads` is the number of slabs, 0 uses a slab per thread
+    /// of the pool. Run it in [rayon::ThreadPool::install] to limit the threads.
+    pub fn march_tetrahedras_parallel<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+        threads: usize,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + Sync + ?Sized,
+        REFINE: RefineStrategy + Sync,
+    {
+        let threads = match threads {
+            0 => rayon::current_num_threads(),
+            threads => threads,
+        };
+        let size = self.vertex_grid_size();
+        let slab_width = (size.x as usize).div_ceil(threads).max(1) as i32;
+        let domain = &*self;
+        let slabs = (0..size.x)
+            .step_by(slab_width as usize)
+            .collect::<Vec<i32>>()
+            .into_par_iter()
+            .map(|x| {
+                let from = IVec3 { x, y: 0, z: 0 };
+                let to = IVec3 {
+                    x: (x + slab_width).min(size.x),
+                    ..size
+                };
+                let mut mesh = MeshBuilder::default();
+                let (min_weight, max_weight) =
+                    domain.march_cells(&mut mesh, from, to, field, refine);
+                let mesh = mesh.finish();
+                let bounds = mesh.bounds();
+                (mesh, min_weight, max_weight, bounds)
+            })
+            .collect::<Vec<Slab>>();
+
+        // Faces are appended unchanged, vertices on the planes between slabs are counted twice,
+        // so the buffers never grow while merging.
+        let face_count = slabs.iter().map(|slab| slab.0.faces.len()).sum();
+        let vert_count = slabs.iter().map(|slab| slab.0.verts.len()).sum();
+        let mut mesh = MeshBuilder::with_capacity(vert_count, face_count);
+        let mut min_weight = Float::INFINITY;
+        let mut max_weight = Float::NEG_INFINITY;
+        let mut bounds: Option<(Vec3, Vec3)> = None;
+        for (slab, slab_min_weight, slab_max_weight, slab_bounds) in slabs {
+            mesh.append(slab);
+            min_weight = min_weight.min(slab_min_weight);
+            max_weight = max_weight.max(slab_max_weight);
+            bounds = match (bounds, slab_bounds) {
+                (Some((min, max)), Some((slab_min, slab_max))) => Some((
+                    Vec3 {
+                        x: min.x.min(slab_min.x),
+                        y: min.y.min(slab_min.y),
+                        z: min.z.min(slab_min.z),
+                    },
+                    Vec3 {
+                        x: max.x.max(slab_max.x),
+                        y: max.y.max(slab_max.y),
+                        z: max.z.max(slab_max.z),
+                    },
+                )),
+                (bounds, slab_bounds) => bounds.or(slab_bounds),
+            };
+        }
+        let mesh = mesh.finish();
+        let result =
+            MarchResult::with_bounds(&mesh, min_weight, max_weight, self.surface_weight, bounds);
+        self.meshes.push(mesh);
+        result
+    }
+}
@@ -0,0 +1,198 @@
+//! Split a large extraction into work items for separate processes or machines.
+//!
+//! [partition_plan] divides the cells of a domain into sub-domains with about the same number of
+//! cells that share the lattice of the domain. Each [WorkItem] has a single line text form, so
+//! it can be passed on a command line or through a queue. Workers call [WorkItem::domain] and
+//! march it, the meshes are merged in any order with [crate::Mesh::weld_with]:
+//!
+//! ```
+//! use marching_cubes::{partition::{WorkItem, partition_plan}, prelude::*};
+//!
+//! let domain = MarchConfig::preset(Preset::Preview).domain(
+//!     Vec3 { x: -4.0, y: -4.0, z: -4.0 },
+//!     Vec3 { x: 4.0, y: 4.0, z: 4.0 },
+//!     1.0,
+//! );
+//! let metaballs = Metaballs::new(vec![Force { position: Vec3::default(), force: 2.0 }]);
+//! let mut merged = Mesh::default();
+//! for item in partition_plan(&domain, 4, 0) {
+//!     // Sent to a worker as text.
+//!     let item = WorkItem::parse(&item.to_string()).unwrap();
+//!     let mut sub_domain = item.domain();
+//!     sub_domain.march_tetrahedras(&metaballs, &Bisection);
+//!     merged.weld_with(&sub_domain.meshes[0]);
+//! }
+//! ```
+
+use std::{collections::HashMap, fmt, io, str::FromStr};
+
+use crate::{Domain, Float, Vec3};
+
+/// Sub-domain to march, see [partition_plan].
+#[derive(Debug, Clone)]
+pub struct WorkItem {
+    pub index: usize,
+    /// First cell of the sub-domain within the partitioned domain, including the halo.
+    pub first_cell: [usize; 3],
+    pub from: Vec3,
+    pub to: Vec3,
+    /// Number of cells along x, y and z.
+    pub resolution: [usize; 3],
+    pub surface_weight: Float,
+    pub grid_offset: Vec3,
+}
+
+impl WorkItem {
+    /// Empty domain of the work item. It shares the lattice of the partitioned domain, so its
+    /// mesh welds with the meshes of the other work items.
+    pub fn domain(&self) -> Domain {
+        Domain {
+            from: self.from,
+            to: self.to,
+            surface_weight: self.surface_weight,
+            width: self.resolution[0],
+            height: self.resolution[1],
+            depth: self.resolution[2],
+            grid_offset: self.grid_offset,
+            meshes: Vec::new(),
+        }
+    }
+
+    /// Parse the text form written by the [fmt::Display] implementation.
+    pub fn parse(text: &str) -> io::Result<WorkItem> {
+        let invalid_data = |message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("work item: {message}"))
+        };
+        let mut values = HashMap::new();
+        for field in text.split_whitespace() {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| invalid_data(format!("expected key=value, found `{field}`")))?;
+            values.insert(key, value);
+        }
+        let value = |key: &str| {
+            values
+                .get(key)
+                .copied()
+                .ok_or_else(|| invalid_data(format!("missing `{key}`")))
+        };
+        fn parse_all<T: FromStr>(text: &str) -> Option<[T; 3]> {
+            let mut elements = text.split(',').map(|element| element.parse::<T>().ok());
+            let result = [elements.next()??, elements.next()??, elements.next()??];
+            elements.next().is_none().then_some(result)
+        }
+        let vector = |key: &str| {
+            parse_all::<Float>(value(key)?)
+                .map(|[x, y, z]| Vec3 { x, y, z })
+                .ok_or_else(|| invalid_data(format!("`{key}` must be 3 numbers")))
+        };
+        let cells = |key: &str| {
+            parse_all::<usize>(value(key)?)
+                .ok_or_else(|| invalid_data(format!("`{key}` must be 3 cell counts")))
+        };
+        Ok(WorkItem {
+            index: value("index")?
+                .parse()
+                .map_err(|_| invalid_data("`index` must be a number".to_string()))?,
+            first_cell: cells("first_cell")?,
+            from: vector("from")?,
+            to: vector("to")?,
+            resolution: cells("resolution")?,
+            surface_weight: value("surface_weight")?
+                .parse()
+                .map_err(|_| invalid_data("`surface_weight` must be a number".to_string()))?,
+            grid_offset: vector("grid_offset")?,
+        })
+    }
+}
+
+impl fmt::Display for WorkItem {
+    /// Single line of `key=value` pairs that [WorkItem::parse] reads back exactly.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [x, y, z] = self.first_cell;
+        let [width, height, depth] = self.resolution;
+        write!(
+            f,
+            "index={} first_cell={x},{y},{z} from={},{},{} to={},{},{} resolution={width},{height},{depth} surface_weight={} grid_offset={},{},{}",
+            self.index,
+            self.from.x,
+            self.from.y,
+            self.from.z,
+            self.to.x,
+            self.to.y,
+            self.to.z,
+            self.surface_weight,
+            self.grid_offset.x,
+            self.grid_offset.y,
+            self.grid_offset.z,
+        )
+    }
+}
+
+/// Split the cells of `domain` into at most `workers` sub-domains with about the same number of
+/// cells, by repeatedly cutting the longest axis.
+///
+/// Every domain also marches the first cell past its upper bounds, so neighboring work items
+/// overlap by a cell and their meshes weld without gaps with `halo` 0. A larger `halo` extends
+/// each sub-domain by that many cells on every side within the domain, for post processing that
+/// needs the neighborhood of the border such as smoothing or face normals; the overlapping faces
+/// are skipped by [crate::Mesh::weld_with]. Fewer items are returned when the domain has fewer
+/// cells than workers.
+pub fn partition_plan(domain: &Domain, workers: usize, halo: usize) -> Vec<WorkItem> {
+    let size = [domain.width, domain.height, domain.depth];
+    let mut boxes = Vec::new();
+    split_cells([0; 3], size, workers.max(1), &mut boxes);
+
+    let cell_size = [
+        (domain.to.x - domain.from.x) / domain.width as Float,
+        (domain.to.y - domain.from.y) / domain.height as Float,
+        (domain.to.z - domain.from.z) / domain.depth as Float,
+    ];
+    let position = |cell: [usize; 3]| Vec3 {
+        x: domain.from.x + cell[0] as Float * cell_size[0],
+        y: domain.from.y + cell[1] as Float * cell_size[1],
+        z: domain.from.z + cell[2] as Float * cell_size[2],
+    };
+    boxes
+        .into_iter()
+        .enumerate()
+        .map(|(index, (first, last))| {
+            let first = [0, 1, 2].map(|axis| first[axis].saturating_sub(halo));
+            let last = [0, 1, 2].map(|axis| (last[axis] + halo).min(size[axis]));
+            WorkItem {
+                index,
+                first_cell: first,
+                from: position(first),
+                to: position(last),
+                resolution: [0, 1, 2].map(|axis| last[axis] - first[axis]),
+                surface_weight: domain.surface_weight,
+                grid_offset: domain.grid_offset,
+            }
+        })
+        .collect()
+}
+
+/// Split the cells from `first` up to `last` into `parts` boxes of about equal volume.
+fn split_cells(
+    first: [usize; 3],
+    last: [usize; 3],
+    parts: usize,
+    boxes: &mut Vec<([usize; 3], [usize; 3])>,
+) {
+    let axis = (0..3)
+        .max_by_key(|axis| last[*axis] - first[*axis])
+        .unwrap();
+    let length = last[axis] - first[axis];
+    if parts == 1 || length < 2 {
+        boxes.push((first, last));
+        return;
+    }
+    let lower_parts = parts / 2;
+    let cut = first[axis] + (length * lower_parts / parts).max(1);
+    let mut lower_last = last;
+    lower_last[axis] = cut;
+    let mut upper_first = first;
+    upper_first[axis] = cut;
+    split_cells(first, lower_last, lower_parts, boxes);
+    split_cells(upper_first, last, parts - lower_parts, boxes);
+}
@@ -0,0 +1,14 @@
+//! Types needed by most users of the crate.
+//!
+//! ```
+//! use marching_cubes::prelude::*;
+//! ```
+
+pub use crate::{
+    Bisection, Domain, Float, LinearInterp, Mesh, Midpoint, RefineStrategy, ScalarField, Vec3,
+    WeightInterp,
+    config::{MarchConfig, Preset},
+    extraction::ExtractionMethod,
+    field::{EllipsoidForce, Force, LineForce, Metaballs},
+    result::{EmptyDiagnosis, MarchResult},
+};
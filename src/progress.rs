@@ -0,0 +1,89 @@
+use std::cell::Cell;
+
+use crate::{
+    Domain, Float, IVec3, RefineStrategy, ScalarField, Vec3, builder::MeshBuilder,
+    result::MarchResult,
+};
+
+/// State of a march reported by [Domain::march_tetrahedras_with_progress].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Progress {
+    /// Number of planes of cells with the same x that are marched.
+    pub planes_done: usize,
+    pub plane_count: usize,
+    /// Faces generated so far.
+    pub faces: usize,
+    /// Number of times the field was sampled so far, including samples of the refine strategy.
+    pub samples: usize,
+}
+
+impl Progress {
+    /// Fraction of the march that is done, between 0.0 and 1.0.
+    pub fn fraction(&self) -> Float {
+        if self.plane_count == 0 {
+            1.0
+        } else {
+            self.planes_done as Float / self.plane_count as Float
+        }
+    }
+}
+
+/// Field counting how often it is sampled.
+struct CountingField<'a, FIELD: ?Sized> {
+    field: &'a FIELD,
+    samples: Cell<usize>,
+}
+
+impl<FIELD> ScalarField for CountingField<'_, FIELD>
+where
+    FIELD: ScalarField + ?Sized,
+{
+    fn sample(&self, position: Vec3) -> Float {
+        self.samples.set(self.samples.get() + 1);
+        self.field.sample(position)
+    }
+
+    fn analytic_gradient(&self, position: Vec3) -> Option<Vec3> {
+        self.field.analytic_gradient(position)
+    }
+}
+
+impl Domain {
+    /// Same as [Domain::march_tetrahedras], calling `progress` after each plane of cells with the
+    /// same x.
+    pub fn march_tetrahedras_with_progress<FIELD, REFINE, PROGRESS>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+        mut progress: PROGRESS,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+        PROGRESS: FnMut(&Progress),
+    {
+        let field = CountingField {
+            field,
+            samples: Cell::new(0),
+        };
+        let cell_count = self.vertex_grid_size();
+        let mut state = Progress {
+            plane_count: cell_count.x as usize,
+            ..Progress::default()
+        };
+        progress(&state);
+        let mut mesh = MeshBuilder::default();
+        let origin = IVec3 { x: 0, y: 0, z: 0 };
+        let (min_weight, max_weight) =
+            self.march_cells_with(&mut mesh, origin, cell_count, &field, refine, &mut |mesh| {
+                state.planes_done += 1;
+                state.faces = mesh.face_count();
+                state.samples = field.samples.get();
+                progress(&state);
+            });
+        let mesh = mesh.finish();
+        let result = MarchResult::new(&mesh, min_weight, max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+}
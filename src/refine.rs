@@ -0,0 +1,300 @@
+use std::mem::swap;
+
+use crate::{Float, ScalarField, Vec3};
+
+/// Root finder placing a vertex where the surface crosses an edge of the lattice.
+pub trait RefineStrategy {
+    /// Position of the surface between `v1` and `v2`. One of the positions is inside the
+    /// surface, the other is outside.
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, field: &FIELD, surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized;
+
+    /// Same as [RefineStrategy::refine] when the weights `w1` and `w2` of the field at `v1` and
+    /// `v2` are already known. The marchers call this with the weights they sampled at the
+    /// lattice vertices, strategies that only need the weights at the ends of the edge can use
+    /// them instead of sampling the field again.
+    fn refine_with_weights<FIELD>(
+        &self,
+        v1: Vec3,
+        w1: Float,
+        v2: Vec3,
+        w2: Float,
+        field: &FIELD,
+        surface_weight: Float,
+    ) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        let _ = (w1, w2);
+        self.refine(v1, v2, field, surface_weight)
+    }
+}
+
+/// Factor between two weights where the surface weight is crossed, assuming the weight changes
+/// linearly along the edge.
+fn linear_factor(w1: Float, w2: Float, surface_weight: Float) -> Float {
+    if w1 != w2 {
+        ((surface_weight - w1) / (w2 - w1)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    }
+}
+
+/// Place the vertex in the middle of the edge without sampling the field. Fast, but the
+/// resulting surface is faceted.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Midpoint;
+
+impl RefineStrategy for Midpoint {
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, _field: &FIELD, _surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        v1.lerp(v2, 0.5)
+    }
+}
+
+/// Search the crossing by halving the edge 8 times. Robust for any field, but samples the field
+/// 10 times per vertex. See [BisectionWithTolerance] to choose the number of steps.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Bisection;
+
+impl RefineStrategy for Bisection {
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, field: &FIELD, surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        BisectionWithTolerance::default().refine(v1, v2, field, surface_weight)
+    }
+}
+
+/// [Bisection] with a configurable number of steps that stops early once the crossing is known
+/// precisely enough. Samples the field at most `max_iterations + 2` times per vertex.
+///
+/// ```
+/// use marching_cubes::{BisectionWithTolerance, RefineStrategy, Vec3};
+///
+/// let plane = |position: Vec3| position.x;
+/// let from = Vec3 { x: -1.0, y: 0.0, z: 0.0 };
+/// let to = Vec3 { x: 3.0, y: 0.0, z: 0.0 };
+/// let refine = BisectionWithTolerance {
+///     max_iterations: 32,
+///     absolute_tolerance: 1e-3,
+///     ..BisectionWithTolerance::default()
+/// };
+/// assert!(refine.refine(from, to, &plane, 0.0).x.abs() <= 1e-3);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BisectionWithTolerance {
+    /// Maximum number of times the edge is halved.
+    pub max_iterations: u32,
+    /// Stop when the remaining part of the edge is at most this long.
+    pub absolute_tolerance: Float,
+    /// Stop when the remaining part of the edge is at most this fraction of the edge.
+    pub relative_tolerance: Float,
+}
+
+impl Default for BisectionWithTolerance {
+    /// The 8 steps of [Bisection], without stopping early.
+    fn default() -> Self {
+        BisectionWithTolerance {
+            max_iterations: 8,
+            absolute_tolerance: 0.0,
+            relative_tolerance: 0.0,
+        }
+    }
+}
+
+impl RefineStrategy for BisectionWithTolerance {
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, field: &FIELD, surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        let mut pos_left = v1;
+        let mut pos_right = v2;
+        let w_left = field.sample(pos_left);
+        let w_right = field.sample(pos_right);
+        if w_left > w_right {
+            swap(&mut pos_left, &mut pos_right);
+        }
+        let tolerance = self
+            .absolute_tolerance
+            .max(self.relative_tolerance * (v2 - v1).length());
+
+        let mut pos_center = pos_left;
+        for _ in 0..self.max_iterations {
+            pos_center = Midpoint.refine(pos_left, pos_right, field, surface_weight);
+            let w_center = field.sample(pos_center);
+            if w_center < surface_weight {
+                pos_left = pos_center;
+            } else {
+                pos_right = pos_center;
+            }
+            if tolerance > 0.0 && (pos_right - pos_left).length() <= tolerance {
+                break;
+            }
+        }
+
+        pos_center
+    }
+}
+
+/// Interpolate linearly between the weights at both ends of the edge. Exact for fields that are
+/// linear along the edge, such as signed distance fields of planes. Samples the field at both
+/// ends, see [WeightInterp] to reuse the weights of the lattice vertices.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct LinearInterp;
+
+impl RefineStrategy for LinearInterp {
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, field: &FIELD, surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        let w1 = field.sample(v1);
+        let w2 = field.sample(v2);
+        v1.lerp(v2, linear_factor(w1, w2, surface_weight))
+    }
+}
+
+/// Interpolate linearly between the weights the marcher already sampled at the lattice vertices,
+/// `t = (surface_weight - w1) / (w2 - w1)`. Places the vertex like [LinearInterp] without
+/// sampling the field at all, the fastest strategy that still follows the field.
+///
+/// Callers that don't know the weights use [RefineStrategy::refine], which samples the field at
+/// both ends like [LinearInterp].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct WeightInterp;
+
+impl RefineStrategy for WeightInterp {
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, field: &FIELD, surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        LinearInterp.refine(v1, v2, field, surface_weight)
+    }
+
+    fn refine_with_weights<FIELD>(
+        &self,
+        v1: Vec3,
+        w1: Float,
+        v2: Vec3,
+        w2: Float,
+        _field: &FIELD,
+        surface_weight: Float,
+    ) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        v1.lerp(v2, linear_factor(w1, w2, surface_weight))
+    }
+}
+
+/// Find the crossing with Newton's method along the edge. Fields with an
+/// [ScalarField::analytic_gradient] converge in 2 to 3 samples per vertex, other fields take
+/// secant steps. The crossing stays bracketed by the ends of the edge, steps that would leave the
+/// bracket fall back to the secant of the bracket, so it is as robust as [Bisection].
+///
+/// ```
+/// use marching_cubes::{Newton, RefineStrategy, Vec3, field::Sphere};
+///
+/// let sphere = Sphere {
+///     center: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+///     radius: 1.0,
+/// };
+/// let from = Vec3 { x: 0.5, y: 0.5, z: 0.5 };
+/// let to = Vec3 { x: 1.0, y: 1.0, z: 1.0 };
+/// let crossing = Newton::default().refine(from, to, &sphere, 0.0);
+/// assert!((crossing.length() - 1.0).abs() < 1e-4);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Newton {
+    /// Maximum number of samples between the ends of the edge.
+    pub max_iterations: u32,
+    /// Stop when the weight is within this fraction of the weights at the ends of the edge from
+    /// the surface weight.
+    pub tolerance: Float,
+}
+
+impl Default for Newton {
+    fn default() -> Self {
+        Newton {
+            max_iterations: 8,
+            tolerance: 1e-4,
+        }
+    }
+}
+
+impl RefineStrategy for Newton {
+    fn refine<FIELD>(&self, v1: Vec3, v2: Vec3, field: &FIELD, surface_weight: Float) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        let w1 = field.sample(v1);
+        let w2 = field.sample(v2);
+        self.refine_with_weights(v1, w1, v2, w2, field, surface_weight)
+    }
+
+    fn refine_with_weights<FIELD>(
+        &self,
+        v1: Vec3,
+        w1: Float,
+        v2: Vec3,
+        w2: Float,
+        field: &FIELD,
+        surface_weight: Float,
+    ) -> Vec3
+    where
+        FIELD: ScalarField + ?Sized,
+    {
+        // Solve f(t) = 0 for the position v1 + t * (v2 - v1), with the bracket kept as the
+        // factors `outside` where f < 0 and `inside` where f > 0.
+        let (f1, f2) = (w1 - surface_weight, w2 - surface_weight);
+        let ((mut outside, mut f_outside), (mut inside, mut f_inside)) = if f1 <= f2 {
+            ((0.0, f1), (1.0, f2))
+        } else {
+            ((1.0, f2), (0.0, f1))
+        };
+        if !(f_outside < 0.0 && f_inside > 0.0) {
+            return v1.lerp(v2, linear_factor(w1, w2, surface_weight));
+        }
+        let tolerance = self.tolerance * (f_inside - f_outside);
+        let direction = v2 - v1;
+        let secant = |outside: Float, f_outside: Float, inside: Float, f_inside: Float| {
+            outside + (inside - outside) * f_outside / (f_outside - f_inside)
+        };
+        let mut factor = secant(outside, f_outside, inside, f_inside);
+        // Side of the bracket moved by the previous step, for the Illinois modification.
+        let mut last_inside = None;
+        for _ in 0..self.max_iterations {
+            let position = v1.lerp(v2, factor);
+            let f = field.sample(position) - surface_weight;
+            if !f.is_finite() || f.abs() <= tolerance {
+                break;
+            }
+            let moved_inside = f > 0.0;
+            if moved_inside {
+                (inside, f_inside) = (factor, f);
+            } else {
+                (outside, f_outside) = (factor, f);
+            }
+            // Halve the weight of the end that didn't move twice in a row, so the secant
+            // doesn't get stuck on one side of a curved field.
+            if last_inside == Some(moved_inside) {
+                if moved_inside {
+                    f_outside *= 0.5;
+                } else {
+                    f_inside *= 0.5;
+                }
+            }
+            last_inside = Some(moved_inside);
+            let (low, high) = (outside.min(inside), outside.max(inside));
+            factor = field
+                .analytic_gradient(position)
+                .map(|gradient| factor - f / gradient.dot(direction))
+                .filter(|newton| *newton > low && *newton < high)
+                .unwrap_or_else(|| secant(outside, f_outside, inside, f_inside));
+        }
+        v1.lerp(v2, factor)
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    Domain, Edge, Face, Float, IVec3, Mesh, RefineStrategy, ScalarField, Vec3, field::gradient,
+};
+
+/// Largest number of fine cells along each axis of a cell tried by [Mesh::refine_region].
+pub const MAX_REGION_SUBDIVISIONS: usize = 16;
+
+/// Summary of a call to [Mesh::refine_region].
+#[derive(Debug, Clone)]
+pub struct RegionRefinement {
+    /// Number of fine cells along each axis of an original cell. 1 when the region was already
+    /// accurate enough and the mesh wasn't changed.
+    pub subdivisions: usize,
+    /// Largest estimated distance between the center of a face in the region and the surface.
+    pub estimated_error: Float,
+    /// Edges on the border of the region that couldn't be stitched and are left open. This
+    /// happens when the finer resolution finds surface crossings on the border that the original
+    /// resolution missed.
+    pub open_edges: usize,
+}
+
+fn centroid(verts: &[Vec3], face: &Face) -> Vec3 {
+    (verts[face.v1] + verts[face.v2] + verts[face.v3]) * (1.0 / 3.0)
+}
+
+fn components(vec: Vec3) -> [Float; 3] {
+    [vec.x, vec.y, vec.z]
+}
+
+/// Largest distance between the face centers and the surface, estimated using the gradient.
+fn surface_error<'a, FIELD, FACES>(
+    verts: &[Vec3],
+    faces: FACES,
+    field: &FIELD,
+    surface_weight: Float,
+    gradient_step: Float,
+) -> Float
+where
+    FIELD: ScalarField + ?Sized,
+    FACES: Iterator<Item = &'a Face>,
+{
+    faces
+        .map(|face| {
+            let center = centroid(verts, face);
+            let slope = gradient(field, center, gradient_step).length();
+            if slope > Float::EPSILON {
+                (field.sample(center) - surface_weight).abs() / slope
+            } else {
+                0.0
+            }
+        })
+        .fold(0.0, Float::max)
+}
+
+impl Mesh {
+    /// Re-march the cells of `domain` that are completely inside `from`..`to` at a higher
+    /// resolution and stitch the result into this mesh.
+    ///
+    /// The mesh must be generated by `domain`. The resolution is doubled until the estimated
+    /// distance between the faces and the surface is at most `target_error`, up to
+    /// [MAX_REGION_SUBDIVISIONS] times the original resolution. Faces are assigned to the region
+    /// by their center, which always lies inside the tetrahedron that generated the face.
+    ///
+    /// Border vertices of the region are kept and the finer border is stitched to them with
+    /// extra faces. The finer lattice doesn't fit the edge keys of the original one, so vertex
+    /// keys, texture coordinates and tangents are cleared.
+    #[allow(clippy::too_many_arguments)]
+    pub fn refine_region<FIELD, REFINE>(
+        &mut self,
+        domain: &Domain,
+        from: Vec3,
+        to: Vec3,
+        field: &FIELD,
+        refine: &REFINE,
+        target_error: Float,
+    ) -> RegionRefinement
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let cell_size = components(domain.to - domain.from);
+        let cell_size = [
+            cell_size[0] / domain.width as Float,
+            cell_size[1] / domain.height as Float,
+            cell_size[2] / domain.depth as Float,
+        ];
+        let grid_size = domain.vertex_grid_size();
+        let grid_size = [grid_size.x, grid_size.y, grid_size.z];
+        let (domain_from, grid_offset) = (components(domain.from), components(domain.grid_offset));
+        let (region_from, region_to) = (components(from), components(to));
+        let mut first = [0; 3];
+        let mut last = [0; 3];
+        for axis in 0..3 {
+            let index = |position: Float| {
+                (position - domain_from[axis]) / cell_size[axis] + grid_offset[axis]
+            };
+            first[axis] = (index(region_from[axis]).ceil().max(0.0) as i32).min(grid_size[axis]);
+            last[axis] =
+                (index(region_to[axis]).floor() as i32).clamp(first[axis], grid_size[axis]);
+        }
+        let to_ivec3 = |index: [i32; 3]| IVec3 {
+            x: index[0],
+            y: index[1],
+            z: index[2],
+        };
+        let box_from = domain.vertex_position(to_ivec3(first));
+        let box_to = domain.vertex_position(to_ivec3(last));
+        let inside = |position: Vec3| {
+            (0..3).all(|axis| {
+                let position = components(position)[axis];
+                position > components(box_from)[axis] && position < components(box_to)[axis]
+            })
+        };
+        let gradient_step = cell_size.iter().copied().fold(Float::INFINITY, Float::min)
+            / (MAX_REGION_SUBDIVISIONS * 10) as Float;
+
+        let removed = self
+            .faces
+            .iter()
+            .map(|face| inside(centroid(&self.verts, face)))
+            .collect::<Vec<bool>>();
+        let unchanged = RegionRefinement {
+            subdivisions: 1,
+            estimated_error: surface_error(
+                &self.verts,
+                self.faces
+                    .iter()
+                    .zip(&removed)
+                    .filter(|(_, removed)| **removed)
+                    .map(|(face, _)| face),
+                field,
+                domain.surface_weight,
+                gradient_step,
+            ),
+            open_edges: 0,
+        };
+        if (0..3).any(|axis| first[axis] == last[axis])
+            || (removed.contains(&true) && unchanged.estimated_error <= target_error)
+        {
+            return unchanged;
+        }
+
+        let mut subdivisions = 2;
+        let (fine, estimated_error) = loop {
+            let cells = (0..3)
+                .map(|axis| (last[axis] - first[axis]) as usize * subdivisions)
+                .collect::<Vec<usize>>();
+            let mut fine_domain = Domain {
+                from: box_from,
+                to: box_to,
+                surface_weight: domain.surface_weight,
+                width: cells[0],
+                height: cells[1],
+                depth: cells[2],
+                grid_offset: Vec3::default(),
+                meshes: Vec::new(),
+            };
+            fine_domain.march_tetrahedras(field, refine);
+            let mut fine = fine_domain.meshes.pop().unwrap();
+            // The march covers one extra cell past `to`.
+            let verts = &fine.verts;
+            fine.faces.retain(|face| inside(centroid(verts, face)));
+            let error = surface_error(
+                &fine.verts,
+                fine.faces.iter(),
+                field,
+                domain.surface_weight,
+                gradient_step,
+            );
+            if error <= target_error || subdivisions >= MAX_REGION_SUBDIVISIONS {
+                break (fine, error);
+            }
+            subdivisions *= 2;
+        };
+
+        // Border of the hole, in the winding of the faces that are kept.
+        let removed_edges = self
+            .faces
+            .iter()
+            .zip(&removed)
+            .filter(|(_, removed)| **removed)
+            .flat_map(|(face, _)| face_edges(face))
+            .collect::<HashSet<(usize, usize)>>();
+        let hole = self
+            .faces
+            .iter()
+            .zip(&removed)
+            .filter(|(_, removed)| !**removed)
+            .flat_map(|(face, _)| face_edges(face))
+            .filter(|(u, v)| removed_edges.contains(&(*v, *u)))
+            .collect::<Vec<(usize, usize)>>();
+
+        let fine_edges = fine
+            .faces
+            .iter()
+            .flat_map(face_edges)
+            .collect::<HashSet<(usize, usize)>>();
+        let mut fine_next = HashMap::new();
+        for (f, g) in &fine_edges {
+            if !fine_edges.contains(&(*g, *f)) {
+                fine_next.entry(*f).or_insert(*g);
+            }
+        }
+
+        // Match each border vertex of the hole with the closest border vertex of the fine mesh.
+        let mut coarse_to_fine = HashMap::new();
+        for vert in hole.iter().flat_map(|(u, v)| [*u, *v]) {
+            if coarse_to_fine.contains_key(&vert) {
+                continue;
+            }
+            let closest = fine_next.keys().copied().min_by(|a, b| {
+                let distance_a = (fine.verts[*a] - self.verts[vert]).length();
+                let distance_b = (fine.verts[*b] - self.verts[vert]).length();
+                distance_a.total_cmp(&distance_b).then(a.cmp(b))
+            });
+            if let Some(closest) = closest {
+                coarse_to_fine.insert(vert, closest);
+            }
+        }
+        let matched = coarse_to_fine.values().copied().collect::<HashSet<usize>>();
+
+        let mut result = Mesh::default();
+        let mut coarse_map = HashMap::new();
+        let mut faces = Vec::new();
+        for (face, _) in self
+            .faces
+            .iter()
+            .zip(&removed)
+            .filter(|(_, removed)| !**removed)
+        {
+            let verts = face_verts(face).map(|vert| {
+                *coarse_map.entry(vert).or_insert_with(|| {
+                    result.verts.push(self.verts[vert]);
+                    result.verts.len() - 1
+                })
+            });
+            faces.push(verts);
+        }
+        let fine_used = fine
+            .faces
+            .iter()
+            .flat_map(face_verts)
+            .collect::<HashSet<usize>>();
+        let fine_map = (0..fine.verts.len())
+            .map(|vert| {
+                if !fine_used.contains(&vert) {
+                    return usize::MAX;
+                }
+                result.verts.push(fine.verts[vert]);
+                result.verts.len() - 1
+            })
+            .collect::<Vec<usize>>();
+        for face in &fine.faces {
+            faces.push(face_verts(face).map(|vert| fine_map[vert]));
+        }
+
+        // Fill the gap between each border edge of the hole and the chain of fine border edges
+        // between the vertices matched with its ends. Consecutive gaps share the edge between a
+        // border vertex and its match.
+        let mut open_edges = 0;
+        let mut stitched = HashSet::new();
+        for (u, v) in hole {
+            let (Some(fine_u), Some(fine_v)) = (coarse_to_fine.get(&u), coarse_to_fine.get(&v))
+            else {
+                open_edges += 1;
+                continue;
+            };
+            let mut chain = vec![*fine_v];
+            let connected = fine_u == fine_v
+                || loop {
+                    match fine_next.get(chain.last().unwrap()) {
+                        Some(next) if next == fine_u => {
+                            chain.push(*next);
+                            break true;
+                        }
+                        Some(next) if !matched.contains(next) && chain.len() < fine_next.len() => {
+                            chain.push(*next)
+                        }
+                        _ => break false,
+                    }
+                };
+            if !connected {
+                open_edges += 1;
+                continue;
+            }
+            stitched.extend(chain[..chain.len() - 1].iter().copied());
+            let (u, v) = (coarse_map[&u], coarse_map[&v]);
+            let mut previous = u;
+            for fine_vert in chain.iter().rev() {
+                let vert = fine_map[*fine_vert];
+                faces.push([v, previous, vert]);
+                previous = vert;
+            }
+        }
+
+        // Fine border edges that weren't stitched, except for those on the bounds of the march.
+        let on_march_bounds = |vert: usize| {
+            let position = components(fine.verts[vert]);
+            (0..3).any(|axis| {
+                let tolerance = cell_size[axis] * 1e-6;
+                (first[axis] == 0
+                    && (position[axis] - components(box_from)[axis]).abs() <= tolerance)
+                    || (last[axis] == grid_size[axis]
+                        && (position[axis] - components(box_to)[axis]).abs() <= tolerance)
+            })
+        };
+        open_edges += fine_next
+            .iter()
+            .filter(|(f, _)| !stitched.contains(*f))
+            .filter(|(f, g)| !on_march_bounds(**f) || !on_march_bounds(**g))
+            .count();
+
+        for [v1, v2, v3] in faces {
+            result.faces.push(Face { v1, v2, v3 });
+            for (v1, v2) in [(v1, v2), (v2, v3), (v3, v1)] {
+                result.edges.push(Edge { v1, v2 });
+            }
+        }
+        *self = result;
+        RegionRefinement {
+            subdivisions,
+            estimated_error,
+            open_edges,
+        }
+    }
+}
+
+fn face_verts(face: &Face) -> [usize; 3] {
+    [face.v1, face.v2, face.v3]
+}
+
+fn face_edges(face: &Face) -> [(usize, usize); 3] {
+    [(face.v1, face.v2), (face.v2, face.v3), (face.v3, face.v1)]
+}
@@ -0,0 +1,265 @@
+use std::{fmt, mem::size_of};
+
+use crate::{Edge, EdgeKey, Face, Float, Mesh, Vec3, limits::LimitExceeded};
+
+/// Summary of a single call to `Domain::march_tetrahedras`.
+#[derive(Debug, Clone)]
+pub struct MarchResult {
+    pub face_count: usize,
+    /// Lowest weight sampled at the grid vertices.
+    pub min_weight: Float,
+    /// Highest weight sampled at the grid vertices.
+    pub max_weight: Float,
+    /// Explanation why the mesh is empty, `None` when faces were generated.
+    pub empty_diagnosis: Option<EmptyDiagnosis>,
+    /// Vertex sharing and memory use of the generated mesh.
+    pub stats: MeshStats,
+    /// Bounding box of the generated mesh, see [Mesh::bounds].
+    pub bounds: Option<(Vec3, Vec3)>,
+    /// Limit that stopped the march early, the mesh only covers part of the domain. See
+    /// [crate::Domain::march_tetrahedras_with_limits].
+    pub limit_exceeded: Option<LimitExceeded>,
+}
+
+/// Counts showing what sharing vertices between faces saves.
+///
+/// Vertices generated on the same lattice edge are welded into a single vertex. Without
+/// welding each face would have its own 3 vertices.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct MeshStats {
+    /// Vertices the mesh would have without welding, 3 per face.
+    pub unwelded_vertex_count: usize,
+    pub vertex_count: usize,
+    /// Entries of [Mesh::edges], every face adds its 3 edges.
+    pub edge_count: usize,
+    /// Edges after merging the entries of [Mesh::edges] that connect the same vertices.
+    pub unique_edge_count: usize,
+    /// Bytes used by the vertex, face and edge buffers and the per vertex attributes.
+    pub memory_bytes: usize,
+}
+
+/// Reason why marching didn't produce any faces.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EmptyDiagnosis {
+    /// All samples are below the surface weight. The surface is outside the bounds or the surface
+    /// weight is too high.
+    AllOutside {
+        max_weight: Float,
+        surface_weight: Float,
+    },
+    /// All samples are above the surface weight. The bounds are completely inside the surface or
+    /// the surface weight is too low.
+    AllInside {
+        min_weight: Float,
+        surface_weight: Float,
+    },
+    /// The field has samples on both sides of the surface weight, but no tetrahedra crossed it.
+    /// This happens when the weight function doesn't return finite values.
+    NoCrossings {
+        min_weight: Float,
+        max_weight: Float,
+    },
+}
+
+impl MarchResult {
+    pub fn new(
+        mesh: &Mesh,
+        min_weight: Float,
+        max_weight: Float,
+        surface_weight: Float,
+    ) -> MarchResult {
+        MarchResult::with_bounds(mesh, min_weight, max_weight, surface_weight, mesh.bounds())
+    }
+
+    /// Same as [MarchResult::new] with bounds that are already known, for example reduced from
+    /// the bounds of the parts the mesh was merged from.
+    pub(crate) fn with_bounds(
+        mesh: &Mesh,
+        min_weight: Float,
+        max_weight: Float,
+        surface_weight: Float,
+        bounds: Option<(Vec3, Vec3)>,
+    ) -> MarchResult {
+        let empty_diagnosis = if !mesh.faces.is_empty() {
+            None
+        } else if max_weight <= surface_weight {
+            Some(EmptyDiagnosis::AllOutside {
+                max_weight,
+                surface_weight,
+            })
+        } else if min_weight > surface_weight {
+            Some(EmptyDiagnosis::AllInside {
+                min_weight,
+                surface_weight,
+            })
+        } else {
+            Some(EmptyDiagnosis::NoCrossings {
+                min_weight,
+                max_weight,
+            })
+        };
+        MarchResult {
+            face_count: mesh.faces.len(),
+            min_weight,
+            max_weight,
+            empty_diagnosis,
+            stats: MeshStats::new(mesh),
+            bounds,
+            limit_exceeded: None,
+        }
+    }
+}
+
+impl MeshStats {
+    pub fn new(mesh: &Mesh) -> MeshStats {
+        let mut unique_edges = mesh
+            .edges
+            .iter()
+            .map(|edge| (edge.v1.min(edge.v2), edge.v1.max(edge.v2)))
+            .collect::<Vec<(usize, usize)>>();
+        unique_edges.sort_unstable();
+        unique_edges.dedup();
+        MeshStats {
+            unwelded_vertex_count: mesh.faces.len() * 3,
+            vertex_count: mesh.verts.len(),
+            edge_count: mesh.edges.len(),
+            unique_edge_count: unique_edges.len(),
+            memory_bytes: mesh.verts.len() * size_of::<Vec3>()
+                + mesh.faces.len() * size_of::<Face>()
+                + mesh.edges.len() * size_of::<Edge>()
+                + mesh.vert_keys.len() * size_of::<EdgeKey>()
+                + mesh.normals.len() * size_of::<Vec3>()
+                + mesh.uvs.len() * size_of::<[Float; 2]>()
+                + mesh.tangents.len() * size_of::<[Float; 4]>()
+                + mesh.joints.len() * size_of::<[u16; 4]>()
+                + mesh.joint_weights.len() * size_of::<[Float; 4]>(),
+        }
+    }
+
+    /// Fraction of the unwelded vertices that were removed by welding.
+    pub fn duplicate_ratio(&self) -> f64 {
+        if self.unwelded_vertex_count == 0 {
+            0.0
+        } else {
+            1.0 - self.vertex_count as f64 / self.unwelded_vertex_count as f64
+        }
+    }
+
+    /// Bytes [MeshStats::memory_bytes] would use with indices of `index_size` bytes instead of
+    /// `usize`, to estimate what exporting or uploading with 16 or 32 bit indices takes.
+    pub fn memory_bytes_with_index_size(&self, index_size: usize) -> usize {
+        let index_count = self.unwelded_vertex_count + self.edge_count * 2;
+        self.memory_bytes - index_count * size_of::<usize>() + index_count * index_size
+    }
+}
+
+impl fmt::Display for MeshStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} vertices welded from {} ({:.1}% duplicates), {} unique edges from {}, {:.1} KiB",
+            self.vertex_count,
+            self.unwelded_vertex_count,
+            self.duplicate_ratio() * 100.0,
+            self.unique_edge_count,
+            self.edge_count,
+            self.memory_bytes as f64 / 1024.0
+        )
+    }
+}
+
+/// Area weighted distribution of the face normals of a mesh, to measure the directional bias of
+/// an extraction method.
+///
+/// The normals of a sphere point in all directions alike. Splitting cells along fixed diagonals
+/// can favor some directions over others, which shows as an [NormalDistribution::anisotropy]
+/// above 0 on a sphere.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct NormalDistribution {
+    /// Area weighted average of `normal * normal^T` over the faces. The identity divided by 3
+    /// when the normals are distributed uniformly. Off diagonal entries show a bias towards the
+    /// diagonals of the cells.
+    pub tensor: [[Float; 3]; 3],
+}
+
+impl NormalDistribution {
+    pub fn new(mesh: &Mesh) -> NormalDistribution {
+        let mut tensor = [[0.0; 3]; 3];
+        let mut total_area = 0.0;
+        for face in &mesh.faces {
+            let [a, b, c] = [face.v1, face.v2, face.v3].map(|vert| mesh.verts[vert]);
+            let cross = (b - a).cross(c - a);
+            let length = cross.length();
+            if length == 0.0 || !length.is_finite() {
+                continue;
+            }
+            // `normal * normal^T` times the area of the face.
+            let cross = [cross.x, cross.y, cross.z];
+            for (row, entries) in tensor.iter_mut().enumerate() {
+                for (column, entry) in entries.iter_mut().enumerate() {
+                    *entry += cross[row] * cross[column] / length * 0.5;
+                }
+            }
+            total_area += length * 0.5;
+        }
+        if total_area > 0.0 {
+            for entry in tensor.iter_mut().flatten() {
+                *entry /= total_area;
+            }
+        }
+        NormalDistribution { tensor }
+    }
+
+    /// Distance between [NormalDistribution::tensor] and the tensor of uniformly distributed
+    /// normals, 0 when no direction is favored and 1 when all normals are parallel.
+    pub fn anisotropy(&self) -> Float {
+        let mut squared = 0.0;
+        for (row, entries) in self.tensor.iter().enumerate() {
+            for (column, entry) in entries.iter().enumerate() {
+                let isotropic = if row == column { 1.0 / 3.0 } else { 0.0 };
+                squared += (entry - isotropic) * (entry - isotropic);
+            }
+        }
+        // A tensor with all normals along one axis is 6.sqrt() / 3 away from the identity / 3.
+        squared.sqrt() * 3.0 / (6.0 as Float).sqrt()
+    }
+}
+
+impl fmt::Display for NormalDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [[xx, xy, xz], [_, yy, yz], [_, _, zz]] = self.tensor;
+        write!(
+            f,
+            "normal anisotropy {:.5} (xx {xx:.4}, yy {yy:.4}, zz {zz:.4}, xy {xy:.4}, xz {xz:.4}, yz {yz:.4})",
+            self.anisotropy()
+        )
+    }
+}
+
+impl fmt::Display for EmptyDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmptyDiagnosis::AllOutside {
+                max_weight,
+                surface_weight,
+            } => write!(
+                f,
+                "empty mesh: all samples are outside (highest weight {max_weight} <= surface weight {surface_weight}), lower the surface weight or move the bounds towards the surface"
+            ),
+            EmptyDiagnosis::AllInside {
+                min_weight,
+                surface_weight,
+            } => write!(
+                f,
+                "empty mesh: all samples are inside (lowest weight {min_weight} > surface weight {surface_weight}), raise the surface weight or enlarge the bounds"
+            ),
+            EmptyDiagnosis::NoCrossings {
+                min_weight,
+                max_weight,
+            } => write!(
+                f,
+                "empty mesh: weights range from {min_weight} to {max_weight} but no tetrahedra crossed the surface, check the weight function for NaN values"
+            ),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Scene files describing a domain and metaballs, so the binary can be driven without writing
+//! Rust.
+//!
+//! Scenes use a subset of TOML: a `[domain]` table, a `[[force]]` table per force, a
+//! `[[segment]]` table per capsule shaped [LineForce] and an `[[ellipsoid]]` table per
+//! [EllipsoidForce]. Values are numbers or arrays of 3 numbers, `#` starts a comment.
+//!
+//! ```toml
+//! [domain]
+//! from = [-8, -8, -8]
+//! to = [8, 8, 8]
+//! resolution = 48            # or [width, height, depth]
+//! surface_weight = 1.0       # optional, defaults to 1.0
+//! grid_offset = [0, 0, 0]    # optional
+//!
+//! [[force]]
+//! position = [0, 0, 0]
+//! force = 2.5
+//!
+//! [[segment]]
+//! from = [0, 0, 0]
+//! to = [0, 4, 0]
+//! force = 1.0
+//!
+//! [[ellipsoid]]
+//! position = [4, 0, 0]
+//! force = 1.0
+//! radii = [2, 1, 1]
+//! axis = [0, 0, 1]           # optional, defaults to [0, 0, 1]
+//! angle = 0.5                # optional, radians, defaults to 0
+//! ```
+
+use std::{fs, io, path::Path};
+
+use crate::{
+    Domain, Float, Vec3,
+    field::{EllipsoidForce, Force, LineForce, Metaballs},
+};
+
+/// Domain and metaballs loaded from a scene file.
+#[derive(Debug)]
+pub struct Scene {
+    pub domain: Domain,
+    pub metaballs: Metaballs,
+}
+
+/// Value on the right hand side of a `key = value` line.
+enum Value {
+    Number(Float),
+    Vector([Float; 3]),
+}
+
+/// Line number, `from`, `to` and `force` of a `[[segment]]` table while it is parsed.
+type PartialSegment = (usize, Option<Vec3>, Option<Vec3>, Option<Float>);
+
+/// Line number, `position`, `force`, `radii`, `axis` and `angle` of an `[[ellipsoid]]` table
+/// while it is parsed.
+type PartialEllipsoid = (
+    usize,
+    Option<Vec3>,
+    Option<Float>,
+    Option<Vec3>,
+    Vec3,
+    Float,
+);
+
+#[derive(Copy, Clone, PartialEq)]
+enum Table {
+    None,
+    Domain,
+    Force,
+    Segment,
+    Ellipsoid,
+}
+
+fn invalid_data(line_number: usize, message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("scene line {line_number}: {message}"),
+    )
+}
+
+fn parse_value(text: &str) -> Option<Value> {
+    if let Some(elements) = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+    {
+        let elements = elements
+            .split(',')
+            .map(|element| element.trim().parse::<Float>().ok())
+            .collect::<Option<Vec<Float>>>()?;
+        let elements: [Float; 3] = elements.try_into().ok()?;
+        Some(Value::Vector(elements))
+    } else {
+        text.parse::<Float>().ok().map(Value::Number)
+    }
+}
+
+fn vector([x, y, z]: [Float; 3]) -> Vec3 {
+    Vec3 { x, y, z }
+}
+
+fn resolution(value: Float) -> Option<usize> {
+    (value >= 1.0 && value.fract() == 0.0).then_some(value as usize)
+}
+
+impl Scene {
+    /// Parse a scene from the contents of a scene file.
+    ///
+    /// Errors are [io::ErrorKind::InvalidData] with the line number in the message.
+    pub fn parse(source: &str) -> io::Result<Scene> {
+        let mut from = None;
+        let mut to = None;
+        let mut size = None;
+        let mut surface_weight = 1.0;
+        let mut grid_offset = Vec3::default();
+        // Forces with missing fields, completed when the next table starts.
+        let mut forces: Vec<(usize, Option<Vec3>, Option<Float>)> = Vec::new();
+        let mut segments: Vec<PartialSegment> = Vec::new();
+        let mut ellipsoids: Vec<PartialEllipsoid> = Vec::new();
+
+        let mut table = Table::None;
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line {
+                "[domain]" => {
+                    table = Table::Domain;
+                    continue;
+                }
+                "[[force]]" => {
+                    table = Table::Force;
+                    forces.push((line_number, None, None));
+                    continue;
+                }
+                "[[segment]]" => {
+                    table = Table::Segment;
+                    segments.push((line_number, None, None, None));
+                    continue;
+                }
+                "[[ellipsoid]]" => {
+                    table = Table::Ellipsoid;
+                    let z_axis = Vec3 {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 1.0,
+                    };
+                    ellipsoids.push((line_number, None, None, None, z_axis, 0.0));
+                    continue;
+                }
+                _ if line.starts_with('[') => {
+                    return Err(invalid_data(line_number, format!("unknown table `{line}`")));
+                }
+                _ => {}
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid_data(line_number, "expected `key = value`"));
+            };
+            let key = key.trim();
+            let value = parse_value(value.trim()).ok_or_else(|| {
+                invalid_data(line_number, "expected a number or an array of 3 numbers")
+            })?;
+            let unexpected = || invalid_data(line_number, format!("unexpected value for `{key}`"));
+            match (table, key, value) {
+                (Table::Domain, "from", Value::Vector(value)) => from = Some(vector(value)),
+                (Table::Domain, "to", Value::Vector(value)) => to = Some(vector(value)),
+                (Table::Domain, "resolution", Value::Number(value)) => {
+                    let value = resolution(value).ok_or_else(unexpected)?;
+                    size = Some([value; 3]);
+                }
+                (Table::Domain, "resolution", Value::Vector(values)) => {
+                    size = Some([
+                        resolution(values[0]).ok_or_else(unexpected)?,
+                        resolution(values[1]).ok_or_else(unexpected)?,
+                        resolution(values[2]).ok_or_else(unexpected)?,
+                    ]);
+                }
+                (Table::Domain, "surface_weight", Value::Number(value)) => surface_weight = value,
+                (Table::Domain, "grid_offset", Value::Vector(value)) => grid_offset = vector(value),
+                (Table::Force, "position", Value::Vector(value)) => {
+                    forces.last_mut().unwrap().1 = Some(vector(value))
+                }
+                (Table::Force, "force", Value::Number(value)) => {
+                    forces.last_mut().unwrap().2 = Some(value)
+                }
+                (Table::Segment, "from", Value::Vector(value)) => {
+                    segments.last_mut().unwrap().1 = Some(vector(value))
+                }
+                (Table::Segment, "to", Value::Vector(value)) => {
+                    segments.last_mut().unwrap().2 = Some(vector(value))
+                }
+                (Table::Segment, "force", Value::Number(value)) => {
+                    segments.last_mut().unwrap().3 = Some(value)
+                }
+                (Table::Ellipsoid, "position", Value::Vector(value)) => {
+                    ellipsoids.last_mut().unwrap().1 = Some(vector(value))
+                }
+                (Table::Ellipsoid, "force", Value::Number(value)) => {
+                    ellipsoids.last_mut().unwrap().2 = Some(value)
+                }
+                (Table::Ellipsoid, "radii", Value::Vector(value)) => {
+                    ellipsoids.last_mut().unwrap().3 = Some(vector(value))
+                }
+                (Table::Ellipsoid, "axis", Value::Vector(value)) => {
+                    ellipsoids.last_mut().unwrap().4 = vector(value)
+                }
+                (Table::Ellipsoid, "angle", Value::Number(value)) => {
+                    ellipsoids.last_mut().unwrap().5 = value
+                }
+                (Table::None, ..) => {
+                    return Err(invalid_data(line_number, "expected a table first"));
+                }
+                (_, "from" | "to" | "resolution" | "surface_weight" | "grid_offset", _)
+                | (Table::Force, "position" | "force", _)
+                | (Table::Segment, "force", _)
+                | (Table::Ellipsoid, "position" | "force" | "radii" | "axis" | "angle", _) => {
+                    return Err(unexpected());
+                }
+                _ => {
+                    return Err(invalid_data(line_number, format!("unknown key `{key}`")));
+                }
+            }
+        }
+
+        let line_count = source.lines().count();
+        let missing = |name: &str| invalid_data(line_count, format!("missing `{name}`"));
+        let from = from.ok_or_else(|| missing("domain.from"))?;
+        let to = to.ok_or_else(|| missing("domain.to"))?;
+        let [width, height, depth] = size.ok_or_else(|| missing("domain.resolution"))?;
+        if !(from.x < to.x && from.y < to.y && from.z < to.z) {
+            return Err(invalid_data(
+                line_count,
+                "`domain.from` must be below `domain.to` on every axis",
+            ));
+        }
+        let forces = forces
+            .into_iter()
+            .map(|(line_number, position, force)| match (position, force) {
+                (Some(position), Some(force)) => Ok(Force { position, force }),
+                (None, _) => Err(invalid_data(line_number, "force without `position`")),
+                (_, None) => Err(invalid_data(line_number, "force without `force`")),
+            })
+            .collect::<io::Result<Vec<Force>>>()?;
+        let lines = segments
+            .into_iter()
+            .map(|(line_number, from, to, force)| match (from, to, force) {
+                (Some(from), Some(to), Some(force)) => Ok(LineForce::segment(from, to, force)),
+                (None, ..) => Err(invalid_data(line_number, "segment without `from`")),
+                (_, None, _) => Err(invalid_data(line_number, "segment without `to`")),
+                (.., None) => Err(invalid_data(line_number, "segment without `force`")),
+            })
+            .collect::<io::Result<Vec<LineForce>>>()?;
+        let ellipsoids = ellipsoids
+            .into_iter()
+            .map(|(line_number, position, force, radii, axis, angle)| {
+                match (position, force, radii) {
+                    (Some(position), Some(force), Some(radii)) => Ok(EllipsoidForce {
+                        position,
+                        force,
+                        radii,
+                        axis,
+                        angle,
+                    }),
+                    (None, ..) => Err(invalid_data(line_number, "ellipsoid without `position`")),
+                    (_, None, _) => Err(invalid_data(line_number, "ellipsoid without `force`")),
+                    (.., None) => Err(invalid_data(line_number, "ellipsoid without `radii`")),
+                }
+            })
+            .collect::<io::Result<Vec<EllipsoidForce>>>()?;
+
+        Ok(Scene {
+            domain: Domain {
+                from,
+                to,
+                surface_weight,
+                width,
+                height,
+                depth,
+                grid_offset,
+                meshes: Vec::new(),
+            },
+            metaballs: Metaballs {
+                forces,
+                lines,
+                ellipsoids,
+            },
+        })
+    }
+
+    /// Read and parse the scene file at `path`, see [Scene::parse].
+    pub fn load(path: &Path) -> io::Result<Scene> {
+        Scene::parse(&fs::read_to_string(path)?)
+    }
+}
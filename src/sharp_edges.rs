@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use crate::{Edge, Face, Float, Mesh};
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+impl Mesh {
+    /// Copy of the mesh where vertices are split along edges whose dihedral angle exceeds
+    /// `max_angle` (in radians).
+    ///
+    /// Meant to be called right before exporting to engines that only support per vertex normals:
+    /// faces on both sides of a sharp edge get their own vertices so shading has a hard crease.
+    /// Faces around a vertex that are connected through smooth edges keep sharing a vertex.
+    /// [Mesh::normals] aren't copied, the vertices on each side of a crease need normals of their
+    /// own.
+    pub fn split_sharp_edges(&self, max_angle: Float) -> Mesh {
+        let min_cos = max_angle.cos();
+        let normals = self.compute_face_normals();
+
+        // Corners are indexed as `face_index * 3 + corner`. Corners sharing a vertex are joined
+        // when their faces share an edge that isn't sharp.
+        let mut parents = (0..self.faces.len() * 3).collect::<Vec<usize>>();
+        let mut edge_faces = HashMap::<(usize, usize), Vec<usize>>::new();
+        for (face_index, face) in self.faces.iter().enumerate() {
+            let verts = face_verts(face);
+            for corner in 0..3 {
+                let a = verts[corner];
+                let b = verts[(corner + 1) % 3];
+                edge_faces
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push(face_index);
+            }
+        }
+        for ((a, b), faces) in &edge_faces {
+            for (index, &face_a) in faces.iter().enumerate() {
+                for &face_b in &faces[index + 1..] {
+                    if normals[face_a].dot(normals[face_b]) < min_cos {
+                        continue;
+                    }
+                    for vert in [*a, *b] {
+                        let corner_a = corner_of(&self.faces[face_a], vert);
+                        let corner_b = corner_of(&self.faces[face_b], vert);
+                        let root_a = find_root(&mut parents, face_a * 3 + corner_a);
+                        let root_b = find_root(&mut parents, face_b * 3 + corner_b);
+                        parents[root_a] = root_b;
+                    }
+                }
+            }
+        }
+
+        let mut result = Mesh::default();
+        let mut root_to_vert = HashMap::<usize, usize>::new();
+        for (face_index, face) in self.faces.iter().enumerate() {
+            let mut new_verts = [0; 3];
+            for (corner, vert) in face_verts(face).into_iter().enumerate() {
+                let root = find_root(&mut parents, face_index * 3 + corner);
+                new_verts[corner] = *root_to_vert.entry(root).or_insert_with(|| {
+                    result.verts.push(self.verts[vert]);
+                    if !self.uvs.is_empty() {
+                        result.uvs.push(self.uvs[vert]);
+                    }
+                    if !self.tangents.is_empty() {
+                        result.tangents.push(self.tangents[vert]);
+                    }
+                    if !self.joints.is_empty() {
+                        result.joints.push(self.joints[vert]);
+                        result.joint_weights.push(self.joint_weights[vert]);
+                    }
+                    result.verts.len() - 1
+                });
+            }
+            result.faces.push(Face {
+                v1: new_verts[0],
+                v2: new_verts[1],
+                v3: new_verts[2],
+            });
+            for corner in 0..3 {
+                result.edges.push(Edge {
+                    v1: new_verts[corner],
+                    v2: new_verts[(corner + 1) % 3],
+                });
+            }
+        }
+        result
+    }
+}
+
+fn face_verts(face: &Face) -> [usize; 3] {
+    [face.v1, face.v2, face.v3]
+}
+
+fn corner_of(face: &Face, vert: usize) -> usize {
+    face_verts(face)
+        .iter()
+        .position(|face_vert| *face_vert == vert)
+        .unwrap()
+}
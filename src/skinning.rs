@@ -0,0 +1,50 @@
+use crate::{Float, Mesh, field::Skeleton};
+
+impl Mesh {
+    /// Compute [Mesh::joints] and [Mesh::joint_weights] from the bones of the skeleton the mesh
+    /// was extracted from, so it can be rigged and animated right away.
+    ///
+    /// Each vertex is bound to the 4 bones with the largest [crate::field::Bone::influence] at
+    /// its position. Their weights are the influences raised to the power `sharpness` and
+    /// normalized to sum up to 1.0. With a sharpness of 1.0 the weights follow the blending of
+    /// the field, but the influence of a bone reaches far, so the tips of limbs still follow the
+    /// body. Higher values bind vertices more firmly to their nearest bone, 4.0 is a good start.
+    ///
+    /// # Panics
+    ///
+    /// When the skeleton has more than 65536 bones, the limit of glTF joint indices.
+    pub fn compute_skin_weights(&mut self, skeleton: &Skeleton, sharpness: Float) {
+        assert!(
+            skeleton.bones.len() <= u16::MAX as usize + 1,
+            "skin weights support at most 65536 bones"
+        );
+        self.joints.clear();
+        self.joint_weights.clear();
+        for vert in &self.verts {
+            let mut joints = [0_u16; 4];
+            let mut weights: [Float; 4] = [0.0; 4];
+            for (bone_index, bone) in skeleton.bones.iter().enumerate() {
+                let influence = bone.influence(*vert).powf(sharpness);
+                // Keep the slots sorted from the largest to the smallest influence.
+                let Some(slot) = weights.iter().position(|weight| influence > *weight) else {
+                    continue;
+                };
+                for moved in (slot + 1..4).rev() {
+                    joints[moved] = joints[moved - 1];
+                    weights[moved] = weights[moved - 1];
+                }
+                joints[slot] = bone_index as u16;
+                weights[slot] = influence;
+            }
+            let total = weights.iter().sum::<Float>();
+            if total.is_finite() && total > 0.0 {
+                weights = weights.map(|weight| weight / total);
+            } else if weights[0].is_infinite() {
+                // Vertex on the axis of the bone.
+                weights = [1.0, 0.0, 0.0, 0.0];
+            }
+            self.joints.push(joints);
+            self.joint_weights.push(weights);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use crate::{Domain, Float, RefineStrategy, ScalarField, Vec3};
+
+/// Closed contours of the field at a single height.
+#[derive(Debug, Default)]
+pub struct Layer {
+    pub z: Float,
+    pub polygons: Vec<Contour>,
+}
+
+/// Closed polygon. The last point connects back to the first point.
+///
+/// Outer boundaries are wound counter-clockwise and holes clockwise, so the inside of the model
+/// is always on the left side when walking along the points.
+#[derive(Debug, Default, Clone)]
+pub struct Contour {
+    pub points: Vec<[Float; 2]>,
+    pub hole: bool,
+    /// Index of the smallest outer boundary in the same layer that encloses this hole.
+    pub parent: Option<usize>,
+}
+
+impl Contour {
+    /// Area of the contour, positive for outer boundaries and negative for holes.
+    pub fn signed_area(&self) -> Float {
+        let mut area = 0.0;
+        for (index, a) in self.points.iter().enumerate() {
+            let b = self.points[(index + 1) % self.points.len()];
+            area += a[0] * b[1] - a[1] * b[0];
+        }
+        area * 0.5
+    }
+
+    /// Even-odd test if a point is inside the contour.
+    pub fn contains(&self, point: [Float; 2]) -> bool {
+        let mut inside = false;
+        for (index, a) in self.points.iter().enumerate() {
+            let b = self.points[(index + 1) % self.points.len()];
+            if (a[1] > point[1]) != (b[1] > point[1])
+                && point[0] < a[0] + (point[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1])
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}
+
+/// Sample on the layer grid. The grid is padded by one sample on each side that is always
+/// outside so every contour is closed, even when the model touches the bounds of the domain.
+#[derive(Copy, Clone)]
+struct LayerSample {
+    position: Vec3,
+    /// Weight of the field, not sampled for padding.
+    weight: Float,
+    inside: bool,
+    padding: bool,
+}
+
+/// Split each grid square into 2 triangles. The diagonal alternates between neighboring squares
+/// in the same way the tetrahedra alternate between neighboring cells.
+const SQUARE_TO_TRIANGLES: [[[[usize; 2]; 3]; 2]; 2] = [
+    [[[0, 0], [1, 0], [1, 1]], [[0, 0], [1, 1], [0, 1]]],
+    [[[0, 0], [1, 0], [0, 1]], [[1, 0], [1, 1], [0, 1]]],
+];
+
+impl Domain {
+    /// Slice the field into horizontal layers of closed polygons without extracting a mesh.
+    ///
+    /// Layers are placed in the middle of each `layer_height` band between `from.z` and `to.z`.
+    /// Each layer is contoured using marching triangles on the x/y resolution of the domain.
+    /// Outer boundaries are wound counter-clockwise and holes clockwise, see [Contour]. Contours
+    /// that would cross the bounds of the domain are closed half a cell outside of it.
+    pub fn slice_layers<FIELD, REFINE>(
+        &self,
+        field: &FIELD,
+        refine: &REFINE,
+        layer_height: Float,
+    ) -> Vec<Layer>
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let mut layers = Vec::new();
+        if layer_height <= 0.0 {
+            return layers;
+        }
+        let mut z = self.from.z + layer_height * 0.5;
+        while z < self.to.z {
+            layers.push(self.slice_layer(field, refine, z));
+            z += layer_height;
+        }
+        layers
+    }
+
+    fn slice_layer<FIELD, REFINE>(&self, field: &FIELD, refine: &REFINE, z: Float) -> Layer
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let samples_x = self.width + 3;
+        let samples_y = self.height + 3;
+        let step_x = (self.to.x - self.from.x) / self.width as Float;
+        let step_y = (self.to.y - self.from.y) / self.height as Float;
+        let mut samples = Vec::with_capacity(samples_x * samples_y);
+        for y in 0..samples_y {
+            for x in 0..samples_x {
+                let position = Vec3 {
+                    x: self.from.x + (x as Float - 1.0 - self.grid_offset.x) * step_x,
+                    y: self.from.y + (y as Float - 1.0 - self.grid_offset.y) * step_y,
+                    z,
+                };
+                let padding = x == 0 || y == 0 || x == samples_x - 1 || y == samples_y - 1;
+                let weight = if padding {
+                    Float::NEG_INFINITY
+                } else {
+                    field.sample(position)
+                };
+                let inside = weight > self.surface_weight;
+                samples.push(LayerSample {
+                    position,
+                    weight,
+                    inside,
+                    padding,
+                });
+            }
+        }
+
+        // Segments are stored by the grid edges they start and end on. Each crossed grid edge is
+        // shared by exactly 2 triangles, once as start and once as end of a segment.
+        let mut segments = BTreeMap::<(usize, usize), (usize, usize)>::new();
+        let edge_key = |a: usize, b: usize| (a.min(b), a.max(b));
+        for y in 0..samples_y - 1 {
+            for x in 0..samples_x - 1 {
+                let parity = (x + y) & 1;
+                for triangle in SQUARE_TO_TRIANGLES[parity] {
+                    let indices = triangle.map(|[dx, dy]| (y + dy) * samples_x + x + dx);
+                    for rotation in 0..3 {
+                        let p = indices[rotation];
+                        let q = indices[(rotation + 1) % 3];
+                        let r = indices[(rotation + 2) % 3];
+                        let p_inside = samples[p].inside;
+                        if p_inside != samples[q].inside && p_inside != samples[r].inside {
+                            if p_inside {
+                                segments.insert(edge_key(p, q), edge_key(r, p));
+                            } else {
+                                segments.insert(edge_key(r, p), edge_key(p, q));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let edge_point = |key: (usize, usize)| {
+            let a = samples[key.0];
+            let b = samples[key.1];
+            let position = if a.padding || b.padding {
+                a.position.lerp(b.position, 0.5)
+            } else {
+                refine.refine_with_weights(
+                    a.position,
+                    a.weight,
+                    b.position,
+                    b.weight,
+                    field,
+                    self.surface_weight,
+                )
+            };
+            [position.x, position.y]
+        };
+
+        let mut layer = Layer {
+            z,
+            polygons: Vec::new(),
+        };
+        while let Some(&start) = segments.keys().next() {
+            let mut polygon = Vec::new();
+            let mut current = start;
+            while let Some(next) = segments.remove(&current) {
+                polygon.push(edge_point(current));
+                current = next;
+            }
+            let mut contour = Contour {
+                points: polygon,
+                hole: false,
+                parent: None,
+            };
+            contour.hole = contour.signed_area() < 0.0;
+            layer.polygons.push(contour);
+        }
+        layer.assign_hole_parents();
+        layer
+    }
+}
+
+impl Layer {
+    fn assign_hole_parents(&mut self) {
+        for index in 0..self.polygons.len() {
+            if !self.polygons[index].hole {
+                continue;
+            }
+            let point = self.polygons[index].points[0];
+            self.polygons[index].parent = self
+                .polygons
+                .iter()
+                .enumerate()
+                .filter(|(_, outer)| !outer.hole && outer.contains(point))
+                .min_by(|(_, a), (_, b)| a.signed_area().total_cmp(&b.signed_area()))
+                .map(|(outer_index, _)| outer_index);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::{Float, Mesh, Vec3};
+
+/// Neighbours of every vertex and whether the vertex is on an open border of the mesh.
+pub(crate) struct Adjacency {
+    pub(crate) neighbours: Vec<Vec<usize>>,
+    pub(crate) border: Vec<bool>,
+}
+
+impl Adjacency {
+    pub(crate) fn new(mesh: &Mesh) -> Adjacency {
+        let mut neighbours = vec![Vec::new(); mesh.verts.len()];
+        let mut edge_faces = HashMap::<(usize, usize), usize>::new();
+        for face in &mesh.faces {
+            for (a, b) in [(face.v1, face.v2), (face.v2, face.v3), (face.v3, face.v1)] {
+                *edge_faces.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        let mut border = vec![false; mesh.verts.len()];
+        for ((a, b), face_count) in edge_faces {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+            if face_count == 1 {
+                border[a] = true;
+                border[b] = true;
+            }
+        }
+        Adjacency { neighbours, border }
+    }
+
+    /// Move every vertex that isn't on the border by `factor` towards the centroid of its
+    /// neighbours. Negative factors move away from it.
+    pub(crate) fn relax(&self, verts: &mut [Vec3], factor: Float) {
+        let previous = verts.to_vec();
+        for (index, vert) in verts.iter_mut().enumerate() {
+            let neighbours = &self.neighbours[index];
+            if self.border[index] || neighbours.is_empty() {
+                continue;
+            }
+            let sum = neighbours
+                .iter()
+                .fold(Vec3::default(), |sum, neighbour| sum + previous[*neighbour]);
+            let centroid = sum * (1.0 / neighbours.len() as Float);
+            *vert = vert.lerp(centroid, factor);
+        }
+    }
+}
+
+/// Smoothing pass for [Mesh::smooth].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Smoothing {
+    /// See [Mesh::smooth_laplacian].
+    Laplacian { iterations: usize, lambda: Float },
+    /// See [Mesh::smooth_taubin].
+    Taubin {
+        iterations: usize,
+        lambda: Float,
+        mu: Float,
+    },
+}
+
+impl Mesh {
+    /// Apply a smoothing pass.
+    pub fn smooth(&mut self, smoothing: Smoothing) {
+        match smoothing {
+            Smoothing::Laplacian { iterations, lambda } => {
+                self.smooth_laplacian(iterations, lambda)
+            }
+            Smoothing::Taubin {
+                iterations,
+                lambda,
+                mu,
+            } => self.smooth_taubin(iterations, lambda, mu),
+        }
+    }
+
+    /// Relax the surface by moving each vertex `lambda` of the way towards the centroid of its
+    /// neighbours, `iterations` times.
+    ///
+    /// Removes the bumps that marching tetrahedra leaves on smooth surfaces. Vertices on open
+    /// borders, where the surface leaves the domain, stay in place so chunks keep fitting
+    /// together. Repeated smoothing shrinks the mesh; keep `lambda` between 0.0 and 1.0 and the
+    /// iterations low. Normals and tangents aren't updated.
+    pub fn smooth_laplacian(&mut self, iterations: usize, lambda: Float) {
+        let adjacency = Adjacency::new(self);
+        for _ in 0..iterations {
+            adjacency.relax(&mut self.verts, lambda);
+        }
+    }
+
+    /// Taubin λ|μ smoothing: each iteration moves the vertices `lambda` towards the centroid of
+    /// their neighbours and then `mu` away from it.
+    ///
+    /// With `mu` negative and slightly larger in magnitude than `lambda` (for example 0.5 and
+    /// -0.53) the second step undoes the shrinking of the first, so the volume is preserved much
+    /// better than with [Mesh::smooth_laplacian]. Vertices on open borders stay in place.
+    pub fn smooth_taubin(&mut self, iterations: usize, lambda: Float, mu: Float) {
+        let adjacency = Adjacency::new(self);
+        for _ in 0..iterations {
+            adjacency.relax(&mut self.verts, lambda);
+            adjacency.relax(&mut self.verts, mu);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Sparse voxel grids for large volumes that are mostly empty.
+//!
+//! A [SparseGrid] has the layout and sampling of a [VoxelGrid], but only stores the cubic tiles
+//! that contain values other than the background, so memory scales with the occupied part of the
+//! volume. It implements [FieldBounds] from the range of each tile, so
+//! `Domain::march_tetrahedras_bounded` skips empty tiles without sampling them:
+//!
+//! ```
+//! use marching_cubes::{prelude::*, sparse::SparseGrid};
+//!
+//! let size = 256;
+//! let mut grid = SparseGrid::new(
+//!     Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+//!     Vec3 { x: 1.0, y: 1.0, z: 1.0 },
+//!     [size; 3],
+//!     16,
+//!     0.0,
+//! );
+//! for z in 100..120 {
+//!     for y in 100..120 {
+//!         for x in 100..120 {
+//!             grid.set(x, y, z, 1.0);
+//!         }
+//!     }
+//! }
+//! assert_eq!(grid.tile_count(), 8);
+//! let mut domain = MarchConfig::preset(Preset::Preview).domain(grid.from, grid.to, 0.5);
+//! domain.march_tetrahedras_bounded(&grid, &Bisection, 4);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    Float, ScalarField, Vec3,
+    field::FieldBounds,
+    grid::MinMax,
+    voxel::{VoxelGrid, Voxels, voxel_index},
+};
+
+/// Stored tile of a [SparseGrid].
+#[derive(Debug, Clone)]
+struct Tile {
+    /// Values ordered x first, then y, then z.
+    values: Vec<f32>,
+    /// Lowest and highest value ever stored in the tile, can be wider than the current values.
+    range: MinMax,
+}
+
+/// Voxel grid storing only the tiles that differ from the background, see [crate::sparse].
+#[derive(Debug, Clone)]
+pub struct SparseGrid {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    /// Number of voxels along each axis of a tile.
+    tile_size: usize,
+    background: f32,
+    tiles: HashMap<[usize; 3], Tile>,
+}
+
+impl SparseGrid {
+    /// Grid of `size` voxels spanning `from`..`to` that has the `background` value everywhere.
+    /// Tiles of `tile_size` voxels along each axis are allocated when a value is set.
+    pub fn new(
+        from: Vec3,
+        to: Vec3,
+        size: [usize; 3],
+        tile_size: usize,
+        background: f32,
+    ) -> SparseGrid {
+        let [width, height, depth] = size;
+        SparseGrid {
+            from,
+            to,
+            width,
+            height,
+            depth,
+            tile_size: tile_size.max(1),
+            background,
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Copy the values of `grid`, leaving out the tiles that only contain `background`.
+    pub fn from_voxel_grid(grid: &VoxelGrid, tile_size: usize, background: f32) -> SparseGrid {
+        let mut sparse = SparseGrid::new(
+            grid.from,
+            grid.to,
+            [grid.width, grid.height, grid.depth],
+            tile_size,
+            background,
+        );
+        for z in 0..grid.depth {
+            for y in 0..grid.height {
+                for x in 0..grid.width {
+                    let value = grid.value(x, y, z);
+                    if value != background {
+                        sparse.set(x, y, z, value);
+                    }
+                }
+            }
+        }
+        sparse
+    }
+
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    pub fn background(&self) -> f32 {
+        self.background
+    }
+
+    /// Number of allocated tiles.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Approximate heap memory used by the allocated tiles.
+    pub fn memory_bytes(&self) -> usize {
+        self.tiles.len()
+            * (self.tile_size.pow(3) * size_of::<f32>() + size_of::<([usize; 3], Tile)>())
+    }
+
+    /// Tile containing the voxel and the index of the voxel in the tile.
+    fn locate(&self, x: usize, y: usize, z: usize) -> ([usize; 3], usize) {
+        let size = self.tile_size;
+        let tile = [x / size, y / size, z / size];
+        let index = ((z % size) * size + y % size) * size + x % size;
+        (tile, index)
+    }
+
+    pub fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        let (tile, index) = self.locate(x, y, z);
+        self.tiles
+            .get(&tile)
+            .map_or(self.background, |tile| tile.values[index])
+    }
+
+    /// Store a value, allocating its tile when needed. Setting the background value in an
+    /// unallocated tile doesn't allocate it.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: f32) {
+        let (tile, index) = self.locate(x, y, z);
+        if value == self.background && !self.tiles.contains_key(&tile) {
+            return;
+        }
+        let background = self.background;
+        let tile_size = self.tile_size;
+        let tile = self.tiles.entry(tile).or_insert_with(|| Tile {
+            values: vec![background; tile_size.pow(3)],
+            range: MinMax {
+                min: Float::from(background),
+                max: Float::from(background),
+            },
+        });
+        tile.values[index] = value;
+        tile.range.include(MinMax {
+            min: Float::from(value),
+            max: Float::from(value),
+        });
+    }
+
+    /// Free the tiles that only contain the background value again, and tighten the ranges of
+    /// the other tiles.
+    pub fn prune(&mut self) {
+        let background = self.background;
+        self.tiles
+            .retain(|_, tile| tile.values.iter().any(|value| *value != background));
+        for tile in self.tiles.values_mut() {
+            tile.range = MinMax::EMPTY;
+            for value in &tile.values {
+                tile.range.include(MinMax {
+                    min: Float::from(*value),
+                    max: Float::from(*value),
+                });
+            }
+        }
+    }
+}
+
+/// Uses the value of the nearest voxel center, like [VoxelGrid].
+impl ScalarField for SparseGrid {
+    fn sample(&self, position: Vec3) -> Float {
+        let x = voxel_index(position.x, self.from.x, self.to.x, self.width);
+        let y = voxel_index(position.y, self.from.y, self.to.y, self.height);
+        let z = voxel_index(position.z, self.from.z, self.to.z, self.depth);
+        Float::from(self.value(x, y, z))
+    }
+}
+
+impl Voxels for SparseGrid {
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.from, self.to)
+    }
+
+    fn size(&self) -> [usize; 3] {
+        [self.width, self.height, self.depth]
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> Float {
+        Float::from(self.value(x, y, z))
+    }
+}
+
+impl FieldBounds for SparseGrid {
+    /// Combines the ranges of the tiles overlapping the box, unallocated tiles only have the
+    /// background value.
+    fn weight_range(&self, from: Vec3, to: Vec3) -> MinMax {
+        let axis = |from: Float, to: Float, grid_from: Float, grid_to: Float, count: usize| {
+            let first = voxel_index(from, grid_from, grid_to, count);
+            let last = voxel_index(to, grid_from, grid_to, count);
+            (first.min(last) / self.tile_size)..=(first.max(last) / self.tile_size)
+        };
+        let xs = axis(from.x, to.x, self.from.x, self.to.x, self.width);
+        let ys = axis(from.y, to.y, self.from.y, self.to.y, self.height);
+        let zs = axis(from.z, to.z, self.from.z, self.to.z, self.depth);
+        let mut range = MinMax::EMPTY;
+        for z in zs {
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    range.include(self.tiles.get(&[x, y, z]).map_or(
+                        MinMax {
+                            min: Float::from(self.background),
+                            max: Float::from(self.background),
+                        },
+                        |tile| tile.range,
+                    ));
+                }
+            }
+        }
+        range
+    }
+}
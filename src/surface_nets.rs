@@ -0,0 +1,233 @@
+use std::{cell::RefCell, collections::HashMap, ops::ControlFlow};
+
+use crate::{
+    CUBE_EDGES_TO_VERT_OFFSETS, Domain, Edge, EdgeKey, Face, Float, GRID_TO_VERT_OFFSETS, IVec3,
+    Mesh, RefineStrategy, ScalarField, Vec3,
+    algorithm::{Cell, ExtractionAlgorithm, MeshSink},
+    builder::MeshBuilder,
+    result::MarchResult,
+};
+
+/// Lattice edge crossing the surface that starts at the lowest corner of a cell.
+struct Crossing {
+    cell: IVec3,
+    /// 0 for x, 1 for y and 2 for z.
+    axis: usize,
+    /// Whether the lowest corner is the inside end of the edge.
+    starts_inside: bool,
+}
+
+/// Cell of the domain that crosses the surface.
+pub(crate) struct NetsCell {
+    pub(crate) position: IVec3,
+    /// Lowest and highest corner.
+    pub(crate) bounds: (Vec3, Vec3),
+    /// Indices of the crossings on the edges of the cell.
+    pub(crate) crossings: Vec<usize>,
+}
+
+/// Cells and crossing lattice edges of a domain, see [Domain::collect_nets].
+#[derive(Default)]
+pub(crate) struct Nets {
+    pub(crate) cells: Vec<NetsCell>,
+    crossings: Vec<Crossing>,
+    /// Positions of the crossings.
+    pub(crate) crossing_positions: Vec<Vec3>,
+    pub(crate) min_weight: Float,
+    pub(crate) max_weight: Float,
+}
+
+/// Collects the cells crossing the surface and the crossing lattice edges. Crossings are shared
+/// through the sink, so the vertex of each cell can be placed after marching.
+#[derive(Default)]
+struct NetsCollector {
+    nets: RefCell<Nets>,
+}
+
+impl ExtractionAlgorithm for NetsCollector {
+    fn polygonize<FIELD, REFINE>(
+        &self,
+        domain: &Domain,
+        cell: &Cell,
+        field: &FIELD,
+        refine: &REFINE,
+        sink: &mut MeshSink,
+    ) where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let is_inside = cell.corner_weights.map(|weight| domain.is_inside(weight));
+        if is_inside.iter().all(|inside| *inside == is_inside[0]) {
+            return;
+        }
+        let mut crossings = Vec::new();
+        for [a, b] in CUBE_EDGES_TO_VERT_OFFSETS {
+            if is_inside[a] == is_inside[b] {
+                continue;
+            }
+            let key = EdgeKey::new(
+                cell.lattice_position + GRID_TO_VERT_OFFSETS[a],
+                cell.lattice_position + GRID_TO_VERT_OFFSETS[b],
+            );
+            crossings.push(sink.vertex(key, || {
+                refine.refine_with_weights(
+                    cell.corner_positions[a],
+                    cell.corner_weights[a],
+                    cell.corner_positions[b],
+                    cell.corner_weights[b],
+                    field,
+                    domain.surface_weight,
+                )
+            }));
+        }
+        let mut nets = self.nets.borrow_mut();
+        nets.cells.push(NetsCell {
+            position: cell.position,
+            bounds: (cell.corner_positions[0], cell.corner_positions[6]),
+            crossings,
+        });
+        // The edges along x, y and z from the lowest corner, each lattice edge is the lowest
+        // edge of exactly one cell.
+        for (axis, corner) in [1, 3, 4].into_iter().enumerate() {
+            if is_inside[0] != is_inside[corner] {
+                nets.crossings.push(Crossing {
+                    cell: cell.position,
+                    axis,
+                    starts_inside: is_inside[0],
+                });
+            }
+        }
+    }
+}
+
+/// Unit step back along an axis.
+fn step_back(axis: usize) -> IVec3 {
+    let mut offset = [0; 3];
+    offset[axis] = -1;
+    IVec3 {
+        x: offset[0],
+        y: offset[1],
+        z: offset[2],
+    }
+}
+
+impl Domain {
+    /// Extract the surface with naive surface nets and add it as a new mesh.
+    ///
+    /// Every cell crossing the surface gets a single vertex at the average of the crossings on
+    /// its edges, placed by `refine`. The 4 cells around each crossing lattice edge are
+    /// connected by a quad, split along its shorter diagonal. The result is smoother and has
+    /// less than half the faces of [Domain::march_tetrahedras], but sharp features are rounded
+    /// off and the vertices lie slightly inside convex surfaces. Cells crossed by more than one
+    /// sheet of the surface, at details smaller than a cell, still get a single vertex, which
+    /// makes the mesh non-manifold there.
+    ///
+    /// The mesh ends half a cell inside the bounds: crossing lattice edges without 4 cells
+    /// around them are skipped, so the mesh is open where the surface leaves the domain. The
+    /// vertices aren't generated on lattice edges, so the mesh has no vertex keys and can't be
+    /// welded with [Mesh::weld_with].
+    pub fn march_surface_nets<FIELD, REFINE>(
+        &mut self,
+        field: &FIELD,
+        refine: &REFINE,
+    ) -> MarchResult
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let nets = self.collect_nets(field, refine);
+        let verts = nets
+            .cells
+            .iter()
+            .map(|cell| {
+                let sum = cell.crossings.iter().fold(Vec3::default(), |sum, vert| {
+                    sum + nets.crossing_positions[*vert]
+                });
+                sum * (1.0 / cell.crossings.len() as Float)
+            })
+            .collect();
+        let mesh = nets_mesh(verts, &nets);
+        let result = MarchResult::new(&mesh, nets.min_weight, nets.max_weight, self.surface_weight);
+        self.meshes.push(mesh);
+        result
+    }
+
+    /// Sample the field and collect the cells crossing the surface, with the crossings on their
+    /// edges placed by `refine`.
+    pub(crate) fn collect_nets<FIELD, REFINE>(&self, field: &FIELD, refine: &REFINE) -> Nets
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let collector = NetsCollector::default();
+        let mut builder = MeshBuilder::default();
+        let origin = IVec3 { x: 0, y: 0, z: 0 };
+        let (min_weight, max_weight) = self.march_cells_using(
+            &mut builder,
+            origin,
+            self.vertex_grid_size(),
+            &collector,
+            field,
+            refine,
+            &mut |_, _| ControlFlow::Continue(()),
+        );
+        Nets {
+            crossing_positions: builder.finish().verts,
+            min_weight,
+            max_weight,
+            ..collector.nets.into_inner()
+        }
+    }
+}
+
+/// Mesh with a vertex per cell of `nets` at `verts` and a quad per crossing lattice edge.
+pub(crate) fn nets_mesh(verts: Vec<Vec3>, nets: &Nets) -> Mesh {
+    let mut mesh = Mesh {
+        verts,
+        ..Mesh::default()
+    };
+    let cell_verts = nets
+        .cells
+        .iter()
+        .enumerate()
+        .map(|(vert, cell)| (cell.position, vert))
+        .collect::<HashMap<IVec3, usize>>();
+
+    for crossing in &nets.crossings {
+        // The cells around the edge, counter-clockwise when looking along the axis.
+        let u = step_back((crossing.axis + 1) % 3);
+        let v = step_back((crossing.axis + 2) % 3);
+        let ring = [
+            crossing.cell,
+            crossing.cell + u,
+            crossing.cell + u + v,
+            crossing.cell + v,
+        ];
+        let Some(mut quad) = ring
+            .iter()
+            .map(|cell| cell_verts.get(cell).copied())
+            .collect::<Option<Vec<usize>>>()
+        else {
+            continue;
+        };
+        // The outside is at the end of the edge that isn't inside.
+        if !crossing.starts_inside {
+            quad.reverse();
+        }
+        let [a, b, c, d] = [quad[0], quad[1], quad[2], quad[3]];
+        let faces = if (mesh.verts[a] - mesh.verts[c]).length()
+            <= (mesh.verts[b] - mesh.verts[d]).length()
+        {
+            [[a, b, c], [a, c, d]]
+        } else {
+            [[a, b, d], [b, c, d]]
+        };
+        for [v1, v2, v3] in faces {
+            mesh.faces.push(Face { v1, v2, v3 });
+            mesh.edges.push(Edge { v1, v2 });
+            mesh.edges.push(Edge { v1: v2, v2: v3 });
+            mesh.edges.push(Edge { v1: v3, v2: v1 });
+        }
+    }
+    mesh
+}
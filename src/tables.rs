@@ -0,0 +1,599 @@
+//! Generation of the tetrahedra and marching cubes lookup tables.
+//!
+//! The tables are derived at compile time from the crossing edges of each vertex mask. The
+//! winding of each face is chosen so the normal points from the inside vertices to the outside
+//! vertices of a reference tetrahedron. This only holds for the other tetrahedra of a
+//! decomposition when they have the same handedness, which is checked at compile time as well.
+//!
+//! The marching cubes table is derived from the faces of the cell instead, see
+//! [generate_cube_case_to_edges].
+
+use crate::{
+    CUBE_EDGES_TO_VERT_OFFSETS, GRID_TO_SIX_TETRAHEDRA_VERTICES, GRID_TO_TETRAHEDRA_VERTICES,
+    GRID_TO_VERT_OFFSETS, IVec3, TETRAHEDRA_EDGES_TO_VERT_OFFSETS,
+};
+
+/// Hand-written table that was used before the tables were generated. Kept to verify the
+/// generator against.
+const HAND_WRITTEN_VERTMASK_TO_EDGES: [[isize; 6]; 8] = [
+    [-1, -1, -1, -1, -1, -1], // 0000/1111
+    [0, 1, 2, -1, -1, -1],    // 0001/1110
+    [0, 5, 3, -1, -1, -1],    // 0010/1101
+    [1, 2, 3, 3, 2, 5],       // 0011/1100
+    [1, 3, 4, -1, -1, -1],    // 0100/1011
+    [4, 2, 3, 3, 2, 0],       // 0101/1010
+    [1, 0, 4, 4, 0, 5],       // 0110/1001
+    [2, 5, 4, -1, -1, -1],    // 0111/1000
+];
+
+const fn sub(a: IVec3, b: IVec3) -> IVec3 {
+    IVec3 {
+        x: a.x - b.x,
+        y: a.y - b.y,
+        z: a.z - b.z,
+    }
+}
+
+const fn cross(a: IVec3, b: IVec3) -> IVec3 {
+    IVec3 {
+        x: a.y * b.z - a.z * b.y,
+        y: a.z * b.x - a.x * b.z,
+        z: a.x * b.y - a.y * b.x,
+    }
+}
+
+const fn dot(a: IVec3, b: IVec3) -> i32 {
+    a.x * b.x + a.y * b.y + a.z * b.z
+}
+
+/// Signed volume (times 6) of a tetrahedron.
+const fn orientation(tetrahedron: [IVec3; 4]) -> i32 {
+    dot(
+        sub(tetrahedron[1], tetrahedron[0]),
+        cross(
+            sub(tetrahedron[2], tetrahedron[0]),
+            sub(tetrahedron[3], tetrahedron[0]),
+        ),
+    )
+}
+
+/// Corners of the tetrahedra of a grid cell decomposition.
+pub const fn decomposition_tetrahedra<const N: usize>(
+    decomposition: [[usize; 4]; N],
+) -> [[IVec3; 4]; N] {
+    let mut result = [[IVec3 { x: 0, y: 0, z: 0 }; 4]; N];
+    let mut tetrahedron = 0;
+    while tetrahedron < N {
+        let mut vert = 0;
+        while vert < 4 {
+            result[tetrahedron][vert] = GRID_TO_VERT_OFFSETS[decomposition[tetrahedron][vert]];
+            vert += 1;
+        }
+        tetrahedron += 1;
+    }
+    result
+}
+
+/// Check that all tetrahedra have the same positive handedness.
+pub const fn has_uniform_orientation<const N: usize>(tetrahedra: [[IVec3; 4]; N]) -> bool {
+    let mut index = 0;
+    while index < N {
+        if orientation(tetrahedra[index]) <= 0 {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+/// Midpoint of an edge, doubled so it stays on the integer lattice.
+const fn edge_midpoint(tetrahedron: [IVec3; 4], edge: usize) -> IVec3 {
+    let [a, b] = TETRAHEDRA_EDGES_TO_VERT_OFFSETS[edge];
+    IVec3 {
+        x: tetrahedron[a].x + tetrahedron[b].x,
+        y: tetrahedron[a].y + tetrahedron[b].y,
+        z: tetrahedron[a].z + tetrahedron[b].z,
+    }
+}
+
+const fn is_inside(mask: usize, vert: usize) -> bool {
+    mask & (1 << vert) != 0
+}
+
+/// Order the face so its normal points from the inside vertices to the outside vertices.
+const fn orient_face(tetrahedron: [IVec3; 4], mask: usize, face: [usize; 3]) -> [usize; 3] {
+    let p0 = edge_midpoint(tetrahedron, face[0]);
+    let p1 = edge_midpoint(tetrahedron, face[1]);
+    let p2 = edge_midpoint(tetrahedron, face[2]);
+    let normal = cross(sub(p1, p0), sub(p2, p0));
+
+    let mut inside_count = 0;
+    let mut inside_sum = IVec3 { x: 0, y: 0, z: 0 };
+    let mut outside_sum = IVec3 { x: 0, y: 0, z: 0 };
+    let mut vert = 0;
+    while vert < 4 {
+        let position = tetrahedron[vert];
+        if is_inside(mask, vert) {
+            inside_count += 1;
+            inside_sum = IVec3 {
+                x: inside_sum.x + position.x,
+                y: inside_sum.y + position.y,
+                z: inside_sum.z + position.z,
+            };
+        } else {
+            outside_sum = IVec3 {
+                x: outside_sum.x + position.x,
+                y: outside_sum.y + position.y,
+                z: outside_sum.z + position.z,
+            };
+        }
+        vert += 1;
+    }
+    let outside_count = 4 - inside_count;
+    // Direction between the centroids, scaled by both counts to stay on integers.
+    let direction = IVec3 {
+        x: outside_sum.x * inside_count - inside_sum.x * outside_count,
+        y: outside_sum.y * inside_count - inside_sum.y * outside_count,
+        z: outside_sum.z * inside_count - inside_sum.z * outside_count,
+    };
+    if dot(normal, direction) > 0 {
+        face
+    } else {
+        [face[0], face[2], face[1]]
+    }
+}
+
+const fn edges_share_vert(a: usize, b: usize) -> bool {
+    let [a1, a2] = TETRAHEDRA_EDGES_TO_VERT_OFFSETS[a];
+    let [b1, b2] = TETRAHEDRA_EDGES_TO_VERT_OFFSETS[b];
+    a1 == b1 || a1 == b2 || a2 == b1 || a2 == b2
+}
+
+/// Generate the table mapping a vertex mask to the edges of its faces (see
+/// `TETRADEDRA_VERTMASK_TO_EDGES`) for the given reference tetrahedron.
+///
+/// Only the first 8 masks are generated; the others are the inverse of these.
+pub const fn generate_vertmask_to_edges(tetrahedron: [IVec3; 4]) -> [[isize; 6]; 8] {
+    let mut table = [[-1; 6]; 8];
+    let mut mask = 0;
+    while mask < 8 {
+        let mut crossing = [0; 4];
+        let mut crossing_count = 0;
+        let mut edge = 0;
+        while edge < 6 {
+            let [a, b] = TETRAHEDRA_EDGES_TO_VERT_OFFSETS[edge];
+            if is_inside(mask, a) != is_inside(mask, b) {
+                crossing[crossing_count] = edge;
+                crossing_count += 1;
+            }
+            edge += 1;
+        }
+
+        if crossing_count == 3 {
+            let face = orient_face(tetrahedron, mask, [crossing[0], crossing[1], crossing[2]]);
+            table[mask] = [
+                face[0] as isize,
+                face[1] as isize,
+                face[2] as isize,
+                -1,
+                -1,
+                -1,
+            ];
+        } else if crossing_count == 4 {
+            // The crossings form a quad. Neighboring corners share a vertex of the tetrahedron,
+            // the opposite corner doesn't.
+            let mut quad = [crossing[0], 0, 0, 0];
+            let mut index = 1;
+            while index < 4 {
+                if !edges_share_vert(crossing[0], crossing[index]) {
+                    quad[2] = crossing[index];
+                } else if quad[1] == 0 {
+                    quad[1] = crossing[index];
+                } else {
+                    quad[3] = crossing[index];
+                }
+                index += 1;
+            }
+            let face1 = orient_face(tetrahedron, mask, [quad[0], quad[1], quad[2]]);
+            let face2 = orient_face(tetrahedron, mask, [quad[0], quad[2], quad[3]]);
+            table[mask] = [
+                face1[0] as isize,
+                face1[1] as isize,
+                face1[2] as isize,
+                face2[0] as isize,
+                face2[1] as isize,
+                face2[2] as isize,
+            ];
+        }
+        mask += 1;
+    }
+    table
+}
+
+/// Check that two table entries describe the same oriented surface.
+///
+/// Quads can be split along either diagonal, so instead of comparing edge indices the directed
+/// boundary edges are compared. The diagonal appears in both directions and cancels out.
+const fn same_surface(a: [isize; 6], b: [isize; 6]) -> bool {
+    // Count directed boundary edges between crossing edges: +1 for a->b, -1 for b->a.
+    let mut counts_a = [[0; 6]; 6];
+    let mut counts_b = [[0; 6]; 6];
+    let mut face = 0;
+    while face < 2 {
+        let mut corner = 0;
+        while corner < 3 {
+            let next = face * 3 + (corner + 1) % 3;
+            if a[face * 3] != -1 {
+                let from = a[face * 3 + corner] as usize;
+                let to = a[next] as usize;
+                counts_a[from][to] += 1;
+                counts_a[to][from] -= 1;
+            }
+            if b[face * 3] != -1 {
+                let from = b[face * 3 + corner] as usize;
+                let to = b[next] as usize;
+                counts_b[from][to] += 1;
+                counts_b[to][from] -= 1;
+            }
+            corner += 1;
+        }
+        face += 1;
+    }
+    let mut from = 0;
+    while from < 6 {
+        let mut to = 0;
+        while to < 6 {
+            if counts_a[from][to] != counts_b[from][to] {
+                return false;
+            }
+            to += 1;
+        }
+        from += 1;
+    }
+    true
+}
+
+/// Check every mask of two tables, see [same_surface].
+pub const fn tables_match(a: [[isize; 6]; 8], b: [[isize; 6]; 8]) -> bool {
+    let mut mask = 0;
+    while mask < 8 {
+        if !same_surface(a[mask], b[mask]) {
+            return false;
+        }
+        mask += 1;
+    }
+    true
+}
+
+const FIVE_TETRAHEDRA: [[IVec3; 4]; 5] = decomposition_tetrahedra(GRID_TO_TETRAHEDRA_VERTICES);
+
+/// Table used when marching the 5 tetrahedra decomposition.
+pub const FIVE_TETRAHEDRA_VERTMASK_TO_EDGES: [[isize; 6]; 8] =
+    generate_vertmask_to_edges(FIVE_TETRAHEDRA[0]);
+
+const _: () = assert!(has_uniform_orientation(FIVE_TETRAHEDRA));
+const _: () = assert!(tables_match(
+    FIVE_TETRAHEDRA_VERTMASK_TO_EDGES,
+    HAND_WRITTEN_VERTMASK_TO_EDGES
+));
+
+const SIX_TETRAHEDRA: [[IVec3; 4]; 6] = decomposition_tetrahedra(GRID_TO_SIX_TETRAHEDRA_VERTICES);
+
+// The 6 tetrahedra decomposition reuses the table of the 5 tetrahedra decomposition.
+const _: () = assert!(has_uniform_orientation(SIX_TETRAHEDRA));
+const _: () = assert!(tables_match(
+    generate_vertmask_to_edges(SIX_TETRAHEDRA[0]),
+    FIVE_TETRAHEDRA_VERTMASK_TO_EDGES
+));
+
+/// Corners of the faces of a cell, counter-clockwise when looking at the cell from outside.
+const CUBE_FACES: [[usize; 4]; 6] = [
+    [0, 3, 2, 1],
+    [4, 5, 6, 7],
+    [0, 1, 5, 4],
+    [3, 7, 6, 2],
+    [0, 4, 7, 3],
+    [1, 2, 6, 5],
+];
+
+/// Check that the corners of every face in [CUBE_FACES] are counter-clockwise from outside.
+const fn cube_faces_point_outward() -> bool {
+    let mut face = 0;
+    while face < 6 {
+        let [a, b, c, d] = CUBE_FACES[face];
+        let [a, b, c, d] = [
+            GRID_TO_VERT_OFFSETS[a],
+            GRID_TO_VERT_OFFSETS[b],
+            GRID_TO_VERT_OFFSETS[c],
+            GRID_TO_VERT_OFFSETS[d],
+        ];
+        let normal = cross(sub(b, a), sub(c, b));
+        // Face center minus cell center, times 4 to stay on integers.
+        let direction = IVec3 {
+            x: a.x + b.x + c.x + d.x - 2,
+            y: a.y + b.y + c.y + d.y - 2,
+            z: a.z + b.z + c.z + d.z - 2,
+        };
+        if dot(normal, direction) <= 0 {
+            return false;
+        }
+        face += 1;
+    }
+    true
+}
+
+/// Index in [CUBE_EDGES_TO_VERT_OFFSETS] of the edge between two corners.
+const fn cube_edge(a: usize, b: usize) -> usize {
+    let mut edge = 0;
+    while edge < 12 {
+        let [c, d] = CUBE_EDGES_TO_VERT_OFFSETS[edge];
+        if (c == a && d == b) || (c == b && d == a) {
+            return edge;
+        }
+        edge += 1;
+    }
+    panic!("corners don't share an edge");
+}
+
+/// Whether the 3 edges are all on the same face of the cell.
+const fn on_one_cube_face(edges: [usize; 3]) -> bool {
+    let mut face = 0;
+    while face < 6 {
+        let corners = CUBE_FACES[face];
+        let mut found = 0;
+        let mut edge = 0;
+        while edge < 3 {
+            let mut corner = 0;
+            while corner < 4 {
+                if cube_edge(corners[corner], corners[(corner + 1) % 4]) == edges[edge] {
+                    found += 1;
+                }
+                corner += 1;
+            }
+            edge += 1;
+        }
+        if found == 3 {
+            return true;
+        }
+        face += 1;
+    }
+    false
+}
+
+/// Whether the fan of the loop around `loop_edges[start]` has no face lying on a face of the
+/// cell. Such faces would be generated by the neighboring cell as well.
+const fn is_valid_fan(loop_edges: [usize; 12], length: usize, start: usize) -> bool {
+    let mut corner = 1;
+    while corner + 1 < length {
+        if on_one_cube_face([
+            loop_edges[start],
+            loop_edges[(start + corner) % length],
+            loop_edges[(start + corner + 1) % length],
+        ]) {
+            return false;
+        }
+        corner += 1;
+    }
+    true
+}
+
+/// Generate the marching cubes table mapping a corner mask to the crossing edges of its faces,
+/// up to 5 faces terminated by -1.
+///
+/// On each face of the cell the surface is a set of segments between crossing edges, each
+/// cutting off a run of inside corners. Faces with two diagonal inside corners are ambiguous,
+/// they are always resolved by separating the inside corners. Neighboring cells see the same
+/// corners on the face they share and resolve it the same way, so the mesh stays watertight.
+///
+/// The segments are directed so the inside corners are on their left when looking at the face
+/// from outside. Every crossing edge starts one segment and ends another, so following them
+/// gives closed loops. The surface inside the cell closes the inside part of the cell, so it runs
+/// against the loops to point outside; each loop is triangulated as a fan in reverse order,
+/// starting at an edge where none of the faces lie on a face of the cell.
+pub const fn generate_cube_case_to_edges() -> [[isize; 15]; 256] {
+    let mut table = [[-1; 15]; 256];
+    let mut mask = 0;
+    while mask < 256 {
+        // Edge at the end of the segment starting at each crossing edge.
+        let mut next = [usize::MAX; 12];
+        let mut face = 0;
+        while face < 6 {
+            let corners = CUBE_FACES[face];
+            let mut corner = 0;
+            while corner < 4 {
+                let following = (corner + 1) % 4;
+                if is_inside(mask, corners[corner]) && !is_inside(mask, corners[following]) {
+                    // Walk back to the first inside corner of the run.
+                    let mut first = corner;
+                    while is_inside(mask, corners[(first + 3) % 4]) && (first + 3) % 4 != corner {
+                        first = (first + 3) % 4;
+                    }
+                    let start = cube_edge(corners[corner], corners[following]);
+                    next[start] = cube_edge(corners[(first + 3) % 4], corners[first]);
+                }
+                corner += 1;
+            }
+            face += 1;
+        }
+
+        let mut visited = [false; 12];
+        let mut entry = 0;
+        let mut edge = 0;
+        while edge < 12 {
+            if next[edge] != usize::MAX && !visited[edge] {
+                let mut loop_edges = [0; 12];
+                let mut length = 0;
+                let mut current = edge;
+                while !visited[current] {
+                    visited[current] = true;
+                    loop_edges[length] = current;
+                    length += 1;
+                    current = next[current];
+                }
+                // A fan can have a face lying flat on a face of the cell when the loop crosses
+                // 3 edges of that face, start the fan where that doesn't happen.
+                let mut start = 0;
+                while start < length && !is_valid_fan(loop_edges, length, start) {
+                    start += 1;
+                }
+                assert!(start < length, "no fan without faces on a face of the cell");
+                let mut corner = 1;
+                while corner + 1 < length {
+                    table[mask][entry] = loop_edges[start] as isize;
+                    table[mask][entry + 1] = loop_edges[(start + corner + 1) % length] as isize;
+                    table[mask][entry + 2] = loop_edges[(start + corner) % length] as isize;
+                    entry += 3;
+                    corner += 1;
+                }
+            }
+            edge += 1;
+        }
+        mask += 1;
+    }
+    table
+}
+
+/// Check that the face of every single corner case points away from the corner.
+const fn cube_corner_cases_point_outward(table: [[isize; 15]; 256]) -> bool {
+    let mut corner = 0;
+    while corner < 8 {
+        let face = table[1 << corner];
+        if face[3] != -1 {
+            return false;
+        }
+        let mut points = [IVec3 { x: 0, y: 0, z: 0 }; 3];
+        let mut index = 0;
+        while index < 3 {
+            // Edge midpoints, doubled so they stay on the integer lattice.
+            let [a, b] = CUBE_EDGES_TO_VERT_OFFSETS[face[index] as usize];
+            let (a, b) = (GRID_TO_VERT_OFFSETS[a], GRID_TO_VERT_OFFSETS[b]);
+            points[index] = IVec3 {
+                x: a.x + b.x,
+                y: a.y + b.y,
+                z: a.z + b.z,
+            };
+            index += 1;
+        }
+        let normal = cross(sub(points[1], points[0]), sub(points[2], points[0]));
+        let position = GRID_TO_VERT_OFFSETS[corner];
+        let direction = sub(
+            points[0],
+            IVec3 {
+                x: position.x * 2,
+                y: position.y * 2,
+                z: position.z * 2,
+            },
+        );
+        if dot(normal, direction) <= 0 {
+            return false;
+        }
+        corner += 1;
+    }
+    true
+}
+
+/// Table used by marching cubes, see [generate_cube_case_to_edges].
+pub static CUBE_CASE_TO_EDGES: [[isize; 15]; 256] = generate_cube_case_to_edges();
+
+const _: () = assert!(cube_faces_point_outward());
+const _: () = assert!(cube_corner_cases_point_outward(
+    generate_cube_case_to_edges()
+));
+
+/// Points of the body-centered cubic tetrahedra of a cell, in doubled lattice coordinates so the
+/// cell centers are on the integer lattice. The 8 corners in [GRID_TO_VERT_OFFSETS] order come
+/// first, followed by the center of the cell and the centers of the 6 neighbors across the faces
+/// in [CUBE_FACES] order.
+const fn generate_bcc_points() -> [IVec3; 15] {
+    let center = IVec3 { x: 1, y: 1, z: 1 };
+    let mut points = [center; 15];
+    let mut corner = 0;
+    while corner < 8 {
+        let offset = GRID_TO_VERT_OFFSETS[corner];
+        points[corner] = IVec3 {
+            x: offset.x * 2,
+            y: offset.y * 2,
+            z: offset.z * 2,
+        };
+        corner += 1;
+    }
+    let mut face = 0;
+    while face < 6 {
+        // The sum of the doubled corners of a face is 4 times its doubled center, the neighbor
+        // center is twice as far from the center.
+        let mut sum = IVec3 { x: 0, y: 0, z: 0 };
+        let mut index = 0;
+        while index < 4 {
+            let point = points[CUBE_FACES[face][index]];
+            sum = IVec3 {
+                x: sum.x + point.x,
+                y: sum.y + point.y,
+                z: sum.z + point.z,
+            };
+            index += 1;
+        }
+        points[9 + face] = IVec3 {
+            x: sum.x / 2 - center.x,
+            y: sum.y / 2 - center.y,
+            z: sum.z / 2 - center.z,
+        };
+        face += 1;
+    }
+    points
+}
+
+/// Doubled offsets of the points used by [BCC_TETRAHEDRA], see [generate_bcc_points].
+pub const BCC_POINTS: [IVec3; 15] = generate_bcc_points();
+
+/// The 4 tetrahedra between the center of a cell and the center of its neighbor across each face
+/// in [CUBE_FACES] order, indexing [BCC_POINTS]. Each spans an edge of the face, with the corners
+/// ordered to have the handedness of the other decompositions.
+const fn generate_bcc_tetrahedra() -> [[usize; 4]; 24] {
+    let mut tetrahedra = [[0; 4]; 24];
+    let mut face = 0;
+    while face < 6 {
+        let mut edge = 0;
+        while edge < 4 {
+            let a = CUBE_FACES[face][edge];
+            let b = CUBE_FACES[face][(edge + 1) % 4];
+            let tetrahedron = [8, 9 + face, a, b];
+            let corners = bcc_tetrahedron(tetrahedron);
+            tetrahedra[face * 4 + edge] = if orientation(corners) > 0 {
+                tetrahedron
+            } else {
+                [8, 9 + face, b, a]
+            };
+            edge += 1;
+        }
+        face += 1;
+    }
+    tetrahedra
+}
+
+const fn bcc_tetrahedron(tetrahedron: [usize; 4]) -> [IVec3; 4] {
+    [
+        BCC_POINTS[tetrahedron[0]],
+        BCC_POINTS[tetrahedron[1]],
+        BCC_POINTS[tetrahedron[2]],
+        BCC_POINTS[tetrahedron[3]],
+    ]
+}
+
+/// Tetrahedra marched by [crate::bcc], see [generate_bcc_tetrahedra].
+pub const BCC_TETRAHEDRA: [[usize; 4]; 24] = generate_bcc_tetrahedra();
+
+const fn bcc_tetrahedra_corners() -> [[IVec3; 4]; 24] {
+    let mut result = [[IVec3 { x: 0, y: 0, z: 0 }; 4]; 24];
+    let mut tetrahedron = 0;
+    while tetrahedron < 24 {
+        result[tetrahedron] = bcc_tetrahedron(BCC_TETRAHEDRA[tetrahedron]);
+        tetrahedron += 1;
+    }
+    result
+}
+
+const _: () = assert!(has_uniform_orientation(bcc_tetrahedra_corners()));
+const _: () = assert!(tables_match(
+    generate_vertmask_to_edges(bcc_tetrahedra_corners()[0]),
+    FIVE_TETRAHEDRA_VERTMASK_TO_EDGES
+));
@@ -0,0 +1,72 @@
+use crate::{Float, Mesh, ScalarField, Vec3, field::gradient};
+
+/// How texture coordinates are projected onto the surface.
+#[derive(Debug, Copy, Clone)]
+pub enum UvProjection {
+    /// Project along the axis that is most aligned with the normal. `scale` is the number of
+    /// texture repeats per world unit.
+    Triplanar { scale: Float },
+}
+
+const AXES: [Vec3; 3] = [
+    Vec3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    },
+    Vec3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    },
+    Vec3 {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    },
+];
+
+impl Mesh {
+    /// Compute texture coordinates and tangent frames for each vertex.
+    ///
+    /// The normal is taken from the gradient of the field instead of the faces so the tangent
+    /// frames are smooth across the surface. The weight increases towards the inside, so the
+    /// normal points against the gradient.
+    pub fn compute_tangents<FIELD>(
+        &mut self,
+        field: &FIELD,
+        gradient_step: Float,
+        projection: UvProjection,
+    ) where
+        FIELD: ScalarField + ?Sized,
+    {
+        self.uvs.clear();
+        self.tangents.clear();
+        let UvProjection::Triplanar { scale } = projection;
+        for vert in &self.verts {
+            let normal = (gradient(field, *vert, gradient_step) * -1.0).normalized();
+            let components = [normal.x.abs(), normal.y.abs(), normal.z.abs()];
+            let dominant_axis = if components[0] >= components[1] && components[0] >= components[2]
+            {
+                0
+            } else if components[1] >= components[2] {
+                1
+            } else {
+                2
+            };
+            let u_axis = AXES[(dominant_axis + 1) % 3];
+            let v_axis = AXES[(dominant_axis + 2) % 3];
+            self.uvs
+                .push([vert.dot(u_axis) * scale, vert.dot(v_axis) * scale]);
+
+            let tangent = (u_axis - normal * normal.dot(u_axis)).normalized();
+            let handedness = if normal.cross(tangent).dot(v_axis) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            self.tangents
+                .push([tangent.x, tangent.y, tangent.z, handedness]);
+        }
+    }
+}
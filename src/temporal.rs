@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use crate::{EdgeKey, Float, Mesh, Vec3};
+
+impl Mesh {
+    /// Blend vertex positions with the vertices of the previous frame that were generated on the
+    /// same lattice edge.
+    ///
+    /// `previous_weight` is the weight of the previous position: 0.0 keeps this mesh as is, 1.0
+    /// keeps the previous positions. Passing the smoothed mesh of the previous frame each frame
+    /// gives an exponential moving average, which hides the popping and crawling of vertices in
+    /// animated fields. Both positions lie on the same lattice edge, so the blended position does
+    /// as well and the topology of this mesh stays valid. Vertices without a match are kept.
+    ///
+    /// Both meshes must be generated by domains that share the same global lattice, see
+    /// `Domain::lattice_offset`. The result only depends on the two meshes.
+    pub fn blend_with_previous(&mut self, previous: &Mesh, previous_weight: Float) {
+        let previous_positions = previous
+            .vert_keys
+            .iter()
+            .zip(&previous.verts)
+            .map(|(key, position)| (*key, *position))
+            .collect::<HashMap<EdgeKey, Vec3>>();
+        for (key, position) in self.vert_keys.iter().zip(&mut self.verts) {
+            if let Some(previous_position) = previous_positions.get(key) {
+                *position = position.lerp(*previous_position, previous_weight);
+            }
+        }
+    }
+}
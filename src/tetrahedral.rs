@@ -0,0 +1,187 @@
+//! Marching tetrahedra over a user supplied tetrahedral mesh.
+//!
+//! Simulations using the finite element method store their results on unstructured tetrahedral
+//! meshes instead of a regular lattice. [TetrahedralMesh] marches such a mesh with the same
+//! tables as [crate::Domain::march_tetrahedras], either with a weight per node or by sampling a
+//! [ScalarField] at the nodes:
+//!
+//! ```
+//! use marching_cubes::{Vec3, tetrahedral::TetrahedralMesh};
+//!
+//! let verts = vec![
+//!     Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+//!     Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+//!     Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+//!     Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+//! ];
+//! let mesh = TetrahedralMesh::new(verts, vec![[0, 1, 2, 3]]).unwrap();
+//! // The surface cuts off the first node.
+//! let surface = mesh.march_weights(&[1.0, 0.0, 0.0, 0.0], 0.5);
+//! assert_eq!(surface.faces.len(), 1);
+//! ```
+//!
+//! Vertices are shared between tetrahedra using the same edge, so a mesh without hanging nodes
+//! gives a watertight surface. The vertices aren't generated on lattice edges, so the surface has
+//! no vertex keys and can't be welded with [Mesh::weld_with].
+
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{Edge, Face, Float, Mesh, RefineStrategy, ScalarField, Vec3, tetrahedra_faces};
+
+/// Reason why [TetrahedralMesh::new] rejected a mesh.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TetrahedronError {
+    /// Index of the tetrahedron in the list.
+    pub tetrahedron: usize,
+    /// Node index that is out of range.
+    pub node: usize,
+}
+
+impl fmt::Display for TetrahedronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tetrahedron {} uses node {} which doesn't exist",
+            self.tetrahedron, self.node
+        )
+    }
+}
+
+impl Error for TetrahedronError {}
+
+/// Nodes and tetrahedra of an unstructured volume mesh, see [crate::tetrahedral].
+#[derive(Debug, Clone, Default)]
+pub struct TetrahedralMesh {
+    verts: Vec<Vec3>,
+    /// Node indices of each tetrahedron, with the handedness of the lattice decompositions.
+    tetrahedra: Vec<[usize; 4]>,
+}
+
+impl TetrahedralMesh {
+    /// Mesh of the tetrahedra with the given node indices into `verts`. The corners can be in any
+    /// order, inverted tetrahedra are reordered so all faces point outwards.
+    pub fn new(
+        verts: Vec<Vec3>,
+        mut tetrahedra: Vec<[usize; 4]>,
+    ) -> Result<TetrahedralMesh, TetrahedronError> {
+        for (index, tetrahedron) in tetrahedra.iter_mut().enumerate() {
+            if let Some(node) = tetrahedron.iter().find(|node| **node >= verts.len()) {
+                return Err(TetrahedronError {
+                    tetrahedron: index,
+                    node: *node,
+                });
+            }
+            let [a, b, c, d] = tetrahedron.map(|node| verts[node]);
+            if (b - a).dot((c - a).cross(d - a)) < 0.0 {
+                tetrahedron.swap(2, 3);
+            }
+        }
+        Ok(TetrahedralMesh { verts, tetrahedra })
+    }
+
+    pub fn verts(&self) -> &[Vec3] {
+        &self.verts
+    }
+
+    /// Node indices of each tetrahedron. Inverted tetrahedra passed to [TetrahedralMesh::new]
+    /// have their last two corners swapped.
+    pub fn tetrahedra(&self) -> &[[usize; 4]] {
+        &self.tetrahedra
+    }
+
+    /// Surface where the per node `weights` cross `surface_weight`. Nodes with a weight above
+    /// the surface weight are inside. Vertices are placed by interpolating the weights of the
+    /// nodes linearly, like the shape functions of linear elements.
+    ///
+    /// Panics when there isn't a weight for every node.
+    pub fn march_weights(&self, weights: &[Float], surface_weight: Float) -> Mesh {
+        assert_eq!(
+            weights.len(),
+            self.verts.len(),
+            "expected a weight for every node"
+        );
+        self.march_with(
+            |node| weights[node] > surface_weight,
+            |a, b| {
+                let (weight_a, weight_b) = (weights[a], weights[b]);
+                let factor = if weight_a != weight_b {
+                    ((surface_weight - weight_a) / (weight_b - weight_a)).clamp(0.0, 1.0)
+                } else {
+                    0.5
+                };
+                self.verts[a].lerp(self.verts[b], factor)
+            },
+        )
+    }
+
+    /// Surface of `field` inside the mesh. The field is sampled once per node, vertices are
+    /// placed by `refine` like [crate::Domain::march_tetrahedras].
+    pub fn march_field<FIELD, REFINE>(
+        &self,
+        field: &FIELD,
+        surface_weight: Float,
+        refine: &REFINE,
+    ) -> Mesh
+    where
+        FIELD: ScalarField + ?Sized,
+        REFINE: RefineStrategy,
+    {
+        let weights = self
+            .verts
+            .iter()
+            .map(|vert| field.sample(*vert))
+            .collect::<Vec<Float>>();
+        self.march_with(
+            |node| weights[node] > surface_weight,
+            |a, b| {
+                refine.refine_with_weights(
+                    self.verts[a],
+                    weights[a],
+                    self.verts[b],
+                    weights[b],
+                    field,
+                    surface_weight,
+                )
+            },
+        )
+    }
+
+    /// Surface between the nodes for which `is_inside` returns true and the other nodes, with the
+    /// vertex on each crossing edge at `position`.
+    fn march_with<INSIDE, POSITION>(&self, is_inside: INSIDE, mut position: POSITION) -> Mesh
+    where
+        INSIDE: Fn(usize) -> bool,
+        POSITION: FnMut(usize, usize) -> Vec3,
+    {
+        let mut mesh = Mesh::default();
+        let mut edge_verts = HashMap::<[usize; 2], usize>::new();
+        for tetrahedron in &self.tetrahedra {
+            let vert_is_inside = tetrahedron.map(&is_inside);
+            tetrahedra_faces(
+                &vert_is_inside,
+                false,
+                &[[0, 1, 2, 3]],
+                &mut |_, edges, flip| {
+                    let verts = edges.map(|edge| {
+                        let [a, b] = edge.map(|corner| tetrahedron[corner]);
+                        let (a, b) = (a.min(b), a.max(b));
+                        *edge_verts.entry([a, b]).or_insert_with(|| {
+                            mesh.verts.push(position(a, b));
+                            mesh.verts.len() - 1
+                        })
+                    });
+                    let [v1, v2, v3] = if flip {
+                        [verts[0], verts[2], verts[1]]
+                    } else {
+                        verts
+                    };
+                    mesh.faces.push(Face { v1, v2, v3 });
+                    mesh.edges.push(Edge { v1, v2 });
+                    mesh.edges.push(Edge { v1: v2, v2: v3 });
+                    mesh.edges.push(Edge { v1: v3, v2: v1 });
+                },
+            );
+        }
+        mesh
+    }
+}
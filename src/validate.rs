@@ -0,0 +1,99 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::Mesh;
+
+/// Topology problems found by [Mesh::validate]. Edges are pairs of vertex indices with the lowest
+/// index first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Edges used by a single face, the mesh has a hole or ends at the bounds of the domain.
+    pub boundary_edges: Vec<[usize; 2]>,
+    /// Edges used by more than 2 faces.
+    pub non_manifold_edges: Vec<[usize; 2]>,
+    /// Faces using the same vertices as an earlier face, in any order.
+    pub duplicate_faces: Vec<usize>,
+    /// Edges shared by 2 faces that traverse it in the same direction, so one of them faces the
+    /// wrong way.
+    pub inconsistent_winding: Vec<[usize; 2]>,
+    /// Faces that use a vertex more than once.
+    pub degenerate_faces: Vec<usize>,
+}
+
+impl ValidationReport {
+    /// Whether the mesh is closed and every edge is shared by exactly 2 faces.
+    pub fn is_watertight(&self) -> bool {
+        self.boundary_edges.is_empty()
+            && self.non_manifold_edges.is_empty()
+            && self.degenerate_faces.is_empty()
+    }
+
+    /// Whether the mesh is watertight, consistently oriented and without duplicate faces, as
+    /// 3D printing and volume computations expect.
+    pub fn is_valid(&self) -> bool {
+        self.is_watertight()
+            && self.duplicate_faces.is_empty()
+            && self.inconsistent_winding.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "watertight and consistently oriented");
+        }
+        write!(
+            f,
+            "{} boundary edges, {} non-manifold edges, {} duplicate faces, {} inconsistently wound edges, {} degenerate faces",
+            self.boundary_edges.len(),
+            self.non_manifold_edges.len(),
+            self.duplicate_faces.len(),
+            self.inconsistent_winding.len(),
+            self.degenerate_faces.len()
+        )
+    }
+}
+
+impl Mesh {
+    /// Check whether the mesh is watertight and consistently oriented.
+    ///
+    /// Vertices are compared by index, so the mesh must be welded. Meshes of a domain are open
+    /// where the surface leaves the bounds, sample a slightly larger domain to close them.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        // Per edge the number of faces using it from the lowest to the highest vertex and back.
+        let mut edges = HashMap::<[usize; 2], [usize; 2]>::new();
+        let mut faces = HashSet::<[usize; 3]>::new();
+        for (index, face) in self.faces.iter().enumerate() {
+            let verts = [face.v1, face.v2, face.v3];
+            if face.v1 == face.v2 || face.v2 == face.v3 || face.v3 == face.v1 {
+                report.degenerate_faces.push(index);
+                continue;
+            }
+            let mut sorted = verts;
+            sorted.sort_unstable();
+            if !faces.insert(sorted) {
+                report.duplicate_faces.push(index);
+            }
+            for (a, b) in [(face.v1, face.v2), (face.v2, face.v3), (face.v3, face.v1)] {
+                let direction = usize::from(a > b);
+                edges.entry([a.min(b), a.max(b)]).or_default()[direction] += 1;
+            }
+        }
+
+        for (edge, [forward, backward]) in edges {
+            match forward + backward {
+                1 => report.boundary_edges.push(edge),
+                2 if forward != 1 => report.inconsistent_winding.push(edge),
+                2 => {}
+                _ => report.non_manifold_edges.push(edge),
+            }
+        }
+        report.boundary_edges.sort_unstable();
+        report.non_manifold_edges.sort_unstable();
+        report.inconsistent_winding.sort_unstable();
+        report
+    }
+}
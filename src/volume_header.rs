@@ -0,0 +1,468 @@
+//! Volumes with a NRRD or MetaImage (MHD) header, the formats used by 3D Slicer, ITK and most
+//! medical and scientific imaging tools.
+//!
+//! Unlike raw volumes these files describe their own size, sample type, spacing and origin.
+//! [VolumeHeader::load] reads the header, [VolumeHeader::read_grid] the values, and
+//! [VolumeHeader::domain] derives a domain that places a lattice vertex at every voxel center:
+//!
+//! ```no_run
+//! use marching_cubes::{prelude::*, volume_header::VolumeHeader};
+//!
+//! let header = VolumeHeader::load("scan.nrrd".as_ref()).unwrap();
+//! let grid = header.read_grid().unwrap();
+//! let mut domain = header.domain(300.0);
+//! domain.march_tetrahedras(&grid, &Bisection);
+//! ```
+//!
+//! Supported are 3 dimensional volumes with a single scalar per voxel, raw or gzip compressed
+//! NRRD data and raw or zlib compressed MetaImage data, attached to the header or in a separate
+//! file. The orientation of the axes is ignored: the volume is placed axis aligned at its
+//! origin, with the lengths of the space directions as spacing.
+
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Domain, Float, Vec3,
+    inflate::{inflate_gzip, inflate_zlib},
+    voxel::VoxelGrid,
+    voxel_file::{FileVoxelGrid, VoxelFormat},
+};
+
+/// Compression of the values of a volume.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VolumeEncoding {
+    Raw,
+    Gzip,
+    Zlib,
+}
+
+/// Layout and placement of a volume, read from a NRRD or MetaImage header.
+#[derive(Debug, Clone)]
+pub struct VolumeHeader {
+    /// Number of voxels along x, y and z.
+    pub size: [usize; 3],
+    /// Distance between the centers of neighboring voxels along each axis.
+    pub spacing: Vec3,
+    /// Position of the center of the first voxel.
+    pub origin: Vec3,
+    pub format: VoxelFormat,
+    pub encoding: VolumeEncoding,
+    /// File containing the values, the header itself when the data is attached.
+    pub data_path: PathBuf,
+    /// Byte offset of the values in `data_path`.
+    pub data_offset: u64,
+}
+
+fn invalid_data(path: &Path, message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {message}", path.display()),
+    )
+}
+
+fn parse_numbers<const N: usize>(text: &str) -> Option<[Float; N]> {
+    let numbers = text
+        .split(|character: char| character.is_whitespace() || "(),".contains(character))
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<Float>().ok())
+        .collect::<Option<Vec<Float>>>()?;
+    numbers.try_into().ok()
+}
+
+fn vector([x, y, z]: [Float; 3]) -> Vec3 {
+    Vec3 { x, y, z }
+}
+
+/// Offset of the byte after `lines` line breaks, starting at `offset`.
+fn skip_lines(data: &[u8], mut offset: usize, lines: usize) -> Option<usize> {
+    for _ in 0..lines {
+        offset += data.get(offset..)?.iter().position(|byte| *byte == b'\n')? + 1;
+    }
+    Some(offset)
+}
+
+impl VolumeHeader {
+    /// Read the header of a `.nrrd`, `.nhdr`, `.mhd` or `.mha` file. Files with other extensions
+    /// are read as NRRD when they start with its magic and as MetaImage otherwise.
+    pub fn load(path: &Path) -> io::Result<VolumeHeader> {
+        let data = fs::read(path)?;
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("nrrd" | "nhdr") => VolumeHeader::parse_nrrd(path, &data),
+            Some("mhd" | "mha") => VolumeHeader::parse_mhd(path, &data),
+            _ if data.starts_with(b"NRRD") => VolumeHeader::parse_nrrd(path, &data),
+            _ => VolumeHeader::parse_mhd(path, &data),
+        }
+    }
+
+    /// Parse a NRRD header. `path` locates detached data files and attached data.
+    pub fn parse_nrrd(path: &Path, data: &[u8]) -> io::Result<VolumeHeader> {
+        let invalid = |message: &str| invalid_data(path, message);
+        if !data.starts_with(b"NRRD000") {
+            return Err(invalid("not a NRRD file"));
+        }
+        let mut size = None;
+        let mut spacing = None;
+        let mut origin = Vec3::default();
+        let mut sample_type = None;
+        let mut big_endian = false;
+        let mut encoding = VolumeEncoding::Raw;
+        let mut data_file = None;
+        let mut line_skip = 0;
+        let mut byte_skip: i64 = 0;
+
+        // The header ends at an empty line, attached data follows it.
+        let mut offset = 0;
+        let mut data_start = None;
+        while offset < data.len() {
+            let end = data[offset..]
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(data.len(), |position| offset + position);
+            let line = String::from_utf8_lossy(&data[offset..end]);
+            let line = line.trim_end_matches('\r');
+            offset = end + 1;
+            if line.is_empty() {
+                data_start = Some(offset.min(data.len()));
+                break;
+            }
+            if line.starts_with('#') || line.starts_with("NRRD") {
+                continue;
+            }
+            // `key:=value` lines are free form key value pairs.
+            let Some((field, value)) = line.split_once(": ") else {
+                continue;
+            };
+            let value = value.trim();
+            match field.trim() {
+                "dimension" if value != "3" => {
+                    return Err(invalid("only 3 dimensional volumes are supported"));
+                }
+                "sizes" => {
+                    let [x, y, z] = parse_numbers(value)
+                        .ok_or_else(|| invalid("expected 3 sizes"))?
+                        .map(|size| size as usize);
+                    size = Some([x, y, z]);
+                }
+                "spacings" => {
+                    spacing = Some(vector(
+                        parse_numbers(value).ok_or_else(|| invalid("expected 3 spacings"))?,
+                    ));
+                }
+                "space directions" => {
+                    let directions: [Float; 9] = parse_numbers(value)
+                        .ok_or_else(|| invalid("expected 3 space directions"))?;
+                    let length = |axis: usize| {
+                        vector([
+                            directions[axis * 3],
+                            directions[axis * 3 + 1],
+                            directions[axis * 3 + 2],
+                        ])
+                        .length()
+                    };
+                    spacing = Some(vector([length(0), length(1), length(2)]));
+                }
+                "space origin" => {
+                    origin = vector(
+                        parse_numbers(value).ok_or_else(|| invalid("expected a 3D origin"))?,
+                    );
+                }
+                "type" => sample_type = Some(value.to_string()),
+                "endian" => big_endian = value == "big",
+                "encoding" => {
+                    encoding = match value {
+                        "raw" => VolumeEncoding::Raw,
+                        "gzip" | "gz" => VolumeEncoding::Gzip,
+                        _ => return Err(invalid("unsupported NRRD encoding")),
+                    }
+                }
+                "data file" | "datafile" => data_file = Some(value.to_string()),
+                "line skip" | "lineskip" => {
+                    line_skip = value.parse().map_err(|_| invalid("invalid line skip"))?;
+                }
+                "byte skip" | "byteskip" => {
+                    byte_skip = value.parse().map_err(|_| invalid("invalid byte skip"))?;
+                }
+                _ => {}
+            }
+        }
+
+        let size = size.ok_or_else(|| invalid("missing `sizes`"))?;
+        let sample_type = sample_type.ok_or_else(|| invalid("missing `type`"))?;
+        // Formats for little and big endian data.
+        let formats = match sample_type.as_str() {
+            "uchar" | "unsigned char" | "uint8" | "uint8_t" => (VoxelFormat::U8, VoxelFormat::U8),
+            "signed char" | "int8" | "int8_t" => (VoxelFormat::I8, VoxelFormat::I8),
+            "short" | "short int" | "signed short" | "signed short int" | "int16" | "int16_t" => {
+                (VoxelFormat::I16LittleEndian, VoxelFormat::I16BigEndian)
+            }
+            "ushort" | "unsigned short" | "unsigned short int" | "uint16" | "uint16_t" => {
+                (VoxelFormat::U16LittleEndian, VoxelFormat::U16BigEndian)
+            }
+            "int" | "signed int" | "int32" | "int32_t" => {
+                (VoxelFormat::I32LittleEndian, VoxelFormat::I32BigEndian)
+            }
+            "uint" | "unsigned int" | "uint32" | "uint32_t" => {
+                (VoxelFormat::U32LittleEndian, VoxelFormat::U32BigEndian)
+            }
+            "float" => (VoxelFormat::F32LittleEndian, VoxelFormat::F32BigEndian),
+            "double" => (VoxelFormat::F64LittleEndian, VoxelFormat::F64BigEndian),
+            _ => return Err(invalid("unsupported NRRD type")),
+        };
+        let format = if big_endian { formats.1 } else { formats.0 };
+
+        let (data_path, data, start) = match data_file {
+            Some(file) => {
+                let data_path = path.parent().unwrap_or(Path::new("")).join(file);
+                let data = fs::read(&data_path)?;
+                (data_path, data, 0)
+            }
+            None => {
+                let start = data_start.ok_or_else(|| invalid("missing attached data"))?;
+                (path.to_path_buf(), data.to_vec(), start)
+            }
+        };
+        let start = skip_lines(&data, start, line_skip)
+            .ok_or_else(|| invalid("line skip beyond the end of the data"))?;
+        let header = VolumeHeader {
+            size,
+            spacing: spacing.unwrap_or(Vec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            }),
+            origin,
+            format,
+            encoding,
+            data_path,
+            data_offset: 0,
+        };
+        let data_offset = if byte_skip == -1 {
+            // The values are at the end of the file.
+            if encoding != VolumeEncoding::Raw {
+                return Err(invalid("byte skip -1 requires raw encoding"));
+            }
+            data.len()
+                .checked_sub(header.data_size())
+                .ok_or_else(|| invalid("data file is smaller than the volume"))?
+        } else if byte_skip >= 0 && (byte_skip == 0 || encoding == VolumeEncoding::Raw) {
+            start + byte_skip as usize
+        } else {
+            return Err(invalid("byte skip is only supported for raw data"));
+        };
+        Ok(VolumeHeader {
+            data_offset: data_offset as u64,
+            ..header
+        })
+    }
+
+    /// Parse a MetaImage header. `path` locates detached data files and attached data.
+    pub fn parse_mhd(path: &Path, data: &[u8]) -> io::Result<VolumeHeader> {
+        let invalid = |message: &str| invalid_data(path, message);
+        let mut size = None;
+        let mut spacing = None;
+        let mut origin = Vec3::default();
+        let mut element_type = None;
+        let mut big_endian = false;
+        let mut compressed = false;
+        let mut header_size: i64 = 0;
+        let mut data_file = None;
+
+        let mut offset = 0;
+        while offset < data.len() && data_file.is_none() {
+            let end = data[offset..]
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(data.len(), |position| offset + position);
+            let line = String::from_utf8_lossy(&data[offset..end])
+                .trim()
+                .to_string();
+            offset = end + 1;
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            let is_true = value.eq_ignore_ascii_case("true");
+            match key.trim() {
+                "NDims" if value != "3" => {
+                    return Err(invalid("only 3 dimensional volumes are supported"));
+                }
+                "ElementNumberOfChannels" if value != "1" => {
+                    return Err(invalid("only volumes with 1 channel are supported"));
+                }
+                "DimSize" => {
+                    let [x, y, z] = parse_numbers(value)
+                        .ok_or_else(|| invalid("expected 3 sizes"))?
+                        .map(|size| size as usize);
+                    size = Some([x, y, z]);
+                }
+                "ElementSpacing" => {
+                    spacing = Some(vector(
+                        parse_numbers(value).ok_or_else(|| invalid("expected 3 spacings"))?,
+                    ));
+                }
+                // Older files only give the size of the voxels.
+                "ElementSize" if spacing.is_none() => {
+                    spacing = Some(vector(
+                        parse_numbers(value).ok_or_else(|| invalid("expected 3 sizes"))?,
+                    ));
+                }
+                "Offset" | "Origin" | "Position" => {
+                    origin = vector(
+                        parse_numbers(value).ok_or_else(|| invalid("expected a 3D origin"))?,
+                    );
+                }
+                "ElementType" => element_type = Some(value.to_string()),
+                "ElementByteOrderMSB" | "BinaryDataByteOrderMSB" => big_endian = is_true,
+                "CompressedData" => compressed = is_true,
+                "HeaderSize" => {
+                    header_size = value.parse().map_err(|_| invalid("invalid header size"))?;
+                }
+                // Always the last key, attached data starts on the next line.
+                "ElementDataFile" => data_file = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let size = size.ok_or_else(|| invalid("missing `DimSize`"))?;
+        let data_file = data_file.ok_or_else(|| invalid("missing `ElementDataFile`"))?;
+        let element_type = element_type.ok_or_else(|| invalid("missing `ElementType`"))?;
+        // Formats for little and big endian data.
+        let formats = match element_type.as_str() {
+            "MET_UCHAR" => (VoxelFormat::U8, VoxelFormat::U8),
+            "MET_CHAR" => (VoxelFormat::I8, VoxelFormat::I8),
+            "MET_SHORT" => (VoxelFormat::I16LittleEndian, VoxelFormat::I16BigEndian),
+            "MET_USHORT" => (VoxelFormat::U16LittleEndian, VoxelFormat::U16BigEndian),
+            "MET_INT" => (VoxelFormat::I32LittleEndian, VoxelFormat::I32BigEndian),
+            "MET_UINT" => (VoxelFormat::U32LittleEndian, VoxelFormat::U32BigEndian),
+            "MET_FLOAT" => (VoxelFormat::F32LittleEndian, VoxelFormat::F32BigEndian),
+            "MET_DOUBLE" => (VoxelFormat::F64LittleEndian, VoxelFormat::F64BigEndian),
+            _ => return Err(invalid("unsupported MetaImage element type")),
+        };
+        let format = if big_endian { formats.1 } else { formats.0 };
+
+        let (data_path, data_length, start) = if data_file == "LOCAL" {
+            (path.to_path_buf(), data.len(), offset.min(data.len()))
+        } else {
+            let data_path = path.parent().unwrap_or(Path::new("")).join(&data_file);
+            let data_length = fs::metadata(&data_path)?.len() as usize;
+            (data_path, data_length, 0)
+        };
+        let header = VolumeHeader {
+            size,
+            spacing: spacing.unwrap_or(Vec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            }),
+            origin,
+            format,
+            encoding: if compressed {
+                VolumeEncoding::Zlib
+            } else {
+                VolumeEncoding::Raw
+            },
+            data_path,
+            data_offset: 0,
+        };
+        let data_offset = match header_size {
+            // The values are at the end of the file.
+            -1 if !compressed => data_length
+                .checked_sub(header.data_size())
+                .ok_or_else(|| invalid("data file is smaller than the volume"))?,
+            0.. => start + header_size as usize,
+            _ => return Err(invalid("invalid header size")),
+        };
+        Ok(VolumeHeader {
+            data_offset: data_offset as u64,
+            ..header
+        })
+    }
+
+    /// Size of the uncompressed values in bytes, `usize::MAX` when it doesn't fit.
+    pub fn data_size(&self) -> usize {
+        self.format.volume_bytes(self.size).unwrap_or(usize::MAX)
+    }
+
+    /// Bounds of a [VoxelGrid] with its voxel centers at the positions given by the header.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let from = self.origin - self.spacing * 0.5;
+        let to = Vec3 {
+            x: from.x + self.spacing.x * self.size[0] as Float,
+            y: from.y + self.spacing.y * self.size[1] as Float,
+            z: from.z + self.spacing.z * self.size[2] as Float,
+        };
+        (from, to)
+    }
+
+    /// Read the values into a [VoxelGrid] spanning [VolumeHeader::bounds].
+    pub fn read_grid(&self) -> io::Result<VoxelGrid> {
+        let (from, to) = self.bounds();
+        let mut file = fs::File::open(&self.data_path)?;
+        file.seek(SeekFrom::Start(self.data_offset))?;
+        if self.encoding == VolumeEncoding::Raw {
+            return VoxelGrid::read_raw(
+                &mut io::BufReader::new(file),
+                from,
+                to,
+                self.size,
+                self.format,
+            );
+        }
+        let mut compressed = Vec::new();
+        file.read_to_end(&mut compressed)?;
+        let values = match self.encoding {
+            VolumeEncoding::Gzip => inflate_gzip(&compressed)?,
+            _ => inflate_zlib(&compressed)?,
+        };
+        VoxelGrid::read_raw(&mut &values[..], from, to, self.size, self.format)
+    }
+
+    /// Open the values as a [FileVoxelGrid] that reads slices on demand, for raw volumes that
+    /// don't fit in memory.
+    pub fn open_file_grid(&self, cached_slices: usize) -> io::Result<FileVoxelGrid> {
+        if self.encoding != VolumeEncoding::Raw {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only raw volumes can be read on demand",
+            ));
+        }
+        let (from, to) = self.bounds();
+        FileVoxelGrid::open(
+            &self.data_path,
+            from,
+            to,
+            self.size,
+            self.format,
+            self.data_offset,
+            cached_slices,
+        )
+    }
+
+    /// Domain with a lattice vertex at the center of every voxel, so the volume is marched at
+    /// its native resolution without resampling.
+    pub fn domain(&self, surface_weight: Float) -> Domain {
+        let [width, height, depth] = self.size.map(|size| size.saturating_sub(1).max(1));
+        Domain {
+            from: self.origin,
+            to: Vec3 {
+                x: self.origin.x + self.spacing.x * width as Float,
+                y: self.origin.y + self.spacing.y * height as Float,
+                z: self.origin.z + self.spacing.z * depth as Float,
+            },
+            surface_weight,
+            width,
+            height,
+            depth,
+            grid_offset: Vec3::default(),
+            meshes: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,534 @@
+use std::{collections::VecDeque, io};
+
+use crate::{Float, ScalarField, Vec3, field::FieldBounds, grid::MinMax};
+
+/// Scalar values stored on a regular grid of voxels spanning `from`..`to`.
+#[derive(Debug, Clone)]
+pub struct VoxelGrid {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    /// Values ordered x first, then y, then z.
+    pub values: Vec<f32>,
+}
+
+/// Voxel labels produced by [VoxelGrid::region_grow]. Label 0 means unlabeled.
+#[derive(Debug, Clone)]
+pub struct LabelGrid {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub labels: Vec<u16>,
+}
+
+/// Neighbors sharing a face with a voxel.
+const NEIGHBOR_OFFSETS: [[isize; 3]; 6] = [
+    [-1, 0, 0],
+    [1, 0, 0],
+    [0, -1, 0],
+    [0, 1, 0],
+    [0, 0, -1],
+    [0, 0, 1],
+];
+
+impl VoxelGrid {
+    pub fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.height + y) * self.width + x
+    }
+
+    pub fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.values[self.index(x, y, z)]
+    }
+
+    /// Segment the grid by growing regions from labeled seed voxels.
+    ///
+    /// Regions grow into face-neighbors whose value is at least `threshold`. All seeds grow at
+    /// the same time, so where two regions meet each voxel gets the label of the nearest seed
+    /// (in steps). Seeds below the threshold or outside of the grid are ignored.
+    pub fn region_grow(&self, seeds: &[([usize; 3], u16)], threshold: f32) -> LabelGrid {
+        let mut labels = LabelGrid {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            labels: vec![0; self.values.len()],
+        };
+        let mut queue = VecDeque::new();
+        for &([x, y, z], label) in seeds {
+            if x >= self.width || y >= self.height || z >= self.depth || label == 0 {
+                continue;
+            }
+            let index = self.index(x, y, z);
+            if labels.labels[index] == 0 && self.values[index] >= threshold {
+                labels.labels[index] = label;
+                queue.push_back([x, y, z]);
+            }
+        }
+
+        while let Some([x, y, z]) = queue.pop_front() {
+            let label = labels.labels[self.index(x, y, z)];
+            for [dx, dy, dz] in NEIGHBOR_OFFSETS {
+                let (Some(nx), Some(ny), Some(nz)) = (
+                    x.checked_add_signed(dx),
+                    y.checked_add_signed(dy),
+                    z.checked_add_signed(dz),
+                ) else {
+                    continue;
+                };
+                if nx >= self.width || ny >= self.height || nz >= self.depth {
+                    continue;
+                }
+                let index = self.index(nx, ny, nz);
+                if labels.labels[index] == 0 && self.values[index] >= threshold {
+                    labels.labels[index] = label;
+                    queue.push_back([nx, ny, nz]);
+                }
+            }
+        }
+        labels
+    }
+}
+
+impl LabelGrid {
+    /// Voxel grid with 1.0 for voxels with the given label and 0.0 elsewhere. March it with a
+    /// surface weight of 0.5 to mesh a single region.
+    pub fn mask(&self, label: u16, from: Vec3, to: Vec3) -> VoxelGrid {
+        VoxelGrid {
+            from,
+            to,
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            values: self
+                .labels
+                .iter()
+                .map(|voxel_label| if *voxel_label == label { 1.0 } else { 0.0 })
+                .collect(),
+        }
+    }
+}
+
+/// Index of the voxel containing `value` along an axis with `count` voxels spanning
+/// `from`..`to`. Positions outside of the grid use the nearest voxel.
+pub(crate) fn voxel_index(value: Float, from: Float, to: Float, count: usize) -> usize {
+    let factor = (value - from) / (to - from);
+    ((factor * count as Float).floor().max(0.0) as usize).min(count - 1)
+}
+
+/// Uses the value of the nearest voxel center. Positions outside of the grid use the value of
+/// the nearest voxel on the border.
+impl ScalarField for VoxelGrid {
+    fn sample(&self, position: Vec3) -> Float {
+        let x = voxel_index(position.x, self.from.x, self.to.x, self.width);
+        let y = voxel_index(position.y, self.from.y, self.to.y, self.height);
+        let z = voxel_index(position.z, self.from.z, self.to.z, self.depth);
+        Float::from(self.value(x, y, z))
+    }
+}
+
+/// Random access to the voxels of a grid, so [Interpolated] can sample it between voxel
+/// centers.
+pub trait Voxels {
+    /// Corners of the box spanned by the voxels.
+    fn bounds(&self) -> (Vec3, Vec3);
+    /// Number of voxels along x, y and z.
+    fn size(&self) -> [usize; 3];
+    /// Value of a voxel, the indices are inside the grid.
+    fn voxel(&self, x: usize, y: usize, z: usize) -> Float;
+}
+
+impl Voxels for VoxelGrid {
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.from, self.to)
+    }
+
+    fn size(&self) -> [usize; 3] {
+        [self.width, self.height, self.depth]
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> Float {
+        Float::from(self.value(x, y, z))
+    }
+}
+
+/// How [Interpolated] reconstructs the field between voxel centers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Value of the nearest voxel, the way the grids sample themselves.
+    Nearest,
+    /// Blend the 8 surrounding voxels.
+    #[default]
+    Trilinear,
+    /// Catmull-Rom spline through the 64 surrounding voxels. Smoother than trilinear, but it
+    /// can overshoot the voxel values near steep changes.
+    Tricubic,
+}
+
+/// Samples a voxel grid with interpolation between the voxel centers.
+///
+/// Grids sample the nearest voxel, which produces staircases when the marching grid is finer
+/// than the voxels, and aliasing when it's coarser or not aligned. Interpolating removes the
+/// staircases:
+///
+/// ```
+/// use marching_cubes::{
+///     prelude::*,
+///     voxel::{Interpolated, Interpolation, VoxelGrid},
+/// };
+///
+/// let size = 8;
+/// let grid = VoxelGrid {
+///     from: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+///     to: Vec3 { x: 1.0, y: 1.0, z: 1.0 },
+///     width: size,
+///     height: size,
+///     depth: size,
+///     values: (0..size * size * size)
+///         .map(|index| if index % size < 4 { 1.0 } else { 0.0 })
+///         .collect(),
+/// };
+/// let field = Interpolated::new(&grid, Interpolation::Trilinear);
+/// assert_eq!(field.sample(Vec3 { x: 0.5, y: 0.5, z: 0.5 }), 0.5);
+/// ```
+///
+/// Positions outside of the grid use the values of the nearest voxels on the border.
+#[derive(Debug, Copy, Clone)]
+pub struct Interpolated<'a, GRID: ?Sized> {
+    pub grid: &'a GRID,
+    pub interpolation: Interpolation,
+}
+
+impl<'a, GRID: Voxels + ?Sized> Interpolated<'a, GRID> {
+    pub fn new(grid: &'a GRID, interpolation: Interpolation) -> Interpolated<'a, GRID> {
+        Interpolated {
+            grid,
+            interpolation,
+        }
+    }
+}
+
+/// Position along an axis in voxel units, with voxel centers at whole numbers.
+fn voxel_coordinate(value: Float, from: Float, to: Float, count: usize) -> Float {
+    let coordinate = (value - from) / (to - from) * count as Float - 0.5;
+    coordinate.clamp(0.0, count.saturating_sub(1) as Float)
+}
+
+/// Voxel indices along an axis and their interpolation weights. The indices are clamped to
+/// the grid, so voxels on the border are repeated.
+fn axis_weights(
+    coordinate: Float,
+    count: usize,
+    interpolation: Interpolation,
+) -> [(usize, Float); 4] {
+    let last = count.saturating_sub(1) as isize;
+    let index = |offset: isize| (coordinate.floor() as isize + offset).clamp(0, last) as usize;
+    let t = coordinate - coordinate.floor();
+    match interpolation {
+        Interpolation::Nearest => [
+            (coordinate.round() as usize, 1.0),
+            (0, 0.0),
+            (0, 0.0),
+            (0, 0.0),
+        ],
+        Interpolation::Trilinear => [(index(0), 1.0 - t), (index(1), t), (0, 0.0), (0, 0.0)],
+        Interpolation::Tricubic => {
+            let t2 = t * t;
+            let t3 = t2 * t;
+            [
+                (index(-1), 0.5 * (-t3 + 2.0 * t2 - t)),
+                (index(0), 0.5 * (3.0 * t3 - 5.0 * t2 + 2.0)),
+                (index(1), 0.5 * (-3.0 * t3 + 4.0 * t2 + t)),
+                (index(2), 0.5 * (t3 - t2)),
+            ]
+        }
+    }
+}
+
+impl<GRID: Voxels + ?Sized> ScalarField for Interpolated<'_, GRID> {
+    fn sample(&self, position: Vec3) -> Float {
+        let (from, to) = self.grid.bounds();
+        let [width, height, depth] = self.grid.size();
+        let xs = axis_weights(
+            voxel_coordinate(position.x, from.x, to.x, width),
+            width,
+            self.interpolation,
+        );
+        let ys = axis_weights(
+            voxel_coordinate(position.y, from.y, to.y, height),
+            height,
+            self.interpolation,
+        );
+        let zs = axis_weights(
+            voxel_coordinate(position.z, from.z, to.z, depth),
+            depth,
+            self.interpolation,
+        );
+        let mut value = 0.0;
+        for (z, z_weight) in zs {
+            if z_weight == 0.0 {
+                continue;
+            }
+            for (y, y_weight) in ys {
+                if y_weight == 0.0 {
+                    continue;
+                }
+                for (x, x_weight) in xs {
+                    if x_weight == 0.0 {
+                        continue;
+                    }
+                    value += self.grid.voxel(x, y, z) * x_weight * y_weight * z_weight;
+                }
+            }
+        }
+        value
+    }
+}
+
+/// Samples only combine voxels within two voxels of the position, so the range of the grid
+/// over the box grown by two voxels bounds them. Tricubic samples can overshoot that range by
+/// up to the sum of the absolute spline weights.
+impl<GRID: Voxels + FieldBounds + ?Sized> FieldBounds for Interpolated<'_, GRID> {
+    fn weight_range(&self, from: Vec3, to: Vec3) -> MinMax {
+        let (grid_from, grid_to) = self.grid.bounds();
+        let [width, height, depth] = self.grid.size();
+        let margin = Vec3 {
+            x: 2.0 * (grid_to.x - grid_from.x).abs() / width.max(1) as Float,
+            y: 2.0 * (grid_to.y - grid_from.y).abs() / height.max(1) as Float,
+            z: 2.0 * (grid_to.z - grid_from.z).abs() / depth.max(1) as Float,
+        };
+        let range = self.grid.weight_range(from - margin, to + margin);
+        if self.interpolation != Interpolation::Tricubic || range.min > range.max {
+            return range;
+        }
+        // The absolute Catmull-Rom weights along an axis sum to at most 1.25.
+        let overshoot = (1.25 * 1.25 * 1.25 - 1.0) * 0.5 * (range.max - range.min);
+        MinMax {
+            min: range.min - overshoot,
+            max: range.max + overshoot,
+        }
+    }
+}
+
+/// How a `.vox` model is converted into a [VoxelGrid] by [VoxelGrid::read_vox].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct VoxImportOptions {
+    /// Store the approximate signed distance (in voxels, positive inside) to the nearest voxel
+    /// boundary instead of 1.0/0.0 occupancy. March with a surface weight of 0.0 when enabled.
+    pub distance_transform: bool,
+    /// Number of 3x3x3 box blur passes applied after loading.
+    pub smoothing_iterations: usize,
+}
+
+/// Largest model size along an axis, voxel positions in `.vox` files are stored as bytes.
+const MAX_VOX_SIZE: usize = 256;
+
+/// Chamfer weights approximating euclidean distances to face, edge and corner neighbors.
+const CHAMFER_WEIGHTS: [f32; 4] = [0.0, 1.0, std::f32::consts::SQRT_2, 1.732_050_8];
+
+impl VoxelGrid {
+    /// Load the first model of a MagicaVoxel `.vox` file as an occupancy field.
+    ///
+    /// Each voxel becomes a unit cube, the grid spans (0, 0, 0) to the model size. Without a
+    /// distance transform occupied voxels have a value of 1.0 and should be marched with a
+    /// surface weight of 0.5.
+    pub fn read_vox<R: io::Read>(
+        reader: &mut R,
+        options: VoxImportOptions,
+    ) -> io::Result<VoxelGrid> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        if data.len() < 8 || &data[0..4] != b"VOX " {
+            return Err(invalid("not a vox file"));
+        }
+        let read_u32 = |offset: usize| -> io::Result<usize> {
+            data.get(offset..offset + 4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+                .ok_or_else(|| invalid("unexpected end of vox file"))
+        };
+
+        let mut size = None;
+        let mut grid = None;
+        // Chunks are walked linearly; MAIN only has a header and its children follow directly.
+        let mut offset = 8;
+        while offset + 12 <= data.len() {
+            let id = &data[offset..offset + 4];
+            let content_size = read_u32(offset + 4)?;
+            let content = offset + 12;
+            match id {
+                b"MAIN" => {
+                    offset = content + content_size;
+                    continue;
+                }
+                b"SIZE" if size.is_none() => {
+                    let model_size = [
+                        read_u32(content)?,
+                        read_u32(content + 4)?,
+                        read_u32(content + 8)?,
+                    ];
+                    if model_size
+                        .iter()
+                        .any(|size| *size == 0 || *size > MAX_VOX_SIZE)
+                    {
+                        return Err(invalid("vox model size out of range"));
+                    }
+                    size = Some(model_size);
+                }
+                b"XYZI" if grid.is_none() => {
+                    let [width, height, depth] =
+                        size.ok_or_else(|| invalid("XYZI chunk before SIZE chunk"))?;
+                    let voxel_count = width
+                        .checked_mul(height)
+                        .and_then(|count| count.checked_mul(depth))
+                        .ok_or_else(|| invalid("vox model size out of range"))?;
+                    let mut voxel_grid = VoxelGrid {
+                        from: Vec3 {
+                            x: 0.0,
+                            y: 0.0,
+                            z: 0.0,
+                        },
+                        to: Vec3 {
+                            x: width as Float,
+                            y: height as Float,
+                            z: depth as Float,
+                        },
+                        width,
+                        height,
+                        depth,
+                        values: vec![0.0; voxel_count],
+                    };
+                    let count = read_u32(content)?;
+                    for voxel in 0..count {
+                        let start = content + 4 + voxel * 4;
+                        let bytes = data
+                            .get(start..start + 4)
+                            .ok_or_else(|| invalid("unexpected end of vox file"))?;
+                        let (x, y, z) = (bytes[0] as usize, bytes[1] as usize, bytes[2] as usize);
+                        if x < width && y < height && z < depth {
+                            let index = voxel_grid.index(x, y, z);
+                            voxel_grid.values[index] = 1.0;
+                        }
+                    }
+                    grid = Some(voxel_grid);
+                }
+                _ => {}
+            }
+            offset = content + content_size + read_u32(offset + 8)?;
+        }
+
+        let mut grid = grid.ok_or_else(|| invalid("vox file doesn't contain a model"))?;
+        if options.distance_transform {
+            grid.distance_transform();
+        }
+        for _ in 0..options.smoothing_iterations {
+            grid.box_blur();
+        }
+        Ok(grid)
+    }
+
+    /// Replace occupancy values (> 0.5 is inside) by the approximate signed distance to the
+    /// boundary, positive inside.
+    pub fn distance_transform(&mut self) {
+        let inside = self
+            .values
+            .iter()
+            .map(|value| *value > 0.5)
+            .collect::<Vec<bool>>();
+        let to_outside = self.chamfer_distance(&inside, false);
+        let to_inside = self.chamfer_distance(&inside, true);
+        for (index, value) in self.values.iter_mut().enumerate() {
+            *value = if inside[index] {
+                to_outside[index] - 0.5
+            } else {
+                0.5 - to_inside[index]
+            };
+        }
+    }
+
+    /// Distance of each voxel to the nearest voxel whose inside state equals `target`, using a
+    /// forward and a backward chamfer pass.
+    fn chamfer_distance(&self, inside: &[bool], target: bool) -> Vec<f32> {
+        let mut distances = inside
+            .iter()
+            .map(|voxel_inside| {
+                if *voxel_inside == target {
+                    0.0
+                } else {
+                    f32::INFINITY
+                }
+            })
+            .collect::<Vec<f32>>();
+        let (width, height, depth) = (
+            self.width as isize,
+            self.height as isize,
+            self.depth as isize,
+        );
+        for backward in [false, true] {
+            for step in 0..width * height * depth {
+                let index = if backward {
+                    width * height * depth - 1 - step
+                } else {
+                    step
+                };
+                let (x, y, z) = (
+                    index % width,
+                    index / width % height,
+                    index / (width * height),
+                );
+                let mut distance = distances[index as usize];
+                for dz in -1..=1_isize {
+                    for dy in -1..=1_isize {
+                        for dx in -1..=1_isize {
+                            // Only look at neighbors that were already visited in this pass.
+                            let neighbor_offset = (dz * height + dy) * width + dx;
+                            if (neighbor_offset < 0) == backward || neighbor_offset == 0 {
+                                continue;
+                            }
+                            let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                            if nx < 0
+                                || ny < 0
+                                || nz < 0
+                                || nx >= width
+                                || ny >= height
+                                || nz >= depth
+                            {
+                                continue;
+                            }
+                            let weight = CHAMFER_WEIGHTS[(dx.abs() + dy.abs() + dz.abs()) as usize];
+                            distance = distance
+                                .min(distances[(index + neighbor_offset) as usize] + weight);
+                        }
+                    }
+                }
+                distances[index as usize] = distance;
+            }
+        }
+        distances
+    }
+
+    /// Average each voxel with its 26 neighbors. Voxels on the border only use neighbors inside
+    /// of the grid.
+    pub fn box_blur(&mut self) {
+        let mut result = Vec::with_capacity(self.values.len());
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let mut sum = 0.0;
+                    let mut count = 0;
+                    for nz in z.saturating_sub(1)..(z + 2).min(self.depth) {
+                        for ny in y.saturating_sub(1)..(y + 2).min(self.height) {
+                            for nx in x.saturating_sub(1)..(x + 2).min(self.width) {
+                                sum += self.value(nx, ny, nz);
+                                count += 1;
+                            }
+                        }
+                    }
+                    result.push(sum / count as f32);
+                }
+            }
+        }
+        self.values = result;
+    }
+}
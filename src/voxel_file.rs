@@ -0,0 +1,271 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    Float, ScalarField, Vec3,
+    voxel::{VoxelGrid, Voxels, voxel_index},
+};
+
+/// Encoding of the values in a voxel file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VoxelFormat {
+    U8,
+    U16LittleEndian,
+    F32LittleEndian,
+    I8,
+    I16LittleEndian,
+    I32LittleEndian,
+    U32LittleEndian,
+    F64LittleEndian,
+    I16BigEndian,
+    U16BigEndian,
+    I32BigEndian,
+    U32BigEndian,
+    F32BigEndian,
+    F64BigEndian,
+}
+
+impl VoxelFormat {
+    pub fn bytes_per_value(self) -> usize {
+        match self {
+            VoxelFormat::U8 | VoxelFormat::I8 => 1,
+            VoxelFormat::U16LittleEndian
+            | VoxelFormat::I16LittleEndian
+            | VoxelFormat::I16BigEndian
+            | VoxelFormat::U16BigEndian => 2,
+            VoxelFormat::F32LittleEndian
+            | VoxelFormat::I32LittleEndian
+            | VoxelFormat::U32LittleEndian
+            | VoxelFormat::I32BigEndian
+            | VoxelFormat::U32BigEndian
+            | VoxelFormat::F32BigEndian => 4,
+            VoxelFormat::F64LittleEndian | VoxelFormat::F64BigEndian => 8,
+        }
+    }
+
+    /// Bytes of a volume of `size` values, `None` when it doesn't fit in `usize`.
+    pub fn volume_bytes(self, size: [usize; 3]) -> Option<usize> {
+        let [width, height, depth] = size;
+        width
+            .checked_mul(height)?
+            .checked_mul(depth)?
+            .checked_mul(self.bytes_per_value())
+    }
+
+    /// Values are converted to f32, 32 bit integers and f64 lose precision beyond 24 bits of
+    /// mantissa.
+    pub(crate) fn decode(self, bytes: &[u8]) -> f32 {
+        let b2 = || [bytes[0], bytes[1]];
+        let b4 = || [bytes[0], bytes[1], bytes[2], bytes[3]];
+        let b8 = || bytes[0..8].try_into().unwrap();
+        match self {
+            VoxelFormat::U8 => f32::from(bytes[0]),
+            VoxelFormat::U16LittleEndian => f32::from(u16::from_le_bytes(b2())),
+            VoxelFormat::F32LittleEndian => f32::from_le_bytes(b4()),
+            VoxelFormat::I8 => f32::from(bytes[0] as i8),
+            VoxelFormat::I16LittleEndian => f32::from(i16::from_le_bytes(b2())),
+            VoxelFormat::I32LittleEndian => i32::from_le_bytes(b4()) as f32,
+            VoxelFormat::U32LittleEndian => u32::from_le_bytes(b4()) as f32,
+            VoxelFormat::F64LittleEndian => f64::from_le_bytes(b8()) as f32,
+            VoxelFormat::I16BigEndian => f32::from(i16::from_be_bytes(b2())),
+            VoxelFormat::U16BigEndian => f32::from(u16::from_be_bytes(b2())),
+            VoxelFormat::I32BigEndian => i32::from_be_bytes(b4()) as f32,
+            VoxelFormat::U32BigEndian => u32::from_be_bytes(b4()) as f32,
+            VoxelFormat::F32BigEndian => f32::from_be_bytes(b4()),
+            VoxelFormat::F64BigEndian => f64::from_be_bytes(b8()) as f32,
+        }
+    }
+}
+
+impl VoxelGrid {
+    /// Read `width * height * depth` values ordered x first, then y, then z, as written by CT
+    /// scanners and simulations that export raw volumes. The values are converted to f32 and
+    /// kept as they are, so march with a surface weight in the units of the data, for example
+    /// the density of bone.
+    pub fn read_raw<R: io::Read>(
+        reader: &mut R,
+        from: Vec3,
+        to: Vec3,
+        size: [usize; 3],
+        format: VoxelFormat,
+    ) -> io::Result<VoxelGrid> {
+        let [width, height, depth] = size;
+        let bytes_per_value = format.bytes_per_value();
+        let mut bytes = vec![0; volume_bytes(size, format)?];
+        reader.read_exact(&mut bytes)?;
+        Ok(VoxelGrid {
+            from,
+            to,
+            width,
+            height,
+            depth,
+            values: bytes
+                .chunks_exact(bytes_per_value)
+                .map(|value| format.decode(value))
+                .collect(),
+        })
+    }
+
+    /// Load a raw volume from the file at `path`, skipping `header_size` bytes, see
+    /// [VoxelGrid::read_raw]. Use [FileVoxelGrid] for volumes that don't fit in memory.
+    pub fn load_raw(
+        path: &Path,
+        from: Vec3,
+        to: Vec3,
+        size: [usize; 3],
+        format: VoxelFormat,
+        header_size: u64,
+    ) -> io::Result<VoxelGrid> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(header_size))?;
+        VoxelGrid::read_raw(&mut io::BufReader::new(file), from, to, size, format)
+    }
+}
+
+/// Bytes of a volume of `size` values, an error when it doesn't fit in `usize`.
+fn volume_bytes(size: [usize; 3], format: VoxelFormat) -> io::Result<usize> {
+    format.volume_bytes(size).ok_or_else(|| {
+        let [width, height, depth] = size;
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("voxel volume of {width}x{height}x{depth} values is too large"),
+        )
+    })
+}
+
+/// Slices read from the file, evicted in the order they were read.
+struct SliceCache {
+    file: File,
+    slices: HashMap<usize, Arc<Vec<f32>>>,
+    order: VecDeque<usize>,
+    error: Option<io::Error>,
+}
+
+/// Read only voxel grid that stays in a file, with the same layout and sampling as
+/// [VoxelGrid](crate::voxel::VoxelGrid).
+///
+/// Slices of constant z are read on demand, converted to f32 and kept in a cache of at most
+/// `cached_slices` slices, so volumes larger than memory can be marched. The march visits the
+/// lattice in x planes, which touch every slice, so a cache that holds all slices
+/// intersecting the domain avoids reading slices more than once.
+pub struct FileVoxelGrid {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub format: VoxelFormat,
+    /// Byte offset of the first value in the file.
+    pub header_size: u64,
+    pub cached_slices: usize,
+    cache: Mutex<SliceCache>,
+}
+
+impl FileVoxelGrid {
+    /// Open a file with `width * height * depth` values ordered x first, then y, then z,
+    /// starting at `header_size`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open(
+        path: &Path,
+        from: Vec3,
+        to: Vec3,
+        size: [usize; 3],
+        format: VoxelFormat,
+        header_size: u64,
+        cached_slices: usize,
+    ) -> io::Result<FileVoxelGrid> {
+        let file = File::open(path)?;
+        let [width, height, depth] = size;
+        let expected = header_size.saturating_add(volume_bytes(size, format)? as u64);
+        if file.metadata()?.len() < expected {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("voxel file is smaller than the expected {expected} bytes"),
+            ));
+        }
+        Ok(FileVoxelGrid {
+            from,
+            to,
+            width,
+            height,
+            depth,
+            format,
+            header_size,
+            cached_slices: cached_slices.max(1),
+            cache: Mutex::new(SliceCache {
+                file,
+                slices: HashMap::new(),
+                order: VecDeque::new(),
+                error: None,
+            }),
+        })
+    }
+
+    /// Values of the slice with the given z, read from the file when not cached.
+    pub fn slice(&self, z: usize) -> io::Result<Arc<Vec<f32>>> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(slice) = cache.slices.get(&z) {
+            return Ok(slice.clone());
+        }
+        let slice_values = self.width * self.height;
+        let bytes_per_value = self.format.bytes_per_value();
+        let mut bytes = vec![0; slice_values * bytes_per_value];
+        let offset = self.header_size + (z * slice_values * bytes_per_value) as u64;
+        cache.file.seek(SeekFrom::Start(offset))?;
+        cache.file.read_exact(&mut bytes)?;
+        let slice = Arc::new(
+            bytes
+                .chunks_exact(bytes_per_value)
+                .map(|value| self.format.decode(value))
+                .collect::<Vec<f32>>(),
+        );
+        while cache.order.len() >= self.cached_slices {
+            let evicted = cache.order.pop_front().unwrap();
+            cache.slices.remove(&evicted);
+        }
+        cache.order.push_back(z);
+        cache.slices.insert(z, slice.clone());
+        Ok(slice)
+    }
+
+    /// First error that occurred while sampling. [ScalarField::sample] can't report errors, it
+    /// returns NaN instead, which is classified as outside.
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.cache.lock().unwrap().error.take()
+    }
+}
+
+impl ScalarField for FileVoxelGrid {
+    fn sample(&self, position: Vec3) -> Float {
+        let x = voxel_index(position.x, self.from.x, self.to.x, self.width);
+        let y = voxel_index(position.y, self.from.y, self.to.y, self.height);
+        let z = voxel_index(position.z, self.from.z, self.to.z, self.depth);
+        self.voxel(x, y, z)
+    }
+}
+
+/// Read errors are reported like in [ScalarField::sample].
+impl Voxels for FileVoxelGrid {
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.from, self.to)
+    }
+
+    fn size(&self) -> [usize; 3] {
+        [self.width, self.height, self.depth]
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> Float {
+        match self.slice(z) {
+            Ok(slice) => Float::from(slice[y * self.width + x]),
+            Err(error) => {
+                self.cache.lock().unwrap().error.get_or_insert(error);
+                Float::NAN
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Edge, Face, Mesh};
+
+impl Mesh {
+    /// Merge `other` into this mesh, reusing vertices that were generated on the same lattice
+    /// edge.
+    ///
+    /// Both meshes must be generated by domains that share the same global lattice (see
+    /// `Domain::lattice_offset`), for example neighboring chunks of a larger domain. Vertices are
+    /// matched by their [crate::EdgeKey] instead of their position, so welding is exact.
+    /// Neighboring chunks both march the cells on their shared border; faces that end up using
+    /// the same vertices as an existing face are skipped.
+    pub fn weld_with(&mut self, other: &Mesh) {
+        let mut key_to_vert = HashMap::new();
+        for (index, key) in self.vert_keys.iter().enumerate() {
+            key_to_vert.entry(*key).or_insert(index);
+        }
+        let mut existing_faces = self
+            .faces
+            .iter()
+            .map(sorted_face)
+            .collect::<HashSet<[usize; 3]>>();
+
+        // Per vertex attributes are only kept when both meshes have them.
+        let (len, other_len) = (self.verts.len(), other.verts.len());
+        let keep_normals = keep_attribute(&mut self.normals, len, &other.normals, other_len);
+        let keep_uvs = keep_attribute(&mut self.uvs, len, &other.uvs, other_len);
+        let keep_tangents = keep_attribute(&mut self.tangents, len, &other.tangents, other_len);
+        let keep_joints = keep_attribute(&mut self.joints, len, &other.joints, other_len)
+            && keep_attribute(
+                &mut self.joint_weights,
+                len,
+                &other.joint_weights,
+                other_len,
+            );
+        if !keep_joints {
+            self.joints.clear();
+            self.joint_weights.clear();
+        }
+        let vert_map = other
+            .vert_keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| {
+                *key_to_vert.entry(*key).or_insert_with(|| {
+                    self.verts.push(other.verts[index]);
+                    if keep_normals {
+                        self.normals.push(other.normals[index]);
+                    }
+                    if keep_uvs {
+                        self.uvs.push(other.uvs[index]);
+                    }
+                    if keep_tangents {
+                        self.tangents.push(other.tangents[index]);
+                    }
+                    if keep_joints {
+                        self.joints.push(other.joints[index]);
+                        self.joint_weights.push(other.joint_weights[index]);
+                    }
+                    self.vert_keys.push(*key);
+                    self.verts.len() - 1
+                })
+            })
+            .collect::<Vec<usize>>();
+
+        for face in &other.faces {
+            let welded = Face {
+                v1: vert_map[face.v1],
+                v2: vert_map[face.v2],
+                v3: vert_map[face.v3],
+            };
+            if !existing_faces.insert(sorted_face(&welded)) {
+                continue;
+            }
+            for (v1, v2) in [
+                (welded.v1, welded.v2),
+                (welded.v2, welded.v3),
+                (welded.v3, welded.v1),
+            ] {
+                self.edges.push(Edge { v1, v2 });
+            }
+            self.faces.push(welded);
+        }
+    }
+}
+
+/// Whether both meshes have `attribute` for every vertex, clearing it otherwise.
+fn keep_attribute<T>(attribute: &mut Vec<T>, len: usize, other: &[T], other_len: usize) -> bool {
+    let keep = attribute.len() == len && other.len() == other_len;
+    if !keep {
+        attribute.clear();
+    }
+    keep
+}
+
+fn sorted_face(face: &Face) -> [usize; 3] {
+    let mut verts = [face.v1, face.v2, face.v3];
+    verts.sort();
+    verts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EdgeKey, Float, IVec3, Vec3};
+
+    /// Triangle on the lattice edges from the origin to the corners `ends`.
+    fn triangle(ends: [i32; 3]) -> Mesh {
+        let mut mesh = Mesh::default();
+        for (index, end) in ends.into_iter().enumerate() {
+            let end = IVec3 { x: end, y: 1, z: 0 };
+            mesh.vert_keys
+                .push(EdgeKey::new(IVec3 { x: 0, y: 0, z: 0 }, end));
+            mesh.verts.push(Vec3 {
+                x: end.x as Float,
+                y: 0.5,
+                z: 0.0,
+            });
+            mesh.uvs.push([index as Float, 0.0]);
+            mesh.joints.push([0; 4]);
+            mesh.joint_weights.push([1.0, 0.0, 0.0, 0.0]);
+        }
+        mesh.faces.push(Face {
+            v1: 0,
+            v2: 1,
+            v3: 2,
+        });
+        mesh
+    }
+
+    #[test]
+    fn attributes_are_merged_or_cleared() {
+        let mut mesh = triangle([0, 1, 2]);
+        let mut other = triangle([1, 2, 3]);
+        other.joints.clear();
+        mesh.weld_with(&other);
+
+        assert_eq!(mesh.verts.len(), 4);
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.uvs.len(), 4);
+        assert_eq!(mesh.uvs[3], [2.0, 0.0]);
+        assert!(mesh.joints.is_empty() && mesh.joint_weights.is_empty());
+        assert!(mesh.normals.is_empty() && mesh.tangents.is_empty());
+    }
+}
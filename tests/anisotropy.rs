@@ -0,0 +1,138 @@
+//! Directional bias of the tetrahedra decompositions, measured on spheres at several offsets
+//! from the lattice, see `examples/anisotropy.rs`.
+//!
+//! The five tetrahedra split mirrors every other cell to keep the faces between cells
+//! consistent, the six tetrahedra split cuts every cell along the same main diagonal. Both favor
+//! the diagonals they cut along, which shows up in the off diagonal entries of the
+//! [NormalDistribution] of a sphere. The body-centered lattice has no preferred diagonal.
+
+use marching_cubes::{algorithm::Decomposition, prelude::*, result::NormalDistribution};
+
+type Method = fn(&mut Domain, &dyn ScalarField) -> MarchResult;
+
+const RESOLUTION: usize = 16;
+const SPHERES: usize = 8;
+
+fn domain() -> Domain {
+    Domain {
+        from: Vec3 {
+            x: -3.0,
+            y: -3.0,
+            z: -3.0,
+        },
+        to: Vec3 {
+            x: 3.0,
+            y: 3.0,
+            z: 3.0,
+        },
+        surface_weight: 0.0,
+        width: RESOLUTION,
+        height: RESOLUTION,
+        depth: RESOLUTION,
+        grid_offset: Vec3::default(),
+        meshes: Vec::new(),
+    }
+}
+
+/// Normal distribution of the face normals averaged over spheres offset within a cell.
+fn mean_distribution(method: Method) -> NormalDistribution {
+    let mut tensor = [[0.0; 3]; 3];
+    for index in 0..SPHERES {
+        let fraction = |factor: Float| (index as Float * factor).fract() - 0.5;
+        let center = Vec3 {
+            x: fraction(0.618_034),
+            y: fraction(0.754_878),
+            z: fraction(0.569_840),
+        } * (6.0 / RESOLUTION as Float);
+        let field = move |position: Vec3| 2.5 - (position - center).length();
+        let mut domain = domain();
+        method(&mut domain, &field);
+        let mesh = &domain.meshes[0];
+        assert!(mesh.validate().is_valid());
+        let distribution = NormalDistribution::new(mesh);
+        for (sum, entry) in tensor
+            .iter_mut()
+            .flatten()
+            .zip(distribution.tensor.iter().flatten())
+        {
+            *sum += entry / SPHERES as Float;
+        }
+    }
+    NormalDistribution { tensor }
+}
+
+/// Largest absolute off diagonal entry of the tensor.
+fn off_diagonal(distribution: &NormalDistribution) -> Float {
+    let [[_, xy, xz], [_, _, yz], _] = distribution.tensor;
+    xy.abs().max(xz.abs()).max(yz.abs())
+}
+
+/// Checks shared by every decomposition: a sphere has nearly the isotropic tensor.
+fn assert_nearly_isotropic(name: &str, distribution: &NormalDistribution, max_anisotropy: Float) {
+    let anisotropy = distribution.anisotropy();
+    assert!(anisotropy.is_finite(), "{name}: {distribution}");
+    assert!(anisotropy < max_anisotropy, "{name}: {distribution}");
+    for axis in 0..3 {
+        let diagonal = distribution.tensor[axis][axis];
+        assert!(
+            (diagonal - 1.0 / 3.0).abs() < 0.005,
+            "{name}: {distribution}"
+        );
+        for other in 0..3 {
+            assert_eq!(
+                distribution.tensor[axis][other], distribution.tensor[other][axis],
+                "{name}: tensor isn't symmetric"
+            );
+        }
+    }
+}
+
+#[test]
+fn five_tetrahedra() {
+    let five = mean_distribution(|domain, field| domain.march_tetrahedras(field, &Bisection));
+    assert_nearly_isotropic("5 tetrahedra", &five, 0.001);
+    assert!(off_diagonal(&five) < 0.0005, "{five}");
+}
+
+#[test]
+fn six_tetrahedra() {
+    let six = mean_distribution(|domain, field| {
+        domain.march_tetrahedras_with_decomposition(Decomposition::Six, field, &Bisection)
+    });
+    assert_nearly_isotropic("6 tetrahedra", &six, 0.005);
+}
+
+#[test]
+fn body_centered() {
+    let body_centered =
+        mean_distribution(|domain, field| domain.march_body_centered(field, &Bisection));
+    assert_nearly_isotropic("body centered", &body_centered, 0.0005);
+    assert!(off_diagonal(&body_centered) < 0.0002, "{body_centered}");
+}
+
+#[test]
+fn decompositions_compared() {
+    let five = mean_distribution(|domain, field| domain.march_tetrahedras(field, &Bisection));
+    let six = mean_distribution(|domain, field| {
+        domain.march_tetrahedras_with_decomposition(Decomposition::Six, field, &Bisection)
+    });
+    let body_centered =
+        mean_distribution(|domain, field| domain.march_body_centered(field, &Bisection));
+    // Cutting every cell along the same diagonal biases the normals towards it, mirroring the
+    // split in every other cell mostly cancels that out.
+    assert!(off_diagonal(&five) < off_diagonal(&six), "{five}\n{six}");
+    assert!(five.anisotropy() < six.anisotropy(), "{five}\n{six}");
+    assert!(
+        body_centered.anisotropy() <= five.anisotropy(),
+        "{body_centered}\n{five}"
+    );
+}
+
+#[test]
+fn plane_is_fully_anisotropic() {
+    let mut domain = domain();
+    domain.march_tetrahedras(&|position: Vec3| 0.3 - position.z, &Bisection);
+    let distribution = NormalDistribution::new(&domain.meshes[0]);
+    assert!((distribution.anisotropy() - 1.0).abs() < 1e-4);
+    assert!((distribution.tensor[2][2] - 1.0).abs() < 1e-4);
+}